@@ -0,0 +1,178 @@
+use crate::{error::NonNativeFieldError, NonNativeFieldMulResultVar, NonNativeFieldVar};
+use ark_ff::{One, PrimeField, Zero};
+use ark_r1cs_std::fields::FieldVar;
+use ark_relations::r1cs::SynthesisError;
+use ark_std::vec::Vec;
+
+/// A sum of products `Σ a_i b_i` that is built incrementally, e.g., across function
+/// boundaries, and reduced once at the end
+///
+/// The products are added without reduction. When the bound of the unreduced sum would no
+/// longer allow a sound reduction, the sum so far is reduced and set aside, and the
+/// accumulation starts over from the new product.
+#[derive(Debug)]
+#[must_use]
+pub struct Accumulator<TargetField: PrimeField, BaseField: PrimeField> {
+    /// The sum of the products that have already been reduced
+    reduced: NonNativeFieldVar<TargetField, BaseField>,
+    /// The sum of the products since the last reduction
+    unreduced: NonNativeFieldMulResultVar<TargetField, BaseField>,
+    /// The number of reductions performed before `finalize`
+    num_of_reductions: usize,
+}
+
+impl<TargetField: PrimeField, BaseField: PrimeField> Default
+    for Accumulator<TargetField, BaseField>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<TargetField: PrimeField, BaseField: PrimeField> Accumulator<TargetField, BaseField> {
+    /// Create an empty accumulator
+    pub fn new() -> Self {
+        Self {
+            reduced: NonNativeFieldVar::zero(),
+            unreduced: NonNativeFieldMulResultVar::zero(),
+            num_of_reductions: 0,
+        }
+    }
+
+    /// Add `a * b` to the sum
+    #[tracing::instrument(target = "r1cs")]
+    pub fn add_product(
+        &mut self,
+        a: &NonNativeFieldVar<TargetField, BaseField>,
+        b: &NonNativeFieldVar<TargetField, BaseField>,
+    ) -> Result<(), SynthesisError> {
+        self.add_unreduced(a.mul_without_reduce(b)?)
+    }
+
+    /// Add `a * c` for the constant `c` to the sum, whose product needs no constraints
+    #[tracing::instrument(target = "r1cs")]
+    pub fn add_product_by_constant(
+        &mut self,
+        a: &NonNativeFieldVar<TargetField, BaseField>,
+        c: &TargetField,
+    ) -> Result<(), SynthesisError> {
+        self.add_unreduced(a.mul_without_reduce(&NonNativeFieldVar::Constant(*c))?)
+    }
+
+    /// Add `a` to the sum, lifted into the unreduced form of the products
+    #[tracing::instrument(target = "r1cs")]
+    pub fn add(
+        &mut self,
+        a: &NonNativeFieldVar<TargetField, BaseField>,
+    ) -> Result<(), SynthesisError> {
+        self.add_unreduced(NonNativeFieldMulResultVar::from_reduced(a)?)
+    }
+
+    fn add_unreduced(
+        &mut self,
+        prod: NonNativeFieldMulResultVar<TargetField, BaseField>,
+    ) -> Result<(), SynthesisError> {
+        use NonNativeFieldMulResultVar::*;
+
+        let sum = match (&self.unreduced, &prod) {
+            (Constant(c1), Constant(c2)) => Some(Constant(*c1 + c2)),
+            (Constant(c), Var(v)) | (Var(v), Constant(c)) => {
+                if v.is_within_bound(v.prod_of_num_of_additions + BaseField::one())? {
+                    Some(Var(v.add_constant(c)?))
+                } else {
+                    None
+                }
+            }
+            (Var(v1), Var(v2)) => {
+                if v1.is_within_bound(v1.prod_of_num_of_additions + v2.prod_of_num_of_additions)? {
+                    Some(Var(v1.add(v2)?))
+                } else {
+                    None
+                }
+            }
+        };
+
+        match sum {
+            Some(sum) => self.unreduced = sum,
+            None => {
+                self.reduced += &self.unreduced.reduce()?;
+                self.unreduced = prod;
+                self.num_of_reductions += 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The number of reductions that the accumulation has needed so far, not counting the
+    /// final one
+    pub fn num_of_reductions(&self) -> usize {
+        self.num_of_reductions
+    }
+
+    /// Reduce the accumulated sum
+    #[tracing::instrument(target = "r1cs")]
+    pub fn finalize(self) -> Result<NonNativeFieldVar<TargetField, BaseField>, SynthesisError> {
+        Ok(&self.reduced + &self.unreduced.reduce()?)
+    }
+}
+
+impl<TargetField: PrimeField, BaseField: PrimeField> NonNativeFieldVar<TargetField, BaseField> {
+    /// Compute the product of `matrix`, given by its rows, and `vector`
+    ///
+    /// Each entry of the result is an inner product accumulated without reduction, so it is
+    /// reduced once unless it runs out of room. Return `NonNativeFieldError::LengthMismatch`
+    /// if a row is not as long as `vector`.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn mat_vec_mul(
+        matrix: &[Vec<Self>],
+        vector: &[Self],
+    ) -> Result<Vec<Self>, NonNativeFieldError> {
+        let mut res = Vec::with_capacity(matrix.len());
+        for row in matrix.iter() {
+            if row.len() != vector.len() {
+                return Err(NonNativeFieldError::LengthMismatch {
+                    expected: vector.len(),
+                    found: row.len(),
+                });
+            }
+
+            let mut acc = Accumulator::new();
+            for (entry, elem) in row.iter().zip(vector.iter()) {
+                acc.add_product(entry, elem)?;
+            }
+            res.push(acc.finalize()?);
+        }
+        Ok(res)
+    }
+
+    /// Compute the product of the constant `matrix`, given by its rows, and `vector`
+    ///
+    /// The products by constants are linear in the limbs, so only the final reductions cost
+    /// constraints, and the zero entries of sparse matrices are skipped. Return
+    /// `NonNativeFieldError::LengthMismatch` if a row is not as long as `vector`.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn mat_vec_mul_by_constant(
+        matrix: &[Vec<TargetField>],
+        vector: &[Self],
+    ) -> Result<Vec<Self>, NonNativeFieldError> {
+        let mut res = Vec::with_capacity(matrix.len());
+        for row in matrix.iter() {
+            if row.len() != vector.len() {
+                return Err(NonNativeFieldError::LengthMismatch {
+                    expected: vector.len(),
+                    found: row.len(),
+                });
+            }
+
+            let mut acc = Accumulator::new();
+            for (entry, elem) in row.iter().zip(vector.iter()) {
+                if !entry.is_zero() {
+                    acc.add_product_by_constant(elem, entry)?;
+                }
+            }
+            res.push(acc.finalize()?);
+        }
+        Ok(res)
+    }
+}
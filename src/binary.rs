@@ -0,0 +1,369 @@
+use ark_ff::PrimeField;
+use ark_r1cs_std::{alloc::AllocVar, boolean::Boolean, eq::EqGadget, R1CSVar};
+use ark_relations::{
+    lc,
+    r1cs::{ConstraintSystemRef, LinearCombination, Namespace, SynthesisError},
+};
+use ark_std::{vec, vec::Vec};
+
+/// A binary extension field `GF(2^degree)`, given by an irreducible polynomial
+/// `x^degree + sum_{t in taps} x^t` with few terms
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BinaryFieldParams {
+    /// The degree of the field over `GF(2)`
+    pub degree: usize,
+    /// The exponents below `degree` of the terms of the irreducible polynomial
+    pub taps: &'static [usize],
+}
+
+/// The field of GHASH, with the irreducible polynomial `x^128 + x^7 + x^2 + x + 1`
+///
+/// GHASH maps the first bit of a block to the coefficient of `x^0`, i.e., the bits of each
+/// byte are reflected with respect to `BinaryFieldVar::bits`.
+pub const GF2_128: BinaryFieldParams = BinaryFieldParams {
+    degree: 128,
+    taps: &[7, 2, 1, 0],
+};
+
+/// An element of a binary extension field, given by the coefficients of its polynomial
+///
+/// Additions are XORs of the coefficients. The products of polynomials are carry-less, so
+/// `mul` spreads the coefficients of limbs of both operands `digit_bits` apart, where the
+/// integer products of the limbs hold the sums of the coefficient products without carries
+/// into the next digit. The parities of these digits are the coefficients of the product,
+/// which is then reduced with the sparse irreducible polynomial by XORs.
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct BinaryFieldVar<BaseField: PrimeField> {
+    /// The field of the element
+    pub params: BinaryFieldParams,
+    /// The coefficients of the polynomial, that of `x^0` first
+    pub bits: Vec<Boolean<BaseField>>,
+}
+
+impl<BaseField: PrimeField> BinaryFieldVar<BaseField> {
+    /// Create a constant from the coefficients `value`, that of `x^0` first
+    pub fn constant(params: BinaryFieldParams, value: &[bool]) -> Result<Self, SynthesisError> {
+        if value.len() > params.degree {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+
+        let mut bits: Vec<_> = value.iter().map(|bit| Boolean::constant(*bit)).collect();
+        bits.resize(params.degree, Boolean::FALSE);
+        Ok(Self { params, bits })
+    }
+
+    /// The additive identity
+    pub fn zero(params: BinaryFieldParams) -> Self {
+        Self {
+            params,
+            bits: vec![Boolean::FALSE; params.degree],
+        }
+    }
+
+    /// The multiplicative identity
+    pub fn one(params: BinaryFieldParams) -> Self {
+        let mut res = Self::zero(params);
+        res.bits[0] = Boolean::TRUE;
+        res
+    }
+
+    /// Allocate a witness with the coefficients output by `f`, that of `x^0` first
+    pub fn new_witness(
+        cs: impl Into<Namespace<BaseField>>,
+        params: BinaryFieldParams,
+        f: impl FnOnce() -> Result<Vec<bool>, SynthesisError>,
+    ) -> Result<Self, SynthesisError> {
+        let ns = cs.into();
+        let cs = ns.cs();
+
+        let value = f().ok();
+        if let Some(value) = &value {
+            if value.len() > params.degree {
+                return Err(SynthesisError::Unsatisfiable);
+            }
+        }
+
+        let mut bits = Vec::with_capacity(params.degree);
+        for i in 0..params.degree {
+            bits.push(Boolean::new_witness(
+                ark_relations::ns!(cs, "coefficient"),
+                || {
+                    value
+                        .as_ref()
+                        .map(|value| value.get(i).copied().unwrap_or(false))
+                        .ok_or(SynthesisError::AssignmentMissing)
+                },
+            )?);
+        }
+
+        Ok(Self { params, bits })
+    }
+
+    /// Add `other`, i.e., XOR the coefficients
+    #[tracing::instrument(target = "r1cs")]
+    pub fn add(&self, other: &Self) -> Result<Self, SynthesisError> {
+        self.check_params(other)?;
+        let bits = self
+            .bits
+            .iter()
+            .zip(other.bits.iter())
+            .map(|(left, right)| left.xor(right))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            params: self.params,
+            bits,
+        })
+    }
+
+    /// Multiply by `other`
+    #[tracing::instrument(target = "r1cs")]
+    pub fn mul(&self, other: &Self) -> Result<Self, SynthesisError> {
+        self.check_params(other)?;
+        let product = self.carry_less_mul(other)?;
+        Ok(Self {
+            params: self.params,
+            bits: reduce(&self.params, product)?,
+        })
+    }
+
+    /// Square the element
+    pub fn square(&self) -> Result<Self, SynthesisError> {
+        self.mul(self)
+    }
+
+    /// Compute the inverse, which is witnessed and checked by a multiplication
+    ///
+    /// The inverse of zero does not exist, so the constraint system is unsatisfied for it.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn inverse(&self) -> Result<Self, SynthesisError> {
+        let cs = self.cs();
+        if cs.is_none() {
+            return Self::constant(self.params, &inverse_native(&self.params, &self.value()?));
+        }
+
+        let value = self.value();
+        let inverse = Self::new_witness(ark_relations::ns!(cs, "inverse"), self.params, || {
+            Ok(inverse_native(&self.params, &value?))
+        })?;
+        self.mul(&inverse)?.enforce_equal(&Self::one(self.params))?;
+        Ok(inverse)
+    }
+
+    /// Check that `other` is an element of the same field
+    fn check_params(&self, other: &Self) -> Result<(), SynthesisError> {
+        if self.params != other.params
+            || self.bits.len() != self.params.degree
+            || other.bits.len() != other.params.degree
+        {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        Ok(())
+    }
+
+    /// The coefficients of the product of the polynomials, `2 · degree - 1` of them
+    fn carry_less_mul(&self, other: &Self) -> Result<Vec<Boolean<BaseField>>, SynthesisError> {
+        let degree = self.params.degree;
+        let cs = self.cs().or(other.cs());
+        if cs.is_none() {
+            let product = carry_less_mul_native(&self.value()?, &other.value()?);
+            return Ok(product.into_iter().map(Boolean::constant).collect());
+        }
+
+        // a digit holds the sum of at most `degree` coefficient products, and the product of
+        // two limbs has `2 · limb_size - 1` digits, which must fit into `BaseField`
+        let digit_bits = ark_std::log2(degree + 1) as usize;
+        let limb_size = match (BaseField::size_in_bits() - 1).checked_div(digit_bits) {
+            Some(num_digits) if num_digits >= 1 => ark_std::cmp::min((num_digits + 1) / 2, degree),
+            _ => return Err(SynthesisError::Unsatisfiable),
+        };
+        let num_limbs = (degree + limb_size - 1) / limb_size;
+
+        let limb_lc = |bits: &[Boolean<BaseField>], limb: usize| {
+            let mut lc = LinearCombination::zero();
+            for (j, bit) in bits
+                .iter()
+                .skip(limb * limb_size)
+                .take(limb_size)
+                .enumerate()
+            {
+                lc = &lc + bit.lc() * BaseField::from(2u64).pow(&[(digit_bits * j) as u64]);
+            }
+            lc
+        };
+        let limb_value = |bits: &Result<Vec<bool>, SynthesisError>, limb: usize| {
+            bits.as_ref()
+                .map(|bits| {
+                    let mut value = BaseField::zero();
+                    for bit in bits.iter().skip(limb * limb_size).take(limb_size).rev() {
+                        value = value * BaseField::from(2u64).pow(&[digit_bits as u64]);
+                        if *bit {
+                            value += BaseField::one();
+                        }
+                    }
+                    value
+                })
+                .map_err(|_| SynthesisError::AssignmentMissing)
+        };
+
+        let self_value = self.value();
+        let other_value = other.value();
+        let product_value = match (&self_value, &other_value) {
+            (Ok(left), Ok(right)) => Some(carry_less_product_sums(left, right, limb_size)),
+            _ => None,
+        };
+
+        // the sums of the products of the limbs whose indices add up to `w`
+        let mut windows = vec![LinearCombination::<BaseField>::zero(); 2 * num_limbs - 1];
+        for i in 0..num_limbs {
+            for j in 0..num_limbs {
+                let value = limb_value(&self_value, i)
+                    .and_then(|left| Ok(left * limb_value(&other_value, j)?));
+                let limb_product = cs.new_witness_variable(|| value)?;
+                cs.enforce_constraint(
+                    limb_lc(&self.bits, i),
+                    limb_lc(&other.bits, j),
+                    lc!() + limb_product,
+                )?;
+                windows[i + j] += (BaseField::one(), limb_product);
+            }
+        }
+
+        let mut product = vec![Boolean::FALSE; 2 * degree - 1];
+        for (w, window) in windows.into_iter().enumerate() {
+            let mut decomposition = window;
+            for digit in 0..(2 * limb_size - 1) {
+                let position = w * limb_size + digit;
+                for k in 0..digit_bits {
+                    let bit = Boolean::new_witness(ark_relations::ns!(cs, "digit bit"), || {
+                        product_value
+                            .as_ref()
+                            .map(|sums| {
+                                let sum = sums
+                                    .get(w)
+                                    .and_then(|window| window.get(digit))
+                                    .copied()
+                                    .unwrap_or(0);
+                                (sum >> k) & 1 == 1
+                            })
+                            .ok_or(SynthesisError::AssignmentMissing)
+                    })?;
+                    let coeff = BaseField::from(2u64).pow(&[(digit_bits * digit + k) as u64]);
+                    decomposition = &decomposition - bit.lc() * coeff;
+
+                    // the parity of the digit is the coefficient
+                    if k == 0 && position < product.len() {
+                        product[position] = product[position].xor(&bit)?;
+                    }
+                }
+            }
+            cs.enforce_constraint(lc!(), lc!(), decomposition)?;
+        }
+
+        Ok(product)
+    }
+}
+
+/// Reduce the coefficients of a product modulo the irreducible polynomial of `params`
+fn reduce<BaseField: PrimeField>(
+    params: &BinaryFieldParams,
+    mut product: Vec<Boolean<BaseField>>,
+) -> Result<Vec<Boolean<BaseField>>, SynthesisError> {
+    let degree = params.degree;
+    for i in (degree..product.len()).rev() {
+        let coefficient = product[i].clone();
+        for tap in params.taps.iter() {
+            let position = i - degree + tap;
+            product[position] = product[position].xor(&coefficient)?;
+        }
+    }
+    product.truncate(degree);
+    Ok(product)
+}
+
+/// The carry-less product of two polynomials over `GF(2)`
+fn carry_less_mul_native(left: &[bool], right: &[bool]) -> Vec<bool> {
+    let mut product = vec![false; (left.len() + right.len()).saturating_sub(1)];
+    for (i, a) in left.iter().enumerate() {
+        if *a {
+            for (j, b) in right.iter().enumerate() {
+                product[i + j] ^= *b;
+            }
+        }
+    }
+    product
+}
+
+/// The sums of the coefficient products in the digits of the windows of `carry_less_mul`
+fn carry_less_product_sums(left: &[bool], right: &[bool], limb_size: usize) -> Vec<Vec<u64>> {
+    let num_limbs = (left.len() + limb_size - 1) / limb_size;
+    let mut sums = vec![vec![0u64; 2 * limb_size - 1]; 2 * num_limbs - 1];
+    for (i, a) in left.iter().enumerate() {
+        for (j, b) in right.iter().enumerate() {
+            if *a && *b {
+                let w = i / limb_size + j / limb_size;
+                sums[w][i % limb_size + j % limb_size] += 1;
+            }
+        }
+    }
+    sums
+}
+
+/// The product of two elements of the field of `params`
+fn mul_native(params: &BinaryFieldParams, left: &[bool], right: &[bool]) -> Vec<bool> {
+    let mut product = carry_less_mul_native(left, right);
+    for i in (params.degree..product.len()).rev() {
+        if product[i] {
+            for tap in params.taps.iter() {
+                product[i - params.degree + tap] ^= true;
+            }
+        }
+    }
+    product.resize(params.degree, false);
+    product
+}
+
+/// The inverse `value^(2^degree - 2)` of a nonzero element of the field of `params`, and zero
+/// for zero
+fn inverse_native(params: &BinaryFieldParams, value: &[bool]) -> Vec<bool> {
+    let mut power = value.to_vec();
+    power.resize(params.degree, false);
+    let mut res = vec![false; params.degree];
+    res[0] = true;
+    for _ in 1..params.degree {
+        power = mul_native(params, &power, &power);
+        res = mul_native(params, &res, &power);
+    }
+    res
+}
+
+impl<BaseField: PrimeField> R1CSVar<BaseField> for BinaryFieldVar<BaseField> {
+    type Value = Vec<bool>;
+
+    fn cs(&self) -> ConstraintSystemRef<BaseField> {
+        self.bits.as_slice().cs()
+    }
+
+    fn value(&self) -> Result<Self::Value, SynthesisError> {
+        self.bits.iter().map(|bit| bit.value()).collect()
+    }
+}
+
+impl<BaseField: PrimeField> EqGadget<BaseField> for BinaryFieldVar<BaseField> {
+    #[tracing::instrument(target = "r1cs")]
+    fn is_eq(&self, other: &Self) -> Result<Boolean<BaseField>, SynthesisError> {
+        self.check_params(other)?;
+        self.bits.is_eq(&other.bits)
+    }
+
+    #[tracing::instrument(target = "r1cs")]
+    fn conditional_enforce_equal(
+        &self,
+        other: &Self,
+        should_enforce: &Boolean<BaseField>,
+    ) -> Result<(), SynthesisError> {
+        self.check_params(other)?;
+        self.bits
+            .conditional_enforce_equal(&other.bits, should_enforce)
+    }
+}
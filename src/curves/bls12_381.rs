@@ -0,0 +1,53 @@
+use super::short_weierstrass::NonNativeAffineVar;
+use ark_bls12_381::{g1::Parameters, Fq, Fr, G1Affine};
+use ark_ec::{models::bls12::Bls12Parameters, AffineCurve, ProjectiveCurve};
+use ark_ff::{Field, One, PrimeField, SquareRootField};
+use ark_r1cs_std::{eq::EqGadget, fields::FieldVar};
+use ark_relations::r1cs::SynthesisError;
+
+/// The square of the parameter `u` of BLS12-381, whose sign does not matter
+fn u_square() -> Fr {
+    Fr::from(<ark_bls12_381::Parameters as Bls12Parameters>::X[0]).square()
+}
+
+/// The cube root of unity `β` for which the endomorphism `(x, y) ↦ (β·x, y)` acts on G1 as
+/// the multiplication by `-u^2`
+///
+/// The cube roots of unity other than one are the roots of `β^2 + β + 1`, and the one that
+/// matches the generator is kept.
+fn beta() -> Result<Fq, SynthesisError> {
+    let sqrt_minus_three = (-Fq::from(3u64))
+        .sqrt()
+        .ok_or(SynthesisError::Unsatisfiable)?;
+    let half = Fq::from(2u64)
+        .inverse()
+        .ok_or(SynthesisError::Unsatisfiable)?;
+    let beta = (sqrt_minus_three - &Fq::one()) * &half;
+
+    let g = G1Affine::prime_subgroup_generator();
+    let minus_u_square_g = -g.mul(u_square().into_repr()).into_affine();
+    if minus_u_square_g.x == beta * &g.x {
+        Ok(beta)
+    } else {
+        Ok(beta.square())
+    }
+}
+
+/// Enforce that `point`, which must be on the curve, lies in G1 of BLS12-381
+///
+/// This is the test of Scott (ePrint 2021/1130, proven in ePrint 2022/352): a point `P` on the
+/// curve is in G1 if and only if `φ(P) = -[u^2] · P` for the endomorphism `φ(x, y) = (β·x, y)`.
+/// The scalar `u^2` has 128 bits instead of the 255 bits of the subgroup order, so this costs
+/// about half of `NonNativeAffineVar::enforce_in_prime_order_subgroup`. A point outside of G1
+/// either fails the comparison or hits an exceptional case of the incomplete formulas, both of
+/// which leave the constraint system unsatisfied.
+#[tracing::instrument(target = "r1cs")]
+pub fn enforce_in_g1<BaseField: PrimeField>(
+    point: &NonNativeAffineVar<Parameters, BaseField>,
+) -> Result<(), SynthesisError> {
+    let res = point
+        .scalar_mul_by_constant(&u_square().into_repr())?
+        .negate()?;
+    res.x.enforce_equal(&(&point.x * beta()?))?;
+    res.y.enforce_equal(&point.y)
+}
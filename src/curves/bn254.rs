@@ -0,0 +1,92 @@
+use super::short_weierstrass::NonNativeAffineVar;
+use crate::{NonNativeFieldParams, NonNativeFieldVar};
+use ark_ec::models::{ModelParameters, SWModelParameters};
+use ark_ff::{biginteger::BigInteger256 as BigInteger, field_new};
+
+/// The base field of BN254, which is the scalar field of Grumpkin
+pub use ark_bn254::Fq;
+
+/// The scalar field of BN254, which is the base field of Grumpkin
+pub use ark_bn254::Fr;
+
+/// The curve `y^2 = x^3 + 3` of the G1 group of BN254 over `Fq`, whose group order is the
+/// modulus of `Fr`
+pub type Bn254G1Parameters = ark_bn254::g1::Parameters;
+
+/// The curve Grumpkin, `y^2 = x^3 - 17` over `Fr`, whose group order is the modulus of `Fq`
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct GrumpkinParameters;
+
+impl ModelParameters for GrumpkinParameters {
+    type BaseField = Fr;
+    type ScalarField = Fq;
+}
+
+impl SWModelParameters for GrumpkinParameters {
+    #[rustfmt::skip]
+    const COEFF_A: Fr = field_new!(Fr, BigInteger([0x0, 0x0, 0x0, 0x0]));
+
+    /// -17
+    #[rustfmt::skip]
+    const COEFF_B: Fr = field_new!(Fr, BigInteger([
+        0xdd7056026000005a,
+        0x223fa97acb319311,
+        0xcc388229877910c0,
+        0x034394632b724eaa,
+    ]));
+
+    const COFACTOR: &'static [u64] = &[0x1];
+
+    /// 1
+    #[rustfmt::skip]
+    const COFACTOR_INV: Fq = field_new!(Fq, BigInteger([
+        0xd35d438dc58f0d9d,
+        0x0a78eb28f5c70b3d,
+        0x666ea36f7879462c,
+        0x0e0a77c19a07df2f,
+    ]));
+
+    /// (1, sqrt(-16))
+    #[rustfmt::skip]
+    const AFFINE_GENERATOR_COEFFS: (Fr, Fr) = (
+        field_new!(Fr, BigInteger([
+            0xac96341c4ffffffb,
+            0x36fc76959f60cd29,
+            0x666ea36f7879462e,
+            0x0e0a77c19a07df2f,
+        ])),
+        field_new!(Fr, BigInteger([
+            0x11b2dff1448c41d8,
+            0x23d3446f21c77dc3,
+            0xaa7b8cf435dfafbb,
+            0x14b34cf69dc25d68,
+        ])),
+    );
+}
+
+/// An element of `Fq`, the base field of BN254 and the scalar field of Grumpkin, simulated in a
+/// constraint system over `Fr`
+pub type FqVarOverFr = NonNativeFieldVar<Fq, Fr>;
+
+/// An element of `Fr`, the scalar field of BN254 and the base field of Grumpkin, simulated in a
+/// constraint system over `Fq`
+pub type FrVarOverFq = NonNativeFieldVar<Fr, Fq>;
+
+/// A point of the G1 group of BN254, simulated in a constraint system over `Fr`
+pub type Bn254G1AffineVarOverFr = NonNativeAffineVar<Bn254G1Parameters, Fr>;
+
+/// A Grumpkin point, simulated in a constraint system over `Fq`
+pub type GrumpkinAffineVarOverFq = NonNativeAffineVar<GrumpkinParameters, Fq>;
+
+/// Parameters for either field of the cycle simulated over the other one, to be stored with
+/// `params::set_params`
+///
+/// Both moduli have 254 bits, which the limbs cover exactly, so `from_native` and `to_native`
+/// convert between the scalar fields of the two curves in both directions. The cost grows with
+/// the number of limbs, so this is also the cheapest layout over every number of limbs.
+pub const PARAMS: NonNativeFieldParams = NonNativeFieldParams {
+    num_limbs: 3,
+    bits_per_top_limb: 68,
+    bits_per_non_top_limb: 93,
+    equality_layout: None,
+};
@@ -0,0 +1,67 @@
+use crate::{NonNativeFieldParams, NonNativeFieldVar};
+
+/// The scalar field of BLS12-377
+pub use ark_bls12_377::Fr;
+
+/// The base field of BLS12-377, which is the scalar field of BW6-761
+pub use ark_bls12_377::Fq;
+
+/// The base field of BW6-761
+pub use ark_bw6_761::Fq as Bw6Fq;
+
+/// An element of `Fr`, the scalar field of BLS12-377, simulated in a constraint system over
+/// `Fq`, which is the native field of BW6-761 circuits verifying BLS12-377 proofs
+pub type FrVarOverFq = NonNativeFieldVar<Fr, Fq>;
+
+/// An element of `Fq`, the base field of BLS12-377, simulated in a constraint system over `Fr`
+pub type FqVarOverFr = NonNativeFieldVar<Fq, Fr>;
+
+/// An element of `Bw6Fq`, the base field of BW6-761, simulated in a constraint system over `Fq`
+pub type Bw6FqVarOverFq = NonNativeFieldVar<Bw6Fq, Fq>;
+
+/// An element of `Fq`, the scalar field of BW6-761, simulated in a constraint system over
+/// `Bw6Fq`
+pub type FqVarOverBw6Fq = NonNativeFieldVar<Fq, Bw6Fq>;
+
+/// Parameters for `FrVarOverFq`, to be stored with `params::set_params`
+///
+/// The two limbs cover the 253 bits of `Fr` exactly, so `from_native` embeds elements of `Fq`
+/// below `2^253` without a reduction.
+pub const FR_OVER_FQ_PARAMS: NonNativeFieldParams = NonNativeFieldParams {
+    num_limbs: 2,
+    bits_per_top_limb: 101,
+    bits_per_non_top_limb: 152,
+    equality_layout: None,
+};
+
+/// Parameters for `FqVarOverFr`, to be stored with `params::set_params`
+///
+/// No layout with fewer than four limbs leaves enough room for the products of 377-bit
+/// elements in a 253-bit field.
+pub const FQ_OVER_FR_PARAMS: NonNativeFieldParams = NonNativeFieldParams {
+    num_limbs: 4,
+    bits_per_top_limb: 59,
+    bits_per_non_top_limb: 106,
+    equality_layout: None,
+};
+
+/// Parameters for `Bw6FqVarOverFq`, to be stored with `params::set_params`
+///
+/// Simulating the 761-bit field over the 377-bit one needs five limbs, which is the largest
+/// number of limbs among the presets of this crate.
+pub const BW6_FQ_OVER_FQ_PARAMS: NonNativeFieldParams = NonNativeFieldParams {
+    num_limbs: 5,
+    bits_per_top_limb: 89,
+    bits_per_non_top_limb: 168,
+    equality_layout: None,
+};
+
+/// Parameters for `FqVarOverBw6Fq`, to be stored with `params::set_params`
+///
+/// The native field is more than twice as wide as the simulated one, so two limbs suffice.
+pub const FQ_OVER_BW6_FQ_PARAMS: NonNativeFieldParams = NonNativeFieldParams {
+    num_limbs: 2,
+    bits_per_top_limb: 188,
+    bits_per_non_top_limb: 189,
+    equality_layout: None,
+};
@@ -0,0 +1,196 @@
+use crate::NonNativeFieldVar;
+use ark_ff::{
+    biginteger::BigInteger256 as BigInteger,
+    fields::{FftParameters, Fp256, Fp256Parameters, FpParameters},
+    PrimeField,
+};
+use ark_r1cs_std::{boolean::Boolean, fields::FieldVar};
+use ark_relations::r1cs::SynthesisError;
+
+/// The base field of Curve25519, with modulus `2^255 - 19`
+pub type Fq25519 = Fp256<Fq25519Parameters>;
+
+/// The parameters of `Fq25519`
+pub struct Fq25519Parameters;
+
+impl Fp256Parameters for Fq25519Parameters {}
+
+impl FftParameters for Fq25519Parameters {
+    type BigInt = BigInteger;
+
+    const TWO_ADICITY: u32 = 2;
+
+    /// 2^((p - 1) / 4), a square root of -1, in the Montgomery form
+    #[rustfmt::skip]
+    const TWO_ADIC_ROOT_OF_UNITY: BigInteger = BigInteger([
+        0x3b5807d4fe2bdb04,
+        0x03f590fdb51be9ed,
+        0x6d6e16bf336202d1,
+        0x75776b0bd6c71ba8,
+    ]);
+}
+
+impl FpParameters for Fq25519Parameters {
+    #[rustfmt::skip]
+    const MODULUS: BigInteger = BigInteger([
+        0xffffffffffffffed,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+        0x7fffffffffffffff,
+    ]);
+
+    const MODULUS_BITS: u32 = 255;
+
+    const CAPACITY: u32 = Self::MODULUS_BITS - 1;
+
+    const REPR_SHAVE_BITS: u32 = 1;
+
+    #[rustfmt::skip]
+    const R: BigInteger = BigInteger([0x26, 0x0, 0x0, 0x0]);
+
+    #[rustfmt::skip]
+    const R2: BigInteger = BigInteger([0x5a4, 0x0, 0x0, 0x0]);
+
+    const INV: u64 = 0x86bca1af286bca1b;
+
+    /// 2, in the Montgomery form
+    #[rustfmt::skip]
+    const GENERATOR: BigInteger = BigInteger([0x4c, 0x0, 0x0, 0x0]);
+
+    #[rustfmt::skip]
+    const MODULUS_MINUS_ONE_DIV_TWO: BigInteger = BigInteger([
+        0xfffffffffffffff6,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+        0x3fffffffffffffff,
+    ]);
+
+    #[rustfmt::skip]
+    const T: BigInteger = BigInteger([
+        0xfffffffffffffffb,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+        0x1fffffffffffffff,
+    ]);
+
+    #[rustfmt::skip]
+    const T_MINUS_ONE_DIV_TWO: BigInteger = BigInteger([
+        0xfffffffffffffffd,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+        0x0fffffffffffffff,
+    ]);
+}
+
+/// `(A - 2) / 4` for the coefficient `A = 486662` of Curve25519
+const A24: u64 = 121665;
+
+/// A point of Curve25519 in projective x-only coordinates `(X : Z)`, which stands for the
+/// u-coordinate `X / Z`
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct MontgomeryXZVar<BaseField: PrimeField> {
+    /// The X coordinate
+    pub x: NonNativeFieldVar<Fq25519, BaseField>,
+    /// The Z coordinate
+    pub z: NonNativeFieldVar<Fq25519, BaseField>,
+}
+
+impl<BaseField: PrimeField> MontgomeryXZVar<BaseField> {
+    /// The point at infinity, `(1 : 0)`
+    pub fn infinity() -> Self {
+        Self {
+            x: NonNativeFieldVar::one(),
+            z: NonNativeFieldVar::zero(),
+        }
+    }
+
+    /// The point with the affine u-coordinate `u`, `(u : 1)`
+    pub fn from_u(u: &NonNativeFieldVar<Fq25519, BaseField>) -> Self {
+        Self {
+            x: u.clone(),
+            z: NonNativeFieldVar::one(),
+        }
+    }
+
+    /// Output the affine u-coordinate `X / Z`, which is zero for the point at infinity as in
+    /// RFC 7748
+    #[tracing::instrument(target = "r1cs")]
+    pub fn to_u(&self) -> Result<NonNativeFieldVar<Fq25519, BaseField>, SynthesisError> {
+        let (z_inv, _) = self.z.inverse_or_zero()?;
+        Ok(&self.x * &z_inv)
+    }
+
+    /// Swap `a` and `b` if `swap` is set
+    fn conditional_swap(
+        swap: &Boolean<BaseField>,
+        a: &Self,
+        b: &Self,
+    ) -> Result<(Self, Self), SynthesisError> {
+        let new_a = Self {
+            x: swap.select(&b.x, &a.x)?,
+            z: swap.select(&b.z, &a.z)?,
+        };
+        let new_b = Self {
+            x: swap.select(&a.x, &b.x)?,
+            z: swap.select(&a.z, &b.z)?,
+        };
+        Ok((new_a, new_b))
+    }
+
+    /// The combined doubling and differential addition `xDBLADD`: compute `(2P, P + Q)` from
+    /// `P = self`, `Q = other`, and the affine u-coordinate `u_diff` of `Q - P`
+    #[tracing::instrument(target = "r1cs")]
+    pub fn double_and_add(
+        &self,
+        other: &Self,
+        u_diff: &NonNativeFieldVar<Fq25519, BaseField>,
+    ) -> Result<(Self, Self), SynthesisError> {
+        let a = &self.x + &self.z;
+        let aa = a.square()?;
+        let b = &self.x - &self.z;
+        let bb = b.square()?;
+        let e = &aa - &bb;
+        let c = &other.x + &other.z;
+        let d = &other.x - &other.z;
+        let da = &d * &a;
+        let cb = &c * &b;
+
+        let double = Self {
+            x: &aa * &bb,
+            z: &e * &(&aa + &(&e * Fq25519::from(A24))),
+        };
+        let sum = Self {
+            x: (&da + &cb).square()?,
+            z: u_diff * &(&da - &cb).square()?,
+        };
+
+        Ok((double, sum))
+    }
+}
+
+/// Compute the X25519 function of RFC 7748 on the scalar given by its little-endian `bits`,
+/// which the caller is expected to have clamped, and the u-coordinate `u`
+///
+/// The Montgomery ladder performs the same conditional swaps and `xDBLADD` at every bit, so
+/// the shape of the constraints does not depend on the scalar.
+#[tracing::instrument(target = "r1cs")]
+pub fn x25519<BaseField: PrimeField>(
+    bits: &[Boolean<BaseField>],
+    u: &NonNativeFieldVar<Fq25519, BaseField>,
+) -> Result<NonNativeFieldVar<Fq25519, BaseField>, SynthesisError> {
+    let mut r0 = MontgomeryXZVar::infinity();
+    let mut r1 = MontgomeryXZVar::from_u(u);
+    let mut swap = Boolean::FALSE;
+    for bit in bits.iter().rev() {
+        swap = swap.xor(bit)?;
+        let (a, b) = MontgomeryXZVar::conditional_swap(&swap, &r0, &r1)?;
+        let (double, sum) = a.double_and_add(&b, u)?;
+        r0 = double;
+        r1 = sum;
+        swap = bit.clone();
+    }
+    let (r0, _) = MontgomeryXZVar::conditional_swap(&swap, &r0, &r1)?;
+
+    r0.to_u()
+}
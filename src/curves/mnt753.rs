@@ -0,0 +1,28 @@
+use crate::{NonNativeFieldParams, NonNativeFieldVar};
+
+/// The scalar field of MNT4-753, which is the base field of MNT6-753
+pub use ark_mnt4_753::Fr;
+
+/// The base field of MNT4-753, which is the scalar field of MNT6-753
+pub use ark_mnt4_753::Fq;
+
+/// An element of `Fr`, the scalar field of MNT4-753 and the base field of MNT6-753, simulated
+/// in a constraint system over `Fq`
+pub type FrVarOverFq = NonNativeFieldVar<Fr, Fq>;
+
+/// An element of `Fq`, the base field of MNT4-753 and the scalar field of MNT6-753, simulated
+/// in a constraint system over `Fr`
+pub type FqVarOverFr = NonNativeFieldVar<Fq, Fr>;
+
+/// Parameters for either field of the cycle simulated over the other one, to be stored with
+/// `params::set_params`
+///
+/// Both moduli have 753 bits, which the limbs cover exactly. The limbs are much wider than
+/// those of the 255-bit cycles, so a product of two limbs stays far below the native modulus
+/// and three limbs remain the cheapest layout.
+pub const PARAMS: NonNativeFieldParams = NonNativeFieldParams {
+    num_limbs: 3,
+    bits_per_top_limb: 233,
+    bits_per_non_top_limb: 260,
+    equality_layout: None,
+};
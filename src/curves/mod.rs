@@ -0,0 +1,70 @@
+use crate::NonNativeFieldVar;
+use ark_ec::models::{SWModelParameters, TEModelParameters};
+use ark_ff::PrimeField;
+use ark_r1cs_std::{eq::EqGadget, fields::FieldVar};
+use ark_relations::r1cs::SynthesisError;
+
+/// the endomorphism-based subgroup check of BLS12-381
+#[cfg(feature = "bls12-381")]
+pub mod bls12_381;
+/// the BN254–Grumpkin cycle, with each field simulated over the other one
+#[cfg(feature = "bn254")]
+pub mod bn254;
+/// the fields of the BLS12-377 and BW6-761 two-chain, each simulated over the other ones
+#[cfg(feature = "bw6-761")]
+pub mod bw6_761;
+/// x-only arithmetic on the Montgomery curve Curve25519 and the X25519 function
+pub mod curve25519;
+/// the fields of the MNT4-753–MNT6-753 cycle, with each one simulated over the other one
+#[cfg(feature = "mnt753")]
+pub mod mnt753;
+/// the NIST curve P-256 and ECDSA signature verification over it
+pub mod p256;
+/// the Pallas–Vesta cycle, with each field simulated over the other one
+pub mod pasta;
+/// points of short Weierstrass curves and their group law
+pub mod short_weierstrass;
+
+/// Enforce that `(x, y)` is on the short Weierstrass curve `y^2 = x^3 + a·x + b` of `P`
+///
+/// The right-hand side is accumulated without reduction, so only `x^2`, `y^2`, and the
+/// right-hand side itself are reduced.
+#[tracing::instrument(target = "r1cs")]
+pub fn enforce_on_curve<P, BaseField>(
+    x: &NonNativeFieldVar<P::BaseField, BaseField>,
+    y: &NonNativeFieldVar<P::BaseField, BaseField>,
+) -> Result<(), SynthesisError>
+where
+    P: SWModelParameters,
+    P::BaseField: PrimeField,
+    BaseField: PrimeField,
+{
+    let x_square = x.square()?;
+    let rhs = x_square
+        .mul_without_reduce(x)?
+        .add_or_reduce(&x.mul_without_reduce(&NonNativeFieldVar::constant(P::COEFF_A))?)?
+        .add_constant_or_reduce(&P::COEFF_B)?;
+
+    rhs.reduce()?.enforce_equal(&y.square()?)
+}
+
+/// Enforce that `(x, y)` is on the twisted Edwards curve `a·x^2 + y^2 = 1 + d·x^2·y^2` of `P`
+///
+/// The equation is rearranged into `x^2 · (a - d·y^2) + y^2 = 1`, which needs one
+/// multiplication fewer than evaluating both sides.
+#[tracing::instrument(target = "r1cs")]
+pub fn enforce_on_te_curve<P, BaseField>(
+    x: &NonNativeFieldVar<P::BaseField, BaseField>,
+    y: &NonNativeFieldVar<P::BaseField, BaseField>,
+) -> Result<(), SynthesisError>
+where
+    P: TEModelParameters,
+    P::BaseField: PrimeField,
+    BaseField: PrimeField,
+{
+    let x_square = x.square()?;
+    let y_square = y.square()?;
+    let t = NonNativeFieldVar::constant(P::COEFF_A) - &(&y_square * P::COEFF_D);
+
+    (&(&x_square * &t) + &y_square).enforce_equal(&NonNativeFieldVar::one())
+}
@@ -0,0 +1,338 @@
+use super::short_weierstrass::NonNativeAffineVar;
+use crate::{NonNativeFieldParams, NonNativeFieldVar};
+use ark_ec::models::{ModelParameters, SWModelParameters};
+use ark_ff::{
+    biginteger::BigInteger320 as BigInteger,
+    field_new,
+    fields::{FftParameters, Fp320, Fp320Parameters, FpParameters},
+    BigInteger as _, Field, PrimeField,
+};
+use ark_r1cs_std::{bits::ToBitsGadget, boolean::Boolean, eq::EqGadget, fields::FieldVar};
+use ark_relations::r1cs::SynthesisError;
+
+/// The base field of P-256, with modulus `2^256 - 2^224 + 2^192 + 2^96 - 1`
+///
+/// The modulus fills all 256 bits, so the field uses five 64-bit words to leave room for the
+/// carries of the native arithmetic.
+pub type Fq = Fp320<FqParameters>;
+
+/// The parameters of `Fq`
+pub struct FqParameters;
+
+impl Fp320Parameters for FqParameters {}
+
+impl FftParameters for FqParameters {
+    type BigInt = BigInteger;
+
+    const TWO_ADICITY: u32 = 1;
+
+    /// -1, in the Montgomery form
+    #[rustfmt::skip]
+    const TWO_ADIC_ROOT_OF_UNITY: BigInteger = BigInteger([
+        0xffffffff00000000,
+        0xfffffffffffffffe,
+        0x0000000100000000,
+        0x0000000100000000,
+        0x0,
+    ]);
+}
+
+impl FpParameters for FqParameters {
+    #[rustfmt::skip]
+    const MODULUS: BigInteger = BigInteger([
+        0xffffffffffffffff,
+        0x00000000ffffffff,
+        0x0000000000000000,
+        0xffffffff00000001,
+        0x0,
+    ]);
+
+    const MODULUS_BITS: u32 = 256;
+
+    const CAPACITY: u32 = Self::MODULUS_BITS - 1;
+
+    const REPR_SHAVE_BITS: u32 = 64;
+
+    #[rustfmt::skip]
+    const R: BigInteger = BigInteger([
+        0x00000000ffffffff,
+        0x0000000100000001,
+        0xfffffffeffffffff,
+        0xfffffffe00000000,
+        0x0,
+    ]);
+
+    #[rustfmt::skip]
+    const R2: BigInteger = BigInteger([
+        0xfffffffdfffffff9,
+        0x0000000c00000002,
+        0xfffffffd00000005,
+        0xfffffff700000000,
+        0x0,
+    ]);
+
+    const INV: u64 = 0x1;
+
+    /// 6, in the Montgomery form
+    #[rustfmt::skip]
+    const GENERATOR: BigInteger = BigInteger([
+        0x00000005ffffffff,
+        0x0000000100000006,
+        0xfffffff9fffffffa,
+        0xfffffff900000000,
+        0x0,
+    ]);
+
+    #[rustfmt::skip]
+    const MODULUS_MINUS_ONE_DIV_TWO: BigInteger = BigInteger([
+        0xffffffffffffffff,
+        0x000000007fffffff,
+        0x8000000000000000,
+        0x7fffffff80000000,
+        0x0,
+    ]);
+
+    #[rustfmt::skip]
+    const T: BigInteger = BigInteger([
+        0xffffffffffffffff,
+        0x000000007fffffff,
+        0x8000000000000000,
+        0x7fffffff80000000,
+        0x0,
+    ]);
+
+    #[rustfmt::skip]
+    const T_MINUS_ONE_DIV_TWO: BigInteger = BigInteger([
+        0xffffffffffffffff,
+        0x000000003fffffff,
+        0x4000000000000000,
+        0x3fffffffc0000000,
+        0x0,
+    ]);
+}
+
+/// The scalar field of P-256, whose modulus is the order of the group
+pub type Fr = Fp320<FrParameters>;
+
+/// The parameters of `Fr`
+pub struct FrParameters;
+
+impl Fp320Parameters for FrParameters {}
+
+impl FftParameters for FrParameters {
+    type BigInt = BigInteger;
+
+    const TWO_ADICITY: u32 = 4;
+
+    #[rustfmt::skip]
+    const TWO_ADIC_ROOT_OF_UNITY: BigInteger = BigInteger([
+        0xa95cfecacfacaf9a,
+        0xd043df04eb521f95,
+        0x326cb065ce1168d3,
+        0x63483e718562561b,
+        0x0,
+    ]);
+}
+
+impl FpParameters for FrParameters {
+    #[rustfmt::skip]
+    const MODULUS: BigInteger = BigInteger([
+        0xf3b9cac2fc632551,
+        0xbce6faada7179e84,
+        0xffffffffffffffff,
+        0xffffffff00000000,
+        0x0,
+    ]);
+
+    const MODULUS_BITS: u32 = 256;
+
+    const CAPACITY: u32 = Self::MODULUS_BITS - 1;
+
+    const REPR_SHAVE_BITS: u32 = 64;
+
+    #[rustfmt::skip]
+    const R: BigInteger = BigInteger([
+        0xf756a571fc632551,
+        0x22159165b6faae70,
+        0x431905529c0166cd,
+        0xfffffffe00000001,
+        0x0,
+    ]);
+
+    #[rustfmt::skip]
+    const R2: BigInteger = BigInteger([
+        0x1d867c1433e0aef3,
+        0xec1ef1a5ef21d5d4,
+        0xe6707de7ee321683,
+        0x3241df4f6500fade,
+        0x0,
+    ]);
+
+    const INV: u64 = 0xccd1c8aaee00bc4f;
+
+    /// 7, in the Montgomery form
+    #[rustfmt::skip]
+    const GENERATOR: BigInteger = BigInteger([
+        0x0d03c58bfc632551,
+        0x812d19b6164d0df9,
+        0xd5af25424409cf9d,
+        0xfffffff800000002,
+        0x0,
+    ]);
+
+    #[rustfmt::skip]
+    const MODULUS_MINUS_ONE_DIV_TWO: BigInteger = BigInteger([
+        0x79dce5617e3192a8,
+        0xde737d56d38bcf42,
+        0x7fffffffffffffff,
+        0x7fffffff80000000,
+        0x0,
+    ]);
+
+    #[rustfmt::skip]
+    const T: BigInteger = BigInteger([
+        0x4f3b9cac2fc63255,
+        0xfbce6faada7179e8,
+        0x0fffffffffffffff,
+        0x0ffffffff0000000,
+        0x0,
+    ]);
+
+    #[rustfmt::skip]
+    const T_MINUS_ONE_DIV_TWO: BigInteger = BigInteger([
+        0x279dce5617e3192a,
+        0xfde737d56d38bcf4,
+        0x07ffffffffffffff,
+        0x07fffffff8000000,
+        0x0,
+    ]);
+}
+
+/// The curve `y^2 = x^3 - 3x + b` of P-256, also known as secp256r1
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct P256Parameters;
+
+impl ModelParameters for P256Parameters {
+    type BaseField = Fq;
+    type ScalarField = Fr;
+}
+
+impl SWModelParameters for P256Parameters {
+    /// -3
+    #[rustfmt::skip]
+    const COEFF_A: Fq = field_new!(Fq, BigInteger([
+        0xfffffffd00000000,
+        0xfffffffffffffffc,
+        0x0000000300000002,
+        0x0000000300000000,
+        0x0,
+    ]));
+
+    #[rustfmt::skip]
+    const COEFF_B: Fq = field_new!(Fq, BigInteger([
+        0xdc30061de0b74e51,
+        0xf7e5911129c4bddf,
+        0xacf005cc9c542a72,
+        0xea2968df1669e085,
+        0x0,
+    ]));
+
+    const COFACTOR: &'static [u64] = &[0x1];
+
+    /// 1
+    #[rustfmt::skip]
+    const COFACTOR_INV: Fr = field_new!(Fr, BigInteger([
+        0xf756a571fc632551,
+        0x22159165b6faae70,
+        0x431905529c0166cd,
+        0xfffffffe00000001,
+        0x0,
+    ]));
+
+    const AFFINE_GENERATOR_COEFFS: (Fq, Fq) = (G_GENERATOR_X, G_GENERATOR_Y);
+}
+
+/// The x-coordinate of the standard generator
+#[rustfmt::skip]
+pub const G_GENERATOR_X: Fq = field_new!(Fq, BigInteger([
+    0x18905f76bdc7b53d,
+    0xbc1f7b9718a9143c,
+    0x75ba95fc475d568a,
+    0x1f32c8f1b99a6fd3,
+    0x0,
+]));
+
+/// The y-coordinate of the standard generator
+#[rustfmt::skip]
+pub const G_GENERATOR_Y: Fq = field_new!(Fq, BigInteger([
+    0x8571ff18aafa5c9d,
+    0x32f7f6bace95560a,
+    0x8b4ab8e434a7e544,
+    0xf870e40d32279688,
+    0x0,
+]));
+
+/// Parameters for the P-256 fields over the 255-bit scalar field of BLS12-381, to be stored
+/// with `params::set_params` so that the limb layout does not depend on the search
+pub const PARAMS_OVER_BLS12_381_FR: NonNativeFieldParams = NonNativeFieldParams {
+    num_limbs: 3,
+    bits_per_top_limb: 68,
+    bits_per_non_top_limb: 94,
+    equality_layout: None,
+};
+
+/// Parameters for the P-256 fields over the 254-bit scalar field of BN254
+pub const PARAMS_OVER_BN254_FR: NonNativeFieldParams = NonNativeFieldParams {
+    num_limbs: 3,
+    bits_per_top_limb: 68,
+    bits_per_non_top_limb: 94,
+    equality_layout: None,
+};
+
+/// Parameters for the P-256 fields over the 298-bit fields of MNT4-298 and MNT6-298
+pub const PARAMS_OVER_MNT_298: NonNativeFieldParams = NonNativeFieldParams {
+    num_limbs: 3,
+    bits_per_top_limb: 84,
+    bits_per_non_top_limb: 86,
+    equality_layout: None,
+};
+
+/// Enforce that `(r, s)` is a valid ECDSA signature of the message hash `hash`, already
+/// truncated and reduced into the scalar field, under `public_key`
+///
+/// This checks that `r` and `s` are nonzero and that the x-coordinate of
+/// `(hash / s) · G + (r / s) · public_key` is `r` modulo the group order. The public key must
+/// be on the curve.
+#[tracing::instrument(target = "r1cs")]
+pub fn verify_ecdsa<BaseField: PrimeField>(
+    public_key: &NonNativeAffineVar<P256Parameters, BaseField>,
+    hash: &NonNativeFieldVar<Fr, BaseField>,
+    r: &NonNativeFieldVar<Fr, BaseField>,
+    s: &NonNativeFieldVar<Fr, BaseField>,
+) -> Result<(), SynthesisError> {
+    r.enforce_not_equal(&NonNativeFieldVar::zero())?;
+    let s_inv = s.inverse()?;
+    let u1 = hash * &s_inv;
+    let u2 = r * &s_inv;
+
+    let generator = NonNativeAffineVar::new(
+        NonNativeFieldVar::constant(G_GENERATOR_X),
+        NonNativeFieldVar::constant(G_GENERATOR_Y),
+    );
+    let point = generator.double_scalar_mul(&u1.to_bits_le()?, public_key, &u2.to_bits_le()?)?;
+
+    // the bits of x must be canonical, since x + p may differ from x modulo the group order
+    let x_bits = point.x.to_bits_le()?;
+    let mut p_minus_one = FqParameters::MODULUS;
+    p_minus_one.sub_noborrow(&BigInteger::from(1));
+    Boolean::enforce_smaller_or_equal_than_le(&x_bits, p_minus_one)?;
+
+    let mut x_mod_n = NonNativeFieldVar::<Fr, BaseField>::zero();
+    let mut power_of_2 = Fr::from(1u64);
+    for bit in x_bits.iter() {
+        x_mod_n += &(NonNativeFieldVar::from(bit.clone()) * power_of_2);
+        power_of_2.double_in_place();
+    }
+
+    x_mod_n.enforce_equal(r)
+}
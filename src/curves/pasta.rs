@@ -0,0 +1,313 @@
+use super::short_weierstrass::NonNativeAffineVar;
+use crate::{NonNativeFieldParams, NonNativeFieldVar};
+use ark_ec::models::{ModelParameters, SWModelParameters};
+use ark_ff::{
+    biginteger::BigInteger256 as BigInteger,
+    field_new,
+    fields::{FftParameters, Fp256, Fp256Parameters, FpParameters},
+};
+
+/// The base field of Pallas, which is the scalar field of Vesta
+pub type Fp = Fp256<PastaFpParameters>;
+
+/// The parameters of `Fp`
+pub struct PastaFpParameters;
+
+impl Fp256Parameters for PastaFpParameters {}
+
+impl FftParameters for PastaFpParameters {
+    type BigInt = BigInteger;
+
+    const TWO_ADICITY: u32 = 32;
+
+    #[rustfmt::skip]
+    const TWO_ADIC_ROOT_OF_UNITY: BigInteger = BigInteger([
+        0xa28db849bad6dbf0,
+        0x9083cd03d3b539df,
+        0xfba6b9ca9dc8448e,
+        0x3ec928747b89c6da,
+    ]);
+}
+
+impl FpParameters for PastaFpParameters {
+    #[rustfmt::skip]
+    const MODULUS: BigInteger = BigInteger([
+        0x992d30ed00000001,
+        0x224698fc094cf91b,
+        0x0000000000000000,
+        0x4000000000000000,
+    ]);
+
+    const MODULUS_BITS: u32 = 255;
+
+    const CAPACITY: u32 = Self::MODULUS_BITS - 1;
+
+    const REPR_SHAVE_BITS: u32 = 1;
+
+    #[rustfmt::skip]
+    const R: BigInteger = BigInteger([
+        0x34786d38fffffffd,
+        0x992c350be41914ad,
+        0xffffffffffffffff,
+        0x3fffffffffffffff,
+    ]);
+
+    #[rustfmt::skip]
+    const R2: BigInteger = BigInteger([
+        0x8c78ecb30000000f,
+        0xd7d30dbd8b0de0e7,
+        0x7797a99bc3c95d18,
+        0x096d41af7b9cb714,
+    ]);
+
+    const INV: u64 = 0x992d30ecffffffff;
+
+    /// 5, in the Montgomery form
+    #[rustfmt::skip]
+    const GENERATOR: BigInteger = BigInteger([
+        0xa1a55e68ffffffed,
+        0x74c2a54b4f4982f3,
+        0xfffffffffffffffd,
+        0x3fffffffffffffff,
+    ]);
+
+    #[rustfmt::skip]
+    const MODULUS_MINUS_ONE_DIV_TWO: BigInteger = BigInteger([
+        0xcc96987680000000,
+        0x11234c7e04a67c8d,
+        0x0000000000000000,
+        0x2000000000000000,
+    ]);
+
+    #[rustfmt::skip]
+    const T: BigInteger = BigInteger([
+        0x094cf91b992d30ed,
+        0x00000000224698fc,
+        0x0000000000000000,
+        0x0000000040000000,
+    ]);
+
+    #[rustfmt::skip]
+    const T_MINUS_ONE_DIV_TWO: BigInteger = BigInteger([
+        0x04a67c8dcc969876,
+        0x0000000011234c7e,
+        0x0000000000000000,
+        0x0000000020000000,
+    ]);
+}
+
+/// The base field of Vesta, which is the scalar field of Pallas
+pub type Fq = Fp256<PastaFqParameters>;
+
+/// The parameters of `Fq`
+pub struct PastaFqParameters;
+
+impl Fp256Parameters for PastaFqParameters {}
+
+impl FftParameters for PastaFqParameters {
+    type BigInt = BigInteger;
+
+    const TWO_ADICITY: u32 = 32;
+
+    #[rustfmt::skip]
+    const TWO_ADIC_ROOT_OF_UNITY: BigInteger = BigInteger([
+        0x218077428c9942de,
+        0xcc49578921b60494,
+        0xac2e5d27b2efbee2,
+        0x0b79fa897f2db056,
+    ]);
+}
+
+impl FpParameters for PastaFqParameters {
+    #[rustfmt::skip]
+    const MODULUS: BigInteger = BigInteger([
+        0x8c46eb2100000001,
+        0x224698fc0994a8dd,
+        0x0000000000000000,
+        0x4000000000000000,
+    ]);
+
+    const MODULUS_BITS: u32 = 255;
+
+    const CAPACITY: u32 = Self::MODULUS_BITS - 1;
+
+    const REPR_SHAVE_BITS: u32 = 1;
+
+    #[rustfmt::skip]
+    const R: BigInteger = BigInteger([
+        0x5b2b3e9cfffffffd,
+        0x992c350be3420567,
+        0xffffffffffffffff,
+        0x3fffffffffffffff,
+    ]);
+
+    #[rustfmt::skip]
+    const R2: BigInteger = BigInteger([
+        0xfc9678ff0000000f,
+        0x67bb433d891a16e3,
+        0x7fae231004ccf590,
+        0x096d41af7ccfdaa9,
+    ]);
+
+    const INV: u64 = 0x8c46eb20ffffffff;
+
+    /// 5, in the Montgomery form
+    #[rustfmt::skip]
+    const GENERATOR: BigInteger = BigInteger([
+        0x96bc8c8cffffffed,
+        0x74c2a54b49f7778e,
+        0xfffffffffffffffd,
+        0x3fffffffffffffff,
+    ]);
+
+    #[rustfmt::skip]
+    const MODULUS_MINUS_ONE_DIV_TWO: BigInteger = BigInteger([
+        0xc623759080000000,
+        0x11234c7e04ca546e,
+        0x0000000000000000,
+        0x2000000000000000,
+    ]);
+
+    #[rustfmt::skip]
+    const T: BigInteger = BigInteger([
+        0x0994a8dd8c46eb21,
+        0x00000000224698fc,
+        0x0000000000000000,
+        0x0000000040000000,
+    ]);
+
+    #[rustfmt::skip]
+    const T_MINUS_ONE_DIV_TWO: BigInteger = BigInteger([
+        0x04ca546ec6237590,
+        0x0000000011234c7e,
+        0x0000000000000000,
+        0x0000000020000000,
+    ]);
+}
+
+/// The curve Pallas, `y^2 = x^3 + 5` over `Fp`, whose group order is the modulus of `Fq`
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct PallasParameters;
+
+impl ModelParameters for PallasParameters {
+    type BaseField = Fp;
+    type ScalarField = Fq;
+}
+
+impl SWModelParameters for PallasParameters {
+    #[rustfmt::skip]
+    const COEFF_A: Fp = field_new!(Fp, BigInteger([0x0, 0x0, 0x0, 0x0]));
+
+    /// 5
+    #[rustfmt::skip]
+    const COEFF_B: Fp = field_new!(Fp, BigInteger([
+        0xa1a55e68ffffffed,
+        0x74c2a54b4f4982f3,
+        0xfffffffffffffffd,
+        0x3fffffffffffffff,
+    ]));
+
+    const COFACTOR: &'static [u64] = &[0x1];
+
+    /// 1
+    #[rustfmt::skip]
+    const COFACTOR_INV: Fq = field_new!(Fq, BigInteger([
+        0x5b2b3e9cfffffffd,
+        0x992c350be3420567,
+        0xffffffffffffffff,
+        0x3fffffffffffffff,
+    ]));
+
+    /// (-1, 2)
+    #[rustfmt::skip]
+    const AFFINE_GENERATOR_COEFFS: (Fp, Fp) = (
+        field_new!(Fp, BigInteger([
+            0x64b4c3b400000004,
+            0x891a63f02533e46e,
+            0x0000000000000000,
+            0x0000000000000000,
+        ])),
+        field_new!(Fp, BigInteger([
+            0xcfc3a984fffffff9,
+            0x1011d11bbee5303e,
+            0xffffffffffffffff,
+            0x3fffffffffffffff,
+        ])),
+    );
+}
+
+/// The curve Vesta, `y^2 = x^3 + 5` over `Fq`, whose group order is the modulus of `Fp`
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct VestaParameters;
+
+impl ModelParameters for VestaParameters {
+    type BaseField = Fq;
+    type ScalarField = Fp;
+}
+
+impl SWModelParameters for VestaParameters {
+    #[rustfmt::skip]
+    const COEFF_A: Fq = field_new!(Fq, BigInteger([0x0, 0x0, 0x0, 0x0]));
+
+    /// 5
+    #[rustfmt::skip]
+    const COEFF_B: Fq = field_new!(Fq, BigInteger([
+        0x96bc8c8cffffffed,
+        0x74c2a54b49f7778e,
+        0xfffffffffffffffd,
+        0x3fffffffffffffff,
+    ]));
+
+    const COFACTOR: &'static [u64] = &[0x1];
+
+    /// 1
+    #[rustfmt::skip]
+    const COFACTOR_INV: Fp = field_new!(Fp, BigInteger([
+        0x34786d38fffffffd,
+        0x992c350be41914ad,
+        0xffffffffffffffff,
+        0x3fffffffffffffff,
+    ]));
+
+    /// (-1, 2)
+    #[rustfmt::skip]
+    const AFFINE_GENERATOR_COEFFS: (Fq, Fq) = (
+        field_new!(Fq, BigInteger([
+            0x311bac8400000004,
+            0x891a63f02652a376,
+            0x0000000000000000,
+            0x0000000000000000,
+        ])),
+        field_new!(Fq, BigInteger([
+            0x2a0f9218fffffff9,
+            0x1011d11bbcef61f1,
+            0xffffffffffffffff,
+            0x3fffffffffffffff,
+        ])),
+    );
+}
+
+/// An element of `Fp`, the base field of Pallas, simulated in a constraint system over `Fq`
+pub type FpVarOverFq = NonNativeFieldVar<Fp, Fq>;
+
+/// An element of `Fq`, the base field of Vesta, simulated in a constraint system over `Fp`
+pub type FqVarOverFp = NonNativeFieldVar<Fq, Fp>;
+
+/// A Pallas point, simulated in a constraint system over `Fq`
+pub type PallasAffineVarOverFq = NonNativeAffineVar<PallasParameters, Fq>;
+
+/// A Vesta point, simulated in a constraint system over `Fp`
+pub type VestaAffineVarOverFp = NonNativeAffineVar<VestaParameters, Fp>;
+
+/// Parameters for either field of the cycle simulated over the other one, to be stored with
+/// `params::set_params`
+///
+/// Both moduli have 255 bits, so the limbs and the products of limbs leave little room for
+/// additions. This is the cheapest layout over every number of limbs, which the search picks
+/// because its cost grows with the number of limbs here.
+pub const PARAMS: NonNativeFieldParams = NonNativeFieldParams {
+    num_limbs: 3,
+    bits_per_top_limb: 69,
+    bits_per_non_top_limb: 93,
+    equality_layout: None,
+};
@@ -0,0 +1,906 @@
+use crate::{error::NonNativeFieldError, wnaf::WnafVar, NonNativeFieldVar};
+use ark_ec::{models::SWModelParameters, short_weierstrass_jacobian::GroupAffine};
+use ark_ff::{BigInteger, BitIteratorBE, FpParameters, PrimeField, Zero};
+use ark_r1cs_std::{
+    alloc::{AllocVar, AllocationMode},
+    bits::ToBitsGadget,
+    boolean::Boolean,
+    eq::EqGadget,
+    fields::FieldVar,
+    select::CondSelectGadget,
+    uint8::UInt8,
+    R1CSVar,
+};
+use ark_relations::r1cs::{ConstraintSystemRef, Namespace, SynthesisError};
+use ark_std::{borrow::Borrow, marker::PhantomData, vec, vec::Vec};
+use derivative::Derivative;
+
+/// The convention that selects one of the two square roots `y` and `-y` for a compressed point
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SignConvention {
+    /// The sign bit is the least significant bit of `y`, as in SEC 1
+    Parity,
+    /// The sign bit is set if `y` is the larger of `y` and `-y`, as in the Zcash encoding of
+    /// BLS12-381 points
+    Lexicographic,
+}
+
+/// An affine point, other than the point at infinity, of the short Weierstrass curve of `P`,
+/// whose coordinates are simulated
+///
+/// The group law uses the incomplete affine formulas, whose divisions enforce that the
+/// denominators are nonzero, so the exceptional cases make the constraint system unsatisfied
+/// instead of yielding an arbitrary point.
+#[derive(Derivative)]
+#[derivative(Clone(bound = ""), Debug(bound = ""))]
+#[must_use]
+pub struct NonNativeAffineVar<P, BaseField>
+where
+    P: SWModelParameters,
+    P::BaseField: PrimeField,
+    BaseField: PrimeField,
+{
+    /// The x-coordinate
+    pub x: NonNativeFieldVar<P::BaseField, BaseField>,
+    /// The y-coordinate
+    pub y: NonNativeFieldVar<P::BaseField, BaseField>,
+    #[doc(hidden)]
+    pub params_phantom: PhantomData<P>,
+}
+
+/// The output of `NonNativeAffineVar::add_incomplete`, whose sum is only meaningful if
+/// neither flag is set
+#[derive(Derivative)]
+#[derivative(Clone(bound = ""), Debug(bound = ""))]
+#[must_use]
+pub struct IncompleteSumVar<P, BaseField>
+where
+    P: SWModelParameters,
+    P::BaseField: PrimeField,
+    BaseField: PrimeField,
+{
+    /// The sum of the inputs
+    pub sum: NonNativeAffineVar<P, BaseField>,
+    /// Whether the inputs are equal, so the sum must be computed by a doubling
+    pub equal_inputs: Boolean<BaseField>,
+    /// Whether the inputs are inverses of each other, so the sum is the point at infinity
+    pub inverse_inputs: Boolean<BaseField>,
+}
+
+impl<P, BaseField> NonNativeAffineVar<P, BaseField>
+where
+    P: SWModelParameters,
+    P::BaseField: PrimeField,
+    BaseField: PrimeField,
+{
+    /// Create a point from its coordinates, which are not checked
+    pub fn new(
+        x: NonNativeFieldVar<P::BaseField, BaseField>,
+        y: NonNativeFieldVar<P::BaseField, BaseField>,
+    ) -> Self {
+        Self {
+            x,
+            y,
+            params_phantom: PhantomData,
+        }
+    }
+
+    /// Decompress the point with x-coordinate `x` whose y-coordinate has the sign `sign`
+    ///
+    /// The y-coordinate is a witness square root that is enforced to satisfy the curve
+    /// equation and the sign convention. If `x` is not the x-coordinate of a point, the
+    /// witness cannot be generated and `SynthesisError::Unsatisfiable` is returned.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn decompress(
+        x: &NonNativeFieldVar<P::BaseField, BaseField>,
+        sign: &Boolean<BaseField>,
+        convention: SignConvention,
+    ) -> Result<Self, SynthesisError> {
+        let cs = x.cs().or(sign.cs());
+        let y = NonNativeFieldVar::new_witness(ark_relations::ns!(cs, "y"), || {
+            let x = x.value()?;
+            let rhs = x.square() * &x + &(P::COEFF_A * &x) + &P::COEFF_B;
+            let y = rhs.sqrt().ok_or(SynthesisError::Unsatisfiable)?;
+            let y_sign = match convention {
+                SignConvention::Parity => y.into_repr().is_odd(),
+                SignConvention::Lexicographic => y > -y,
+            };
+            Ok(if y_sign == sign.value()? { y } else { -y })
+        })?;
+
+        super::enforce_on_curve::<P, BaseField>(x, &y)?;
+        match convention {
+            SignConvention::Parity => {
+                // the low bit is that of the canonical integer only once the bits are at most
+                // p - 1, since the bits of y + p have the other parity
+                let y_bits = y.to_bits_le()?;
+                let mut p_minus_one =
+                    <<P::BaseField as PrimeField>::Params as FpParameters>::MODULUS;
+                p_minus_one.sub_noborrow(&1u64.into());
+                Boolean::enforce_smaller_or_equal_than_le(&y_bits, p_minus_one)?;
+                y_bits[0].enforce_equal(sign)?;
+            }
+            SignConvention::Lexicographic => {
+                // the smaller of y and -y is at most (p - 1) / 2
+                let smaller = sign.select(&y.negate()?, &y)?;
+                Boolean::enforce_smaller_or_equal_than_le(
+                    &smaller.to_bits_le()?,
+                    <<P::BaseField as PrimeField>::Params as FpParameters>::MODULUS_MINUS_ONE_DIV_TWO,
+                )?;
+            }
+        }
+
+        Ok(Self::new(x.clone(), y))
+    }
+
+    /// Encode the point in the SEC 1 compressed form, i.e., the byte `0x02` or `0x03` for an
+    /// even or odd `y`, followed by the big-endian bytes of `x`
+    ///
+    /// The parity of `y` and the bits of `x` are those of the canonical integers, so the bytes
+    /// are the unique encoding of the point.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn to_sec1_compressed(&self) -> Result<Vec<UInt8<BaseField>>, SynthesisError> {
+        let mut prefix_bits = vec![self.y.is_odd()?, Boolean::TRUE];
+        prefix_bits.resize(8, Boolean::FALSE);
+
+        // the bits of a constant omit the leading zeros, and those of a variable may have more
+        // leading zeros than the bytes
+        let mut x_bits = self.x.to_canonical_bits_le()?;
+        x_bits.resize(8 * Self::sec1_coordinate_len(), Boolean::FALSE);
+
+        let mut res = vec![UInt8::from_bits_le(&prefix_bits)];
+        res.extend(x_bits.chunks(8).rev().map(UInt8::from_bits_le));
+        Ok(res)
+    }
+
+    /// Decode the point from its SEC 1 compressed form, as output by `to_sec1_compressed`
+    ///
+    /// The prefix is enforced to be `0x02` or `0x03` and `x` to be canonical, and the point is
+    /// decompressed with the parity convention, so only the unique encoding of a point on the
+    /// curve satisfies the constraints. Return `NonNativeFieldError::LengthMismatch` if `bytes`
+    /// has the wrong length, and `SynthesisError::Unsatisfiable` if, when generating the
+    /// witness, `x` is not on the curve.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn from_sec1_compressed(bytes: &[UInt8<BaseField>]) -> Result<Self, NonNativeFieldError> {
+        if bytes.len() != 1 + Self::sec1_coordinate_len() {
+            return Err(NonNativeFieldError::LengthMismatch {
+                expected: 1 + Self::sec1_coordinate_len(),
+                found: bytes.len(),
+            });
+        }
+
+        let prefix_bits = bytes[0].to_bits_le()?;
+        prefix_bits[1].enforce_equal(&Boolean::TRUE)?;
+        for bit in prefix_bits.iter().skip(2) {
+            bit.enforce_equal(&Boolean::FALSE)?;
+        }
+
+        let mut x_bits = Vec::with_capacity(8 * Self::sec1_coordinate_len());
+        for byte in bytes[1..].iter().rev() {
+            x_bits.extend(byte.to_bits_le()?);
+        }
+        let mut p_minus_one = <<P::BaseField as PrimeField>::Params as FpParameters>::MODULUS;
+        p_minus_one.sub_noborrow(&<P::BaseField as PrimeField>::BigInt::from(1u64));
+        Boolean::enforce_smaller_or_equal_than_le(&x_bits, p_minus_one)?;
+        // the padding bits above the modulus are zero by the comparison
+        x_bits.truncate(P::BaseField::size_in_bits());
+        let x = NonNativeFieldVar::from_bits_le(&x_bits)?;
+
+        let point = Self::decompress(&x, &prefix_bits[0], SignConvention::Parity)?;
+        Ok(point)
+    }
+
+    /// The number of bytes of a coordinate in the SEC 1 encoding
+    fn sec1_coordinate_len() -> usize {
+        (P::BaseField::size_in_bits() + 7) / 8
+    }
+
+    /// Enforce that the point is on the curve
+    #[tracing::instrument(target = "r1cs")]
+    pub fn enforce_on_curve(&self) -> Result<(), SynthesisError> {
+        super::enforce_on_curve::<P, BaseField>(&self.x, &self.y)
+    }
+
+    /// Compute `-self`
+    #[tracing::instrument(target = "r1cs")]
+    pub fn negate(&self) -> Result<Self, SynthesisError> {
+        Ok(Self::new(self.x.clone(), self.y.negate()?))
+    }
+
+    /// Compute `2 · self`, which requires `y != 0`
+    #[tracing::instrument(target = "r1cs")]
+    pub fn double(&self) -> Result<Self, SynthesisError> {
+        // λ = (3x^2 + a) / 2y
+        let x_square = self.x.square()?;
+        let numerator = &(&x_square.double()? + &x_square) + P::COEFF_A;
+        let lambda = numerator.mul_by_inverse(&self.y.double()?)?;
+
+        self.finish_addition(&lambda, &self.x)
+    }
+
+    /// Compute `self + other`, which requires `self.x != other.x`
+    #[tracing::instrument(target = "r1cs")]
+    pub fn add_unchecked(&self, other: &Self) -> Result<Self, SynthesisError> {
+        // λ = (y2 - y1) / (x2 - x1)
+        let lambda = (&other.y - &self.y).mul_by_inverse(&(&other.x - &self.x))?;
+
+        self.finish_addition(&lambda, &other.x)
+    }
+
+    /// Compute `self + other` without handling the exceptional cases in the circuit, and flag
+    /// them instead
+    ///
+    /// Unlike `add_unchecked`, the constraint system stays satisfiable when `self.x == other.x`,
+    /// where the denominator of the slope is replaced by one. The sum is then meaningless, and
+    /// the caller must constrain `equal_inputs` and `inverse_inputs`, e.g., enforce them to be
+    /// false when the structure of a multi-scalar multiplication rules these cases out, or
+    /// select a doubling or the point at infinity. The points must be on the curve.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn add_incomplete(
+        &self,
+        other: &Self,
+    ) -> Result<IncompleteSumVar<P, BaseField>, SynthesisError> {
+        let equal_x = self.x.is_eq(&other.x)?;
+        let equal_y = self.y.is_eq(&other.y)?;
+        let equal_inputs = equal_x.and(&equal_y)?;
+        let inverse_inputs = equal_x.and(&equal_y.not())?;
+
+        // λ = (y2 - y1) / (x2 - x1 + [x1 == x2])
+        let denominator = &(&other.x - &self.x) + &NonNativeFieldVar::from(equal_x);
+        let lambda = (&other.y - &self.y).mul_by_inverse(&denominator)?;
+
+        Ok(IncompleteSumVar {
+            sum: self.finish_addition(&lambda, &other.x)?,
+            equal_inputs,
+            inverse_inputs,
+        })
+    }
+
+    /// Compute the sum of `self` and a point with x-coordinate `other_x` on the line of
+    /// slope `lambda` through `self`
+    fn finish_addition(
+        &self,
+        lambda: &NonNativeFieldVar<P::BaseField, BaseField>,
+        other_x: &NonNativeFieldVar<P::BaseField, BaseField>,
+    ) -> Result<Self, SynthesisError> {
+        let x = &(&lambda.square()? - &self.x) - other_x;
+        let y = &(lambda * &(&self.x - &x)) - &self.y;
+
+        Ok(Self::new(x, y))
+    }
+
+    /// Compute `scalar · self` for a constant `scalar >= 1` with double-and-add
+    ///
+    /// The intermediate multiples must not hit the exceptional cases of the incomplete
+    /// formulas, which holds when `self` has a prime order larger than `2 · scalar`.
+    #[tracing::instrument(target = "r1cs", skip(scalar))]
+    pub fn scalar_mul_by_constant<B: BigInteger>(
+        &self,
+        scalar: &B,
+    ) -> Result<Self, SynthesisError> {
+        let mut bits = BitIteratorBE::without_leading_zeros(scalar);
+        match bits.next() {
+            Some(true) => {}
+            _ => return Err(SynthesisError::Unsatisfiable),
+        }
+
+        let mut res = self.clone();
+        for bit in bits {
+            res = res.double()?;
+            if bit {
+                res = res.add_unchecked(self)?;
+            }
+        }
+
+        Ok(res)
+    }
+
+    /// Compute `k · self` for the scalar `k` given by its little-endian `bits` with a
+    /// Montgomery ladder
+    ///
+    /// Every step performs the same operations whatever its bit, so the shape of the
+    /// constraints does not depend on the scalar. The ladder runs on x-coordinates only, with
+    /// the differential addition and doubling formulas, and recovers `y` at the end. The top
+    /// bit is enforced to be set, `k` must be smaller than `n - 1` for the order `n` of
+    /// `self`, and `self` must not be a 2-torsion point.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn scalar_mul_ladder(&self, bits: &[Boolean<BaseField>]) -> Result<Self, SynthesisError> {
+        let (top_bit, bits) = match bits.split_last() {
+            Some(split) => split,
+            None => return Err(SynthesisError::Unsatisfiable),
+        };
+        top_bit.enforce_equal(&Boolean::TRUE)?;
+
+        // the ladder keeps x(m · self) and x((m + 1) · self), whose difference is always self
+        let mut x0 = self.x.clone();
+        let mut x1 = self.x_double(&self.x)?;
+        for bit in bits.iter().rev() {
+            let sum = self.x_differential_add(&x0, &x1)?;
+            let double = self.x_double(&bit.select(&x1, &x0)?)?;
+            x0 = bit.select(&sum, &double)?;
+            x1 = bit.select(&double, &sum)?;
+        }
+
+        // y(k · self) = (2b + (a + x·x0)(x + x0) - x1·(x - x0)^2) / 2y, where x and y are
+        // the coordinates of self
+        let x = &self.x;
+        let x_minus_x0 = x - &x0;
+        let numerator = &(&(&(&(x * &x0) + P::COEFF_A) * &(x + &x0)) + P::COEFF_B.double())
+            - &(&x1 * &x_minus_x0.square()?);
+        let y0 = numerator.mul_by_inverse(&self.y.double()?)?;
+
+        Ok(Self::new(x0, y0))
+    }
+
+    /// Compute `k · self` for the scalar `k` given by its little-endian `bits`, which may be
+    /// zero or exceed the group order
+    ///
+    /// The ladder computes `(k + 2^m) · self` for the number `m` of bits, whose top bit is set,
+    /// and the offset `2^m · self` is subtracted at the end. The incomplete formulas only fail
+    /// for a negligible fraction of the scalars, and for `k = 0`.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn scalar_mul_le(&self, bits: &[Boolean<BaseField>]) -> Result<Self, SynthesisError> {
+        let mut ladder_bits = bits.to_vec();
+        ladder_bits.push(Boolean::TRUE);
+        let res = self.scalar_mul_ladder(&ladder_bits)?;
+
+        let mut offset_point = self.clone();
+        for _ in 0..bits.len() {
+            offset_point = offset_point.double()?;
+        }
+
+        res.add_unchecked(&offset_point.negate()?)
+    }
+
+    /// Compute `k1 · self + k2 · other` for the scalars `k1` and `k2` given by their
+    /// little-endian `self_bits` and `other_bits`, as in signature and proof verification
+    ///
+    /// Each product is computed with `scalar_mul_le`, so the same exceptional cases apply, and
+    /// the sum must not be computed from two points with the same x-coordinate.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn dual_scalar_mul(
+        &self,
+        self_bits: &[Boolean<BaseField>],
+        other: &Self,
+        other_bits: &[Boolean<BaseField>],
+    ) -> Result<Self, SynthesisError> {
+        let self_product = self.scalar_mul_le(self_bits)?;
+        let other_product = other.scalar_mul_le(other_bits)?;
+
+        self_product.add_unchecked(&other_product)
+    }
+
+    /// Compute `a · self + b · other` for the scalars `a` and `b` given by their little-endian
+    /// `self_bits` and `other_bits` with Shamir's trick
+    ///
+    /// The scalars are scanned together in windows of two bits, and the table holds
+    /// `i · self + j · other` for `i, j < 4`, so the doublings are shared and each window costs
+    /// one lookup and one addition. This takes about half the group operations of
+    /// `dual_scalar_mul`. As in `scalar_mul_le`, the accumulator starts at `self`, whose
+    /// multiple is subtracted at the end. The incomplete formulas only fail for a negligible
+    /// fraction of the scalars, for `a = b = 0`, and when `other` is a small multiple of `self`,
+    /// as the table requires `self` and `other` to be independent.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn double_scalar_mul(
+        &self,
+        self_bits: &[Boolean<BaseField>],
+        other: &Self,
+        other_bits: &[Boolean<BaseField>],
+    ) -> Result<Self, SynthesisError> {
+        // the multiples of self and other by 0, 1, 2, and 3, where the zero multiples are
+        // placeholders that are never added
+        let self_double = self.double()?;
+        let self_multiples = [
+            self.clone(),
+            self.clone(),
+            self_double.clone(),
+            self_double.add_unchecked(self)?,
+        ];
+        let other_double = other.double()?;
+        let other_multiples = [
+            other.clone(),
+            other.clone(),
+            other_double.clone(),
+            other_double.add_unchecked(other)?,
+        ];
+
+        // the entry at index i + 4j is i · self + j · other
+        let mut table = Vec::with_capacity(16);
+        for j in 0..4 {
+            for i in 0..4 {
+                table.push(match (i, j) {
+                    (_, 0) => self_multiples[i].clone(),
+                    (0, _) => other_multiples[j].clone(),
+                    _ => self_multiples[i].add_unchecked(&other_multiples[j])?,
+                });
+            }
+        }
+
+        let num_bits = ark_std::cmp::max(self_bits.len(), other_bits.len());
+        let num_windows = (num_bits + 1) / 2;
+        let bit =
+            |bits: &[Boolean<BaseField>], i: usize| bits.get(i).cloned().unwrap_or(Boolean::FALSE);
+
+        let mut res = self.clone();
+        for window in (0..num_windows).rev() {
+            res = res.double()?.double()?;
+
+            let index = [
+                bit(self_bits, 2 * window),
+                bit(self_bits, 2 * window + 1),
+                bit(other_bits, 2 * window),
+                bit(other_bits, 2 * window + 1),
+            ];
+            let sum = res.add_unchecked(&lookup(&table, &index)?)?;
+            res = Boolean::kary_or(&index)?.select(&sum, &res)?;
+        }
+
+        let mut offset_point = self.clone();
+        for _ in 0..2 * num_windows {
+            offset_point = offset_point.double()?;
+        }
+
+        res.add_unchecked(&offset_point.negate()?)
+    }
+
+    /// Compute `k · self` for the scalar `k` given by its windowed non-adjacent form `wnaf`
+    ///
+    /// The table holds `2^t · m · self` for the odd `m < 2^(window - 1)` and `t < window`. Each
+    /// aligned block of `window` digits has at most one nonzero digit, so it costs one lookup
+    /// and one addition besides the doublings, instead of one addition per bit. As in
+    /// `scalar_mul_le`, the accumulator starts at `self`, whose multiple `2^m · self` for the
+    /// number `m` of padded digits is subtracted at the end, and the incomplete formulas only
+    /// fail for a negligible fraction of the scalars, and for `k = 0`. Return
+    /// `NonNativeFieldError::InvalidWindow` if the window is smaller than 2, and
+    /// `NonNativeFieldError::LengthMismatch` if a digit does not have `window - 2` bits of
+    /// magnitude.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn scalar_mul_wnaf(&self, wnaf: &WnafVar<BaseField>) -> Result<Self, NonNativeFieldError> {
+        let window = wnaf.window;
+        if window < 2 {
+            return Err(NonNativeFieldError::InvalidWindow {
+                window,
+                reason: "the window must be at least 2",
+            });
+        }
+        for digit in wnaf.digits.iter() {
+            if digit.magnitude.len() != window - 2 {
+                return Err(NonNativeFieldError::LengthMismatch {
+                    expected: window - 2,
+                    found: digit.magnitude.len(),
+                });
+            }
+        }
+
+        let mut odd_multiples = vec![self.clone()];
+        let double = self.double()?;
+        for _ in 1..1usize << (window - 2) {
+            let next = odd_multiples[odd_multiples.len() - 1].add_unchecked(&double)?;
+            odd_multiples.push(next);
+        }
+        let mut table = vec![odd_multiples];
+        for _ in 1..window {
+            let row = table[table.len() - 1]
+                .iter()
+                .map(|point| point.double())
+                .collect::<Result<Vec<_>, _>>()?;
+            table.push(row);
+        }
+
+        let num_blocks = (wnaf.digits.len() + window - 1) / window;
+        let mut res = self.clone();
+        for block in wnaf.digits.chunks(window).rev() {
+            for _ in 0..window {
+                res = res.double()?;
+            }
+
+            let mut term: Option<Self> = None;
+            let mut is_nonzero = Vec::with_capacity(window);
+            for (digit, row) in block.iter().zip(table.iter()) {
+                let entry = lookup(row, &digit.magnitude)?;
+                let entry = digit.is_negative.select(&entry.negate()?, &entry)?;
+                term = Some(match term {
+                    Some(term) => digit.is_nonzero.select(&entry, &term)?,
+                    None => entry,
+                });
+                is_nonzero.push(digit.is_nonzero.clone());
+            }
+            let term = term.ok_or(SynthesisError::Unsatisfiable)?;
+            let sum = res.add_unchecked(&term)?;
+            res = Boolean::kary_or(&is_nonzero)?.select(&sum, &res)?;
+        }
+
+        let mut offset_point = self.clone();
+        for _ in 0..num_blocks * window {
+            offset_point = offset_point.double()?;
+        }
+
+        Ok(res.add_unchecked(&offset_point.negate()?)?)
+    }
+
+    /// Compute x(2 · R) from x(R) as `((x^2 - a)^2 - 8b·x) / 4(x^3 + a·x + b)`
+    fn x_double(
+        &self,
+        x: &NonNativeFieldVar<P::BaseField, BaseField>,
+    ) -> Result<NonNativeFieldVar<P::BaseField, BaseField>, SynthesisError> {
+        let x_square = x.square()?;
+        let eight_b = P::COEFF_B.double().double().double();
+        let numerator = &(&x_square - P::COEFF_A).square()? - &(x * eight_b);
+        let denominator = (&(&(&x_square * x) + &(x * P::COEFF_A)) + P::COEFF_B)
+            .double()?
+            .double()?;
+
+        numerator.mul_by_inverse(&denominator)
+    }
+
+    /// Compute x(R + S) from x(R) and x(S), where `S - R = self`, as
+    /// `(2(x_R + x_S)(x_R·x_S + a) + 4b) / (x_R - x_S)^2 - x_self`
+    ///
+    /// This is the additive form of the formula, which does not divide by `x_self`, so it also
+    /// holds for the points with `x = 0`, which exist when `b` is a square.
+    fn x_differential_add(
+        &self,
+        x_r: &NonNativeFieldVar<P::BaseField, BaseField>,
+        x_s: &NonNativeFieldVar<P::BaseField, BaseField>,
+    ) -> Result<NonNativeFieldVar<P::BaseField, BaseField>, SynthesisError> {
+        let four_b = P::COEFF_B.double().double();
+        let numerator = &(&(x_r + x_s) * &(&(x_r * x_s) + P::COEFF_A)).double()? + four_b;
+        let denominator = (x_r - x_s).square()?;
+
+        Ok(&numerator.mul_by_inverse(&denominator)? - &self.x)
+    }
+
+    /// Enforce that the point lies in the prime-order subgroup
+    ///
+    /// For a cofactor of one, every point on the curve is in the subgroup. Otherwise, this
+    /// checks `[n - 1] · self = -self` for the subgroup order `n`, which implies `[n] · self`
+    /// is the point at infinity. The point must be on the curve. For G1 of BLS12-381,
+    /// `curves::bls12_381::enforce_in_g1` uses an endomorphism to halve the cost.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn enforce_in_prime_order_subgroup(&self) -> Result<(), SynthesisError> {
+        if P::COFACTOR.first() == Some(&1) && P::COFACTOR.iter().skip(1).all(|limb| *limb == 0) {
+            return Ok(());
+        }
+
+        let mut n_minus_one = <<P::ScalarField as PrimeField>::Params as FpParameters>::MODULUS;
+        n_minus_one.sub_noborrow(&<P::ScalarField as PrimeField>::BigInt::from(1u64));
+
+        let res = self.scalar_mul_by_constant(&n_minus_one)?;
+        let minus_self = self.negate()?;
+        res.x.enforce_equal(&minus_self.x)?;
+        res.y.enforce_equal(&minus_self.y)
+    }
+}
+
+/// The number of bases that share a table of subset sums in
+/// `NonNativeProjectiveVar::multi_scalar_mul_le`
+///
+/// Each lookup in a table of `2^MSM_CHUNK_SIZE` entries costs `2^MSM_CHUNK_SIZE - 1` selections
+/// of a point, which stays cheaper than the additions it saves for chunks of up to four bases.
+pub const MSM_CHUNK_SIZE: usize = 4;
+
+/// A point of the short Weierstrass curve of `P` in homogeneous projective coordinates
+/// `(X : Y : Z)`, which stands for the affine point `(X / Z, Y / Z)`, or the point at infinity
+/// if `Z = 0`
+///
+/// The group law uses the complete formulas of Renes, Costello, and Batina, which have no
+/// exceptional cases on curves of odd order, so the sum is correct even for equal, inverse, or
+/// infinite inputs chosen by an adversary. An addition costs twelve multiplications and a few
+/// multiplications by the constants of the curve, whatever its inputs.
+#[derive(Derivative)]
+#[derivative(Clone(bound = ""), Debug(bound = ""))]
+#[must_use]
+pub struct NonNativeProjectiveVar<P, BaseField>
+where
+    P: SWModelParameters,
+    P::BaseField: PrimeField,
+    BaseField: PrimeField,
+{
+    /// The X coordinate
+    pub x: NonNativeFieldVar<P::BaseField, BaseField>,
+    /// The Y coordinate
+    pub y: NonNativeFieldVar<P::BaseField, BaseField>,
+    /// The Z coordinate
+    pub z: NonNativeFieldVar<P::BaseField, BaseField>,
+    #[doc(hidden)]
+    pub params_phantom: PhantomData<P>,
+}
+
+impl<P, BaseField> NonNativeProjectiveVar<P, BaseField>
+where
+    P: SWModelParameters,
+    P::BaseField: PrimeField,
+    BaseField: PrimeField,
+{
+    /// Create a point from its coordinates, which are not checked
+    pub fn new(
+        x: NonNativeFieldVar<P::BaseField, BaseField>,
+        y: NonNativeFieldVar<P::BaseField, BaseField>,
+        z: NonNativeFieldVar<P::BaseField, BaseField>,
+    ) -> Self {
+        Self {
+            x,
+            y,
+            z,
+            params_phantom: PhantomData,
+        }
+    }
+
+    /// The point at infinity, `(0 : 1 : 0)`
+    pub fn zero() -> Self {
+        Self::new(
+            NonNativeFieldVar::zero(),
+            NonNativeFieldVar::one(),
+            NonNativeFieldVar::zero(),
+        )
+    }
+
+    /// The affine point `point`, `(x : y : 1)`
+    pub fn from_affine(point: &NonNativeAffineVar<P, BaseField>) -> Self {
+        Self::new(point.x.clone(), point.y.clone(), NonNativeFieldVar::one())
+    }
+
+    /// Output the affine coordinates, which requires the point not to be the point at infinity
+    #[tracing::instrument(target = "r1cs")]
+    pub fn to_affine(&self) -> Result<NonNativeAffineVar<P, BaseField>, SynthesisError> {
+        let z_inv = self.z.inverse()?;
+        Ok(NonNativeAffineVar::new(&self.x * &z_inv, &self.y * &z_inv))
+    }
+
+    /// Output whether the point is the point at infinity
+    #[tracing::instrument(target = "r1cs")]
+    pub fn is_zero(&self) -> Result<Boolean<BaseField>, SynthesisError> {
+        self.z.is_eq(&NonNativeFieldVar::zero())
+    }
+
+    /// Compute `-self`
+    #[tracing::instrument(target = "r1cs")]
+    pub fn negate(&self) -> Result<Self, SynthesisError> {
+        Ok(Self::new(self.x.clone(), self.y.negate()?, self.z.clone()))
+    }
+
+    /// Compute `self + other` with Algorithm 1 of Renes, Costello, and Batina for any `a`
+    #[tracing::instrument(target = "r1cs")]
+    pub fn add(&self, other: &Self) -> Result<Self, SynthesisError> {
+        let b3 = P::COEFF_B.double() + &P::COEFF_B;
+
+        let t0 = &self.x * &other.x;
+        let t1 = &self.y * &other.y;
+        let t2 = &self.z * &other.z;
+        let t3 = &(&(&self.x + &self.y) * &(&other.x + &other.y)) - &(&t0 + &t1);
+        let t4 = &(&(&self.x + &self.z) * &(&other.x + &other.z)) - &(&t0 + &t2);
+        let t5 = &(&(&self.y + &self.z) * &(&other.y + &other.z)) - &(&t1 + &t2);
+
+        let z3 = &(&t4 * P::COEFF_A) + &(&t2 * b3);
+        let x3 = &t1 - &z3;
+        let z3 = &t1 + &z3;
+        let y3 = &x3 * &z3;
+
+        let t1 = &(&t0.double()? + &t0) + &(&t2 * P::COEFF_A);
+        let t2 = &(&t0 - &(&t2 * P::COEFF_A)) * P::COEFF_A;
+        let t4 = &(&t4 * b3) + &t2;
+
+        let y3 = &y3 + &(&t1 * &t4);
+        let x3 = &(&t3 * &x3) - &(&t5 * &t4);
+        let z3 = &(&t5 * &z3) + &(&t3 * &t1);
+
+        Ok(Self::new(x3, y3, z3))
+    }
+
+    /// Compute `2 · self` with the complete addition formulas
+    #[tracing::instrument(target = "r1cs")]
+    pub fn double(&self) -> Result<Self, SynthesisError> {
+        self.add(self)
+    }
+
+    /// Compute `k · self` for the scalar `k` given by its little-endian `bits`, with
+    /// double-and-add
+    ///
+    /// The formulas are complete, so this is correct for every scalar and every point, at the
+    /// cost of one doubling and one addition per bit.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn scalar_mul_le(&self, bits: &[Boolean<BaseField>]) -> Result<Self, SynthesisError> {
+        let mut res = Self::zero();
+        for bit in bits.iter().rev() {
+            res = res.double()?;
+            let sum = res.add(self)?;
+            res = bit.select(&sum, &res)?;
+        }
+        Ok(res)
+    }
+
+    /// Compute `Σ k_i · bases[i]` for the scalars `k_i` given by their little-endian `bits[i]`
+    ///
+    /// The bases are split into chunks of `MSM_CHUNK_SIZE`, and each chunk gets a table of the
+    /// sums of its subsets, indexed by the bits of its scalars at the same position, as in
+    /// Shamir's trick. The scalars are then scanned together from the most significant bit, so
+    /// each bit costs one doubling, and one lookup and one addition per chunk instead of one
+    /// addition per base. For `m` bases and `n` bits, this takes about
+    /// `n · (1 + ⌈m / MSM_CHUNK_SIZE⌉)` complete additions, plus
+    /// `2^MSM_CHUNK_SIZE - MSM_CHUNK_SIZE - 1` additions per chunk for the tables, each of them
+    /// 12 nonnative multiplications. The formulas are complete, so the bases may be chosen by an
+    /// adversary, and the empty subset is the point at infinity. Return
+    /// `NonNativeFieldError::LengthMismatch` if there are not as many scalars as bases.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn multi_scalar_mul_le(
+        bases: &[Self],
+        bits: &[Vec<Boolean<BaseField>>],
+    ) -> Result<Self, NonNativeFieldError> {
+        if bases.len() != bits.len() {
+            return Err(NonNativeFieldError::LengthMismatch {
+                expected: bases.len(),
+                found: bits.len(),
+            });
+        }
+
+        // the entry of a table at index i is the sum of the bases of the chunk at the set bits
+        // of i
+        let mut tables = Vec::with_capacity((bases.len() + MSM_CHUNK_SIZE - 1) / MSM_CHUNK_SIZE);
+        for chunk in bases.chunks(MSM_CHUNK_SIZE) {
+            let mut table = vec![Self::zero()];
+            for base in chunk.iter() {
+                let mut sums = vec![base.clone()];
+                for entry in table.iter().skip(1) {
+                    sums.push(entry.add(base)?);
+                }
+                table.extend(sums);
+            }
+            tables.push(table);
+        }
+
+        let num_bits = bits.iter().map(|bits| bits.len()).max().unwrap_or(0);
+        let mut res: Option<Self> = None;
+        for i in (0..num_bits).rev() {
+            if let Some(acc) = res.as_mut() {
+                *acc = acc.double()?;
+            }
+            for (table, bits) in tables.iter().zip(bits.chunks(MSM_CHUNK_SIZE)) {
+                let index = bits
+                    .iter()
+                    .map(|bits| bits.get(i).cloned().unwrap_or(Boolean::FALSE))
+                    .collect::<Vec<_>>();
+                let entry = lookup(table, &index)?;
+                res = Some(match res {
+                    Some(acc) => acc.add(&entry)?,
+                    None => entry,
+                });
+            }
+        }
+        Ok(res.unwrap_or_else(Self::zero))
+    }
+}
+
+/// Select the entry of `table` at the little-endian `index`, with a tree of selections
+fn lookup<T, BaseField>(table: &[T], index: &[Boolean<BaseField>]) -> Result<T, SynthesisError>
+where
+    T: CondSelectGadget<BaseField> + Clone,
+    BaseField: PrimeField,
+{
+    let mut level = table.to_vec();
+    for bit in index.iter() {
+        level = level
+            .chunks(2)
+            .map(|pair| bit.select(&pair[1], &pair[0]))
+            .collect::<Result<Vec<_>, _>>()?;
+    }
+    Ok(level[0].clone())
+}
+
+impl<P, BaseField> R1CSVar<BaseField> for NonNativeProjectiveVar<P, BaseField>
+where
+    P: SWModelParameters,
+    P::BaseField: PrimeField,
+    BaseField: PrimeField,
+{
+    type Value = GroupAffine<P>;
+
+    fn cs(&self) -> ConstraintSystemRef<BaseField> {
+        self.x.cs().or(self.y.cs()).or(self.z.cs())
+    }
+
+    fn value(&self) -> Result<Self::Value, SynthesisError> {
+        let z = self.z.value()?;
+        match z.inverse() {
+            Some(z_inv) => Ok(GroupAffine::new(
+                self.x.value()? * &z_inv,
+                self.y.value()? * &z_inv,
+                false,
+            )),
+            None => Ok(GroupAffine::zero()),
+        }
+    }
+}
+
+impl<P, BaseField> CondSelectGadget<BaseField> for NonNativeProjectiveVar<P, BaseField>
+where
+    P: SWModelParameters,
+    P::BaseField: PrimeField,
+    BaseField: PrimeField,
+{
+    #[tracing::instrument(target = "r1cs")]
+    fn conditionally_select(
+        cond: &Boolean<BaseField>,
+        true_value: &Self,
+        false_value: &Self,
+    ) -> Result<Self, SynthesisError> {
+        Ok(Self::new(
+            cond.select(&true_value.x, &false_value.x)?,
+            cond.select(&true_value.y, &false_value.y)?,
+            cond.select(&true_value.z, &false_value.z)?,
+        ))
+    }
+}
+
+impl<P, BaseField> R1CSVar<BaseField> for NonNativeAffineVar<P, BaseField>
+where
+    P: SWModelParameters,
+    P::BaseField: PrimeField,
+    BaseField: PrimeField,
+{
+    type Value = GroupAffine<P>;
+
+    fn cs(&self) -> ConstraintSystemRef<BaseField> {
+        self.x.cs().or(self.y.cs())
+    }
+
+    fn value(&self) -> Result<Self::Value, SynthesisError> {
+        Ok(GroupAffine::new(self.x.value()?, self.y.value()?, false))
+    }
+}
+
+impl<P, BaseField> CondSelectGadget<BaseField> for NonNativeAffineVar<P, BaseField>
+where
+    P: SWModelParameters,
+    P::BaseField: PrimeField,
+    BaseField: PrimeField,
+{
+    #[tracing::instrument(target = "r1cs")]
+    fn conditionally_select(
+        cond: &Boolean<BaseField>,
+        true_value: &Self,
+        false_value: &Self,
+    ) -> Result<Self, SynthesisError> {
+        Ok(Self::new(
+            cond.select(&true_value.x, &false_value.x)?,
+            cond.select(&true_value.y, &false_value.y)?,
+        ))
+    }
+}
+
+impl<P, BaseField> AllocVar<GroupAffine<P>, BaseField> for NonNativeAffineVar<P, BaseField>
+where
+    P: SWModelParameters,
+    P::BaseField: PrimeField,
+    BaseField: PrimeField,
+{
+    /// Allocate a point, which must not be the point at infinity and is not checked to be on
+    /// the curve
+    fn new_variable<T: Borrow<GroupAffine<P>>>(
+        cs: impl Into<Namespace<BaseField>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let ns = cs.into();
+        let cs = ns.cs();
+
+        let point = f().map(|p| *p.borrow());
+        if let Ok(point) = point {
+            if point.infinity {
+                return Err(SynthesisError::Unsatisfiable);
+            }
+        }
+
+        let x = NonNativeFieldVar::new_variable(
+            ark_relations::ns!(cs, "x"),
+            || point.map(|p| p.x),
+            mode,
+        )?;
+        let y = NonNativeFieldVar::new_variable(
+            ark_relations::ns!(cs, "y"),
+            || point.map(|p| p.y),
+            mode,
+        )?;
+
+        Ok(Self::new(x, y))
+    }
+}
@@ -0,0 +1,124 @@
+use crate::curves::short_weierstrass::NonNativeAffineVar;
+use crate::poseidon::{PoseidonParameters, PoseidonSpongeVar};
+use crate::NonNativeFieldVar;
+use ark_ec::models::SWModelParameters;
+use ark_ff::{BitIteratorLE, PrimeField};
+use ark_r1cs_std::{alloc::AllocVar, bits::ToBitsGadget, boolean::Boolean, eq::EqGadget, R1CSVar};
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use ark_std::vec::Vec;
+use derivative::Derivative;
+
+/// The number of bits of a challenge, which are the least significant bits of the first
+/// element squeezed from the transcript
+pub const CHALLENGE_BITS: usize = 128;
+
+/// A proof that `a = x · g` and `b = x · h` for the same `x`, in the Chaum–Pedersen protocol
+/// made non-interactive with a Poseidon transcript
+///
+/// For the nonce `r` of the prover, the commitments are `t1 = r · g` and `t2 = r · h`, the
+/// challenge `c` is given by `challenge_bits` on `g, h, a, b, t1, t2`, and the response is
+/// `s = r - c · x`.
+#[derive(Derivative)]
+#[derivative(Clone(bound = ""), Debug(bound = ""))]
+pub struct DleqProofVar<P, BaseField>
+where
+    P: SWModelParameters,
+    P::BaseField: PrimeField,
+    P::ScalarField: PrimeField,
+    BaseField: PrimeField,
+{
+    /// The challenge `c`
+    pub challenge: NonNativeFieldVar<P::ScalarField, BaseField>,
+    /// The response `s`
+    pub response: NonNativeFieldVar<P::ScalarField, BaseField>,
+}
+
+/// Compute the little-endian bits of the challenge of a transcript of `points`
+///
+/// The coordinates of the points are absorbed into a Poseidon sponge over the base field of
+/// the curve, and the challenge is given by the lowest `CHALLENGE_BITS` bits of the canonical
+/// integer of the first squeezed element. For constant points, so does a prover outside of
+/// the circuit.
+#[tracing::instrument(target = "r1cs", skip(parameters))]
+pub fn challenge_bits<P, BaseField>(
+    parameters: &PoseidonParameters<P::BaseField>,
+    points: &[&NonNativeAffineVar<P, BaseField>],
+) -> Result<Vec<Boolean<BaseField>>, SynthesisError>
+where
+    P: SWModelParameters,
+    P::BaseField: PrimeField,
+    BaseField: PrimeField,
+{
+    let cs = points
+        .iter()
+        .fold(ConstraintSystemRef::None, |cs, point| cs.or(point.cs()));
+    let mut sponge = PoseidonSpongeVar::new(cs, parameters);
+    let mut coordinates = Vec::with_capacity(2 * points.len());
+    for point in points.iter() {
+        coordinates.push(point.x.clone());
+        coordinates.push(point.y.clone());
+    }
+    sponge.absorb(&coordinates)?;
+    let element = sponge.squeeze(1)?.remove(0);
+
+    let mut bits = element.to_canonical_bits_le()?;
+    bits.resize(CHALLENGE_BITS, Boolean::FALSE);
+    Ok(bits)
+}
+
+/// Enforce that `proof` shows that `a = x · g` and `b = x · h` for the same `x`
+///
+/// The commitments are recomputed as `t1 = s · g + c · a` and `t2 = s · h + c · b` with
+/// `dual_scalar_mul`, and the challenge of the transcript is enforced to be `c`. The challenge
+/// is witnessed in `CHALLENGE_BITS` bits, which are used for the scalar multiplications. The
+/// points must be on the curve and in the prime-order subgroup, and the scalar
+/// multiplications share the exceptional cases of `dual_scalar_mul`.
+#[tracing::instrument(target = "r1cs", skip(parameters))]
+pub fn verify_dleq<P, BaseField>(
+    parameters: &PoseidonParameters<P::BaseField>,
+    g: &NonNativeAffineVar<P, BaseField>,
+    h: &NonNativeAffineVar<P, BaseField>,
+    a: &NonNativeAffineVar<P, BaseField>,
+    b: &NonNativeAffineVar<P, BaseField>,
+    proof: &DleqProofVar<P, BaseField>,
+) -> Result<(), SynthesisError>
+where
+    P: SWModelParameters,
+    P::BaseField: PrimeField,
+    P::ScalarField: PrimeField,
+    BaseField: PrimeField,
+{
+    let cs = proof
+        .challenge
+        .cs()
+        .or(proof.response.cs())
+        .or(a.cs())
+        .or(b.cs());
+
+    // the challenge has at most `CHALLENGE_BITS` bits, so its bits are witnessed and packed
+    let challenge_value = proof.challenge.value();
+    let mut challenge_bits_var = Vec::with_capacity(CHALLENGE_BITS);
+    for i in 0..CHALLENGE_BITS {
+        challenge_bits_var.push(Boolean::new_witness(
+            ark_relations::ns!(cs, "challenge bit"),
+            || {
+                let challenge = challenge_value?;
+                Ok(BitIteratorLE::new(challenge.into_repr())
+                    .nth(i)
+                    .unwrap_or(false))
+            },
+        )?);
+    }
+    NonNativeFieldVar::from_bits_le(&challenge_bits_var)?.enforce_equal(&proof.challenge)?;
+
+    let response_bits = proof.response.to_bits_le()?;
+    let t1 = g.dual_scalar_mul(&response_bits, a, &challenge_bits_var)?;
+    let t2 = h.dual_scalar_mul(&response_bits, b, &challenge_bits_var)?;
+
+    let expected_bits = challenge_bits(parameters, &[g, h, a, b, &t1, &t2])?;
+    for (bit, expected_bit) in challenge_bits_var.iter().zip(expected_bits.iter()) {
+        bit.enforce_equal(expected_bit)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,140 @@
+use crate::NonNativeFieldParams;
+use ark_relations::r1cs::SynthesisError;
+use core::fmt;
+
+/// The errors of the functions of this crate that do not implement the traits of ark-r1cs-std
+///
+/// The gadgets that implement the traits return a `SynthesisError`, into which these errors
+/// convert. The conversion keeps the errors of the constraint system and turns the others into
+/// `SynthesisError::Unsatisfiable`, after emitting their description as a `tracing` error event.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NonNativeFieldError {
+    /// An error of the constraint system
+    Synthesis(SynthesisError),
+    /// The search found no parameters for a target field and a base field of these sizes
+    NoParameters {
+        /// The number of bits of the target field
+        target_bits: usize,
+        /// The number of bits of the base field
+        base_bits: usize,
+    },
+    /// The parameters do not suit the target field and the base field
+    InvalidParameters {
+        /// The rejected parameters
+        params: NonNativeFieldParams,
+        /// The requirement that the parameters violate
+        reason: &'static str,
+    },
+    /// Different parameters are already in use for the sizes of the two fields
+    ParametersInUse {
+        /// The parameters in use
+        current: NonNativeFieldParams,
+        /// The parameters that were to replace them
+        requested: NonNativeFieldParams,
+    },
+    /// The dimensions of the Poseidon parameters are inconsistent
+    InvalidPoseidonParameters {
+        /// The requirement that the parameters violate
+        reason: &'static str,
+    },
+    /// An input does not have a length that the function accepts
+    InvalidLength {
+        /// The length of the input
+        length: usize,
+        /// The requirement that the length violates
+        reason: &'static str,
+    },
+    /// Two inputs that must have the same length do not
+    LengthMismatch {
+        /// The length of the first input
+        expected: usize,
+        /// The length of the second input
+        found: usize,
+    },
+    /// The window of a windowed non-adjacent form is not supported
+    InvalidWindow {
+        /// The rejected window
+        window: usize,
+        /// The requirement that the window violates
+        reason: &'static str,
+    },
+    /// The limbs of an unreduced product are too large for one more addition
+    Overflow {
+        /// The operation that would overflow, e.g., `add`
+        operation: &'static str,
+        /// The number of bits that the limbs of the sum would have
+        bits: usize,
+        /// The largest number of bits of a limb that the reduction can handle
+        max_bits: usize,
+    },
+}
+
+impl fmt::Display for NonNativeFieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Synthesis(error) => error.fmt(f),
+            Self::NoParameters {
+                target_bits,
+                base_bits,
+            } => write!(
+                f,
+                "no limb parameters exist for a {}-bit target field in a {}-bit base field",
+                target_bits, base_bits
+            ),
+            Self::InvalidParameters { params, reason } => {
+                write!(f, "invalid limb parameters {:?}: {}", params, reason)
+            }
+            Self::ParametersInUse { current, requested } => write!(
+                f,
+                "cannot replace the limb parameters {:?} in use with {:?}",
+                current, requested
+            ),
+            Self::InvalidPoseidonParameters { reason } => {
+                write!(f, "invalid Poseidon parameters: {}", reason)
+            }
+            Self::InvalidLength { length, reason } => {
+                write!(f, "invalid length {}: {}", length, reason)
+            }
+            Self::LengthMismatch { expected, found } => write!(
+                f,
+                "mismatching lengths: expected {}, found {}",
+                expected, found
+            ),
+            Self::InvalidWindow { window, reason } => {
+                write!(f, "invalid wNAF window {}: {}", window, reason)
+            }
+            Self::Overflow {
+                operation,
+                bits,
+                max_bits,
+            } => write!(
+                f,
+                "`{}` would produce {}-bit limbs (max {}); call `reduce()` on the operands \
+                 first to normalize them",
+                operation, bits, max_bits
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for NonNativeFieldError {}
+
+impl From<SynthesisError> for NonNativeFieldError {
+    fn from(error: SynthesisError) -> Self {
+        Self::Synthesis(error)
+    }
+}
+
+impl From<NonNativeFieldError> for SynthesisError {
+    fn from(error: NonNativeFieldError) -> Self {
+        match error {
+            NonNativeFieldError::Synthesis(error) => error,
+            error => {
+                // `SynthesisError` has no variant to carry the description, so it is logged
+                tracing::error!(target: "r1cs", "{}", error);
+                SynthesisError::Unsatisfiable
+            }
+        }
+    }
+}
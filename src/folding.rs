@@ -0,0 +1,35 @@
+use crate::{accumulator::Accumulator, error::NonNativeFieldError, NonNativeFieldVar};
+use ark_ff::PrimeField;
+use ark_r1cs_std::boolean::Boolean;
+use ark_std::vec::Vec;
+
+/// Compute `u_i + r · v_i` for every `i`, for the native challenge `r` given by its
+/// little-endian bits, as in the folding of the instances of Nova-style schemes
+///
+/// The bits are packed into the limbs of `r` without constraints, and each entry is reduced
+/// once. The outputs are fresh reductions, so their bounds do not grow when they are folded
+/// again, however many times this is repeated. Return `NonNativeFieldError::LengthMismatch`
+/// if `u` and `v` do not have the same length.
+#[tracing::instrument(target = "r1cs")]
+pub fn fold<TargetField: PrimeField, BaseField: PrimeField>(
+    r_bits: &[Boolean<BaseField>],
+    u: &[NonNativeFieldVar<TargetField, BaseField>],
+    v: &[NonNativeFieldVar<TargetField, BaseField>],
+) -> Result<Vec<NonNativeFieldVar<TargetField, BaseField>>, NonNativeFieldError> {
+    if u.len() != v.len() {
+        return Err(NonNativeFieldError::LengthMismatch {
+            expected: u.len(),
+            found: v.len(),
+        });
+    }
+
+    let r = NonNativeFieldVar::from_bits_le(r_bits)?;
+    let mut res = Vec::with_capacity(u.len());
+    for (u_i, v_i) in u.iter().zip(v.iter()) {
+        let mut acc = Accumulator::new();
+        acc.add(u_i)?;
+        acc.add_product(v_i, &r)?;
+        res.push(acc.finalize()?);
+    }
+    Ok(res)
+}
@@ -0,0 +1,246 @@
+use ark_ff::PrimeField;
+use ark_r1cs_std::fields::fp::AllocatedFp;
+use ark_relations::r1cs::ConstraintSystemRef;
+#[cfg(feature = "expression-graph")]
+use ark_relations::r1cs::{SynthesisError, Variable};
+use ark_std::marker::PhantomData;
+#[cfg(feature = "expression-graph")]
+use ark_std::{
+    any::TypeId, boxed::Box, collections::BTreeMap, fmt::Write, string::String, vec::Vec,
+};
+
+/// A node of an `ExpressionGraph`, i.e., a nonnative operation
+#[cfg(feature = "expression-graph")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExpressionNode {
+    /// The name of the operation, or `input` for an element produced outside of the recorded
+    /// operations
+    pub operation: &'static str,
+    /// The indices of the nodes that produced the operands
+    pub inputs: Vec<usize>,
+    /// The number of constraints generated by the operation
+    pub num_constraints: usize,
+}
+
+/// The dependency graph of the nonnative operations run in a block, with a node per operation
+/// in the order in which they finished
+///
+/// Only the outermost operations are nodes, so the reductions inside a multiplication count
+/// towards the multiplication, while a reduction called directly is a node of its own. The
+/// elements are identified by the variables of their limbs.
+#[cfg(feature = "expression-graph")]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExpressionGraph {
+    /// The nodes, each of which only depends on earlier ones
+    pub nodes: Vec<ExpressionNode>,
+}
+
+/// The graph recorded in the cache map while an `ExpressionGraph` is being recorded
+#[cfg(feature = "expression-graph")]
+#[derive(Default)]
+struct GraphRecorder {
+    graph: ExpressionGraph,
+    /// The node that produced each element, keyed by the variables of its limbs
+    producers: BTreeMap<Vec<Variable>, usize>,
+    /// The number of operations in progress
+    depth: usize,
+}
+
+#[cfg(feature = "expression-graph")]
+impl ExpressionGraph {
+    /// Run `f` on `cs` and record the graph of the nonnative operations that it ran
+    ///
+    /// Recordings do not nest: the operations of an inner block are only part of its own
+    /// graph.
+    pub fn record<BaseField: PrimeField, T>(
+        cs: &ConstraintSystemRef<BaseField>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+    ) -> Result<(T, Self), SynthesisError> {
+        let outer = Self::swap_recorder(cs, Some(GraphRecorder::default()));
+        let res = f();
+        let recorder = Self::swap_recorder(cs, None).unwrap_or_default();
+        if let Some(outer) = outer {
+            Self::swap_recorder(cs, Some(outer));
+        }
+
+        Ok((res?, recorder.graph))
+    }
+
+    /// The total number of constraints of the nodes
+    pub fn num_constraints(&self) -> usize {
+        self.nodes.iter().map(|node| node.num_constraints).sum()
+    }
+
+    /// Export the graph in the DOT language of Graphviz, with the operation and the number
+    /// of constraints as the label of each node
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        // writing to a `String` does not fail
+        let _ = writeln!(dot, "digraph nonnative {{");
+        for (i, node) in self.nodes.iter().enumerate() {
+            let _ = writeln!(
+                dot,
+                "    n{} [label=\"{}\\n{} constraints\"];",
+                i, node.operation, node.num_constraints
+            );
+            for input in node.inputs.iter() {
+                let _ = writeln!(dot, "    n{} -> n{};", input, i);
+            }
+        }
+        let _ = writeln!(dot, "}}");
+        dot
+    }
+
+    /// Replace the recorder in the cache map of `cs` and return the previous one
+    fn swap_recorder<BaseField: PrimeField>(
+        cs: &ConstraintSystemRef<BaseField>,
+        recorder: Option<GraphRecorder>,
+    ) -> Option<GraphRecorder> {
+        match cs {
+            ConstraintSystemRef::None => None,
+            ConstraintSystemRef::CS(v) => {
+                let cs_sys = v.borrow();
+                let mut big_map = cs_sys.cache_map.borrow_mut();
+                let previous = big_map
+                    .remove(&TypeId::of::<GraphRecorder>())
+                    .and_then(|recorder| recorder.downcast::<GraphRecorder>().ok())
+                    .map(|recorder| *recorder);
+                if let Some(recorder) = recorder {
+                    big_map.insert(TypeId::of::<GraphRecorder>(), Box::new(recorder));
+                }
+                previous
+            }
+        }
+    }
+}
+
+/// Run `f` on the recorder in the cache map of `cs`, if an `ExpressionGraph` is being recorded
+#[cfg(feature = "expression-graph")]
+fn with_recorder<BaseField: PrimeField, T>(
+    cs: &ConstraintSystemRef<BaseField>,
+    f: impl FnOnce(&mut GraphRecorder) -> T,
+) -> Option<T> {
+    match cs {
+        ConstraintSystemRef::None => None,
+        ConstraintSystemRef::CS(v) => {
+            let cs_sys = v.borrow();
+            let mut big_map = cs_sys.cache_map.borrow_mut();
+            big_map
+                .get_mut(&TypeId::of::<GraphRecorder>())
+                .and_then(|recorder| recorder.downcast_mut::<GraphRecorder>())
+                .map(f)
+        }
+    }
+}
+
+/// An operation in progress, which becomes a node of the `ExpressionGraph` being recorded, if
+/// any, once it finishes, unless it is nested in another operation
+///
+/// Without the `expression-graph` feature, this records nothing.
+pub(crate) struct GraphScope<BaseField: PrimeField> {
+    #[cfg(feature = "expression-graph")]
+    cs: ConstraintSystemRef<BaseField>,
+    #[cfg(feature = "expression-graph")]
+    operation: &'static str,
+    #[cfg(feature = "expression-graph")]
+    inputs: Vec<Vec<Variable>>,
+    #[cfg(feature = "expression-graph")]
+    num_constraints_before: usize,
+    /// Whether the operation counts towards the depth of the recorder
+    #[cfg(feature = "expression-graph")]
+    active: bool,
+    /// Whether the operation is not nested in another one
+    #[cfg(feature = "expression-graph")]
+    outermost: bool,
+    base_phantom: PhantomData<BaseField>,
+}
+
+impl<BaseField: PrimeField> GraphScope<BaseField> {
+    /// Start `operation` on `cs` with operands whose limbs are `inputs`
+    #[allow(unused_variables)]
+    pub(crate) fn start(
+        operation: &'static str,
+        cs: &ConstraintSystemRef<BaseField>,
+        inputs: &[&[AllocatedFp<BaseField>]],
+    ) -> Self {
+        #[cfg(feature = "expression-graph")]
+        let depth = with_recorder(cs, |recorder| {
+            recorder.depth += 1;
+            recorder.depth
+        });
+
+        Self {
+            #[cfg(feature = "expression-graph")]
+            cs: cs.clone(),
+            #[cfg(feature = "expression-graph")]
+            operation,
+            #[cfg(feature = "expression-graph")]
+            inputs: if depth == Some(1) {
+                inputs
+                    .iter()
+                    .map(|limbs| limbs.iter().map(|limb| limb.variable).collect())
+                    .collect()
+            } else {
+                Vec::new()
+            },
+            #[cfg(feature = "expression-graph")]
+            num_constraints_before: cs.num_constraints(),
+            #[cfg(feature = "expression-graph")]
+            active: depth.is_some(),
+            #[cfg(feature = "expression-graph")]
+            outermost: depth == Some(1),
+            base_phantom: PhantomData,
+        }
+    }
+
+    /// Finish the operation, which produced the element whose limbs are `output`, if any
+    #[allow(unused_variables)]
+    pub(crate) fn finish(self, output: Option<&[AllocatedFp<BaseField>]>) {
+        #[cfg(feature = "expression-graph")]
+        {
+            if self.outermost {
+                let num_constraints = self.cs.num_constraints() - self.num_constraints_before;
+                with_recorder(&self.cs, |recorder| {
+                    let mut inputs = Vec::with_capacity(self.inputs.len());
+                    for key in self.inputs.iter() {
+                        let producer = match recorder.producers.get(key) {
+                            Some(producer) => *producer,
+                            None => {
+                                recorder.graph.nodes.push(ExpressionNode {
+                                    operation: "input",
+                                    inputs: Vec::new(),
+                                    num_constraints: 0,
+                                });
+                                let producer = recorder.graph.nodes.len() - 1;
+                                recorder.producers.insert(key.clone(), producer);
+                                producer
+                            }
+                        };
+                        inputs.push(producer);
+                    }
+
+                    recorder.graph.nodes.push(ExpressionNode {
+                        operation: self.operation,
+                        inputs,
+                        num_constraints,
+                    });
+                    if let Some(output) = output {
+                        let key = output.iter().map(|limb| limb.variable).collect();
+                        recorder
+                            .producers
+                            .insert(key, recorder.graph.nodes.len() - 1);
+                    }
+                });
+            }
+        }
+    }
+}
+
+#[cfg(feature = "expression-graph")]
+impl<BaseField: PrimeField> Drop for GraphScope<BaseField> {
+    fn drop(&mut self) {
+        if self.active {
+            with_recorder(&self.cs, |recorder| recorder.depth -= 1);
+        }
+    }
+}
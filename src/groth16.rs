@@ -0,0 +1,46 @@
+use crate::NonNativeFieldVar;
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::PrimeField;
+use ark_r1cs_std::{bits::ToBitsGadget, groups::CurveVar};
+use ark_relations::r1cs::SynthesisError;
+use ark_std::vec::Vec;
+
+/// Compute `IC_0 + Σ a_i · IC_i` for the public inputs `a_i` of an inner Groth16 proof
+///
+/// The inner proof's scalar field is simulated (the `a_i` are `NonNativeFieldVar`s), while its
+/// G1 group is native to the constraint field, so the result is a native curve gadget `GG`.
+/// The `IC_i` (`gamma_abc_g1` in the verifying key) are constants, so each term uses the
+/// precomputed-base scalar multiplication with a table of the doublings of `IC_i`.
+///
+/// Since the order of G1 is the simulated field's modulus, the possibly non-unique bit
+/// decomposition of the inputs yields the same group element and is used for efficiency.
+#[tracing::instrument(target = "r1cs")]
+pub fn prepare_inputs<C, GG, BaseField>(
+    gamma_abc_g1: &[C::Affine],
+    public_inputs: &[NonNativeFieldVar<C::ScalarField, BaseField>],
+) -> Result<GG, SynthesisError>
+where
+    C: ProjectiveCurve,
+    GG: CurveVar<C, BaseField>,
+    BaseField: PrimeField,
+{
+    if gamma_abc_g1.len() != public_inputs.len() + 1 {
+        return Err(SynthesisError::MalformedVerifyingKey);
+    }
+
+    let mut res = GG::constant(gamma_abc_g1[0].into_projective());
+    for (input, base) in public_inputs.iter().zip(gamma_abc_g1.iter().skip(1)) {
+        let bits = input.to_non_unique_bits_le()?;
+
+        let mut bases = Vec::with_capacity(bits.len());
+        let mut cur = base.into_projective();
+        for _ in 0..bits.len() {
+            bases.push(cur);
+            cur.double_in_place();
+        }
+
+        res.precomputed_base_scalar_mul_le(bits.iter().zip(bases.iter()))?;
+    }
+
+    Ok(res)
+}
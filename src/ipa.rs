@@ -0,0 +1,299 @@
+use crate::curves::short_weierstrass::{NonNativeAffineVar, NonNativeProjectiveVar};
+use crate::{
+    error::NonNativeFieldError, poly::batch_inverse, NonNativeFieldMulResultVar, NonNativeFieldVar,
+};
+use ark_ec::models::SWModelParameters;
+use ark_ff::PrimeField;
+use ark_r1cs_std::{boolean::Boolean, eq::EqGadget, fields::FieldVar};
+use ark_relations::r1cs::SynthesisError;
+use ark_std::{vec, vec::Vec};
+use derivative::Derivative;
+
+/// The scalars of the final check of a Bulletproofs inner-product argument, computed over the
+/// simulated scalar field
+///
+/// In round `j`, the vectors are split into halves, and the generators are folded into
+/// `G' = u_j^{-1} G_lo + u_j G_hi` and `H' = u_j H_lo + u_j^{-1} H_hi` with the challenge `u_j`.
+/// For the final scalars `a` and `b`, the check is
+/// `P + Σ u_j^2 L_j + Σ u_j^{-2} R_j = a Σ s_i G_i + b Σ s_i^{-1} H_i + a·b U`.
+/// This struct holds the coefficients of that equation with `P` moved to the right-hand side,
+/// so that the multi-scalar multiplication can be delegated or emulated separately.
+#[derive(Clone, Debug)]
+pub struct IPACheckScalarsVar<TargetField: PrimeField, BaseField: PrimeField> {
+    /// The coefficients `a·s_i` of the generators `G_i`
+    pub g_coeffs: Vec<NonNativeFieldVar<TargetField, BaseField>>,
+    /// The coefficients `b·s_i^{-1}` of the generators `H_i`
+    pub h_coeffs: Vec<NonNativeFieldVar<TargetField, BaseField>>,
+    /// The coefficient `a·b` of the inner-product generator `U`
+    pub u_coeff: NonNativeFieldVar<TargetField, BaseField>,
+    /// The coefficients `-u_j^2` of the left commitments `L_j`
+    pub l_coeffs: Vec<NonNativeFieldVar<TargetField, BaseField>>,
+    /// The coefficients `-u_j^{-2}` of the right commitments `R_j`
+    pub r_coeffs: Vec<NonNativeFieldVar<TargetField, BaseField>>,
+}
+
+/// Compute the inverses of the nonzero `challenges` with a single inversion
+#[tracing::instrument(target = "r1cs")]
+pub fn challenge_inverses<TargetField: PrimeField, BaseField: PrimeField>(
+    challenges: &[NonNativeFieldVar<TargetField, BaseField>],
+) -> Result<Vec<NonNativeFieldVar<TargetField, BaseField>>, SynthesisError> {
+    batch_inverse(challenges)
+}
+
+/// Compute the vector `s` of length `2^k` for the `k` challenges, where
+/// `s_i = Π_j u_j^{±1}` with the exponent `+1` if the bit of `i` for round `j` is set
+///
+/// The first round splits on the most significant bit of `i`. The vector is built from the
+/// last challenge, doubling its length with each challenge, so it costs one multiplication
+/// per entry. Return `NonNativeFieldError::LengthMismatch` if there are not as many inverses
+/// as challenges.
+#[tracing::instrument(target = "r1cs")]
+pub fn s_vector<TargetField: PrimeField, BaseField: PrimeField>(
+    challenges: &[NonNativeFieldVar<TargetField, BaseField>],
+    challenge_inverses: &[NonNativeFieldVar<TargetField, BaseField>],
+) -> Result<Vec<NonNativeFieldVar<TargetField, BaseField>>, NonNativeFieldError> {
+    if challenges.len() != challenge_inverses.len() {
+        return Err(NonNativeFieldError::LengthMismatch {
+            expected: challenges.len(),
+            found: challenge_inverses.len(),
+        });
+    }
+
+    let mut res = vec![NonNativeFieldVar::one()];
+    for (u, u_inv) in challenges.iter().zip(challenge_inverses.iter()).rev() {
+        let mut next = Vec::with_capacity(2 * res.len());
+        for s in res.iter() {
+            next.push(s * u_inv);
+        }
+        for s in res.iter() {
+            next.push(s * u);
+        }
+        res = next;
+    }
+    Ok(res)
+}
+
+/// Fold `scalars`, of even length, with the challenge `u` of a round like the generators `G`,
+/// i.e., into `u^{-1} scalars_lo + u scalars_hi`
+///
+/// Each folded entry is a sum of two products that is reduced once. Folding the scalars of
+/// public generators through all the rounds yields their inner product with `s`. Return
+/// `NonNativeFieldError::InvalidLength` if `scalars` has an odd length.
+#[tracing::instrument(target = "r1cs")]
+pub fn fold_scalars<TargetField: PrimeField, BaseField: PrimeField>(
+    scalars: &[NonNativeFieldVar<TargetField, BaseField>],
+    challenge: &NonNativeFieldVar<TargetField, BaseField>,
+    challenge_inverse: &NonNativeFieldVar<TargetField, BaseField>,
+) -> Result<Vec<NonNativeFieldVar<TargetField, BaseField>>, NonNativeFieldError> {
+    if scalars.len() % 2 != 0 {
+        return Err(NonNativeFieldError::InvalidLength {
+            length: scalars.len(),
+            reason: "the scalars of a round must have an even length",
+        });
+    }
+
+    let (lo, hi) = scalars.split_at(scalars.len() / 2);
+    let res = lo
+        .iter()
+        .zip(hi.iter())
+        .map(|(lo, hi)| {
+            let mut acc = NonNativeFieldMulResultVar::zero();
+            acc += &lo.mul_without_reduce(challenge_inverse)?;
+            acc += &hi.mul_without_reduce(challenge)?;
+            acc.reduce()
+        })
+        .collect::<Result<Vec<_>, SynthesisError>>()?;
+    Ok(res)
+}
+
+/// Compute the scalars of the final check of an inner-product argument over `2^k` generators
+/// from its `k` challenges and its final scalars `a` and `b`
+///
+/// The challenges are inverted with a single inversion, and `s_i^{-1}` is `s_{n - 1 - i}`, so
+/// it needs no inversion. Return `NonNativeFieldError::InvalidLength` if there are no
+/// challenges.
+#[tracing::instrument(target = "r1cs")]
+pub fn ipa_check_scalars<TargetField: PrimeField, BaseField: PrimeField>(
+    challenges: &[NonNativeFieldVar<TargetField, BaseField>],
+    a: &NonNativeFieldVar<TargetField, BaseField>,
+    b: &NonNativeFieldVar<TargetField, BaseField>,
+) -> Result<IPACheckScalarsVar<TargetField, BaseField>, NonNativeFieldError> {
+    if challenges.is_empty() {
+        return Err(NonNativeFieldError::InvalidLength {
+            length: 0,
+            reason: "an inner-product argument has at least one round",
+        });
+    }
+
+    let inverses = challenge_inverses(challenges)?;
+    let s = s_vector(challenges, &inverses)?;
+
+    let g_coeffs = s.iter().map(|s_i| a * s_i).collect();
+    let h_coeffs = s.iter().rev().map(|s_i_inv| b * s_i_inv).collect();
+    let l_coeffs = challenges
+        .iter()
+        .map(|u| u.square()?.negate())
+        .collect::<Result<Vec<_>, _>>()?;
+    let r_coeffs = inverses
+        .iter()
+        .map(|u_inv| u_inv.square()?.negate())
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(IPACheckScalarsVar {
+        g_coeffs,
+        h_coeffs,
+        u_coeff: a * b,
+        l_coeffs,
+        r_coeffs,
+    })
+}
+
+/// Evaluate at `z` the polynomial `b(X) = Π_j (u_j^{-1} + u_j X^{2^{k - 1 - j}})` of the `k`
+/// challenges, which is `Σ s_i z^i`, i.e., the folded vector `(1, z, ..., z^{n - 1})`
+///
+/// This costs `O(k)` multiplications instead of the `O(n)` of folding the powers of `z`.
+/// Return `NonNativeFieldError::LengthMismatch` if there are not as many inverses as
+/// challenges.
+#[tracing::instrument(target = "r1cs")]
+pub fn challenge_polynomial_eval<TargetField: PrimeField, BaseField: PrimeField>(
+    challenges: &[NonNativeFieldVar<TargetField, BaseField>],
+    challenge_inverses: &[NonNativeFieldVar<TargetField, BaseField>],
+    z: &NonNativeFieldVar<TargetField, BaseField>,
+) -> Result<NonNativeFieldVar<TargetField, BaseField>, NonNativeFieldError> {
+    if challenges.len() != challenge_inverses.len() {
+        return Err(NonNativeFieldError::LengthMismatch {
+            expected: challenges.len(),
+            found: challenge_inverses.len(),
+        });
+    }
+
+    // the last challenge multiplies z itself, and each earlier one the square of the power
+    let mut res = NonNativeFieldVar::one();
+    let mut power = z.clone();
+    for (u, u_inv) in challenges.iter().zip(challenge_inverses.iter()).rev() {
+        let mut factor = NonNativeFieldMulResultVar::from_reduced(u_inv)?;
+        factor += &u.mul_without_reduce(&power)?;
+        res *= &factor.reduce()?;
+        power = power.square()?;
+    }
+    Ok(res)
+}
+
+/// A proof of the opening of a polynomial commitment with the inner-product argument, over a
+/// curve whose base and scalar fields are both simulated
+///
+/// The vector `b` is the powers of the evaluation point, so only the prover's vector `a` is
+/// folded down to the scalar `a`. The folded generator `Σ s_i G_i`, i.e., the commitment to the
+/// polynomial of the challenges, is given as `challenge_point` instead of being computed from
+/// the `n` generators, as in the accumulation scheme of Halo.
+#[derive(Derivative)]
+#[derivative(Clone(bound = ""), Debug(bound = ""))]
+pub struct IPAOpeningProofVar<P, BaseField>
+where
+    P: SWModelParameters,
+    P::BaseField: PrimeField,
+    BaseField: PrimeField,
+{
+    /// The left commitments `L_j` of the rounds
+    pub l: Vec<NonNativeAffineVar<P, BaseField>>,
+    /// The right commitments `R_j` of the rounds
+    pub r: Vec<NonNativeAffineVar<P, BaseField>>,
+    /// The final scalar `a`
+    pub a: NonNativeFieldVar<P::ScalarField, BaseField>,
+    /// The folded generator `Σ s_i G_i`
+    pub challenge_point: NonNativeAffineVar<P, BaseField>,
+}
+
+/// The accumulator of a verified opening, whose `challenge_point` is yet to be checked to be
+/// the commitment to the polynomial of the `challenges`
+///
+/// The checks of several accumulators can be deferred to a single multi-scalar multiplication
+/// of length `n` in the decider, outside of the circuit.
+#[derive(Derivative)]
+#[derivative(Clone(bound = ""), Debug(bound = ""))]
+pub struct IPAAccumulatorVar<P, BaseField>
+where
+    P: SWModelParameters,
+    P::BaseField: PrimeField,
+    BaseField: PrimeField,
+{
+    /// The challenges `u_j` of the rounds
+    pub challenges: Vec<NonNativeFieldVar<P::ScalarField, BaseField>>,
+    /// The claimed folded generator `Σ s_i G_i`
+    pub challenge_point: NonNativeAffineVar<P, BaseField>,
+}
+
+impl<P, BaseField> IPAOpeningProofVar<P, BaseField>
+where
+    P: SWModelParameters,
+    P::BaseField: PrimeField,
+    BaseField: PrimeField,
+{
+    /// Enforce that the proof opens `commitment` to `value` at `z`, with the challenges
+    /// `challenges` of the caller's transcript, up to the check of the accumulator it outputs
+    ///
+    /// This checks `C + v·U + Σ u_j^2 L_j + Σ u_j^{-2} R_j = a·G + a·b(z)·U` for the folded
+    /// generator `G = challenge_point` and the evaluation `b(z)` of the challenge polynomial,
+    /// where `U` is `u_generator`, typically scaled by a challenge to bind `v`. The group
+    /// equation is a single multi-scalar multiplication with the complete formulas, since the
+    /// commitments of the proof are chosen by the prover, and the scalars are decomposed into
+    /// their canonical bits. For `k` rounds and an `n`-bit scalar field, the `2k + 2` bases share
+    /// the tables of `multi_scalar_mul_le`, so this costs about `n · (1 + ⌈(2k + 2) / 4⌉)`
+    /// complete additions, rather than `n · (2k + 3)` with one addition per base. Return
+    /// `NonNativeFieldError::LengthMismatch` if the proof does not have one `L_j` and one `R_j`
+    /// per challenge.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn verify(
+        &self,
+        commitment: &NonNativeAffineVar<P, BaseField>,
+        z: &NonNativeFieldVar<P::ScalarField, BaseField>,
+        value: &NonNativeFieldVar<P::ScalarField, BaseField>,
+        u_generator: &NonNativeAffineVar<P, BaseField>,
+        challenges: &[NonNativeFieldVar<P::ScalarField, BaseField>],
+    ) -> Result<IPAAccumulatorVar<P, BaseField>, NonNativeFieldError> {
+        for commitments in [&self.l, &self.r].iter() {
+            if commitments.len() != challenges.len() {
+                return Err(NonNativeFieldError::LengthMismatch {
+                    expected: challenges.len(),
+                    found: commitments.len(),
+                });
+            }
+        }
+
+        let inverses = challenge_inverses(challenges)?;
+        let b = challenge_polynomial_eval(challenges, &inverses, z)?;
+
+        // Σ u_j^2 L_j + Σ u_j^{-2} R_j + (v - a·b(z))·U - a·G = -C
+        let mut bases = Vec::with_capacity(2 * challenges.len() + 2);
+        let mut scalars = Vec::with_capacity(2 * challenges.len() + 2);
+        for (l, u) in self.l.iter().zip(challenges.iter()) {
+            bases.push(l);
+            scalars.push(u.square()?);
+        }
+        for (r, u_inv) in self.r.iter().zip(inverses.iter()) {
+            bases.push(r);
+            scalars.push(u_inv.square()?);
+        }
+        bases.push(u_generator);
+        scalars.push(value - &(&self.a * &b));
+        bases.push(&self.challenge_point);
+        scalars.push(self.a.negate()?);
+
+        let bases: Vec<_> = bases
+            .into_iter()
+            .map(NonNativeProjectiveVar::from_affine)
+            .collect();
+        let bits = scalars
+            .iter()
+            .map(|scalar| scalar.to_canonical_bits_le())
+            .collect::<Result<Vec<_>, _>>()?;
+        let res = NonNativeProjectiveVar::multi_scalar_mul_le(&bases, &bits)?
+            .add(&NonNativeProjectiveVar::from_affine(commitment))?;
+        res.is_zero()?.enforce_equal(&Boolean::TRUE)?;
+
+        Ok(IPAAccumulatorVar {
+            challenges: challenges.to_vec(),
+            challenge_point: self.challenge_point.clone(),
+        })
+    }
+}
@@ -0,0 +1,114 @@
+use crate::{error::NonNativeFieldError, NonNativeFieldMulResultVar, NonNativeFieldVar};
+use ark_ff::PrimeField;
+use ark_r1cs_std::{eq::EqGadget, fields::FieldVar};
+use ark_relations::r1cs::SynthesisError;
+use ark_std::{vec, vec::Vec};
+
+/// The scalars of a batched KZG opening check, computed over the simulated scalar field
+///
+/// For openings `(C_j, z_j, y_j, π_j)` batched with a challenge `r`, the pairing check is
+/// `e(Σ r^j C_j - (Σ r^j y_j) G + Σ r^j z_j π_j, H) = e(Σ r^j π_j, τH)`.
+/// This struct holds the scalars of that equation so that the group-side
+/// multi-scalar multiplications and the pairing can be delegated or emulated separately.
+#[derive(Clone, Debug)]
+pub struct KZGBatchedScalarsVar<TargetField: PrimeField, BaseField: PrimeField> {
+    /// The coefficients `r^j` of the commitments `C_j` and of the proofs `π_j` on the right-hand side
+    pub commitment_coeffs: Vec<NonNativeFieldVar<TargetField, BaseField>>,
+    /// The coefficients `r^j z_j` of the proofs `π_j` on the left-hand side
+    pub proof_coeffs: Vec<NonNativeFieldVar<TargetField, BaseField>>,
+    /// The coefficient `Σ r^j y_j` of the generator `G`
+    pub combined_evaluation: NonNativeFieldVar<TargetField, BaseField>,
+}
+
+/// Compute `[1, x, x^2, ..., x^{n-1}]`
+fn powers<TargetField: PrimeField, BaseField: PrimeField>(
+    x: &NonNativeFieldVar<TargetField, BaseField>,
+    n: usize,
+) -> Vec<NonNativeFieldVar<TargetField, BaseField>> {
+    let mut res = Vec::with_capacity(n);
+    let mut cur = NonNativeFieldVar::one();
+    for i in 0..n {
+        if i > 0 {
+            cur *= x;
+        }
+        res.push(cur.clone());
+    }
+    res
+}
+
+/// Compute `Σ a_i b_i` with a single reduction at the end
+fn inner_product<TargetField: PrimeField, BaseField: PrimeField>(
+    a: &[NonNativeFieldVar<TargetField, BaseField>],
+    b: &[NonNativeFieldVar<TargetField, BaseField>],
+) -> Result<NonNativeFieldVar<TargetField, BaseField>, SynthesisError> {
+    let mut acc = NonNativeFieldMulResultVar::zero();
+    for (a_i, b_i) in a.iter().zip(b.iter()) {
+        acc += &a_i.mul_without_reduce(b_i)?;
+    }
+    acc.reduce()
+}
+
+/// Enforce that the polynomial with coefficients `coeffs` (lowest degree first)
+/// evaluates to `evaluation` at `point`.
+#[tracing::instrument(target = "r1cs")]
+pub fn enforce_evaluation<TargetField: PrimeField, BaseField: PrimeField>(
+    coeffs: &[NonNativeFieldVar<TargetField, BaseField>],
+    point: &NonNativeFieldVar<TargetField, BaseField>,
+    evaluation: &NonNativeFieldVar<TargetField, BaseField>,
+) -> Result<(), SynthesisError> {
+    let point_powers = powers(point, coeffs.len());
+    let res = inner_product(coeffs, &point_powers)?;
+    res.enforce_equal(evaluation)
+}
+
+/// Compute the scalars for batching openings of several polynomials at the same point `z`
+///
+/// The combined check is `e(Σ ν^i C_i - (Σ ν^i y_i) G + z π, H) = e(π, τH)`, where `π` is
+/// the single proof for the combined polynomial; the returned `proof_coeffs` is `[z]`.
+#[tracing::instrument(target = "r1cs")]
+pub fn batch_openings_at_point<TargetField: PrimeField, BaseField: PrimeField>(
+    point: &NonNativeFieldVar<TargetField, BaseField>,
+    evaluations: &[NonNativeFieldVar<TargetField, BaseField>],
+    challenge: &NonNativeFieldVar<TargetField, BaseField>,
+) -> Result<KZGBatchedScalarsVar<TargetField, BaseField>, SynthesisError> {
+    let commitment_coeffs = powers(challenge, evaluations.len());
+    let combined_evaluation = inner_product(&commitment_coeffs, evaluations)?;
+
+    Ok(KZGBatchedScalarsVar {
+        commitment_coeffs,
+        proof_coeffs: vec![point.clone()],
+        combined_evaluation,
+    })
+}
+
+/// Compute the scalars for batching openings at (possibly) different points `z_j`
+///
+/// Return `NonNativeFieldError::LengthMismatch` if there are not as many points as
+/// evaluations.
+#[tracing::instrument(target = "r1cs")]
+pub fn batch_openings<TargetField: PrimeField, BaseField: PrimeField>(
+    points: &[NonNativeFieldVar<TargetField, BaseField>],
+    evaluations: &[NonNativeFieldVar<TargetField, BaseField>],
+    challenge: &NonNativeFieldVar<TargetField, BaseField>,
+) -> Result<KZGBatchedScalarsVar<TargetField, BaseField>, NonNativeFieldError> {
+    if points.len() != evaluations.len() {
+        return Err(NonNativeFieldError::LengthMismatch {
+            expected: points.len(),
+            found: evaluations.len(),
+        });
+    }
+
+    let commitment_coeffs = powers(challenge, evaluations.len());
+    let combined_evaluation = inner_product(&commitment_coeffs, evaluations)?;
+    let proof_coeffs = commitment_coeffs
+        .iter()
+        .zip(points.iter())
+        .map(|(coeff, point)| coeff * point)
+        .collect();
+
+    Ok(KZGBatchedScalarsVar {
+        commitment_coeffs,
+        proof_coeffs,
+        combined_evaluation,
+    })
+}
@@ -0,0 +1,485 @@
+use crate::params::{get_params, OptimizationMode};
+use crate::range_check;
+use crate::reduce::{bigint_to_biguint, biguint_to_field, field_to_biguint, Reducer};
+use crate::{overhead, AllocatedNonNativeFieldVar, NonNativeFieldParams};
+use ark_ff::{BigInteger, FpParameters, PrimeField};
+use ark_r1cs_std::{alloc::AllocVar, boolean::Boolean, fields::fp::AllocatedFp, R1CSVar};
+use ark_relations::{
+    lc,
+    r1cs::{ConstraintSystemRef, LinearCombination, SynthesisError, Variable},
+};
+use ark_std::{cmp::max, vec::Vec};
+use num_bigint::BigUint;
+use num_traits::{CheckedSub, One};
+
+/// A term `value · 2^position` of one side of an integer equality
+struct Term<BaseField: PrimeField> {
+    position: usize,
+    lc: LinearCombination<BaseField>,
+    value: BigUint,
+    num_bits: usize,
+}
+
+/// The position and the size of each limb of `layout`, big limb first
+fn limb_positions(layout: &NonNativeFieldParams) -> Vec<(usize, usize)> {
+    (0..layout.num_limbs)
+        .map(|i| {
+            let num_lower_limbs = layout.num_limbs - 1 - i;
+            let num_bits = if i == 0 {
+                layout.bits_per_top_limb
+            } else {
+                layout.bits_per_non_top_limb
+            };
+            (num_lower_limbs * layout.bits_per_non_top_limb, num_bits)
+        })
+        .collect()
+}
+
+/// The number of bits covered by the limbs of `layout`
+fn num_covered_bits(layout: &NonNativeFieldParams) -> usize {
+    limb_positions(layout)
+        .iter()
+        .map(|(position, num_bits)| position + num_bits)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Split `value` into the limbs of `layout`, big limb first
+fn split(value: &BigUint, layout: &NonNativeFieldParams) -> Vec<BigUint> {
+    limb_positions(layout)
+        .iter()
+        .map(|(position, num_bits)| (value >> *position) & ((BigUint::one() << *num_bits) - 1u64))
+        .collect()
+}
+
+/// The terms of the integer represented by `limbs` in `layout`, whose limbs exceed the sizes
+/// of `layout` by at most `surfeit` bits
+fn limb_terms<BaseField: PrimeField>(
+    limbs: &[AllocatedFp<BaseField>],
+    layout: &NonNativeFieldParams,
+    surfeit: usize,
+) -> Vec<Term<BaseField>> {
+    limbs
+        .iter()
+        .zip(limb_positions(layout))
+        .map(|(limb, (position, num_bits))| Term {
+            position,
+            lc: lc!() + limb.variable,
+            value: field_to_biguint(&limb.value().unwrap_or_default()),
+            num_bits: num_bits + surfeit,
+        })
+        .collect()
+}
+
+/// Enforce that the integers represented by `lhs` in `lhs_layout` and by `rhs` in
+/// `rhs_layout` are congruent modulo the `TargetField` modulus, where the limbs are
+/// range-checked to the sizes of their layouts, or exceed them by at most `lhs_surfeit` bits
+/// on the left-hand side
+///
+/// With `X` and `Y` the two integers and `p` the modulus, a witness `k` shows that
+/// `X + 2^b · p = Y + k · p` over the integers, where `2^b` makes `k` nonnegative. The terms
+/// of both sides are grouped by their positions so that each group fits into a `BaseField`
+/// element, and the groups are enforced with one constraint each, carrying the signed
+/// difference of the sides from a group to the next one.
+fn enforce_congruent<TargetField: PrimeField, BaseField: PrimeField>(
+    cs: &ConstraintSystemRef<BaseField>,
+    lhs: &[AllocatedFp<BaseField>],
+    lhs_layout: &NonNativeFieldParams,
+    lhs_surfeit: usize,
+    rhs: &[AllocatedFp<BaseField>],
+    rhs_layout: &NonNativeFieldParams,
+) -> Result<(), SynthesisError> {
+    enforce_congruent_with::<TargetField, BaseField>(
+        cs,
+        lhs,
+        lhs_layout,
+        lhs_surfeit,
+        rhs,
+        rhs_layout,
+        None,
+    )
+}
+
+/// Enforce the congruence of `lhs` and `rhs` as `from_layout` does, with the witnesses of a
+/// dishonest prover that computes `k` for `claimed_rhs_value` instead of the integer of `rhs`,
+/// and solves the constraint of each group for its carry in `BaseField`
+///
+/// The carries then satisfy the constraints of the groups whenever `X - Y` is a multiple of
+/// the `BaseField` modulus, so the soundness tests can check that they fail their range
+/// checks.
+#[doc(hidden)]
+pub fn enforce_congruent_with_wrapping_carries<TargetField: PrimeField, BaseField: PrimeField>(
+    cs: &ConstraintSystemRef<BaseField>,
+    lhs: &[AllocatedFp<BaseField>],
+    lhs_layout: &NonNativeFieldParams,
+    rhs: &[AllocatedFp<BaseField>],
+    rhs_layout: &NonNativeFieldParams,
+    claimed_rhs_value: &BigUint,
+) -> Result<(), SynthesisError> {
+    enforce_congruent_with::<TargetField, BaseField>(
+        cs,
+        lhs,
+        lhs_layout,
+        0,
+        rhs,
+        rhs_layout,
+        Some(claimed_rhs_value),
+    )
+}
+
+/// `enforce_congruent`, with the witnesses of `enforce_congruent_with_wrapping_carries` if
+/// `claimed_rhs_value` is set
+fn enforce_congruent_with<TargetField: PrimeField, BaseField: PrimeField>(
+    cs: &ConstraintSystemRef<BaseField>,
+    lhs: &[AllocatedFp<BaseField>],
+    lhs_layout: &NonNativeFieldParams,
+    lhs_surfeit: usize,
+    rhs: &[AllocatedFp<BaseField>],
+    rhs_layout: &NonNativeFieldParams,
+    claimed_rhs_value: Option<&BigUint>,
+) -> Result<(), SynthesisError> {
+    let modulus = bigint_to_biguint(&<TargetField::Params as FpParameters>::MODULUS);
+
+    // `|X - Y| < 2^max_bits`, and `p >= 2^(size - 1)`
+    let max_bits = max(
+        num_covered_bits(lhs_layout) + lhs_surfeit,
+        num_covered_bits(rhs_layout),
+    );
+    let k_bits = (max_bits + 1).saturating_sub(TargetField::size_in_bits());
+
+    let mut lhs_terms = limb_terms(lhs, lhs_layout, lhs_surfeit);
+    let mut rhs_terms = limb_terms(rhs, rhs_layout, 0);
+    let lhs_value: BigUint = lhs_terms.iter().map(|t| &t.value << t.position).sum();
+    let rhs_value: BigUint = match claimed_rhs_value {
+        Some(claimed_rhs_value) => claimed_rhs_value.clone(),
+        None => rhs_terms.iter().map(|t| &t.value << t.position).sum(),
+    };
+
+    let offset = &modulus << k_bits;
+    let k_value = (&lhs_value + &offset)
+        .checked_sub(&rhs_value)
+        .map(|diff| diff / &modulus)
+        .unwrap_or_default();
+    let k = AllocatedFp::<BaseField>::new_witness(ark_relations::ns!(cs, "k"), || {
+        Ok(biguint_to_field(&k_value))
+    })?;
+    range_check::enforce_range(&k, k_bits + 1)?;
+
+    // the modulus is split in the layout of the left-hand side, whose terms it adds to
+    for ((position, num_bits), p_limb) in limb_positions(lhs_layout)
+        .into_iter()
+        .zip(split(&modulus, lhs_layout))
+    {
+        let offset_limb = &p_limb << k_bits;
+        lhs_terms.push(Term {
+            position,
+            lc: lc!() + (biguint_to_field::<BaseField>(&offset_limb), Variable::One),
+            value: offset_limb,
+            num_bits: num_bits + k_bits,
+        });
+        rhs_terms.push(Term {
+            position,
+            lc: lc!() + (biguint_to_field::<BaseField>(&p_limb), k.variable),
+            value: &p_limb * &k_value,
+            num_bits: num_bits + k_bits + 1,
+        });
+    }
+
+    let num_terms = lhs_terms.len() + rhs_terms.len();
+    let term_bits = lhs_terms
+        .iter()
+        .chain(rhs_terms.iter())
+        .map(|t| t.num_bits)
+        .max()
+        .unwrap_or(0);
+    let sum_bits = term_bits + overhead!(BaseField::from(num_terms as u64));
+    let carry_bits = sum_bits;
+    // a group ends at most `max_span` positions after its start, so its terms stay below
+    // `2^(sum_bits + max_span - 1)` and its shifted carry out below `2^(carry_bits + max_span)`
+    // in absolute value, i.e., below `2^(size - 5)`, and its constraint holds over the
+    // integers rather than only modulo the `BaseField` modulus
+    let max_span = match BaseField::size_in_bits().checked_sub(sum_bits + 5) {
+        Some(max_span) if max_span > 0 => max_span,
+        _ => return Err(SynthesisError::Unsatisfiable),
+    };
+
+    let mut positions: Vec<usize> = lhs_terms
+        .iter()
+        .chain(rhs_terms.iter())
+        .map(|t| t.position)
+        .collect();
+    positions.sort_unstable();
+    positions.dedup();
+
+    // a gap between two positions wider than `max_span` is crossed by groups without terms,
+    // whose constraints only carry the difference further up
+    let mut group_starts: Vec<usize> = Vec::new();
+    for position in positions.iter() {
+        if let Some(mut start) = group_starts.last().copied() {
+            if position - start < max_span {
+                continue;
+            }
+            while position - start > max_span {
+                start += max_span;
+                group_starts.push(start);
+            }
+            if *position == start {
+                continue;
+            }
+        }
+        group_starts.push(*position);
+    }
+
+    let carry_offset = BigUint::one() << carry_bits;
+    let carry_offset_field: BaseField = biguint_to_field(&carry_offset);
+    let mut carry_in: Option<AllocatedFp<BaseField>> = None;
+    for (i, start) in group_starts.iter().enumerate() {
+        let end = group_starts.get(i + 1).copied();
+        let in_group =
+            |t: &&Term<BaseField>| t.position >= *start && end.map_or(true, |end| t.position < end);
+
+        let mut group_lc = LinearCombination::zero();
+        let mut group_value = BaseField::zero();
+        for t in lhs_terms.iter().filter(in_group) {
+            let coeff = BaseField::from(2u64).pow(&[(t.position - start) as u64]);
+            group_lc = &group_lc + t.lc.clone() * coeff;
+            group_value += biguint_to_field::<BaseField>(&t.value) * coeff;
+        }
+        for t in rhs_terms.iter().filter(in_group) {
+            let coeff = BaseField::from(2u64).pow(&[(t.position - start) as u64]);
+            group_lc = &group_lc - t.lc.clone() * coeff;
+            group_value -= biguint_to_field::<BaseField>(&t.value) * coeff;
+        }
+        if let Some(carry_in) = &carry_in {
+            group_lc = group_lc + carry_in.variable - (carry_offset_field, Variable::One);
+            group_value += carry_in.value().unwrap_or_default() - carry_offset_field;
+        }
+
+        if let Some(end) = end {
+            let shift = BaseField::from(2u64).pow(&[(end - start) as u64]);
+            let carry_value = match claimed_rhs_value {
+                // the solution of the constraint of the group in `BaseField`
+                Some(_) => group_value * shift.inverse().unwrap_or_default() + carry_offset_field,
+                // the signed difference of the sides below `end`, divided by `2^end` and offset
+                None => {
+                    let below_end = |terms: &[Term<BaseField>]| -> BigUint {
+                        terms
+                            .iter()
+                            .filter(|t| t.position < end)
+                            .map(|t| &t.value << t.position)
+                            .sum()
+                    };
+                    let carry_value = (below_end(&lhs_terms[..]) + (&carry_offset << end))
+                        .checked_sub(&below_end(&rhs_terms[..]))
+                        .map(|diff| diff >> end)
+                        .unwrap_or_default();
+                    biguint_to_field(&carry_value)
+                }
+            };
+            let carry =
+                AllocatedFp::<BaseField>::new_witness(ark_relations::ns!(cs, "carry"), || {
+                    Ok(carry_value)
+                })?;
+            range_check::enforce_range(&carry, carry_bits + 1)?;
+
+            group_lc =
+                group_lc - (shift, carry.variable) + (shift * &carry_offset_field, Variable::One);
+            carry_in = Some(carry);
+        }
+
+        cs.enforce_constraint(lc!(), lc!(), group_lc)?;
+    }
+
+    Ok(())
+}
+
+/// Enforce that the integer represented by `limbs` in `layout`, whose limbs are range-checked
+/// to the sizes of `layout`, is at most `p - 1` for the `TargetField` modulus `p`
+///
+/// As in `is_odd`, a witness complement `D` with limbs of the same sizes is added to the limbs
+/// limb by limb with boolean carries, so that `X + D = p - 1` over the integers, where the top
+/// limb does not carry out.
+fn enforce_canonical<TargetField: PrimeField, BaseField: PrimeField>(
+    cs: &ConstraintSystemRef<BaseField>,
+    limbs: &[AllocatedFp<BaseField>],
+    layout: &NonNativeFieldParams,
+) -> Result<(), SynthesisError> {
+    let modulus_minus_one =
+        bigint_to_biguint(&<TargetField::Params as FpParameters>::MODULUS) - 1u64;
+    let value: BigUint = limb_terms(limbs, layout, 0)
+        .iter()
+        .map(|t| &t.value << t.position)
+        .sum();
+    let complement = modulus_minus_one.checked_sub(&value).unwrap_or_default();
+
+    let positions = limb_positions(layout);
+    let modulus_minus_one_limbs = split(&modulus_minus_one, layout);
+    let complement_limbs = split(&complement, layout);
+
+    // the limbs are big limb first, so the carries go from the last limb to the first one
+    let mut carry_in: Option<Boolean<BaseField>> = None;
+    for i in (0..layout.num_limbs).rev() {
+        let num_bits = positions[i].1;
+        let d =
+            AllocatedFp::<BaseField>::new_witness(ark_relations::ns!(cs, "complement"), || {
+                Ok(biguint_to_field(&complement_limbs[i]))
+            })?;
+        range_check::enforce_range(&d, num_bits)?;
+
+        let mut sum_lc = lc!() + limbs[i].variable + d.variable
+            - (
+                biguint_to_field::<BaseField>(&modulus_minus_one_limbs[i]),
+                Variable::One,
+            );
+        let mut sum_value =
+            field_to_biguint(&limbs[i].value().unwrap_or_default()) + &complement_limbs[i];
+        if let Some(carry_in) = &carry_in {
+            sum_lc = sum_lc + carry_in.lc();
+            if carry_in.value().unwrap_or_default() {
+                sum_value += 1u64;
+            }
+        }
+
+        // the top limb does not carry out, which bounds the sum by p - 1
+        if i == 0 {
+            cs.enforce_constraint(lc!(), lc!(), sum_lc)?;
+        } else {
+            let carry_out = Boolean::new_witness(ark_relations::ns!(cs, "carry"), || {
+                Ok(sum_value.bits() > num_bits as u64)
+            })?;
+            let shift = BaseField::from(2u64).pow(&[num_bits as u64]);
+            cs.enforce_constraint(lc!(), lc!(), sum_lc - carry_out.lc() * shift)?;
+            carry_in = Some(carry_out);
+        }
+    }
+
+    Ok(())
+}
+
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode>
+    AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>
+{
+    /// Output the limbs of `self` in `layout`, big limb first, e.g., for a gadget or a crate
+    /// that chose different parameters for `TargetField`
+    ///
+    /// The limbs are those of the canonical integer of `self`, range-checked to the sizes of
+    /// `layout`. They are enforced to represent `self` with a single grouped check against the
+    /// normal form of `self`, instead of a decomposition of both into bits, and to represent an
+    /// integer of at most `p - 1` with a carry chain against its complement.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn to_layout(
+        &self,
+        layout: &NonNativeFieldParams,
+    ) -> Result<Vec<AllocatedFp<BaseField>>, SynthesisError> {
+        let cs = self.cs.clone();
+        let params = get_params::<TargetField, BaseField>(&cs)?;
+        if layout.num_limbs == 0 || num_covered_bits(layout) < TargetField::size_in_bits() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+
+        let mut self_normal = self.clone();
+        Reducer::<TargetField, BaseField, Opt>::pre_eq_reduce(&mut self_normal)?;
+
+        let value = field_to_biguint(&self.value().unwrap_or_default());
+        let mut limbs = Vec::with_capacity(layout.num_limbs);
+        for (limb_value, (_, num_bits)) in split(&value, layout).iter().zip(limb_positions(layout))
+        {
+            let limb =
+                AllocatedFp::<BaseField>::new_witness(ark_relations::ns!(cs, "limb"), || {
+                    Ok(biguint_to_field(limb_value))
+                })?;
+            range_check::enforce_range(&limb, num_bits)?;
+            limbs.push(limb);
+        }
+
+        enforce_congruent::<TargetField, BaseField>(
+            &cs,
+            &self_normal.limbs,
+            &params,
+            0,
+            &limbs,
+            layout,
+        )?;
+        enforce_canonical::<TargetField, BaseField>(&cs, &limbs, layout)?;
+        Ok(limbs)
+    }
+
+    /// Output the limbs of the canonical integer of `self` in `layout`, big limb first, for an
+    /// equality check in a layout of its own
+    ///
+    /// Unlike `to_layout`, this checks the limbs against those of `self` directly, which may
+    /// have a surfeit, so no normal form of `self` is allocated in the layout of the
+    /// parameters. The limbs are not enforced to be canonical: the equality of the limbs of two
+    /// elements implies their congruence either way, and only a dishonest prover can make it
+    /// fail for equal elements.
+    pub(crate) fn to_equality_layout(
+        &self,
+        layout: &NonNativeFieldParams,
+    ) -> Result<Vec<AllocatedFp<BaseField>>, SynthesisError> {
+        let cs = self.cs.clone();
+        let params = get_params::<TargetField, BaseField>(&cs)?;
+        if layout.num_limbs == 0 || num_covered_bits(layout) < TargetField::size_in_bits() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+
+        let surfeit = if self.is_in_the_normal_form {
+            0
+        } else {
+            overhead!(self.num_of_additions_over_normal_form + BaseField::one())
+        };
+
+        let value = field_to_biguint(&self.value().unwrap_or_default());
+        let mut limbs = Vec::with_capacity(layout.num_limbs);
+        for (limb_value, (_, num_bits)) in split(&value, layout).iter().zip(limb_positions(layout))
+        {
+            let limb = AllocatedFp::<BaseField>::new_witness(
+                ark_relations::ns!(cs, "equality limb"),
+                || Ok(biguint_to_field(limb_value)),
+            )?;
+            range_check::enforce_range(&limb, num_bits)?;
+            limbs.push(limb);
+        }
+
+        enforce_congruent::<TargetField, BaseField>(
+            &cs,
+            &self.limbs,
+            &params,
+            surfeit,
+            &limbs,
+            layout,
+        )?;
+        Ok(limbs)
+    }
+
+    /// Obtain the element represented by `limbs` in `layout`, big limb first, e.g., as output
+    /// by a gadget or a crate that chose different parameters for `TargetField`
+    ///
+    /// The limbs are range-checked to the sizes of `layout`, and the result is allocated in the
+    /// normal form and enforced to represent the same element with a single grouped check.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn from_layout(
+        cs: ConstraintSystemRef<BaseField>,
+        limbs: &[AllocatedFp<BaseField>],
+        layout: &NonNativeFieldParams,
+    ) -> Result<Self, SynthesisError> {
+        let params = get_params::<TargetField, BaseField>(&cs)?;
+        if limbs.len() != layout.num_limbs || limbs.is_empty() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+
+        for (limb, (_, num_bits)) in limbs.iter().zip(limb_positions(layout)) {
+            range_check::enforce_range(limb, num_bits)?;
+        }
+
+        let value: BigUint = limb_terms(limbs, layout, 0)
+            .iter()
+            .map(|t| &t.value << t.position)
+            .sum();
+        let res = Self::new_witness(ark_relations::ns!(cs, "from layout"), || {
+            Ok(biguint_to_field::<TargetField>(&value))
+        })?;
+
+        enforce_congruent::<TargetField, BaseField>(&cs, &res.limbs, &params, 0, limbs, layout)?;
+        Ok(res)
+    }
+}
@@ -36,10 +36,19 @@
 #[macro_use]
 extern crate ark_r1cs_std;
 
-use crate::params::{gen_params, get_params};
-use crate::reduce::Reducer;
-use ark_ff::PrimeField;
+use crate::error::NonNativeFieldError;
+use crate::graph::GraphScope;
+use crate::params::{
+    get_params, get_shared_params, mark_params_in_use, validate_params, ConstraintsOpt,
+    IdentityCache, KnownConstants, OptimizationMode, OverflowWarnings,
+};
+use crate::reduce::{
+    bigint_to_biguint, biguint_to_field, field_to_biguint, limbs_to_biguint, pseudo_mersenne_form,
+    Reducer, ReductionTrace,
+};
+use crate::report::{MemoryStats, Operation, OperationScope, ProductHandle};
 use ark_ff::{to_bytes, BigInteger};
+use ark_ff::{FpParameters, PrimeField};
 use ark_r1cs_std::fields::fp::FpVar;
 use ark_r1cs_std::{
     bits::{ToBitsGadget, ToBytesGadget},
@@ -53,11 +62,51 @@ use ark_r1cs_std::{
 };
 use ark_relations::{
     lc,
-    r1cs::{ConstraintSystemRef, LinearCombination, Namespace, SynthesisError},
+    r1cs::{ConstraintSystemRef, LinearCombination, Namespace, SynthesisError, Variable},
+};
+use ark_std::{
+    borrow::{Borrow, Cow},
+    cmp::{max, min},
+    fmt::Debug,
+    iter::{Product, Sum},
+    marker::PhantomData,
+    ops::Neg,
+    vec,
+    vec::Vec,
 };
-use ark_std::{borrow::Borrow, cmp::max, fmt::Debug, marker::PhantomData, vec, vec::Vec};
 use core::hash::{Hash, Hasher};
-
+use num_bigint::BigUint;
+use num_traits::{One, ToPrimitive};
+#[cfg(feature = "zeroize")]
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// an accumulator of unreduced products that reduces only when it runs out of room, and the
+/// matrix-vector products built on it
+pub mod accumulator;
+/// emulation of binary extension fields `GF(2^k)`, such as that of GHASH
+pub mod binary;
+/// gadgets for elliptic curves whose base field is simulated
+pub mod curves;
+/// verification of discrete-log-equality (Chaum–Pedersen) proofs over simulated curves
+pub mod dleq;
+/// the errors of the functions that do not implement the traits of ark-r1cs-std
+pub mod error;
+/// Nova-style folding of vectors of simulated elements with native challenges
+pub mod folding;
+/// the dependency graph of the nonnative operations, recorded and exported as DOT with the
+/// `expression-graph` feature
+pub mod graph;
+/// Groth16 verifier helpers whose public inputs live in the simulated scalar field
+pub mod groth16;
+/// verification of inner-product arguments and of IPA polynomial-commitment openings over
+/// simulated fields
+pub mod ipa;
+/// helpers for the scalar side of KZG opening checks over a simulated scalar field
+pub mod kzg;
+/// conversions between the limb layouts of different parameters for the same target field
+pub mod layout;
+/// homomorphic operations on Paillier ciphertexts modulo `N^2`, built on `uint`
+pub mod paillier;
 /// example parameters of non-native field gadget
 ///
 /// Sample parameters for non-native field gadgets
@@ -71,9 +120,36 @@ use core::hash::{Hash, Hasher};
 /// - `bits_per_top_limb <= bits_per_non_top_limb`, since the current implementation does not handle the other case, and the other case would not be significantly more efficient.
 /// - `BaseField's prime length - 1` > `2 * (bits_per_non_top_limb + 5)`, which ensures that the reducer is able to reduce the representations using the `sum of residues` method.
 pub mod params;
+/// polynomial evaluation gadgets over the simulated field
+pub mod poly;
+/// a Poseidon permutation and sponge whose state lives in the simulated field
+pub mod poseidon;
+/// the range checks of the limbs, which can use the lookup tables of the constraint systems
+/// that support them
+pub mod range_check;
 /// a submodule for reducing the representations
 #[doc(hidden)]
 pub mod reduce;
+/// a breakdown of the constraints generated by blocks of nonnative operations
+pub mod report;
+/// an adapter of simulated scalars for the scalar multiplications of the curve gadgets of
+/// ark-r1cs-std
+pub mod scalar;
+/// proptest strategies for simulated values, with the edge cases of the limb arithmetic
+#[cfg(feature = "proptest-strategies")]
+pub mod strategies;
+/// small prime target fields for near-exhaustive testing of the reductions
+#[cfg(feature = "test-fields")]
+pub mod test_fields;
+/// empirical tuning of the parameters with synthesized microbenchmarks
+#[cfg(feature = "std")]
+pub mod tune;
+/// 256-bit unsigned and signed integers with wrapping (EVM) semantics built on range-checked limbs
+pub mod u256;
+/// unsigned integers of any number of limbs, e.g., RSA moduli, with arithmetic modulo a witness
+pub mod uint;
+/// windowed non-adjacent form recoding of scalars for signed-digit scalar multiplications
+pub mod wnaf;
 
 /// a macro for computing ceil(log2(x)) for a field element x
 #[doc(hidden)]
@@ -106,8 +182,87 @@ macro_rules! overhead {
     }};
 }
 
+/// Generate a module for simulating one target field in one base field, with optional fixed
+/// parameters
+///
+/// ```ignore
+/// nonnative! {
+///     /// Elements of `Fr` in a constraint system over `Fq`
+///     pub mod fr_var: NonNativeFieldVar<Fr, Fq>, params = PARAMS;
+/// }
+///
+/// let a = fr_var::new_witness(ns!(cs, "a"), || Ok(a_native))?;
+/// let b: fr_var::Var = fr_var::constant(b_native);
+/// ```
+///
+/// The module provides the alias `Var` for the `NonNativeFieldVar`, and constructors that
+/// store the parameters, if any, in the constraint system before allocating, so that the
+/// gadgets never search for them. The constructors can be used in place of the turbofish
+/// calls of `AllocVar`.
+#[macro_export]
+macro_rules! nonnative {
+    ($(#[$attr:meta])* $vis:vis mod $name:ident: NonNativeFieldVar<$target:ty, $base:ty> $(, params = $params:expr)? $(;)?) => {
+        $(#[$attr])*
+        #[allow(dead_code)]
+        $vis mod $name {
+            #[allow(unused_imports)]
+            use super::*;
+            use ::ark_r1cs_std::alloc::AllocVar;
+            use ::ark_relations::r1cs::{ConstraintSystemRef, Namespace, SynthesisError};
+
+            /// The simulated field
+            pub type TargetField = $target;
+            /// The constraint field
+            pub type BaseField = $base;
+            /// An element of `TargetField` simulated in a constraint system over `BaseField`
+            pub type Var = $crate::NonNativeFieldVar<TargetField, BaseField>;
+
+            /// Store the fixed parameters, if any, in the cache of `cs`, unless it already
+            /// holds their limbs, so that only the first allocation in `cs` stores them and a
+            /// layout of the equality checks set in `cs` is kept
+            #[allow(unused_variables)]
+            pub fn set_params(cs: &ConstraintSystemRef<BaseField>) -> Result<(), SynthesisError> {
+                $(
+                    let params = $params;
+                    if !$crate::params::get_cached_params::<TargetField, BaseField>(cs)
+                        .map_or(false, |cached| cached.has_same_limbs(&params))
+                    {
+                        $crate::params::set_params::<TargetField, BaseField>(cs, params)?;
+                    }
+                )?
+                Ok(())
+            }
+
+            /// Allocate a witness
+            pub fn new_witness(
+                cs: impl Into<Namespace<BaseField>>,
+                f: impl FnOnce() -> Result<TargetField, SynthesisError>,
+            ) -> Result<Var, SynthesisError> {
+                let ns = cs.into();
+                set_params(&ns.cs())?;
+                Var::new_witness(ns, f)
+            }
+
+            /// Allocate a public input
+            pub fn new_input(
+                cs: impl Into<Namespace<BaseField>>,
+                f: impl FnOnce() -> Result<TargetField, SynthesisError>,
+            ) -> Result<Var, SynthesisError> {
+                let ns = cs.into();
+                set_params(&ns.cs())?;
+                Var::new_input(ns, f)
+            }
+
+            /// Create a constant
+            pub fn constant(value: TargetField) -> Var {
+                <Var as ::ark_r1cs_std::fields::FieldVar<TargetField, BaseField>>::constant(value)
+            }
+        }
+    };
+}
+
 /// Parameters for a specific `NonNativeFieldVar` instantiation
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct NonNativeFieldParams {
     /// The number of limbs (`BaseField` elements) used to represent a `TargetField` element. Highest limb first.
     /// Searched by the Python script
@@ -120,12 +275,122 @@ pub struct NonNativeFieldParams {
     /// The number of bits of the limbs other than the top one
     /// Searched by the Python script
     pub bits_per_non_top_limb: usize,
+
+    /// The layout in which the equality checks compare both sides, instead of comparing their
+    /// normal forms in the layout above
+    pub equality_layout: Option<EqualityLayout>,
+}
+
+impl NonNativeFieldParams {
+    /// Whether the elements have the same limbs with `self` and with `other`, which may only
+    /// differ in the layout of the equality checks
+    pub fn has_same_limbs(&self, other: &Self) -> bool {
+        self.num_limbs == other.num_limbs
+            && self.bits_per_top_limb == other.bits_per_top_limb
+            && self.bits_per_non_top_limb == other.bits_per_non_top_limb
+    }
+}
+
+/// A layout of limbs for the equality checks alone, e.g., with limbs whose sizes are multiples
+/// of the bits of a lookup table, while the products keep the layout of the parameters
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EqualityLayout {
+    /// The number of limbs. Highest limb first.
+    pub num_limbs: usize,
+
+    /// The number of bits of the top limb
+    pub bits_per_top_limb: usize,
+
+    /// The number of bits of the limbs other than the top one
+    pub bits_per_non_top_limb: usize,
+}
+
+impl From<EqualityLayout> for NonNativeFieldParams {
+    fn from(layout: EqualityLayout) -> Self {
+        Self {
+            num_limbs: layout.num_limbs,
+            bits_per_top_limb: layout.bits_per_top_limb,
+            bits_per_non_top_limb: layout.bits_per_non_top_limb,
+            equality_layout: None,
+        }
+    }
+}
+
+/// The number of operations that a simulated element can still absorb before a reduction
+/// becomes mandatory, saturating at `usize::MAX`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Headroom {
+    /// The number of additions of elements in the normal form that keep the result unreduced
+    pub additions: usize,
+    /// The number of additions of elements in the normal form after which the result can
+    /// still be multiplied by an element in the normal form without a reduction, or `None` if
+    /// the multiplication already requires one
+    pub additions_before_mul: Option<usize>,
+}
+
+impl Headroom {
+    /// The headroom of constants, which are never reduced
+    const UNLIMITED: Self = Self {
+        additions: usize::MAX,
+        additions_before_mul: Some(usize::MAX),
+    };
+}
+
+/// Find the largest value below `2^(BaseField::size_in_bits() - 1)` that satisfies `pred`,
+/// which must hold for all values below a satisfying one, or `None` if zero does not
+fn largest_satisfying<BaseField: PrimeField>(
+    mut pred: impl FnMut(BaseField) -> Result<bool, SynthesisError>,
+) -> Result<Option<BigUint>, SynthesisError> {
+    if !pred(BaseField::zero())? {
+        return Ok(None);
+    }
+
+    let mut lo = BigUint::from(0u32);
+    let mut hi = BigUint::one() << (BaseField::size_in_bits() - 1);
+    while &hi - &lo > BigUint::one() {
+        let mid = (&lo + &hi) >> 1usize;
+        if pred(biguint_to_field(&mid))? {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+
+    Ok(Some(lo))
+}
+
+/// The number of unit steps from `cur` up to `max`, saturating at zero and at `usize::MAX`
+fn steps_up_to<BaseField: PrimeField>(cur: &BaseField, max: &BigUint) -> usize {
+    let cur = field_to_biguint(cur);
+    if &cur >= max {
+        0
+    } else {
+        (max - cur).to_usize().unwrap_or(usize::MAX)
+    }
+}
+
+/// The limb values of an element being allocated, which are scrubbed when the buffer is
+/// dropped if the `zeroize` feature is enabled
+struct LimbBuffer<BaseField: PrimeField>(Vec<BaseField>);
+
+#[cfg(feature = "zeroize")]
+impl<BaseField: PrimeField> Drop for LimbBuffer<BaseField> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
 }
 
+#[cfg(feature = "zeroize")]
+impl<BaseField: PrimeField> ZeroizeOnDrop for LimbBuffer<BaseField> {}
+
 /// The allocated version of `NonNativeFieldVar` (introduced below)
 #[derive(Debug)]
 #[must_use]
-pub struct AllocatedNonNativeFieldVar<TargetField: PrimeField, BaseField: PrimeField> {
+pub struct AllocatedNonNativeFieldVar<
+    TargetField: PrimeField,
+    BaseField: PrimeField,
+    Opt: OptimizationMode = ConstraintsOpt,
+> {
     /// Reference to the constraint system
     pub cs: ConstraintSystemRef<BaseField>,
     /// The limbs, each of which is a BaseField gadget.
@@ -135,21 +400,28 @@ pub struct AllocatedNonNativeFieldVar<TargetField: PrimeField, BaseField: PrimeF
     /// Whether the limb representation is the normal form (using only the bits specified in the parameters, and the representation is strictly within the range of TargetField).
     pub is_in_the_normal_form: bool,
     #[doc(hidden)]
-    pub target_phantom: PhantomData<TargetField>,
+    pub target_phantom: PhantomData<(TargetField, Opt)>,
 }
 
 /// A gadget for representing non-native (`TargetField`) field elements over the constraint field (`BaseField`).
+///
+/// The optimization mode `Opt`, `ConstraintsOpt` by default, selects how the reductions
+/// allocate their intermediates; see `params::OptimizationMode`.
 #[derive(Clone, Debug)]
 #[must_use]
-pub enum NonNativeFieldVar<TargetField: PrimeField, BaseField: PrimeField> {
+pub enum NonNativeFieldVar<
+    TargetField: PrimeField,
+    BaseField: PrimeField,
+    Opt: OptimizationMode = ConstraintsOpt,
+> {
     /// Constant
     Constant(TargetField),
     /// Allocated gadget
-    Var(AllocatedNonNativeFieldVar<TargetField, BaseField>),
+    Var(AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>),
 }
 
-impl<TargetField: PrimeField, BaseField: PrimeField> PartialEq
-    for NonNativeFieldVar<TargetField, BaseField>
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode> PartialEq
+    for NonNativeFieldVar<TargetField, BaseField, Opt>
 {
     fn eq(&self, other: &Self) -> bool {
         self.value()
@@ -158,21 +430,21 @@ impl<TargetField: PrimeField, BaseField: PrimeField> PartialEq
     }
 }
 
-impl<TargetField: PrimeField, BaseField: PrimeField> Eq
-    for NonNativeFieldVar<TargetField, BaseField>
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode> Eq
+    for NonNativeFieldVar<TargetField, BaseField, Opt>
 {
 }
 
-impl<TargetField: PrimeField, BaseField: PrimeField> Hash
-    for NonNativeFieldVar<TargetField, BaseField>
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode> Hash
+    for NonNativeFieldVar<TargetField, BaseField, Opt>
 {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.value().unwrap_or_default().hash(state);
     }
 }
 
-impl<TargetField: PrimeField, BaseField: PrimeField> R1CSVar<BaseField>
-    for NonNativeFieldVar<TargetField, BaseField>
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode> R1CSVar<BaseField>
+    for NonNativeFieldVar<TargetField, BaseField, Opt>
 {
     type Value = TargetField;
 
@@ -191,8 +463,8 @@ impl<TargetField: PrimeField, BaseField: PrimeField> R1CSVar<BaseField>
     }
 }
 
-impl<TargetField: PrimeField, BaseField: PrimeField> From<Boolean<BaseField>>
-    for NonNativeFieldVar<TargetField, BaseField>
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode> From<Boolean<BaseField>>
+    for NonNativeFieldVar<TargetField, BaseField, Opt>
 {
     fn from(other: Boolean<BaseField>) -> Self {
         if let Boolean::Constant(b) = other {
@@ -206,17 +478,17 @@ impl<TargetField: PrimeField, BaseField: PrimeField> From<Boolean<BaseField>>
     }
 }
 
-impl<TargetField: PrimeField, BaseField: PrimeField>
-    From<AllocatedNonNativeFieldVar<TargetField, BaseField>>
-    for NonNativeFieldVar<TargetField, BaseField>
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode>
+    From<AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>>
+    for NonNativeFieldVar<TargetField, BaseField, Opt>
 {
-    fn from(other: AllocatedNonNativeFieldVar<TargetField, BaseField>) -> Self {
+    fn from(other: AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>) -> Self {
         Self::Var(other)
     }
 }
 
-impl<TargetField: PrimeField, BaseField: PrimeField> Clone
-    for AllocatedNonNativeFieldVar<TargetField, BaseField>
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode> Clone
+    for AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>
 {
     fn clone(&self) -> Self {
         AllocatedNonNativeFieldVar {
@@ -229,51 +501,123 @@ impl<TargetField: PrimeField, BaseField: PrimeField> Clone
     }
 }
 
-impl<'a, TargetField: PrimeField, BaseField: PrimeField> FieldOpsBounds<'a, TargetField, Self>
-    for NonNativeFieldVar<TargetField, BaseField>
+#[cfg(feature = "zeroize")]
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode> Zeroize
+    for AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>
 {
+    /// Scrub the limb values held by this gadget and drop them, keeping the variables.
+    /// The assignment stored in the constraint system is not affected.
+    fn zeroize(&mut self) {
+        for limb in self.limbs.iter_mut() {
+            // the value of an `AllocatedFp` is private, so it is first overwritten in place by
+            // a zero, which `black_box` keeps from being elided, and only then dropped
+            *limb = AllocatedFp::new(Some(BaseField::zero()), limb.variable, limb.cs.clone());
+            core::hint::black_box(&*limb);
+            *limb = AllocatedFp::new(None, limb.variable, limb.cs.clone());
+        }
+    }
 }
 
-impl<'a, TargetField: PrimeField, BaseField: PrimeField>
-    FieldOpsBounds<'a, TargetField, NonNativeFieldVar<TargetField, BaseField>>
-    for &'a NonNativeFieldVar<TargetField, BaseField>
+#[cfg(feature = "zeroize")]
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode> Zeroize
+    for NonNativeFieldVar<TargetField, BaseField, Opt>
 {
+    fn zeroize(&mut self) {
+        match self {
+            Self::Constant(c) => c.zeroize(),
+            Self::Var(v) => v.zeroize(),
+        }
+    }
 }
 
-impl<TargetField: PrimeField, BaseField: PrimeField>
-    AllocatedNonNativeFieldVar<TargetField, BaseField>
+impl<'a, TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode>
+    FieldOpsBounds<'a, TargetField, Self> for NonNativeFieldVar<TargetField, BaseField, Opt>
 {
-    /// Obtain the value of a nonnative field element
-    pub fn value(&self) -> Result<TargetField, SynthesisError> {
-        let params = get_params::<TargetField, BaseField>(&self.cs);
-        let bits_per_non_top_limb = params.bits_per_non_top_limb;
+}
 
-        let mut result = TargetField::zero();
-        let mut power = TargetField::one();
-        let mut base_repr: <TargetField as PrimeField>::BigInt = TargetField::one().into_repr();
-        base_repr.muln(bits_per_non_top_limb as u32);
-        let base: TargetField = TargetField::from_repr(base_repr).unwrap();
+/// The modulus of the target field as a `BigUint`
+#[cfg(feature = "reference-check")]
+fn target_modulus<TargetField: PrimeField>() -> BigUint {
+    bigint_to_biguint(&<<TargetField as PrimeField>::Params as FpParameters>::MODULUS)
+}
+
+#[cfg(feature = "reference-check")]
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode>
+    AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>
+{
+    /// The integer represented by the limbs, without reducing it modulo the target field
+    fn value_as_biguint(&self) -> Result<BigUint, SynthesisError> {
+        let params = get_params::<TargetField, BaseField>(&self.cs)?;
+        let limbs = self
+            .limbs
+            .iter()
+            .map(|limb| limb.value())
+            .collect::<Result<Vec<BaseField>, SynthesisError>>()?;
 
-        for limb in self.limbs.iter().rev() {
-            // switch field with the same representation
-            let this_limb_as_base = limb.value().unwrap_or_default();
-            let this_limb_as_bits = this_limb_as_base.into_repr().to_bits();
+        Ok(limbs_to_biguint(&limbs, params.bits_per_non_top_limb))
+    }
 
-            let mut val = TargetField::zero();
-            let mut cur = TargetField::one();
+    /// Check that `self` is congruent to the result of `operation` recomputed with plain
+    /// `BigUint` arithmetic on the limbs of its operands by `reference`
+    ///
+    /// This runs at witness generation only; it is skipped when some values are missing.
+    fn reference_check(
+        &self,
+        operation: &'static str,
+        reference: impl FnOnce() -> Result<BigUint, SynthesisError>,
+    ) -> Result<(), SynthesisError> {
+        if self.cs.is_in_setup_mode() {
+            return Ok(());
+        }
 
-            for bit in this_limb_as_bits.iter().rev() {
-                if *bit {
-                    val += &cur;
-                }
-                cur.double_in_place();
-            }
+        let (actual, expected) = match (self.value_as_biguint(), reference()) {
+            (Ok(actual), Ok(expected)) => (actual, expected),
+            _ => return Ok(()),
+        };
 
-            result += &(val * power);
-            power *= &base;
+        let modulus = target_modulus::<TargetField>();
+        if actual % &modulus != expected % &modulus {
+            tracing::error!(
+                target: "r1cs",
+                operation,
+                "the witness diverges from the reference computation"
+            );
+            return Err(SynthesisError::Unsatisfiable);
         }
 
-        Ok(result)
+        Ok(())
+    }
+}
+
+impl<'a, TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode>
+    FieldOpsBounds<'a, TargetField, NonNativeFieldVar<TargetField, BaseField, Opt>>
+    for &'a NonNativeFieldVar<TargetField, BaseField, Opt>
+{
+}
+
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode>
+    AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>
+{
+    /// The variables of the limbs, which identify the element
+    pub(crate) fn limb_variables(&self) -> Vec<Variable> {
+        self.limbs.iter().map(|limb| limb.variable).collect()
+    }
+
+    /// Obtain the value of a nonnative field element
+    pub fn value(&self) -> Result<TargetField, SynthesisError> {
+        let params = get_params::<TargetField, BaseField>(&self.cs)?;
+        let bits_per_non_top_limb = params.bits_per_non_top_limb;
+
+        let limbs: Vec<BaseField> = self
+            .limbs
+            .iter()
+            .map(|limb| limb.value().unwrap_or_default())
+            .collect();
+
+        Ok(biguint_to_field(&limbs_to_biguint(
+            &limbs,
+            bits_per_non_top_limb,
+        )))
     }
 
     /// Return cs
@@ -281,9 +625,59 @@ impl<TargetField: PrimeField, BaseField: PrimeField>
         self.cs.clone()
     }
 
+    /// Convert `self` into an element of the optimization mode `OtherOpt`, e.g., to pass it to a
+    /// gadget that works in another mode
+    ///
+    /// The modes share the parameters of the constraint system and only differ in how the
+    /// reductions allocate their intermediates, so the limbs are kept as they are and the
+    /// conversion generates no constraints. The elements of a gadget or a crate that chose
+    /// different parameters are converted with `to_layout` and `from_layout` instead.
+    pub fn to_mode<OtherOpt: OptimizationMode>(
+        &self,
+    ) -> AllocatedNonNativeFieldVar<TargetField, BaseField, OtherOpt> {
+        AllocatedNonNativeFieldVar {
+            cs: self.cs.clone(),
+            limbs: self.limbs.clone(),
+            num_of_additions_over_normal_form: self.num_of_additions_over_normal_form,
+            is_in_the_normal_form: self.is_in_the_normal_form,
+            target_phantom: PhantomData,
+        }
+    }
+
+    /// Return how many more operations `self` can absorb before a reduction becomes mandatory
+    ///
+    /// This only inspects the tracked bounds and generates no constraints, so gadgets can use
+    /// it to choose between variants of an algorithm, e.g., to reorder operations.
+    pub fn headroom(&self) -> Result<Headroom, SynthesisError> {
+        let mut probe = self.clone();
+        let mut normal_form = self.clone();
+        normal_form.num_of_additions_over_normal_form = BaseField::zero();
+
+        let max_for_additions = largest_satisfying(|num_of_additions| {
+            probe.num_of_additions_over_normal_form = num_of_additions;
+            Reducer::can_stay_unreduced(&probe)
+        })?;
+        let max_for_mul = largest_satisfying(|num_of_additions| {
+            probe.num_of_additions_over_normal_form = num_of_additions;
+            Reducer::can_safely_mul(&probe, &normal_form)
+        })?;
+
+        let cur = &self.num_of_additions_over_normal_form;
+        let additions_before_mul = match max_for_mul {
+            Some(max) if field_to_biguint(cur) <= max => Some(steps_up_to(cur, &max)),
+            _ => None,
+        };
+
+        Ok(Headroom {
+            additions: max_for_additions.map_or(0, |max| steps_up_to(cur, &max)),
+            additions_before_mul,
+        })
+    }
+
     /// Add a nonnative field element
     #[tracing::instrument(target = "r1cs")]
     pub fn add(&self, other: &Self) -> Result<Self, SynthesisError> {
+        let scope = GraphScope::start("add", &self.cs, &[&self.limbs[..], &other.limbs[..]]);
         let mut limbs = Vec::<AllocatedFp<BaseField>>::new();
 
         for (this_limb, other_limb) in self.limbs.iter().zip(other.limbs.iter()) {
@@ -301,7 +695,12 @@ impl<TargetField: PrimeField, BaseField: PrimeField>
             target_phantom: PhantomData,
         };
 
-        Reducer::<TargetField, BaseField>::post_add_reduce(&mut res)?;
+        Reducer::<TargetField, BaseField, Opt>::post_add_reduce(&mut res)?;
+        #[cfg(feature = "reference-check")]
+        res.reference_check("add", || {
+            Ok(self.value_as_biguint()? + other.value_as_biguint()?)
+        })?;
+        scope.finish(Some(&res.limbs));
 
         Ok(res)
     }
@@ -309,6 +708,7 @@ impl<TargetField: PrimeField, BaseField: PrimeField>
     /// Add a constant
     #[tracing::instrument(target = "r1cs")]
     pub fn add_constant(&self, other: &TargetField) -> Result<Self, SynthesisError> {
+        let scope = GraphScope::start("add_constant", &self.cs, &[&self.limbs[..]]);
         let mut limbs = Vec::<AllocatedFp<BaseField>>::new();
         let other_limbs = Self::get_limbs_representations(other, Some(&self.cs))?;
 
@@ -326,7 +726,12 @@ impl<TargetField: PrimeField, BaseField: PrimeField>
             target_phantom: PhantomData,
         };
 
-        Reducer::<TargetField, BaseField>::post_add_reduce(&mut res)?;
+        Reducer::<TargetField, BaseField, Opt>::post_add_reduce(&mut res)?;
+        #[cfg(feature = "reference-check")]
+        res.reference_check("add_constant", || {
+            Ok(self.value_as_biguint()? + field_to_biguint(other))
+        })?;
+        scope.finish(Some(&res.limbs));
 
         Ok(res)
     }
@@ -334,29 +739,45 @@ impl<TargetField: PrimeField, BaseField: PrimeField>
     /// Subtract a nonnative field element
     #[tracing::instrument(target = "r1cs")]
     pub fn sub(&self, other: &Self) -> Result<Self, SynthesisError> {
+        let scope = GraphScope::start("sub", &self.cs, &[&self.limbs[..], &other.limbs[..]]);
         let result: TargetField = self
             .value()
             .unwrap_or_default()
             .sub(&other.value().unwrap_or_default());
-        let result_gadget = AllocatedNonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        let result_gadget = AllocatedNonNativeFieldVar::<TargetField, BaseField, Opt>::new_witness(
             self.cs.clone(),
             || Ok(result),
         )?;
         let result_computed = other.add(&result_gadget)?;
         self.conditional_enforce_equal(&result_computed, &Boolean::TRUE)?;
+        #[cfg(feature = "reference-check")]
+        result_gadget.reference_check("sub", || {
+            let modulus = target_modulus::<TargetField>();
+            Ok(self.value_as_biguint()? + &modulus - other.value_as_biguint()? % &modulus)
+        })?;
+        scope.finish(Some(&result_gadget.limbs));
         Ok(result_gadget)
     }
 
     /// Subtract a constant
     #[tracing::instrument(target = "r1cs")]
     pub fn sub_constant(&self, other: &TargetField) -> Result<Self, SynthesisError> {
+        let scope = GraphScope::start("sub_constant", &self.cs, &[&self.limbs[..]]);
         let result: TargetField = self.value().unwrap_or_default().sub(other);
-        let result_gadget = AllocatedNonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        let result_gadget = AllocatedNonNativeFieldVar::<TargetField, BaseField, Opt>::new_witness(
             self.cs.clone(),
             || Ok(result),
         )?;
         let result_computed = result_gadget.add_constant(&other)?;
         self.conditional_enforce_equal(&result_computed, &Boolean::TRUE)?;
+        #[cfg(feature = "reference-check")]
+        result_gadget.reference_check("sub_constant", || {
+            Ok(
+                self.value_as_biguint()? + target_modulus::<TargetField>()
+                    - field_to_biguint(other),
+            )
+        })?;
+        scope.finish(Some(&result_gadget.limbs));
         Ok(result_gadget)
     }
 
@@ -364,19 +785,72 @@ impl<TargetField: PrimeField, BaseField: PrimeField>
     #[tracing::instrument(target = "r1cs")]
     pub fn mul(&self, other: &Self) -> Result<Self, SynthesisError> {
         let mul = self.mul_without_reduce(&other)?;
-        mul.reduce()
+        let res = mul.reduce()?;
+        #[cfg(feature = "reference-check")]
+        res.reference_check("mul", || {
+            Ok(self.value_as_biguint()? * other.value_as_biguint()?)
+        })?;
+        Ok(res)
     }
 
     /// Multiply a constant
     pub fn mul_constant(&self, other: &TargetField) -> Result<Self, SynthesisError> {
-        let other_gadget = AllocatedNonNativeFieldVar::new_constant(self.cs.clone(), other)?;
-        self.mul(&other_gadget)
+        self.mul_constant_without_reduce(other)?.reduce()
+    }
+
+    /// The zero of `cs` as an allocated constant, whose limbs are allocated once per
+    /// constraint system and shared by the later calls
+    pub fn zero(cs: ConstraintSystemRef<BaseField>) -> Result<Self, SynthesisError> {
+        Self::identity(cs, false)
+    }
+
+    /// The one of `cs` as an allocated constant, whose limbs are allocated once per
+    /// constraint system and shared by the later calls
+    pub fn one(cs: ConstraintSystemRef<BaseField>) -> Result<Self, SynthesisError> {
+        Self::identity(cs, true)
+    }
+
+    /// The one if `is_one` is set, or the zero otherwise, with the cached limbs if any
+    fn identity(cs: ConstraintSystemRef<BaseField>, is_one: bool) -> Result<Self, SynthesisError> {
+        let value = if is_one {
+            TargetField::one()
+        } else {
+            TargetField::zero()
+        };
+
+        let params = get_params::<TargetField, BaseField>(&cs)?;
+        let variables = match IdentityCache::<TargetField, BaseField>::load(&cs, &params, is_one) {
+            Some(variables) => variables,
+            None => {
+                let res = Self::new_constant(cs.clone(), &value)?;
+                IdentityCache::<TargetField, BaseField>::store(
+                    &cs,
+                    &params,
+                    is_one,
+                    res.limbs.iter().map(|limb| limb.variable).collect(),
+                );
+                return Ok(res);
+            }
+        };
+
+        let limbs = Self::get_limbs_representations(&value, Some(&cs))?
+            .into_iter()
+            .zip(variables)
+            .map(|(limb, variable)| AllocatedFp::new(Some(limb), variable, cs.clone()))
+            .collect();
+        Ok(Self {
+            cs,
+            limbs,
+            num_of_additions_over_normal_form: BaseField::zero(),
+            is_in_the_normal_form: true,
+            target_phantom: PhantomData,
+        })
     }
 
     /// Compute the negate of a nonnative field element
     #[tracing::instrument(target = "r1cs")]
     pub fn negate(&self) -> Result<Self, SynthesisError> {
-        let zero = Self::new_constant(self.cs.clone(), &TargetField::zero())?;
+        let zero = Self::zero(self.cs.clone())?;
         zero.sub(self)
     }
 
@@ -384,15 +858,10 @@ impl<TargetField: PrimeField, BaseField: PrimeField>
     #[tracing::instrument(target = "r1cs")]
     pub fn inverse(&self) -> Result<Self, SynthesisError> {
         let inverse = Self::new_witness(self.cs.clone(), || {
-            Ok({
-                self.value()
-                    .unwrap()
-                    .inverse()
-                    .unwrap_or_else(TargetField::zero)
-            })
+            Ok({ self.value()?.inverse().unwrap_or_else(TargetField::zero) })
         })?;
 
-        let one = AllocatedNonNativeFieldVar::new_constant(self.cs.clone(), &TargetField::one())?;
+        let one = Self::one(self.cs.clone())?;
 
         let actual_result = self.clone().mul(&inverse)?;
         actual_result.conditional_enforce_equal(&one, &Boolean::TRUE)?;
@@ -415,41 +884,42 @@ impl<TargetField: PrimeField, BaseField: PrimeField>
         elem: &<TargetField as PrimeField>::BigInt,
         cs: Option<&ConstraintSystemRef<BaseField>>,
     ) -> Result<Vec<BaseField>, SynthesisError> {
-        let mut limbs: Vec<BaseField> = Vec::new();
-        let mut cur = *elem;
-
         let params = match cs {
-            Some(cs) => get_params::<TargetField, BaseField>(cs),
-            None => gen_params::<TargetField, BaseField>(),
+            Some(cs) => get_params::<TargetField, BaseField>(cs)?,
+            None => get_shared_params::<TargetField, BaseField>()?,
         };
 
-        let num_limbs = params.num_limbs;
-        let bits_per_top_limb = params.bits_per_top_limb;
-        let bits_per_non_top_limb = params.bits_per_non_top_limb;
+        Ok(Self::limbs_in_layout(elem, &params))
+    }
 
-        // push the lower limbs first
-        for _ in 0..num_limbs - 1 {
-            let cur_bits = cur.to_bits(); // `to_bits` is big endian
-            let cur_mod_r = <BaseField as PrimeField>::BigInt::from_bits(
-                &cur_bits[cur_bits.len() - bits_per_non_top_limb..],
-            ); // therefore, the lowest `bits_per_non_top_limb` bits is what we want.
-            limbs.push(BaseField::from_repr(cur_mod_r).unwrap());
-            cur.divn(bits_per_non_top_limb as u32);
-        }
+    /// Split `elem` into the limbs of `params`, big limb first
+    fn limbs_in_layout(
+        elem: &<TargetField as PrimeField>::BigInt,
+        params: &NonNativeFieldParams,
+    ) -> Vec<BaseField> {
+        // the limbs are accumulated bit by bit in place, big limb first, without intermediate
+        // big integers that could not be scrubbed, and the vector is never reallocated
+        let num_bits = elem.num_bits() as usize;
+        let mut limbs: Vec<BaseField> = Vec::with_capacity(params.num_limbs);
+        for i in 0..params.num_limbs {
+            let start = (params.num_limbs - 1 - i) * params.bits_per_non_top_limb;
+            let num_limb_bits = if i == 0 {
+                params.bits_per_top_limb
+            } else {
+                params.bits_per_non_top_limb
+            };
 
-        // push the top limb
-        {
-            let cur_bits = cur.to_bits();
-            let cur_mod_r = <BaseField as PrimeField>::BigInt::from_bits(
-                &cur_bits[cur_bits.len() - bits_per_top_limb..],
-            );
-            limbs.push(BaseField::from_repr(cur_mod_r).unwrap());
+            limbs.push(BaseField::zero());
+            let limb = &mut limbs[i];
+            for j in (start..start + num_limb_bits).rev() {
+                limb.double_in_place();
+                if j < num_bits && elem.get_bit(j) {
+                    *limb += BaseField::one();
+                }
+            }
         }
 
-        // then we reserve, so that the limbs are ``big limb first''
-        limbs.reverse();
-
-        Ok(limbs)
+        limbs
     }
 
     /// for advanced use, multiply and output the intermediate representations (without reduction)
@@ -457,14 +927,25 @@ impl<TargetField: PrimeField, BaseField: PrimeField>
     pub fn mul_without_reduce(
         &self,
         other: &Self,
-    ) -> Result<AllocatedNonNativeFieldMulResultVar<TargetField, BaseField>, SynthesisError> {
-        let params = get_params::<TargetField, BaseField>(&self.cs);
+    ) -> Result<AllocatedNonNativeFieldMulResultVar<TargetField, BaseField, Opt>, SynthesisError>
+    {
+        let _ns = ark_relations::ns!(self.cs, "nonnative::mul");
+        let _scope = OperationScope::start(&self.cs, Operation::Multiplication);
+        let scope = GraphScope::start(
+            "mul_without_reduce",
+            &self.cs,
+            &[&self.limbs[..], &other.limbs[..]],
+        );
+        let params = get_params::<TargetField, BaseField>(&self.cs)?;
 
         let num_limbs = params.num_limbs;
 
         let mut self_reduced = self.clone();
         let mut other_reduced = other.clone();
-        Reducer::<TargetField, BaseField>::pre_mul_reduce(&mut self_reduced, &mut other_reduced)?;
+        Reducer::<TargetField, BaseField, Opt>::pre_mul_reduce(
+            &mut self_reduced,
+            &mut other_reduced,
+        )?;
 
         let x_num_of_additions = self_reduced.num_of_additions_over_normal_form;
         let y_num_of_additions = other_reduced.num_of_additions_over_normal_form;
@@ -511,16 +992,15 @@ impl<TargetField: PrimeField, BaseField: PrimeField>
             .map(|f| LinearCombination::from((BaseField::one(), f.variable)))
             .collect();
 
+        let group_check_ns = ark_relations::ns!(self.cs, "group_check");
+        let group_check_cs = group_check_ns.cs();
         for c in 0..(2 * num_limbs - 1) {
+            // computed in the base field, since `c^i` overflows a machine word once there are
+            // many limbs, as with small base fields
             let c_pows: Vec<_> = (0..(2 * num_limbs - 1))
-                .map(|i| {
-                    BaseField::from_repr(<<BaseField as PrimeField>::BigInt as From<u64>>::from(
-                        c.pow(i as u32) as u64,
-                    ))
-                    .unwrap()
-                })
+                .map(|i| BaseField::from(c as u64).pow(&[i as u64]))
                 .collect();
-            self.cs.enforce_constraint(
+            group_check_cs.enforce_constraint(
                 x_vars
                     .iter()
                     .enumerate()
@@ -538,13 +1018,10 @@ impl<TargetField: PrimeField, BaseField: PrimeField>
                     .fold(lc!(), |new_lc, term| new_lc + term),
             )?;
         }
+        drop(group_check_ns);
 
         let mut prod_limbs_unbalanced_cut: Vec<AllocatedFp<BaseField>> = Vec::new();
-        let bits_per_non_top_limb = vec![params.bits_per_non_top_limb as u64];
-        let adjustment_factor: BaseField =
-            BaseField::from_repr(<BaseField as PrimeField>::BigInt::from(2u64))
-                .unwrap()
-                .pow(&bits_per_non_top_limb);
+        let adjustment_factor = BaseField::from(2u64).pow(&[params.bits_per_non_top_limb as u64]);
 
         let adjusted_zero = prod_limbs[0].mul_constant(adjustment_factor);
         prod_limbs_unbalanced_cut.push(adjusted_zero.add(&prod_limbs[1]));
@@ -553,13 +1030,75 @@ impl<TargetField: PrimeField, BaseField: PrimeField>
             prod_limbs_unbalanced_cut.push((*prod_limb).clone());
         }
 
-        Ok(AllocatedNonNativeFieldMulResultVar {
+        let res = AllocatedNonNativeFieldMulResultVar {
             cs: self.cs.clone(),
             limbs: prod_limbs_unbalanced_cut,
             prod_of_num_of_additions: (x_num_of_additions + BaseField::one())
                 * (y_num_of_additions + BaseField::one()),
             target_phantom: PhantomData,
-        })
+            memory_handle: ProductHandle::default(),
+        }
+        .tracked();
+        scope.finish(Some(&res.limbs));
+        Ok(res)
+    }
+
+    /// Multiply by a constant and output the intermediate representation, like
+    /// `mul_without_reduce`
+    ///
+    /// The limbs of the product are linear combinations of the limbs of `self`, so no limb
+    /// products are allocated and checked.
+    pub fn mul_constant_without_reduce(
+        &self,
+        other: &TargetField,
+    ) -> Result<AllocatedNonNativeFieldMulResultVar<TargetField, BaseField, Opt>, SynthesisError>
+    {
+        let _ns = ark_relations::ns!(self.cs, "nonnative::mul_constant");
+        let _scope = OperationScope::start(&self.cs, Operation::Multiplication);
+        let scope = GraphScope::start("mul_constant_without_reduce", &self.cs, &[&self.limbs[..]]);
+        let params = get_params::<TargetField, BaseField>(&self.cs)?;
+
+        let num_limbs = params.num_limbs;
+
+        let mut self_reduced = self.clone();
+        let mut other_gadget = Self::new_constant(self.cs.clone(), other)?;
+        Reducer::<TargetField, BaseField, Opt>::pre_mul_reduce(
+            &mut self_reduced,
+            &mut other_gadget,
+        )?;
+        let other_limbs = Self::get_limbs_representations(other, Some(&self.cs))?;
+
+        let mut prod_limbs: Vec<AllocatedFp<BaseField>> = Vec::new();
+        for k in 0..(2 * num_limbs - 1) {
+            let first = k.saturating_sub(num_limbs - 1);
+            let mut prod_limb = self_reduced.limbs[k - first].mul_constant(other_limbs[first]);
+            for i in (first + 1)..=min(k, num_limbs - 1) {
+                prod_limb = prod_limb.add(&self_reduced.limbs[k - i].mul_constant(other_limbs[i]));
+            }
+            prod_limbs.push(prod_limb);
+        }
+
+        let mut prod_limbs_unbalanced_cut: Vec<AllocatedFp<BaseField>> = Vec::new();
+        let adjustment_factor = BaseField::from(2u64).pow(&[params.bits_per_non_top_limb as u64]);
+
+        let adjusted_zero = prod_limbs[0].mul_constant(adjustment_factor);
+        prod_limbs_unbalanced_cut.push(adjusted_zero.add(&prod_limbs[1]));
+
+        for prod_limb in prod_limbs.iter().skip(2) {
+            prod_limbs_unbalanced_cut.push((*prod_limb).clone());
+        }
+
+        let res = AllocatedNonNativeFieldMulResultVar {
+            cs: self.cs.clone(),
+            limbs: prod_limbs_unbalanced_cut,
+            prod_of_num_of_additions: self_reduced.num_of_additions_over_normal_form
+                + BaseField::one(),
+            target_phantom: PhantomData,
+            memory_handle: ProductHandle::default(),
+        }
+        .tracked();
+        scope.finish(Some(&res.limbs));
+        Ok(res)
     }
 
     fn frobenius_map(&self, _power: usize) -> Result<Self, SynthesisError> {
@@ -571,6 +1110,41 @@ impl<TargetField: PrimeField, BaseField: PrimeField>
         other: &Self,
         should_enforce: &Boolean<BaseField>,
     ) -> Result<(), SynthesisError> {
+        let _ns = ark_relations::ns!(self.cs, "nonnative::enforce_equal");
+        if let Boolean::Constant(false) = should_enforce {
+            return Ok(());
+        }
+        let _scope = OperationScope::start(&self.cs, Operation::Equality);
+
+        // two operands in the normal form are compared limb by limb
+        if self.is_in_the_normal_form && other.is_in_the_normal_form {
+            for (left, right) in self.limbs.iter().zip(other.limbs.iter()) {
+                left.conditional_enforce_equal(right, should_enforce)?;
+            }
+            return Ok(());
+        }
+
+        // an unconditional equality with an operand in the normal form needs no normal form of
+        // the other operand
+        if let Boolean::Constant(true) = should_enforce {
+            if self.is_in_the_normal_form {
+                return Reducer::enforce_equal_to_normal_form(other, self);
+            } else if other.is_in_the_normal_form {
+                return Reducer::enforce_equal_to_normal_form(self, other);
+            }
+        }
+
+        // with a layout of their own, the sides are compared in it without normal forms
+        if let Some(layout) = get_params::<TargetField, BaseField>(&self.cs)?.equality_layout {
+            let layout = NonNativeFieldParams::from(layout);
+            let self_limbs = self.to_equality_layout(&layout)?;
+            let other_limbs = other.to_equality_layout(&layout)?;
+            for (left, right) in self_limbs.iter().zip(other_limbs.iter()) {
+                left.conditional_enforce_equal(right, should_enforce)?;
+            }
+            return Ok(());
+        }
+
         let mut self_normal = self.clone();
         let mut other_normal = other.clone();
         Reducer::pre_eq_reduce(&mut self_normal)?;
@@ -583,59 +1157,294 @@ impl<TargetField: PrimeField, BaseField: PrimeField>
         Ok(())
     }
 
-    fn conditional_enforce_not_equal(
+    /// Enforce `lhs[i] == rhs[i]` for every `i`
+    ///
+    /// The limbs of the normal forms are bounded by `2^bits_per_non_top_limb`, so the
+    /// differences of several limbs, scaled by distinct powers of this bound, can be summed
+    /// without overlapping. This enforces the limb equalities of all the pairs with one
+    /// constraint per such group instead of one per limb.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn enforce_equal_many(lhs: &[Self], rhs: &[Self]) -> Result<(), SynthesisError> {
+        if lhs.len() != rhs.len() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        if lhs.is_empty() {
+            return Ok(());
+        }
+
+        let cs = lhs[0].cs.clone();
+        let _scope = OperationScope::start(&cs, Operation::Equality);
+        let params = get_params::<TargetField, BaseField>(&cs)?;
+
+        // the sum of a group must stay below half of the base field modulus in absolute value
+        let limbs_per_group = max(
+            1,
+            (BaseField::size_in_bits() - 2) / params.bits_per_non_top_limb,
+        );
+
+        let mut differences = Vec::new();
+        for (left, right) in lhs.iter().zip(rhs.iter()) {
+            let mut left_normal = left.clone();
+            let mut right_normal = right.clone();
+            Reducer::pre_eq_reduce(&mut left_normal)?;
+            Reducer::pre_eq_reduce(&mut right_normal)?;
+
+            for (l, r) in left_normal.limbs.iter().zip(right_normal.limbs.iter()) {
+                differences.push((l.variable, r.variable));
+            }
+        }
+
+        let shift = BaseField::from(2u64).pow(&[params.bits_per_non_top_limb as u64]);
+        for group in differences.chunks(limbs_per_group) {
+            let mut lc = LinearCombination::zero();
+            let mut coeff = BaseField::one();
+            for (l, r) in group.iter() {
+                lc += (coeff, *l);
+                lc += (-coeff, *r);
+                coeff *= &shift;
+            }
+            cs.enforce_constraint(lc!(), lc!(), lc)?;
+        }
+
+        Ok(())
+    }
+
+    /// Enforce `self == constant` if `should_enforce` is set, comparing the limbs of the normal
+    /// form with those of `constant` without allocating them
+    fn conditional_enforce_equal_to_constant(
         &self,
-        other: &Self,
+        constant: &TargetField,
         should_enforce: &Boolean<BaseField>,
     ) -> Result<(), SynthesisError> {
-        let cs = self.cs().or(other.cs()).or(should_enforce.cs());
+        let _ns = ark_relations::ns!(self.cs, "nonnative::enforce_equal");
+        if let Boolean::Constant(false) = should_enforce {
+            return Ok(());
+        }
+        let _scope = OperationScope::start(&self.cs, Operation::Equality);
 
-        if cs == ConstraintSystemRef::None {
-            assert!(self.value()? != other.value()?);
-        } else {
-            let val = should_enforce.select(
-                &self.sub(other)?,
-                &AllocatedNonNativeFieldVar::new_constant(cs, TargetField::one())?,
+        // for an unconditional equality, checking the element against the constant is cheaper
+        // than allocating a normal form of its own
+        if !self.is_in_the_normal_form && should_enforce.is_constant() {
+            let constant = Self::new_constant(self.cs.clone(), constant)?;
+            return Reducer::enforce_equal_to_normal_form(self, &constant);
+        }
+        let mut self_normal = self.clone();
+        Reducer::pre_eq_reduce(&mut self_normal)?;
+
+        let constant_limbs = Self::get_limbs_representations(constant, Some(&self.cs))?;
+        for (limb, constant_limb) in self_normal.limbs.iter().zip(constant_limbs.iter()) {
+            self.cs.enforce_constraint(
+                lc!() + limb.variable - (*constant_limb, Variable::One),
+                should_enforce.lc(),
+                lc!(),
             )?;
-            let _ = val.inverse()?;
         }
 
         Ok(())
     }
-}
-
-impl<TargetField: PrimeField, BaseField: PrimeField> ToBitsGadget<BaseField>
-    for AllocatedNonNativeFieldVar<TargetField, BaseField>
-{
-    #[tracing::instrument(target = "r1cs")]
-    fn to_bits_le(&self) -> Result<Vec<Boolean<BaseField>>, SynthesisError> {
-        let params = get_params::<TargetField, BaseField>(&self.cs);
 
-        let mut self_normal = self.clone();
-        Reducer::<TargetField, BaseField>::pre_eq_reduce(&mut self_normal)?;
+    /// Select between the constants `true_value` and `false_value`
+    ///
+    /// Each limb of the result is linear in `cond`, so the selection needs no constraints, and
+    /// the result is in the normal form as both constants are.
+    fn select_constants(
+        cond: &Boolean<BaseField>,
+        true_value: &TargetField,
+        false_value: &TargetField,
+    ) -> Result<Self, SynthesisError> {
+        let cs = cond.cs();
+        let true_limbs = Self::get_limbs_representations(true_value, Some(&cs))?;
+        let false_limbs = Self::get_limbs_representations(false_value, Some(&cs))?;
+        let cond_value = cond.value().ok();
+
+        let mut limbs = Vec::with_capacity(true_limbs.len());
+        for (t, f) in true_limbs.iter().zip(false_limbs.iter()) {
+            let lc = cond.lc() * (*t - f) + (*f, Variable::One);
+            let value = cond_value.map(|cond| if cond { *t } else { *f });
+            limbs.push(AllocatedFp::new(value, cs.new_lc(lc)?, cs.clone()));
+        }
 
-        let mut bits = Vec::<Boolean<BaseField>>::new();
+        Ok(Self {
+            cs,
+            limbs,
+            num_of_additions_over_normal_form: BaseField::zero(),
+            is_in_the_normal_form: true,
+            target_phantom: PhantomData,
+        })
+    }
 
-        for (i, limb) in self_normal.limbs.iter().enumerate() {
-            let limb_bits = Reducer::<TargetField, BaseField>::limb_to_bits(&limb, {
-                if i == 0 {
-                    params.bits_per_top_limb
-                } else {
-                    params.bits_per_non_top_limb
+    /// Output `(b, a)` if `cond` is true and `(a, b)` otherwise
+    ///
+    /// The first output is witnessed with one constraint per limb, which multiplies the
+    /// difference of the limbs by the same `cond`, and the second one is the sum of the limbs
+    /// minus the first output, which needs no constraints.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn conditionally_swap(
+        cond: &Boolean<BaseField>,
+        a: &Self,
+        b: &Self,
+    ) -> Result<(Self, Self), SynthesisError> {
+        let cs = a.cs().or(b.cs()).or(cond.cs());
+        let cond_lc = cond.lc();
+        let cond_value = cond.value().ok();
+
+        let mut first_limbs = Vec::with_capacity(a.limbs.len());
+        let mut second_limbs = Vec::with_capacity(a.limbs.len());
+        for (a_limb, b_limb) in a.limbs.iter().zip(b.limbs.iter()) {
+            let first = AllocatedFp::<BaseField>::new_witness(cs.clone(), || {
+                match cond_value.ok_or(SynthesisError::AssignmentMissing)? {
+                    true => b_limb.value(),
+                    false => a_limb.value(),
                 }
             })?;
+            cs.enforce_constraint(
+                cond_lc.clone(),
+                lc!() + b_limb.variable - a_limb.variable,
+                lc!() + first.variable - a_limb.variable,
+            )?;
 
-            bits.extend_from_slice(&limb_bits);
+            second_limbs.push(a_limb.add(b_limb).sub(&first));
+            first_limbs.push(first);
         }
 
-        bits.reverse();
-
-        Ok(bits)
-    }
+        let num_of_additions_over_normal_form = max(
+            a.num_of_additions_over_normal_form,
+            b.num_of_additions_over_normal_form,
+        );
+        let is_in_the_normal_form = a.is_in_the_normal_form && b.is_in_the_normal_form;
+        let swapped = |limbs| Self {
+            cs: cs.clone(),
+            limbs,
+            num_of_additions_over_normal_form,
+            is_in_the_normal_form,
+            target_phantom: PhantomData,
+        };
+
+        Ok((swapped(first_limbs), swapped(second_limbs)))
+    }
+
+    /// Output the least significant bit of the canonical integer of `self`
+    ///
+    /// The canonical integer `x` is witnessed in the normal form and enforced to equal `self`.
+    /// It is canonical, i.e., smaller than p, if there is a normal-form `d` with
+    /// `x + d = p - 1` over the integers, which is enforced limb by limb with a carry bit
+    /// between the limbs. Only the lowest limb of `x` is then decomposed, into its parity bit
+    /// and the range-checked remaining bits.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn is_odd(&self) -> Result<Boolean<BaseField>, SynthesisError> {
+        let cs = self.cs();
+        if cs.is_none() {
+            return Ok(Boolean::constant(self.value()?.into_repr().is_odd()));
+        }
+        let params = get_params::<TargetField, BaseField>(&cs)?;
+
+        let x_native = self.value().unwrap_or_default();
+        let x = Self::new_witness(ark_relations::ns!(cs, "canonical"), || Ok(x_native))?;
+        self.conditional_enforce_equal(&x, &Boolean::TRUE)?;
+
+        let modulus_minus_one = -TargetField::one();
+        let d = Self::new_witness(ark_relations::ns!(cs, "complement"), || {
+            Ok(modulus_minus_one - &x_native)
+        })?;
+        let modulus_minus_one_limbs =
+            Self::get_limbs_representations(&modulus_minus_one, Some(&cs))?;
+
+        // the limbs are big limb first, so the carries go from the last limb to the first one
+        let num_bits_of_limb = |i: usize| {
+            if i == 0 {
+                params.bits_per_top_limb
+            } else {
+                params.bits_per_non_top_limb
+            }
+        };
+        let mut carry_in: Option<Boolean<BaseField>> = None;
+        for i in (0..params.num_limbs).rev() {
+            let mut sum_lc = lc!() + x.limbs[i].variable + d.limbs[i].variable
+                - (modulus_minus_one_limbs[i], Variable::One);
+            let mut sum_value = field_to_biguint(&x.limbs[i].value().unwrap_or_default())
+                + field_to_biguint(&d.limbs[i].value().unwrap_or_default());
+            if let Some(carry_in) = &carry_in {
+                sum_lc = sum_lc + carry_in.lc();
+                if carry_in.value().unwrap_or_default() {
+                    sum_value += 1u64;
+                }
+            }
+
+            // the top limb does not carry out, which bounds the sum by p - 1
+            if i == 0 {
+                cs.enforce_constraint(lc!(), lc!(), sum_lc)?;
+            } else {
+                let carry_out = Boolean::new_witness(ark_relations::ns!(cs, "carry"), || {
+                    Ok(sum_value.bits() > num_bits_of_limb(i) as u64)
+                })?;
+                let shift = BaseField::from(2u64).pow(&[num_bits_of_limb(i) as u64]);
+                cs.enforce_constraint(lc!(), lc!(), sum_lc - carry_out.lc() * shift)?;
+                carry_in = Some(carry_out);
+            }
+        }
+
+        let lowest_limb = &x.limbs[params.num_limbs - 1];
+        let lowest_limb_value = field_to_biguint(&lowest_limb.value().unwrap_or_default());
+        let parity = Boolean::new_witness(ark_relations::ns!(cs, "parity"), || {
+            Ok(&lowest_limb_value % 2u32 == BigUint::one())
+        })?;
+        let rest = AllocatedFp::<BaseField>::new_witness(ark_relations::ns!(cs, "rest"), || {
+            Ok(biguint_to_field(&(&lowest_limb_value >> 1usize)))
+        })?;
+        range_check::enforce_range(&rest, num_bits_of_limb(params.num_limbs - 1) - 1)?;
+        cs.enforce_constraint(
+            lc!(),
+            lc!(),
+            lc!() + lowest_limb.variable - parity.lc() - (BaseField::from(2u64), rest.variable),
+        )?;
+
+        Ok(parity)
+    }
+
+    fn conditional_enforce_not_equal(
+        &self,
+        other: &Self,
+        should_enforce: &Boolean<BaseField>,
+    ) -> Result<(), SynthesisError> {
+        if let Boolean::Constant(false) = should_enforce {
+            return Ok(());
+        }
+        let cs = self.cs().or(other.cs()).or(should_enforce.cs());
+        let _scope = OperationScope::start(&cs, Operation::Equality);
+
+        if cs == ConstraintSystemRef::None {
+            if self.value()? == other.value()? {
+                return Err(SynthesisError::Unsatisfiable);
+            }
+        } else {
+            let val = match should_enforce {
+                Boolean::Constant(true) => self.sub(other)?,
+                _ => should_enforce
+                    .select(&self.sub(other)?, &AllocatedNonNativeFieldVar::one(cs)?)?,
+            };
+            let _ = val.inverse()?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode> ToBitsGadget<BaseField>
+    for AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>
+{
+    #[tracing::instrument(target = "r1cs")]
+    fn to_bits_le(&self) -> Result<Vec<Boolean<BaseField>>, SynthesisError> {
+        let mut self_normal = self.clone();
+        let mut bits =
+            Reducer::<TargetField, BaseField, Opt>::pre_eq_reduce_with_bits(&mut self_normal)?;
+        bits.reverse();
+
+        Ok(bits)
+    }
 }
 
-impl<TargetField: PrimeField, BaseField: PrimeField> ToBytesGadget<BaseField>
-    for AllocatedNonNativeFieldVar<TargetField, BaseField>
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode> ToBytesGadget<BaseField>
+    for AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>
 {
     #[tracing::instrument(target = "r1cs")]
     fn to_bytes(&self) -> Result<Vec<UInt8<BaseField>>, SynthesisError> {
@@ -658,8 +1467,8 @@ impl<TargetField: PrimeField, BaseField: PrimeField> ToBytesGadget<BaseField>
     }
 }
 
-impl<TargetField: PrimeField, BaseField: PrimeField> CondSelectGadget<BaseField>
-    for AllocatedNonNativeFieldVar<TargetField, BaseField>
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode>
+    CondSelectGadget<BaseField> for AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>
 {
     #[tracing::instrument(target = "r1cs")]
     fn conditionally_select(
@@ -687,8 +1496,8 @@ impl<TargetField: PrimeField, BaseField: PrimeField> CondSelectGadget<BaseField>
     }
 }
 
-impl<TargetField: PrimeField, BaseField: PrimeField> TwoBitLookupGadget<BaseField>
-    for AllocatedNonNativeFieldVar<TargetField, BaseField>
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode>
+    TwoBitLookupGadget<BaseField> for AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>
 {
     type TableConstant = TargetField;
 
@@ -704,7 +1513,7 @@ impl<TargetField: PrimeField, BaseField: PrimeField> TwoBitLookupGadget<BaseFiel
         if is_variable {
             let cs = bits.cs();
 
-            let params = get_params::<TargetField, BaseField>(&cs);
+            let params = get_params::<TargetField, BaseField>(&cs)?;
 
             let mut limbs_constants = Vec::new();
             for _ in 0..params.num_limbs {
@@ -713,7 +1522,7 @@ impl<TargetField: PrimeField, BaseField: PrimeField> TwoBitLookupGadget<BaseFiel
 
             for constant in constants.iter() {
                 let representations =
-                    AllocatedNonNativeFieldVar::<TargetField, BaseField>::get_limbs_representations(
+                    AllocatedNonNativeFieldVar::<TargetField, BaseField, Opt>::get_limbs_representations(
                         constant,
                         Some(&cs),
                     )?;
@@ -731,7 +1540,7 @@ impl<TargetField: PrimeField, BaseField: PrimeField> TwoBitLookupGadget<BaseFiel
                 )?);
             }
 
-            Ok(AllocatedNonNativeFieldVar::<TargetField, BaseField> {
+            Ok(AllocatedNonNativeFieldVar::<TargetField, BaseField, Opt> {
                 cs,
                 limbs,
                 num_of_additions_over_normal_form: BaseField::zero(),
@@ -739,13 +1548,14 @@ impl<TargetField: PrimeField, BaseField: PrimeField> TwoBitLookupGadget<BaseFiel
                 target_phantom: PhantomData,
             })
         } else {
-            unreachable!("must provide a way to obtain a ConstraintSystemRef")
+            Err(SynthesisError::MissingCS)
         }
     }
 }
 
-impl<TargetField: PrimeField, BaseField: PrimeField> ThreeBitCondNegLookupGadget<BaseField>
-    for AllocatedNonNativeFieldVar<TargetField, BaseField>
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode>
+    ThreeBitCondNegLookupGadget<BaseField>
+    for AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>
 {
     type TableConstant = TargetField;
 
@@ -760,7 +1570,7 @@ impl<TargetField: PrimeField, BaseField: PrimeField> ThreeBitCondNegLookupGadget
 
         let is_variable = !bits.cs().is_none();
         if is_variable {
-            let params = get_params::<TargetField, BaseField>(&bits.cs());
+            let params = get_params::<TargetField, BaseField>(&bits.cs())?;
 
             let mut limbs_constants = Vec::new();
             for _ in 0..params.num_limbs {
@@ -769,7 +1579,7 @@ impl<TargetField: PrimeField, BaseField: PrimeField> ThreeBitCondNegLookupGadget
 
             for constant in constants.iter() {
                 let representations =
-                    AllocatedNonNativeFieldVar::<TargetField, BaseField>::get_limbs_representations(
+                    AllocatedNonNativeFieldVar::<TargetField, BaseField, Opt>::get_limbs_representations(
                         constant,
                         Some(&bits.cs()),
                     )?;
@@ -788,7 +1598,7 @@ impl<TargetField: PrimeField, BaseField: PrimeField> ThreeBitCondNegLookupGadget
                 )?);
             }
 
-            Ok(AllocatedNonNativeFieldVar::<TargetField, BaseField> {
+            Ok(AllocatedNonNativeFieldVar::<TargetField, BaseField, Opt> {
                 cs: bits.cs(),
                 limbs,
                 num_of_additions_over_normal_form: BaseField::zero(),
@@ -796,41 +1606,75 @@ impl<TargetField: PrimeField, BaseField: PrimeField> ThreeBitCondNegLookupGadget
                 target_phantom: PhantomData,
             })
         } else {
-            unreachable!("must provide a way to obtain a ConstraintSystemRef")
+            Err(SynthesisError::MissingCS)
         }
     }
 }
 
-impl<TargetField: PrimeField, BaseField: PrimeField> AllocVar<TargetField, BaseField>
-    for AllocatedNonNativeFieldVar<TargetField, BaseField>
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode>
+    AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>
 {
-    fn new_variable<T: Borrow<TargetField>>(
+    /// Allocate the limbs of an element in the layout of `params` instead of that of the
+    /// parameters of `cs`, big limb first, e.g., to match a layout fixed outside of the circuit
+    /// such as that of a commitment
+    ///
+    /// The limbs of a witness are range-checked to the sizes of `params`. The arithmetic of the
+    /// crate uses the parameters of `cs`, so the limbs are not an element of this type; they
+    /// can be converted to one with `from_layout`. Return `NonNativeFieldError::InvalidParameters`,
+    /// as a `SynthesisError`, if `params` have fewer than two limbs, do not cover `TargetField`,
+    /// or are too large for the reductions, as `set_params` does.
+    #[tracing::instrument(target = "r1cs", skip(cs, f))]
+    pub fn new_limbs_with_params<T: Borrow<TargetField>>(
+        cs: impl Into<Namespace<BaseField>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+        params: &NonNativeFieldParams,
+    ) -> Result<Vec<AllocatedFp<BaseField>>, SynthesisError> {
+        validate_params(
+            params,
+            TargetField::size_in_bits(),
+            BaseField::size_in_bits(),
+            2,
+        )?;
+        Ok(Self::new_variable_in_layout(cs, f, mode, params)?.limbs)
+    }
+
+    /// Allocate an element with the limbs of `params`, which are assumed to be valid
+    fn new_variable_in_layout<T: Borrow<TargetField>>(
         cs: impl Into<Namespace<BaseField>>,
         f: impl FnOnce() -> Result<T, SynthesisError>,
         mode: AllocationMode,
+        params: &NonNativeFieldParams,
     ) -> Result<Self, SynthesisError> {
         let ns = cs.into();
         let cs = ns.cs();
-
-        let params = get_params::<TargetField, BaseField>(&cs);
-
-        assert!(
-            BaseField::size_in_bits() - 1
-                > 2 * max(
-                    params.bits_per_top_limb + 5,
-                    params.bits_per_non_top_limb + 5
-                )
-        );
-        assert!(params.bits_per_top_limb <= params.bits_per_non_top_limb);
-
-        let elem = f()?;
-        let elem_representations = Self::get_limbs_representations(&elem.borrow(), Some(&cs))?;
+        let _scope = OperationScope::start(&cs, Operation::Allocation);
+        let scope = GraphScope::start("alloc", &cs, &[]);
+
+        // the value is only needed inside the allocation closures, so that a missing value
+        // (e.g., in the setup mode) does not change the shape of the constraints; the limbs of
+        // a possibly secret witness are computed from its representation without temporaries,
+        // and the representation and the limbs are scrubbed once they are no longer needed.
+        // The element returned by `f` belongs to the caller, who must scrub it if needed.
+        let elem_representations = f().map(|elem| {
+            #[allow(unused_mut)]
+            let mut repr = elem.borrow().into_repr();
+            let limbs = LimbBuffer(Self::limbs_in_layout(&repr, params));
+            #[cfg(feature = "zeroize")]
+            repr.zeroize();
+            limbs
+        });
         let mut limbs = Vec::new();
 
-        for limb in &elem_representations {
+        for i in 0..params.num_limbs {
             limbs.push(AllocatedFp::<BaseField>::new_variable(
                 ark_relations::ns!(cs, "alloc"),
-                || Ok(limb),
+                || {
+                    elem_representations
+                        .as_ref()
+                        .map(|representations| representations.0[i])
+                        .map_err(Clone::clone)
+                },
                 mode,
             )?);
         }
@@ -843,15 +1687,14 @@ impl<TargetField: PrimeField, BaseField: PrimeField> AllocVar<TargetField, BaseF
 
         if mode == AllocationMode::Witness {
             for limb in limbs.iter().rev().take(params.num_limbs - 1) {
-                Reducer::<TargetField, BaseField>::limb_to_bits(
-                    limb,
-                    params.bits_per_non_top_limb,
-                )?;
+                range_check::enforce_range(limb, params.bits_per_non_top_limb)?;
             }
 
-            Reducer::<TargetField, BaseField>::limb_to_bits(&limbs[0], params.bits_per_top_limb)?;
+            range_check::enforce_range(&limbs[0], params.bits_per_top_limb)?;
         }
 
+        MemoryStats::record_limbs(&cs, limbs.len());
+        scope.finish(Some(&limbs));
         Ok(Self {
             cs,
             limbs,
@@ -862,16 +1705,32 @@ impl<TargetField: PrimeField, BaseField: PrimeField> AllocVar<TargetField, BaseF
     }
 }
 
-impl<TargetField: PrimeField, BaseField: PrimeField> ToConstraintFieldGadget<BaseField>
-    for AllocatedNonNativeFieldVar<TargetField, BaseField>
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode>
+    AllocVar<TargetField, BaseField> for AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>
+{
+    fn new_variable<T: Borrow<TargetField>>(
+        cs: impl Into<Namespace<BaseField>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let ns = cs.into();
+        let cs = ns.cs();
+        let params = get_params::<TargetField, BaseField>(&cs)?;
+        mark_params_in_use::<TargetField, BaseField>(&cs);
+        Self::new_variable_in_layout(ns, f, mode, &params)
+    }
+}
+
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode>
+    ToConstraintFieldGadget<BaseField> for AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>
 {
     fn to_constraint_field(&self) -> Result<Vec<FpVar<BaseField>>, SynthesisError> {
         Ok(self.limbs.iter().cloned().map(FpVar::from).collect())
     }
 }
 
-impl<TargetField: PrimeField, BaseField: PrimeField> FieldVar<TargetField, BaseField>
-    for NonNativeFieldVar<TargetField, BaseField>
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode>
+    FieldVar<TargetField, BaseField> for NonNativeFieldVar<TargetField, BaseField, Opt>
 {
     fn zero() -> Self {
         Self::Constant(TargetField::zero())
@@ -918,76 +1777,133 @@ impl<TargetField: PrimeField, BaseField: PrimeField> FieldVar<TargetField, BaseF
 /****************************************************************************/
 
 impl_bounded_ops!(
-    NonNativeFieldVar<TargetField, BaseField>,
+    NonNativeFieldVar<TargetField, BaseField, Opt>,
     TargetField,
     Add,
     add,
     AddAssign,
     add_assign,
-    |this: &'a NonNativeFieldVar<TargetField, BaseField>, other: &'a NonNativeFieldVar<TargetField, BaseField>| {
+    |this: &'a NonNativeFieldVar<TargetField, BaseField, Opt>, other: &'a NonNativeFieldVar<TargetField, BaseField, Opt>| {
         use NonNativeFieldVar::*;
-        match (this, other) {
+        match (&*this.resolve_constant(), &*other.resolve_constant()) {
             (Constant(c1), Constant(c2)) => Constant(*c1 + c2),
+            (Constant(c), Var(v)) | (Var(v), Constant(c)) if c.is_zero() => Var(v.clone()),
             (Constant(c), Var(v)) | (Var(v), Constant(c)) => Var(v.add_constant(c).unwrap()),
             (Var(v1), Var(v2)) => Var(v1.add(v2).unwrap()),
         }
     },
-    |this: &'a NonNativeFieldVar<TargetField, BaseField>, other: TargetField| { this + &NonNativeFieldVar::Constant(other) },
-    (TargetField: PrimeField, BaseField: PrimeField),
+    |this: &'a NonNativeFieldVar<TargetField, BaseField, Opt>, other: TargetField| { this + &NonNativeFieldVar::Constant(other) },
+    (TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode),
 );
 
 impl_bounded_ops!(
-    NonNativeFieldVar<TargetField, BaseField>,
+    NonNativeFieldVar<TargetField, BaseField, Opt>,
     TargetField,
     Sub,
     sub,
     SubAssign,
     sub_assign,
-    |this: &'a NonNativeFieldVar<TargetField, BaseField>, other: &'a NonNativeFieldVar<TargetField, BaseField>| {
+    |this: &'a NonNativeFieldVar<TargetField, BaseField, Opt>, other: &'a NonNativeFieldVar<TargetField, BaseField, Opt>| {
         use NonNativeFieldVar::*;
-        match (this, other) {
+        match (&*this.resolve_constant(), &*other.resolve_constant()) {
             (Constant(c1), Constant(c2)) => Constant(*c1 - c2),
+            (Var(v), Constant(c)) if c.is_zero() => Var(v.clone()),
             (Var(v), Constant(c)) => Var(v.sub_constant(c).unwrap()),
+            (Constant(c), Var(v)) if c.is_zero() => Var(v.negate().unwrap()),
             (Constant(c), Var(v)) => Var(v.sub_constant(c).unwrap().negate().unwrap()),
             (Var(v1), Var(v2)) => Var(v1.sub(v2).unwrap()),
         }
     },
-    |this: &'a NonNativeFieldVar<TargetField, BaseField>, other: TargetField| {
+    |this: &'a NonNativeFieldVar<TargetField, BaseField, Opt>, other: TargetField| {
         this - &NonNativeFieldVar::Constant(other)
     },
-    (TargetField: PrimeField, BaseField: PrimeField),
+    (TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode),
 );
 
 impl_bounded_ops!(
-    NonNativeFieldVar<TargetField, BaseField>,
+    NonNativeFieldVar<TargetField, BaseField, Opt>,
     TargetField,
     Mul,
     mul,
     MulAssign,
     mul_assign,
-    |this: &'a NonNativeFieldVar<TargetField, BaseField>, other: &'a NonNativeFieldVar<TargetField, BaseField>| {
+    |this: &'a NonNativeFieldVar<TargetField, BaseField, Opt>, other: &'a NonNativeFieldVar<TargetField, BaseField, Opt>| {
         use NonNativeFieldVar::*;
-        match (this, other) {
+        match (&*this.resolve_constant(), &*other.resolve_constant()) {
             (Constant(c1), Constant(c2)) => Constant(*c1 * c2),
+            (Constant(c), Var(_)) | (Var(_), Constant(c)) if c.is_zero() => Constant(TargetField::zero()),
+            (Constant(c), Var(v)) | (Var(v), Constant(c)) if c.is_one() => Var(v.clone()),
             (Constant(c), Var(v)) | (Var(v), Constant(c)) => Var(v.mul_constant(c).unwrap()),
             (Var(v1), Var(v2)) => Var(v1.mul(v2).unwrap()),
         }
     },
-    |this: &'a NonNativeFieldVar<TargetField, BaseField>, other: TargetField| {
+    |this: &'a NonNativeFieldVar<TargetField, BaseField, Opt>, other: TargetField| {
         if other.is_zero() {
             NonNativeFieldVar::zero()
         } else {
             this * &NonNativeFieldVar::Constant(other)
         }
     },
-    (TargetField: PrimeField, BaseField: PrimeField),
+    (TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode),
 );
 
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode> Neg
+    for NonNativeFieldVar<TargetField, BaseField, Opt>
+{
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        self.negate().unwrap()
+    }
+}
+
+impl<'a, TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode> Neg
+    for &'a NonNativeFieldVar<TargetField, BaseField, Opt>
+{
+    type Output = NonNativeFieldVar<TargetField, BaseField, Opt>;
+
+    fn neg(self) -> Self::Output {
+        self.negate().unwrap()
+    }
+}
+
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode> Sum<Self>
+    for NonNativeFieldVar<TargetField, BaseField, Opt>
+{
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        Self::add_many(&iter.collect::<Vec<_>>()).unwrap()
+    }
+}
+
+impl<'a, TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode> Sum<&'a Self>
+    for NonNativeFieldVar<TargetField, BaseField, Opt>
+{
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        Self::add_many(&iter.cloned().collect::<Vec<_>>()).unwrap()
+    }
+}
+
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode> Product<Self>
+    for NonNativeFieldVar<TargetField, BaseField, Opt>
+{
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        Self::mul_many(&iter.collect::<Vec<_>>()).unwrap()
+    }
+}
+
+impl<'a, TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode> Product<&'a Self>
+    for NonNativeFieldVar<TargetField, BaseField, Opt>
+{
+    fn product<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        Self::mul_many(&iter.cloned().collect::<Vec<_>>()).unwrap()
+    }
+}
+
 /****************************************************************************/
 /****************************************************************************/
 
-impl<TargetField: PrimeField, BaseField: PrimeField> EqGadget<BaseField>
-    for NonNativeFieldVar<TargetField, BaseField>
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode> EqGadget<BaseField>
+    for NonNativeFieldVar<TargetField, BaseField, Opt>
 {
     fn is_eq(&self, other: &Self) -> Result<Boolean<BaseField>, SynthesisError> {
         let cs = self.cs().or(other.cs());
@@ -1010,6 +1926,11 @@ impl<TargetField: PrimeField, BaseField: PrimeField> EqGadget<BaseField>
         other: &Self,
         should_enforce: &Boolean<BaseField>,
     ) -> Result<(), SynthesisError> {
+        // a false constant condition needs no constant to be allocated
+        if let Boolean::Constant(false) = should_enforce {
+            return Ok(());
+        }
+
         match (self, other) {
             (Self::Constant(c1), Self::Constant(c2)) => {
                 if c1 != c2 {
@@ -1018,9 +1939,11 @@ impl<TargetField: PrimeField, BaseField: PrimeField> EqGadget<BaseField>
                 Ok(())
             }
             (Self::Constant(c), Self::Var(v)) | (Self::Var(v), Self::Constant(c)) => {
-                let cs = v.cs.clone();
-                let c = AllocatedNonNativeFieldVar::new_constant(cs, c)?;
-                c.conditional_enforce_equal(v, should_enforce)
+                v.conditional_enforce_equal_to_constant(c, should_enforce)?;
+                if should_enforce.is_constant() {
+                    KnownConstants::<TargetField, BaseField>::store(&v.cs, v.limb_variables(), *c);
+                }
+                Ok(())
             }
             (Self::Var(v1), Self::Var(v2)) => v1.conditional_enforce_equal(v2, should_enforce),
         }
@@ -1031,6 +1954,10 @@ impl<TargetField: PrimeField, BaseField: PrimeField> EqGadget<BaseField>
         other: &Self,
         should_enforce: &Boolean<BaseField>,
     ) -> Result<(), SynthesisError> {
+        if let Boolean::Constant(false) = should_enforce {
+            return Ok(());
+        }
+
         match (self, other) {
             (Self::Constant(c1), Self::Constant(c2)) => {
                 if c1 == c2 {
@@ -1048,8 +1975,8 @@ impl<TargetField: PrimeField, BaseField: PrimeField> EqGadget<BaseField>
     }
 }
 
-impl<TargetField: PrimeField, BaseField: PrimeField> ToBitsGadget<BaseField>
-    for NonNativeFieldVar<TargetField, BaseField>
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode> ToBitsGadget<BaseField>
+    for NonNativeFieldVar<TargetField, BaseField, Opt>
 {
     #[tracing::instrument(target = "r1cs")]
     fn to_bits_le(&self) -> Result<Vec<Boolean<BaseField>>, SynthesisError> {
@@ -1071,8 +1998,8 @@ impl<TargetField: PrimeField, BaseField: PrimeField> ToBitsGadget<BaseField>
     }
 }
 
-impl<TargetField: PrimeField, BaseField: PrimeField> ToBytesGadget<BaseField>
-    for NonNativeFieldVar<TargetField, BaseField>
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode> ToBytesGadget<BaseField>
+    for NonNativeFieldVar<TargetField, BaseField, Opt>
 {
     /// Outputs the unique byte decomposition of `self` in *little-endian*
     /// form.
@@ -1092,8 +2019,8 @@ impl<TargetField: PrimeField, BaseField: PrimeField> ToBytesGadget<BaseField>
     }
 }
 
-impl<TargetField: PrimeField, BaseField: PrimeField> CondSelectGadget<BaseField>
-    for NonNativeFieldVar<TargetField, BaseField>
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode>
+    CondSelectGadget<BaseField> for NonNativeFieldVar<TargetField, BaseField, Opt>
 {
     #[tracing::instrument(target = "r1cs")]
     fn conditionally_select(
@@ -1101,9 +2028,13 @@ impl<TargetField: PrimeField, BaseField: PrimeField> CondSelectGadget<BaseField>
         true_value: &Self,
         false_value: &Self,
     ) -> Result<Self, SynthesisError> {
-        match cond {
-            Boolean::Constant(true) => Ok(true_value.clone()),
-            Boolean::Constant(false) => Ok(false_value.clone()),
+        match (cond, true_value, false_value) {
+            (Boolean::Constant(true), _, _) => Ok(true_value.clone()),
+            (Boolean::Constant(false), _, _) => Ok(false_value.clone()),
+            (_, Self::Constant(c1), Self::Constant(c2)) if c1 == c2 => Ok(true_value.clone()),
+            (_, Self::Constant(c1), Self::Constant(c2)) => {
+                AllocatedNonNativeFieldVar::select_constants(cond, c1, c2).map(Self::Var)
+            }
             _ => {
                 let cs = cond.cs();
                 let true_value = match true_value {
@@ -1122,8 +2053,8 @@ impl<TargetField: PrimeField, BaseField: PrimeField> CondSelectGadget<BaseField>
 
 /// Uses two bits to perform a lookup into a table
 /// `b` is little-endian: `b[0]` is LSB.
-impl<TargetField: PrimeField, BaseField: PrimeField> TwoBitLookupGadget<BaseField>
-    for NonNativeFieldVar<TargetField, BaseField>
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode>
+    TwoBitLookupGadget<BaseField> for NonNativeFieldVar<TargetField, BaseField, Opt>
 {
     type TableConstant = TargetField;
 
@@ -1147,8 +2078,8 @@ impl<TargetField: PrimeField, BaseField: PrimeField> TwoBitLookupGadget<BaseFiel
     }
 }
 
-impl<TargetField: PrimeField, BaseField: PrimeField> ThreeBitCondNegLookupGadget<BaseField>
-    for NonNativeFieldVar<TargetField, BaseField>
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode>
+    ThreeBitCondNegLookupGadget<BaseField> for NonNativeFieldVar<TargetField, BaseField, Opt>
 {
     type TableConstant = TargetField;
 
@@ -1182,8 +2113,8 @@ impl<TargetField: PrimeField, BaseField: PrimeField> ThreeBitCondNegLookupGadget
     }
 }
 
-impl<TargetField: PrimeField, BaseField: PrimeField> AllocVar<TargetField, BaseField>
-    for NonNativeFieldVar<TargetField, BaseField>
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode>
+    AllocVar<TargetField, BaseField> for NonNativeFieldVar<TargetField, BaseField, Opt>
 {
     fn new_variable<T: Borrow<TargetField>>(
         cs: impl Into<Namespace<BaseField>>,
@@ -1198,8 +2129,42 @@ impl<TargetField: PrimeField, BaseField: PrimeField> AllocVar<TargetField, BaseF
     }
 }
 
-impl<TargetField: PrimeField, BaseField: PrimeField> ToConstraintFieldGadget<BaseField>
-    for NonNativeFieldVar<TargetField, BaseField>
+// `From<TargetField>` would conflict with `From<u64>`, since `u64` could implement
+// `PrimeField` upstream, so the constants are converted from references
+impl<'a, TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode>
+    From<&'a TargetField> for NonNativeFieldVar<TargetField, BaseField, Opt>
+{
+    fn from(value: &'a TargetField) -> Self {
+        Self::Constant(*value)
+    }
+}
+
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode> From<u64>
+    for NonNativeFieldVar<TargetField, BaseField, Opt>
+{
+    fn from(value: u64) -> Self {
+        Self::Constant(TargetField::from(value))
+    }
+}
+
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode> From<u128>
+    for NonNativeFieldVar<TargetField, BaseField, Opt>
+{
+    fn from(value: u128) -> Self {
+        Self::Constant(TargetField::from(value))
+    }
+}
+
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode> From<bool>
+    for NonNativeFieldVar<TargetField, BaseField, Opt>
+{
+    fn from(value: bool) -> Self {
+        Self::Constant(TargetField::from(value))
+    }
+}
+
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode>
+    ToConstraintFieldGadget<BaseField> for NonNativeFieldVar<TargetField, BaseField, Opt>
 {
     fn to_constraint_field(&self) -> Result<Vec<FpVar<BaseField>>, SynthesisError> {
         match self {
@@ -1214,144 +2179,1010 @@ impl<TargetField: PrimeField, BaseField: PrimeField> ToConstraintFieldGadget<Bas
     }
 }
 
-impl<TargetField: PrimeField, BaseField: PrimeField> NonNativeFieldVar<TargetField, BaseField> {
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode>
+    NonNativeFieldVar<TargetField, BaseField, Opt>
+{
+    /// Replace a variable that has been enforced to equal a constant by that constant, so that
+    /// the operations special-case it as they do constants
+    fn resolve_constant(&self) -> Cow<'_, Self> {
+        match self {
+            Self::Var(v) => {
+                match KnownConstants::<TargetField, BaseField>::load(&v.cs, &v.limb_variables()) {
+                    Some(c) => Cow::Owned(Self::Constant(c)),
+                    None => Cow::Borrowed(self),
+                }
+            }
+            Self::Constant(_) => Cow::Borrowed(self),
+        }
+    }
+
     /// The `mul_without_reduce` for `NonNativeFieldVar`
     pub fn mul_without_reduce(
         &self,
         other: &Self,
-    ) -> Result<NonNativeFieldMulResultVar<TargetField, BaseField>, SynthesisError> {
-        match self {
-            Self::Constant(c) => Ok(NonNativeFieldMulResultVar::Constant(*c)),
-            Self::Var(v) => {
-                let other_v = match other {
-                    Self::Constant(other_c) => {
-                        AllocatedNonNativeFieldVar::new_constant(self.cs(), other_c)?
-                    }
-                    Self::Var(other_v) => other_v.clone(),
-                };
-                Ok(NonNativeFieldMulResultVar::Var(
-                    v.mul_without_reduce(&other_v)?,
-                ))
+    ) -> Result<NonNativeFieldMulResultVar<TargetField, BaseField, Opt>, SynthesisError> {
+        match (&*self.resolve_constant(), &*other.resolve_constant()) {
+            (Self::Constant(c1), Self::Constant(c2)) => {
+                Ok(NonNativeFieldMulResultVar::Constant(*c1 * c2))
+            }
+            (Self::Constant(c), Self::Var(_)) | (Self::Var(_), Self::Constant(c))
+                if c.is_zero() =>
+            {
+                Ok(NonNativeFieldMulResultVar::zero())
+            }
+            (Self::Constant(c), Self::Var(v)) | (Self::Var(v), Self::Constant(c)) => Ok(
+                NonNativeFieldMulResultVar::Var(v.mul_constant_without_reduce(c)?),
+            ),
+            (Self::Var(v1), Self::Var(v2)) => {
+                Ok(NonNativeFieldMulResultVar::Var(v1.mul_without_reduce(v2)?))
             }
         }
     }
-}
 
-/// The allocated form of `NonNativeFieldMulResultVar` (introduced below)
-#[derive(Debug)]
-#[must_use]
-pub struct AllocatedNonNativeFieldMulResultVar<TargetField: PrimeField, BaseField: PrimeField> {
-    /// A reference to the constraint system
-    pub cs: ConstraintSystemRef<BaseField>,
-    /// Limbs of the intermediate representations (2 * num_limbs - 2)
-    pub limbs: Vec<AllocatedFp<BaseField>>,
-    /// The cumulative num of additions
-    pub prod_of_num_of_additions: BaseField,
-    /// Phantom for TargetField
-    pub target_phantom: PhantomData<TargetField>,
-}
+    /// Compute the sum of `elems`
+    ///
+    /// The constants are added up natively and added to the variables once, and the
+    /// variables are only reduced when their additions exceed the room of the limbs.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn add_many(elems: &[Self]) -> Result<Self, SynthesisError> {
+        let mut constant = TargetField::zero();
+        let mut sum: Option<AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>> = None;
+        for elem in elems.iter() {
+            match elem {
+                Self::Constant(c) => constant += c,
+                Self::Var(v) => {
+                    sum = Some(match sum {
+                        None => v.clone(),
+                        Some(sum) => sum.add(v)?,
+                    })
+                }
+            }
+        }
 
-/// An intermediate representation especially for the result of a multiplication, containing more limbs.
-/// It is intended for advanced usage to improve the efficiency.
-///
-/// That is, instead of calling `mul`, one can call `mul_without_reduce` to
-/// obtain this intermediate representation, which can still be added.
-/// Then, one can call `reduce` to reduce it back to `NonNativeFieldVar`.
-/// This may help cut the number of reduce operations.
-#[derive(Debug)]
-#[must_use]
-pub enum NonNativeFieldMulResultVar<TargetField: PrimeField, BaseField: PrimeField> {
-    /// as a constant
-    Constant(TargetField),
-    /// as an allocated gadget
-    Var(AllocatedNonNativeFieldMulResultVar<TargetField, BaseField>),
-}
+        match sum {
+            None => Ok(Self::Constant(constant)),
+            Some(sum) if constant.is_zero() => Ok(Self::Var(sum)),
+            Some(sum) => Ok(Self::Var(sum.add_constant(&constant)?)),
+        }
+    }
 
-impl<TargetField: PrimeField, BaseField: PrimeField>
-    AllocatedNonNativeFieldMulResultVar<TargetField, BaseField>
-{
+    /// Compute the sum of `elems` by carry-save accumulation, e.g., for sums of hundreds of
+    /// terms
+    ///
+    /// The limbs of the variables are added up column by column into native partial sums,
+    /// which need no constraints. Their bounds are tracked exactly and may grow up to the size
+    /// of `BaseField`, regardless of the maximal surfeit of the configuration. The carries of
+    /// all the columns are then resolved once, from the lowest column to the top, and the bits
+    /// carried out of the top limb are reduced. The carries are only resolved in between if a
+    /// column would no longer fit into `BaseField`, which takes about
+    /// `2^(BaseField::size_in_bits() - bits_per_limb)` terms in the normal form.
     #[tracing::instrument(target = "r1cs")]
-    fn to_bits(&self) -> Result<Vec<Vec<Boolean<BaseField>>>, SynthesisError> {
-        let params = get_params::<TargetField, BaseField>(&self.cs);
+    pub fn add_many_with_deferred_reduction(elems: &[Self]) -> Result<Self, SynthesisError> {
+        let mut constant = TargetField::zero();
+        let mut vars = Vec::new();
+        for elem in elems.iter() {
+            match elem {
+                Self::Constant(c) => constant += c,
+                Self::Var(v) => vars.push(v),
+            }
+        }
 
-        let num_limbs_unreduced = self.limbs.len();
-        let bits_per_top_limb = params.bits_per_top_limb;
-        let bits_per_non_top_limb = params.bits_per_non_top_limb;
+        let sum = match vars.as_slice() {
+            [] => return Ok(Self::Constant(constant)),
+            [v] => (*v).clone(),
+            [first, rest @ ..] => {
+                let cs = first.cs.clone();
+                let add_bounds = |bounds: &[BigUint], other: &[BigUint]| -> Vec<BigUint> {
+                    bounds
+                        .iter()
+                        .zip(other.iter())
+                        .map(|(a, b)| a + b)
+                        .collect()
+                };
 
-        let log_top_limb = overhead!(self.prod_of_num_of_additions);
-        let log_sub_top_limb = overhead!(self.prod_of_num_of_additions.double());
-        let log_other_limbs_upper_bound = overhead!(self
-            .prod_of_num_of_additions
-            .mul(&BaseField::from_repr(BaseField::BigInt::from(params.num_limbs as u64)).unwrap()));
+                let mut columns = first.limbs.clone();
+                let mut bounds = Reducer::<TargetField, BaseField, Opt>::column_bounds(first)?;
+                for v in rest.iter() {
+                    let mut v_limbs = v.limbs.clone();
+                    let mut v_bounds = Reducer::<TargetField, BaseField, Opt>::column_bounds(v)?;
+
+                    if !Reducer::<TargetField, BaseField, Opt>::can_resolve_carries(
+                        &cs,
+                        &add_bounds(&bounds, &v_bounds),
+                    )? {
+                        let resolved = Reducer::<TargetField, BaseField, Opt>::resolve_carries(
+                            &cs, &columns, &bounds,
+                        )?;
+                        bounds = Reducer::<TargetField, BaseField, Opt>::column_bounds(&resolved)?;
+                        columns = resolved.limbs;
+
+                        // the columns of `v` alone may not fit next to the resolved ones
+                        if !Reducer::<TargetField, BaseField, Opt>::can_resolve_carries(
+                            &cs,
+                            &add_bounds(&bounds, &v_bounds),
+                        )? {
+                            let resolved = Reducer::<TargetField, BaseField, Opt>::resolve_carries(
+                                &cs, &v_limbs, &v_bounds,
+                            )?;
+                            v_bounds =
+                                Reducer::<TargetField, BaseField, Opt>::column_bounds(&resolved)?;
+                            v_limbs = resolved.limbs;
+                        }
+                    }
 
-        let bits_per_unreduced_top_limb = max(
-            2 * (bits_per_top_limb + 1) + log_top_limb + bits_per_non_top_limb + 1,
-            2 * (bits_per_non_top_limb + 1) + log_sub_top_limb + 1,
-        );
-        let bits_per_unreduced_non_top_limb =
-            2 * (bits_per_non_top_limb + 1) + log_other_limbs_upper_bound;
+                    for (column, limb) in columns.iter_mut().zip(v_limbs.iter()) {
+                        *column = column.add(limb);
+                    }
+                    bounds = add_bounds(&bounds, &v_bounds);
+                }
 
-        let mut bits = Vec::<Vec<Boolean<BaseField>>>::with_capacity(
-            (num_limbs_unreduced - 1) * bits_per_unreduced_non_top_limb
-                + bits_per_unreduced_top_limb,
-        );
+                Reducer::<TargetField, BaseField, Opt>::resolve_carries(&cs, &columns, &bounds)?
+            }
+        };
 
-        for _ in 0..((num_limbs_unreduced - 1) * bits_per_unreduced_non_top_limb
-            + bits_per_unreduced_top_limb)
-        {
-            bits.push(Vec::new());
+        if constant.is_zero() {
+            Ok(Self::Var(sum))
+        } else {
+            Ok(Self::Var(sum.add_constant(&constant)?))
         }
+    }
 
-        let mut cur = 0;
-        for (l, limb) in self.limbs.iter().rev().enumerate() {
-            let bits_this_limb = if l == self.limbs.len() - 1 {
-                // top limb
-                bits_per_unreduced_top_limb
-            } else {
-                bits_per_unreduced_non_top_limb
-            };
-
-            let mut limb_bits =
-                Reducer::<TargetField, BaseField>::limb_to_bits(limb, bits_this_limb)?;
-            limb_bits.reverse();
+    /// Compute the product of `elems`
+    ///
+    /// The constants are multiplied natively, so that the variables are multiplied by them
+    /// once, and not at all if one of them is zero.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn mul_many(elems: &[Self]) -> Result<Self, SynthesisError> {
+        let mut constant = TargetField::one();
+        for elem in elems.iter() {
+            if let Self::Constant(c) = elem {
+                constant *= c;
+            }
+        }
+        if constant.is_zero() {
+            return Ok(Self::zero());
+        }
 
-            for (i, limb_bit) in limb_bits.iter().enumerate().take(bits_this_limb) {
-                let index = cur + i;
-                bits[index].push((*limb_bit).clone());
+        let mut prod: Option<AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>> = None;
+        for elem in elems.iter() {
+            if let Self::Var(v) = elem {
+                prod = Some(match prod {
+                    None => v.clone(),
+                    Some(prod) => prod.mul(v)?,
+                })
             }
-            cur += params.bits_per_non_top_limb;
         }
 
-        Ok(bits)
+        match prod {
+            None => Ok(Self::Constant(constant)),
+            Some(prod) if constant.is_one() => Ok(Self::Var(prod)),
+            Some(prod) => Ok(Self::Var(prod.mul_constant(&constant)?)),
+        }
     }
 
-    /// Constraints for reducing the result of a multiplication mod p, to get an original representation.
+    /// Enforce `lhs[i] == rhs[i]` for every `i`, grouping the limb equalities of all the pairs
+    /// into combined constraints
     #[tracing::instrument(target = "r1cs")]
-    pub fn reduce(
-        &self,
-    ) -> Result<AllocatedNonNativeFieldVar<TargetField, BaseField>, SynthesisError> {
-        let cs = self.cs.clone();
-        let params = get_params::<TargetField, BaseField>(&self.cs);
+    pub fn enforce_equal_many(lhs: &[Self], rhs: &[Self]) -> Result<(), SynthesisError> {
+        if lhs.len() != rhs.len() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
 
-        let num_limbs = params.num_limbs;
+        let cs = lhs.cs().or(rhs.cs());
+        let mut lhs_allocated = Vec::with_capacity(lhs.len());
+        let mut rhs_allocated = Vec::with_capacity(rhs.len());
+        for (left, right) in lhs.iter().zip(rhs.iter()) {
+            match (left, right) {
+                (Self::Constant(c1), Self::Constant(c2)) => {
+                    if c1 != c2 {
+                        return Err(SynthesisError::Unsatisfiable);
+                    }
+                }
+                _ => {
+                    lhs_allocated.push(Self::to_allocated(&cs, left)?);
+                    rhs_allocated.push(Self::to_allocated(&cs, right)?);
+                }
+            }
+        }
 
-        let mut sum = vec![BaseField::zero(); num_limbs];
-        let mut sum_lc = vec![LinearCombination::zero(); num_limbs];
+        AllocatedNonNativeFieldVar::enforce_equal_many(&lhs_allocated, &rhs_allocated)
+    }
 
-        let bits = self.to_bits()?;
+    /// Output `(inv, is_nonzero)`, where `inv` is the inverse of `self` if it is nonzero and
+    /// zero otherwise
+    #[tracing::instrument(target = "r1cs")]
+    pub fn inverse_or_zero(&self) -> Result<(Self, Boolean<BaseField>), SynthesisError> {
+        let v = match self {
+            Self::Constant(c) => {
+                return Ok((
+                    Self::Constant(c.inverse().unwrap_or_else(TargetField::zero)),
+                    Boolean::constant(!c.is_zero()),
+                ));
+            }
+            Self::Var(v) => v,
+        };
 
-        let mut num_of_additions = BaseField::zero();
-        let mut powers_of_2_cur = TargetField::one();
-        for (i, bit) in bits.iter().enumerate() {
-            let powers_of_2_representation =
-                AllocatedNonNativeFieldVar::<TargetField, BaseField>::get_limbs_representations(
-                    &powers_of_2_cur,
-                    Some(&cs),
-                )?;
-            for bits_cond in bit.iter() {
-                let val: bool = bits_cond.value().unwrap_or(false);
+        let cs = v.cs();
+        let is_nonzero = Boolean::new_witness(ark_relations::ns!(cs, "is_nonzero"), || {
+            Ok(!v.value()?.is_zero())
+        })?;
+        let inv = Self::new_witness(ark_relations::ns!(cs, "inv"), || {
+            Ok(v.value()?.inverse().unwrap_or_else(TargetField::zero))
+        })?;
 
-                num_of_additions += &BaseField::one();
+        // if nonzero, self * inv = 1; otherwise, both self and inv are zero
+        (self * &inv).conditional_enforce_equal(&Self::one(), &is_nonzero)?;
+        self.conditional_enforce_equal(&Self::zero(), &is_nonzero.not())?;
+        inv.conditional_enforce_equal(&Self::zero(), &is_nonzero.not())?;
+
+        Ok((inv, is_nonzero))
+    }
+
+    /// Compute `base^e` for a constant `base`, where `e` is given by its little-endian `bits`
+    ///
+    /// The bits are taken two at a time: each pair selects a factor from a constant table of
+    /// `[1, g, g^2, g^3]` for `g = base^{4^i}`, so that only one multiplication is needed for
+    /// every two bits.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn fixed_base_pow(
+        base: &TargetField,
+        bits: &[Boolean<BaseField>],
+    ) -> Result<Self, SynthesisError> {
+        let mut res = Self::one();
+        let mut power = *base;
+        for chunk in bits.chunks(2) {
+            let power_square = power.square();
+            let table = [
+                TargetField::one(),
+                power,
+                power_square,
+                power_square * &power,
+            ];
+            let chunk_bits = [
+                chunk[0].clone(),
+                chunk.get(1).cloned().unwrap_or(Boolean::FALSE),
+            ];
+            res *= &Self::two_bit_lookup(&chunk_bits, &table)?;
+            power = power_square.square();
+        }
+
+        Ok(res)
+    }
+
+    /// Compute `self^exponent`, where the exponent is the canonical integer of a nonnative
+    /// field element
+    ///
+    /// The exponent is decomposed into its canonical bits, from the least significant one, and
+    /// every step multiplies the result by either the current power or one, selected by the
+    /// bit. Each product and square is reduced once, and the square after the last bit is
+    /// skipped.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn pow(&self, exponent: &Self) -> Result<Self, SynthesisError> {
+        let bits = match (self, exponent) {
+            (_, Self::Constant(e)) => return self.pow_by_constant(&e.into_repr()),
+            (Self::Constant(base), _) => {
+                return Self::fixed_base_pow(base, &exponent.to_canonical_bits_le()?)
+            }
+            (Self::Var(_), Self::Var(_)) => exponent.to_canonical_bits_le()?,
+        };
+
+        let mut res = Self::one();
+        let mut power = self.clone();
+        for (i, bit) in bits.iter().enumerate() {
+            res *= &bit.select(&power, &Self::one())?;
+            if i + 1 < bits.len() {
+                power = power.square()?;
+            }
+        }
+
+        Ok(res)
+    }
+
+    /// Enforce that the elements of `elems` are pairwise distinct
+    ///
+    /// The product of all the pairwise differences is shown to be nonzero with a single
+    /// witnessed inverse, instead of one `enforce_not_equal` per pair.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn enforce_all_distinct(elems: &[Self]) -> Result<(), SynthesisError> {
+        let mut prod = Self::one();
+        for (i, a) in elems.iter().enumerate() {
+            for b in elems.iter().skip(i + 1) {
+                prod *= &(a - b);
+            }
+        }
+
+        match prod {
+            Self::Constant(c) if c.is_zero() => Err(SynthesisError::Unsatisfiable),
+            Self::Constant(_) => Ok(()),
+            Self::Var(v) => {
+                let _inverse = v.inverse()?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Enforce that `self` equals one of the constants in `set`
+    ///
+    /// The product of the differences between `self` and the constants is enforced to be
+    /// zero. Each difference adds `p - c` to `self` as a constant, so the subtractions share
+    /// the modulus `p` as their padding and allocate no witnesses.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn enforce_in_set(&self, set: &[TargetField]) -> Result<(), SynthesisError> {
+        let v = match self {
+            Self::Constant(c) if set.contains(c) => return Ok(()),
+            Self::Constant(_) => return Err(SynthesisError::Unsatisfiable),
+            Self::Var(v) => v,
+        };
+
+        let mut distinct = Vec::<TargetField>::with_capacity(set.len());
+        for c in set.iter() {
+            if !distinct.contains(c) {
+                distinct.push(*c);
+            }
+        }
+
+        let mut prod: Option<AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>> = None;
+        for c in distinct.iter() {
+            let diff = v.add_constant(&-*c)?;
+            prod = Some(match prod {
+                None => diff,
+                Some(prod) => prod.mul(&diff)?,
+            });
+        }
+
+        match prod {
+            None => Err(SynthesisError::Unsatisfiable),
+            Some(prod) => Self::Var(prod).enforce_equal(&Self::zero()),
+        }
+    }
+
+    /// Select `options[i]`, where `i` is given by the little-endian `selector_bits` and there
+    /// must be exactly `2^selector_bits.len()` options
+    ///
+    /// The options are combined in a binary tree, each level of which selects the limbs of
+    /// pairs of options with one bit.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn mux(
+        selector_bits: &[Boolean<BaseField>],
+        options: &[Self],
+    ) -> Result<Self, SynthesisError> {
+        if selector_bits.len() >= 64 || options.len() != 1 << selector_bits.len() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+
+        let cs = selector_bits.cs().or(options.cs());
+        if selector_bits.cs().is_none() || cs.is_none() {
+            let mut index = 0;
+            for (i, bit) in selector_bits.iter().enumerate() {
+                if bit.value()? {
+                    index |= 1 << i;
+                }
+            }
+            return Ok(options[index].clone());
+        }
+
+        let mut level = options
+            .iter()
+            .map(|option| Self::to_allocated(&cs, option))
+            .collect::<Result<Vec<_>, SynthesisError>>()?;
+        for bit in selector_bits.iter() {
+            level = level
+                .chunks(2)
+                .map(|pair| {
+                    AllocatedNonNativeFieldVar::conditionally_select(bit, &pair[1], &pair[0])
+                })
+                .collect::<Result<Vec<_>, SynthesisError>>()?;
+        }
+
+        Ok(Self::Var(level.remove(0)))
+    }
+
+    /// Output `(b, a)` if `cond` is true and `(a, b)` otherwise, e.g., for the ladders of
+    /// scalar multiplications and the comparators of sorting networks
+    #[tracing::instrument(target = "r1cs")]
+    pub fn conditionally_swap(
+        cond: &Boolean<BaseField>,
+        a: &Self,
+        b: &Self,
+    ) -> Result<(Self, Self), SynthesisError> {
+        match (cond, a, b) {
+            (Boolean::Constant(false), _, _) => Ok((a.clone(), b.clone())),
+            (Boolean::Constant(true), _, _) => Ok((b.clone(), a.clone())),
+            (_, Self::Constant(_), Self::Constant(_)) => Ok((
+                Self::conditionally_select(cond, b, a)?,
+                Self::conditionally_select(cond, a, b)?,
+            )),
+            _ => {
+                let cs = cond.cs().or(a.cs()).or(b.cs());
+                let (first, second) = AllocatedNonNativeFieldVar::conditionally_swap(
+                    cond,
+                    &Self::to_allocated(&cs, a)?,
+                    &Self::to_allocated(&cs, b)?,
+                )?;
+                Ok((Self::Var(first), Self::Var(second)))
+            }
+        }
+    }
+
+    /// Output the least significant bit of the canonical integer of `self`, e.g., for the sign
+    /// of a coordinate in point compression
+    #[tracing::instrument(target = "r1cs")]
+    pub fn is_odd(&self) -> Result<Boolean<BaseField>, SynthesisError> {
+        match self {
+            Self::Constant(c) => Ok(Boolean::constant(c.into_repr().is_odd())),
+            Self::Var(v) => v.is_odd(),
+        }
+    }
+
+    /// The Montgomery constant `R` of `TargetField`, as a field element
+    fn montgomery_r() -> TargetField {
+        // `R` is stored reduced modulo the modulus, so this never fails
+        TargetField::from_repr(<<TargetField as PrimeField>::Params as FpParameters>::R)
+            .unwrap_or_default()
+    }
+
+    /// Compute the element whose canonical integer is the Montgomery representation of `self`,
+    /// i.e., `self · R`
+    #[tracing::instrument(target = "r1cs")]
+    pub fn to_montgomery_repr(&self) -> Result<Self, SynthesisError> {
+        Ok(self * Self::montgomery_r())
+    }
+
+    /// Compute the element whose Montgomery representation is the canonical integer of `self`,
+    /// i.e., `self · R^{-1}`
+    #[tracing::instrument(target = "r1cs")]
+    pub fn from_montgomery_repr(&self) -> Result<Self, SynthesisError> {
+        let r_inv = Self::montgomery_r()
+            .inverse()
+            .ok_or(SynthesisError::Unsatisfiable)?;
+        Ok(self * r_inv)
+    }
+
+    /// Convert a native `BaseField` element into a simulated one, reducing it modulo the
+    /// `TargetField` modulus
+    ///
+    /// The canonical bits of `x` are packed into the limbs directly, so the limbs must cover
+    /// `BaseField::size_in_bits()` bits, as they do for two fields of the same size, such as
+    /// those of the Pallas–Vesta cycle. Return `SynthesisError::Unsatisfiable` otherwise.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn from_native(x: &FpVar<BaseField>) -> Result<Self, SynthesisError> {
+        match x {
+            FpVar::Constant(c) => Ok(Self::Constant(biguint_to_field(&field_to_biguint(c)))),
+            FpVar::Var(_) => Self::from_bits_le(&x.to_bits_le()?),
+        }
+    }
+
+    /// Pack the little-endian `bits` into the limbs of a simulated element, reducing the
+    /// integer that they encode modulo the `TargetField` modulus
+    ///
+    /// The bits are not checked again, and each limb is allocated as the packing of its bits
+    /// with one constraint, so this takes one constraint per limb. With fewer bits than the
+    /// `TargetField` modulus, the result is in the normal form, as for native challenges. The
+    /// limbs must cover the bits; return `SynthesisError::Unsatisfiable` otherwise.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn from_bits_le(bits: &[Boolean<BaseField>]) -> Result<Self, SynthesisError> {
+        let cs = bits.cs();
+        if cs.is_none() {
+            let mut value = TargetField::zero();
+            let mut coeff = TargetField::one();
+            for bit in bits.iter() {
+                if bit.value()? {
+                    value += &coeff;
+                }
+                coeff.double_in_place();
+            }
+            return Ok(Self::Constant(value));
+        }
+
+        let params = get_params::<TargetField, BaseField>(&cs)?;
+        if bits.len()
+            > params.bits_per_top_limb + (params.num_limbs - 1) * params.bits_per_non_top_limb
+        {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+
+        // the limbs are stored from the top one, which takes the remaining high bits
+        let mut limbs = Vec::with_capacity(params.num_limbs);
+        for i in 0..params.num_limbs {
+            let start = (params.num_limbs - 1 - i) * params.bits_per_non_top_limb;
+            let end = if i == 0 {
+                bits.len()
+            } else {
+                start + params.bits_per_non_top_limb
+            };
+
+            let mut lc = LinearCombination::zero();
+            let mut value = BaseField::zero();
+            let mut coeff = BaseField::one();
+            for bit in bits.iter().take(end).skip(start) {
+                lc = &lc + bit.lc() * coeff;
+                if bit.value().unwrap_or_default() {
+                    value += &coeff;
+                }
+                coeff.double_in_place();
+            }
+
+            limbs.push(Reducer::<TargetField, BaseField, Opt>::alloc_limb_from_lc(
+                &cs, value, lc,
+            )?);
+        }
+
+        // like a witness, a representation of as many bits as the modulus may exceed it
+        let is_in_the_normal_form = bits.len() < TargetField::size_in_bits();
+        Ok(Self::Var(AllocatedNonNativeFieldVar {
+            cs,
+            limbs,
+            num_of_additions_over_normal_form: if is_in_the_normal_form {
+                BaseField::zero()
+            } else {
+                BaseField::one()
+            },
+            is_in_the_normal_form,
+            target_phantom: PhantomData,
+        }))
+    }
+
+    /// Return how many more operations `self` can absorb before a reduction becomes mandatory
+    ///
+    /// Constants are never reduced, so their headroom is unlimited.
+    pub fn headroom(&self) -> Result<Headroom, SynthesisError> {
+        match self {
+            Self::Constant(_) => Ok(Headroom::UNLIMITED),
+            Self::Var(v) => v.headroom(),
+        }
+    }
+
+    /// Convert `self` into an element of the optimization mode `OtherOpt`, which generates no
+    /// constraints; see `AllocatedNonNativeFieldVar::to_mode`
+    pub fn to_mode<OtherOpt: OptimizationMode>(
+        &self,
+    ) -> NonNativeFieldVar<TargetField, BaseField, OtherOpt> {
+        match self {
+            Self::Constant(c) => NonNativeFieldVar::Constant(*c),
+            Self::Var(v) => NonNativeFieldVar::Var(v.to_mode()),
+        }
+    }
+
+    /// Convert `self` into a native `BaseField` element, reducing its canonical integer modulo
+    /// the `BaseField` modulus
+    ///
+    /// The bits of `self` are enforced to be canonical, so that the conversion is a function
+    /// of the `TargetField` element. It is injective when the `TargetField` modulus is at most
+    /// the `BaseField` modulus.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn to_native(&self) -> Result<FpVar<BaseField>, SynthesisError> {
+        let cs = match self {
+            Self::Constant(c) => {
+                return Ok(FpVar::Constant(biguint_to_field(&field_to_biguint(c))))
+            }
+            Self::Var(v) => v.cs.clone(),
+        };
+
+        let bits = self.to_canonical_bits_le()?;
+
+        let mut lc = LinearCombination::zero();
+        let mut value = BaseField::zero();
+        let mut coeff = BaseField::one();
+        for bit in bits.iter() {
+            lc = &lc + bit.lc() * coeff;
+            if bit.value().unwrap_or_default() {
+                value += &coeff;
+            }
+            coeff.double_in_place();
+        }
+
+        Ok(FpVar::Var(
+            Reducer::<TargetField, BaseField, Opt>::alloc_limb_from_lc(&cs, value, lc)?,
+        ))
+    }
+
+    /// Output the little-endian bits of the canonical integer of `self`, which are enforced to
+    /// be smaller than the modulus
+    pub(crate) fn to_canonical_bits_le(&self) -> Result<Vec<Boolean<BaseField>>, SynthesisError> {
+        if let Self::Constant(_) = self {
+            return self.to_non_unique_bits_le();
+        }
+
+        let bits = self.to_bits_le()?;
+        let mut modulus_minus_one = <<TargetField as PrimeField>::Params as FpParameters>::MODULUS;
+        modulus_minus_one.sub_noborrow(&TargetField::BigInt::from(1));
+        Boolean::enforce_smaller_or_equal_than_le(&bits, modulus_minus_one)?;
+        Ok(bits)
+    }
+
+    /// Obtain the allocated form, with constants allocated in `cs`
+    fn to_allocated(
+        cs: &ConstraintSystemRef<BaseField>,
+        elem: &Self,
+    ) -> Result<AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>, SynthesisError> {
+        match elem {
+            Self::Constant(c) if c.is_zero() => AllocatedNonNativeFieldVar::zero(cs.clone()),
+            Self::Constant(c) if c.is_one() => AllocatedNonNativeFieldVar::one(cs.clone()),
+            Self::Constant(c) => AllocatedNonNativeFieldVar::new_constant(cs.clone(), c),
+            Self::Var(v) => Ok(v.clone()),
+        }
+    }
+}
+
+/// The allocated form of `NonNativeFieldMulResultVar` (introduced below)
+#[derive(Debug)]
+#[must_use]
+pub struct AllocatedNonNativeFieldMulResultVar<
+    TargetField: PrimeField,
+    BaseField: PrimeField,
+    Opt: OptimizationMode = ConstraintsOpt,
+> {
+    /// A reference to the constraint system
+    pub cs: ConstraintSystemRef<BaseField>,
+    /// Limbs of the intermediate representations (2 * num_limbs - 2)
+    pub limbs: Vec<AllocatedFp<BaseField>>,
+    /// The cumulative num of additions
+    pub prod_of_num_of_additions: BaseField,
+    /// Phantom for TargetField
+    pub target_phantom: PhantomData<(TargetField, Opt)>,
+    /// The handle by which the `MemoryStats` of the constraint system count the product as
+    /// alive, which is empty unless they are enabled
+    pub memory_handle: ProductHandle,
+}
+
+// the products are counted from their creation to their drop by `MemoryStats`, so that the
+// retained ones can be located
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode> Clone
+    for AllocatedNonNativeFieldMulResultVar<TargetField, BaseField, Opt>
+{
+    fn clone(&self) -> Self {
+        Self {
+            cs: self.cs.clone(),
+            limbs: self.limbs.clone(),
+            prod_of_num_of_additions: self.prod_of_num_of_additions,
+            target_phantom: PhantomData,
+            memory_handle: ProductHandle::default(),
+        }
+        .tracked()
+    }
+}
+
+/// An intermediate representation especially for the result of a multiplication, containing more limbs.
+/// It is intended for advanced usage to improve the efficiency.
+///
+/// That is, instead of calling `mul`, one can call `mul_without_reduce` to
+/// obtain this intermediate representation, which can still be added.
+/// Then, one can call `reduce` to reduce it back to `NonNativeFieldVar`.
+/// This may help cut the number of reduce operations.
+#[derive(Clone, Debug)]
+#[must_use]
+pub enum NonNativeFieldMulResultVar<
+    TargetField: PrimeField,
+    BaseField: PrimeField,
+    Opt: OptimizationMode = ConstraintsOpt,
+> {
+    /// as a constant
+    Constant(TargetField),
+    /// as an allocated gadget
+    Var(AllocatedNonNativeFieldMulResultVar<TargetField, BaseField, Opt>),
+}
+
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode>
+    AllocatedNonNativeFieldMulResultVar<TargetField, BaseField, Opt>
+{
+    /// Record the creation of the product in the `MemoryStats` of its constraint system
+    fn tracked(mut self) -> Self {
+        self.memory_handle = MemoryStats::record_product(&self.cs, self.limbs.len());
+        self
+    }
+
+    /// Compute the number of bits of the unreduced top limb and of the other unreduced limbs
+    fn unreduced_limb_sizes(
+        params: &NonNativeFieldParams,
+        prod_of_num_of_additions: BaseField,
+    ) -> (usize, usize) {
+        let bits_per_top_limb = params.bits_per_top_limb;
+        let bits_per_non_top_limb = params.bits_per_non_top_limb;
+
+        let log_top_limb = overhead!(prod_of_num_of_additions);
+        let log_sub_top_limb = overhead!(prod_of_num_of_additions.double());
+        let log_other_limbs_upper_bound =
+            overhead!(prod_of_num_of_additions.mul(&BaseField::from(params.num_limbs as u64)));
+
+        let bits_per_unreduced_top_limb = max(
+            2 * (bits_per_top_limb + 1) + log_top_limb + bits_per_non_top_limb + 1,
+            2 * (bits_per_non_top_limb + 1) + log_sub_top_limb + 1,
+        );
+        let bits_per_unreduced_non_top_limb =
+            2 * (bits_per_non_top_limb + 1) + log_other_limbs_upper_bound;
+
+        (bits_per_unreduced_top_limb, bits_per_unreduced_non_top_limb)
+    }
+
+    /// Allocate the constant `elem` in the unreduced form, i.e., as the product of `elem` and
+    /// one, whose limbs above the normal form are zero
+    pub fn new_constant(
+        cs: ConstraintSystemRef<BaseField>,
+        elem: &TargetField,
+    ) -> Result<Self, SynthesisError> {
+        let params = get_params::<TargetField, BaseField>(&cs)?;
+        let elem_limbs =
+            AllocatedNonNativeFieldVar::<TargetField, BaseField, Opt>::get_limbs_representations(
+                elem,
+                Some(&cs),
+            )?;
+
+        let mut limbs = Vec::with_capacity(2 * params.num_limbs - 2);
+        for _ in 0..(params.num_limbs - 2) {
+            limbs.push(AllocatedFp::new_constant(cs.clone(), BaseField::zero())?);
+        }
+        for limb in elem_limbs.iter() {
+            limbs.push(AllocatedFp::new_constant(cs.clone(), limb)?);
+        }
+
+        Ok(Self {
+            cs,
+            limbs,
+            prod_of_num_of_additions: BaseField::one(),
+            target_phantom: PhantomData,
+            memory_handle: ProductHandle::default(),
+        }
+        .tracked())
+    }
+
+    /// Lift `elem` into the unreduced form, so that it can be added to products before they
+    /// are reduced
+    ///
+    /// The limbs of `elem` are far below those of a product, so the bound of a product with
+    /// as many additions covers them.
+    pub fn from_reduced(
+        elem: &AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>,
+    ) -> Result<Self, SynthesisError> {
+        let params = get_params::<TargetField, BaseField>(&elem.cs)?;
+        let zero = AllocatedFp::new_constant(elem.cs.clone(), BaseField::zero())?;
+
+        let mut limbs = vec![zero; params.num_limbs - 2];
+        limbs.extend(elem.limbs.iter().cloned());
+
+        Ok(Self {
+            cs: elem.cs.clone(),
+            limbs,
+            prod_of_num_of_additions: elem.num_of_additions_over_normal_form + BaseField::one(),
+            target_phantom: PhantomData,
+            memory_handle: ProductHandle::default(),
+        }
+        .tracked())
+    }
+
+    /// Check whether a result with `prod_of_num_of_additions` can still be soundly reduced,
+    /// i.e., whether its unreduced limbs fit into `BaseField`
+    pub(crate) fn is_within_bound(
+        &self,
+        prod_of_num_of_additions: BaseField,
+    ) -> Result<bool, SynthesisError> {
+        let params = get_params::<TargetField, BaseField>(&self.cs)?;
+        let (bits_per_unreduced_top_limb, bits_per_unreduced_non_top_limb) =
+            Self::unreduced_limb_sizes(&params, prod_of_num_of_additions);
+
+        Ok(
+            max(bits_per_unreduced_top_limb, bits_per_unreduced_non_top_limb)
+                <= BaseField::size_in_bits() - 1,
+        )
+    }
+
+    /// Return how many more products of elements in the normal form can be added to `self`
+    /// before it has to be reduced, saturating at `usize::MAX`
+    ///
+    /// This generates no constraints.
+    pub fn headroom(&self) -> Result<usize, SynthesisError> {
+        let max = largest_satisfying(|prod_of_num_of_additions| {
+            self.is_within_bound(prod_of_num_of_additions)
+        })?;
+
+        Ok(max.map_or(0, |max| steps_up_to(&self.prod_of_num_of_additions, &max)))
+    }
+
+    /// Check that a result with `prod_of_num_of_additions` can still be soundly reduced, and
+    /// return `NonNativeFieldError::Overflow` otherwise
+    fn check_bound(
+        &self,
+        prod_of_num_of_additions: BaseField,
+        operation: &'static str,
+    ) -> Result<(), NonNativeFieldError> {
+        let params = get_params::<TargetField, BaseField>(&self.cs)?;
+        let (bits_per_unreduced_top_limb, bits_per_unreduced_non_top_limb) =
+            Self::unreduced_limb_sizes(&params, prod_of_num_of_additions);
+        let bits = max(bits_per_unreduced_top_limb, bits_per_unreduced_non_top_limb);
+        let max_bits = BaseField::size_in_bits() - 1;
+        if bits > max_bits {
+            return Err(NonNativeFieldError::Overflow {
+                operation,
+                bits,
+                max_bits,
+            });
+        }
+
+        if let Some(warnings) = OverflowWarnings::get(&self.cs) {
+            warnings.check(&self.cs, operation, bits, max_bits);
+        }
+        Ok(())
+    }
+
+    /// Add unreduced elements as `add` does, but reduce both of them first if the sum would
+    /// exceed the bound that the reduction can soundly handle
+    ///
+    /// This is the addition of the `+` operator, which cannot return the error of `add`.
+    pub(crate) fn add_or_reduce(&self, other: &Self) -> Result<Self, SynthesisError> {
+        if self.is_within_bound(self.prod_of_num_of_additions + other.prod_of_num_of_additions)? {
+            return Ok(self.add(other)?);
+        }
+
+        let this = Self::from_reduced(&self.reduce()?)?;
+        let other = Self::from_reduced(&other.reduce()?)?;
+        Ok(this.add(&other)?)
+    }
+
+    /// Add a constant as `add_constant` does, but reduce `self` first if the sum would exceed
+    /// the bound that the reduction can soundly handle
+    pub(crate) fn add_constant_or_reduce(
+        &self,
+        other: &TargetField,
+    ) -> Result<Self, SynthesisError> {
+        if self.is_within_bound(self.prod_of_num_of_additions + BaseField::one())? {
+            return Ok(self.add_constant(other)?);
+        }
+
+        Ok(Self::from_reduced(&self.reduce()?)?.add_constant(other)?)
+    }
+
+    #[tracing::instrument(target = "r1cs")]
+    fn to_bits(&self) -> Result<Vec<Vec<Boolean<BaseField>>>, SynthesisError> {
+        let params = get_params::<TargetField, BaseField>(&self.cs)?;
+
+        let num_limbs_unreduced = self.limbs.len();
+        let (bits_per_unreduced_top_limb, bits_per_unreduced_non_top_limb) =
+            Self::unreduced_limb_sizes(&params, self.prod_of_num_of_additions);
+
+        let mut bits = Vec::<Vec<Boolean<BaseField>>>::with_capacity(
+            (num_limbs_unreduced - 1) * bits_per_unreduced_non_top_limb
+                + bits_per_unreduced_top_limb,
+        );
+
+        for _ in 0..((num_limbs_unreduced - 1) * bits_per_unreduced_non_top_limb
+            + bits_per_unreduced_top_limb)
+        {
+            bits.push(Vec::new());
+        }
+
+        let mut cur = 0;
+        for (l, limb) in self.limbs.iter().rev().enumerate() {
+            let bits_this_limb = if l == self.limbs.len() - 1 {
+                // top limb
+                bits_per_unreduced_top_limb
+            } else {
+                bits_per_unreduced_non_top_limb
+            };
+
+            let mut limb_bits =
+                Reducer::<TargetField, BaseField, Opt>::limb_to_bits(limb, bits_this_limb)?;
+            limb_bits.reverse();
+
+            for (i, limb_bit) in limb_bits.iter().enumerate().take(bits_this_limb) {
+                let index = cur + i;
+                bits[index].push((*limb_bit).clone());
+            }
+            cur += params.bits_per_non_top_limb;
+        }
+
+        Ok(bits)
+    }
+
+    /// The bits of `self` as output by `to_bits`, but with the limbs at and above `2^k` folded
+    /// into the lower limbs, for a modulus `p = 2^k - c` with a small `c`
+    ///
+    /// As `2^k = c` modulo p, a limb at the position `k + q · w + r`, where `w` is the size of
+    /// the non-top limbs and `r < w`, is added to the limb at `q · w` times `c · 2^r`, which
+    /// needs no constraints. A limb is only folded if the sum still fits into `BaseField`,
+    /// which saves its bit decomposition for that of a few more bits of the lower limb.
+    /// Return `None` if the modulus has no such form or no limb can be folded.
+    fn pseudo_mersenne_bits(&self) -> Result<Option<Vec<Vec<Boolean<BaseField>>>>, SynthesisError> {
+        let (k, c) = match pseudo_mersenne_form::<TargetField>() {
+            Some(form) => form,
+            None => return Ok(None),
+        };
+        let params = get_params::<TargetField, BaseField>(&self.cs)?;
+        let bits_per_non_top_limb = params.bits_per_non_top_limb;
+        let (bits_per_unreduced_top_limb, bits_per_unreduced_non_top_limb) =
+            Self::unreduced_limb_sizes(&params, self.prod_of_num_of_additions);
+
+        // the limbs and their sizes, lowest first
+        let num_limbs_unreduced = self.limbs.len();
+        let mut limbs: Vec<AllocatedFp<BaseField>> = self.limbs.iter().rev().cloned().collect();
+        let mut sizes: Vec<usize> = (0..num_limbs_unreduced)
+            .map(|i| {
+                if i == num_limbs_unreduced - 1 {
+                    bits_per_unreduced_top_limb
+                } else {
+                    bits_per_unreduced_non_top_limb
+                }
+            })
+            .collect();
+
+        let num_low_limbs = (k + bits_per_non_top_limb - 1) / bits_per_non_top_limb;
+        let c_bits = c.bits() as usize;
+        let mut is_folded = vec![false; num_limbs_unreduced];
+        for i in num_low_limbs..num_limbs_unreduced {
+            let offset = i * bits_per_non_top_limb - k;
+            let (q, r) = (
+                offset / bits_per_non_top_limb,
+                offset % bits_per_non_top_limb,
+            );
+            let folded_size = max(sizes[q], sizes[i] + c_bits + r) + 1;
+            if q >= num_low_limbs || folded_size > BaseField::size_in_bits() - 1 {
+                continue;
+            }
+
+            let coeff = biguint_to_field::<BaseField>(&(&c << r));
+            limbs[q] = limbs[q].add(&limbs[i].mul_constant(coeff));
+            sizes[q] = folded_size;
+            is_folded[i] = true;
+        }
+        if !is_folded.iter().any(|folded| *folded) {
+            return Ok(None);
+        }
+
+        let num_positions = (0..num_limbs_unreduced)
+            .filter(|i| !is_folded[*i])
+            .map(|i| i * bits_per_non_top_limb + sizes[i])
+            .max()
+            .unwrap_or(0);
+        let mut bits = vec![Vec::new(); num_positions];
+        for i in (0..num_limbs_unreduced).filter(|i| !is_folded[*i]) {
+            let mut limb_bits =
+                Reducer::<TargetField, BaseField, Opt>::limb_to_bits(&limbs[i], sizes[i])?;
+            limb_bits.reverse();
+            for (j, limb_bit) in limb_bits.into_iter().enumerate() {
+                bits[i * bits_per_non_top_limb + j].push(limb_bit);
+            }
+        }
+
+        Ok(Some(bits))
+    }
+
+    /// Constraints for reducing the result of a multiplication mod p, to get an original representation.
+    ///
+    /// For a pseudo-Mersenne modulus `p = 2^k - c`, the high limbs are folded into the low
+    /// ones first, as in `pseudo_mersenne_bits`.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn reduce(
+        &self,
+    ) -> Result<AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>, SynthesisError> {
+        let ns = ark_relations::ns!(self.cs, "nonnative::reduce");
+        let cs = ns.cs();
+        let _scope = OperationScope::start(&cs, Operation::Reduction);
+        let scope = GraphScope::start("reduce", &cs, &[&self.limbs[..]]);
+        let trace = ReductionTrace::start("reduce", &cs, self.prod_of_num_of_additions);
+        let params = get_params::<TargetField, BaseField>(&self.cs)?;
+
+        let num_limbs = params.num_limbs;
+
+        let mut sum = vec![BaseField::zero(); num_limbs];
+        let mut sum_lc = vec![LinearCombination::zero(); num_limbs];
+
+        let bits = match self.pseudo_mersenne_bits()? {
+            Some(bits) => bits,
+            None => self.to_bits()?,
+        };
+
+        let mut num_of_additions = BaseField::zero();
+        let mut powers_of_2_cur = TargetField::one();
+        for (i, bit) in bits.iter().enumerate() {
+            let powers_of_2_representation = AllocatedNonNativeFieldVar::<
+                TargetField,
+                BaseField,
+                Opt,
+            >::get_limbs_representations(
+                &powers_of_2_cur, Some(&cs)
+            )?;
+            for bits_cond in bit.iter() {
+                let val: bool = bits_cond.value().unwrap_or(false);
+
+                num_of_additions += &BaseField::one();
 
                 for (j, limb) in powers_of_2_representation.iter().enumerate() {
                     if val {
@@ -1371,10 +3202,9 @@ impl<TargetField: PrimeField, BaseField: PrimeField>
         }
 
         let mut sum_gadget = Vec::<AllocatedFp<BaseField>>::new();
-        for limb in sum.iter().take(num_limbs) {
-            sum_gadget.push(AllocatedFp::<BaseField>::new_witness(
-                ark_relations::ns!(cs, "alloc_sum"),
-                || Ok(limb),
+        for (limb, limb_lc) in sum.iter().zip(sum_lc.into_iter()).take(num_limbs) {
+            sum_gadget.push(Reducer::<TargetField, BaseField, Opt>::alloc_limb_from_lc(
+                &cs, *limb, limb_lc,
             )?);
         }
 
@@ -1385,18 +3215,51 @@ impl<TargetField: PrimeField, BaseField: PrimeField>
             is_in_the_normal_form: false,
             target_phantom: PhantomData,
         };
-
-        for (sum_lc_elem, sum_elem) in sum_lc.iter().zip(sum_gadget.limbs.iter()) {
-            let sum_elem_lc = LinearCombination::from((BaseField::one(), sum_elem.variable));
-            cs.enforce_constraint(lc!(), lc!(), sum_elem_lc - sum_lc_elem)?;
-        }
+        trace.finish(
+            sum_gadget.limbs.len(),
+            sum_gadget.num_of_additions_over_normal_form,
+        );
+        scope.finish(Some(&sum_gadget.limbs));
 
         Ok(sum_gadget)
     }
 
-    /// Add unreduced elements.
+    /// Enforce that the result reduces to `dst` modulo p
+    ///
+    /// The sum of the bits is checked against the normal form of `dst` directly, so this
+    /// saves the fresh normal form and the limb equalities of `reduce()` followed by
+    /// `enforce_equal`.
     #[tracing::instrument(target = "r1cs")]
+    pub fn reduce_into(
+        &self,
+        dst: &AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>,
+    ) -> Result<(), SynthesisError> {
+        let mut sum_gadget = self.reduce()?;
+        let mut dst_normal = dst.clone();
+        Reducer::pre_eq_reduce(&mut dst_normal)?;
+
+        Reducer::enforce_normal_form(&mut sum_gadget, dst_normal)
+    }
+
+    /// Add unreduced elements.
+    ///
+    /// Return `SynthesisError::Unsatisfiable` if the sum could not be soundly reduced, in
+    /// which case the operands need to be reduced first; `try_add` returns the typed error.
     pub fn add(&self, other: &Self) -> Result<Self, SynthesisError> {
+        Ok(self.try_add(other)?)
+    }
+
+    /// Add unreduced elements as `add` does
+    ///
+    /// Return `NonNativeFieldError::Overflow` if the sum could not be soundly reduced, in
+    /// which case the operands need to be reduced first.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn try_add(&self, other: &Self) -> Result<Self, NonNativeFieldError> {
+        let scope = GraphScope::start("add", &self.cs, &[&self.limbs[..], &other.limbs[..]]);
+        let prod_of_num_of_additions =
+            self.prod_of_num_of_additions + other.prod_of_num_of_additions;
+        self.check_bound(prod_of_num_of_additions, "add")?;
+
         let mut new_limbs: Vec<AllocatedFp<BaseField>> = Vec::new();
 
         for (l1, l2) in self.limbs.iter().zip(other.limbs.iter()) {
@@ -1404,20 +3267,37 @@ impl<TargetField: PrimeField, BaseField: PrimeField>
             new_limbs.push(new_limb);
         }
 
-        Ok(Self {
+        let res = Self {
             cs: self.cs.clone(),
             limbs: new_limbs,
-            prod_of_num_of_additions: self.prod_of_num_of_additions
-                + other.prod_of_num_of_additions,
+            prod_of_num_of_additions,
             target_phantom: PhantomData,
-        })
+            memory_handle: ProductHandle::default(),
+        }
+        .tracked();
+        scope.finish(Some(&res.limbs));
+        Ok(res)
     }
 
     /// Add native constant elem
-    #[tracing::instrument(target = "r1cs")]
+    ///
+    /// Return `SynthesisError::Unsatisfiable` if the sum could not be soundly reduced;
+    /// `try_add_constant` returns the typed error.
     pub fn add_constant(&self, other: &TargetField) -> Result<Self, SynthesisError> {
+        Ok(self.try_add_constant(other)?)
+    }
+
+    /// Add native constant elem as `add_constant` does
+    ///
+    /// Return `NonNativeFieldError::Overflow` if the sum could not be soundly reduced.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn try_add_constant(&self, other: &TargetField) -> Result<Self, NonNativeFieldError> {
+        let scope = GraphScope::start("add_constant", &self.cs, &[&self.limbs[..]]);
+        let prod_of_num_of_additions = self.prod_of_num_of_additions + BaseField::one();
+        self.check_bound(prod_of_num_of_additions, "add_constant")?;
+
         let mut other_limbs =
-            AllocatedNonNativeFieldVar::<TargetField, BaseField>::get_limbs_representations(
+            AllocatedNonNativeFieldVar::<TargetField, BaseField, Opt>::get_limbs_representations(
                 other,
                 Some(&self.cs),
             )?;
@@ -1435,17 +3315,87 @@ impl<TargetField: PrimeField, BaseField: PrimeField>
 
         new_limbs.reverse();
 
-        Ok(Self {
+        let res = Self {
             cs: self.cs.clone(),
             limbs: new_limbs,
-            prod_of_num_of_additions: self.prod_of_num_of_additions + BaseField::one(),
+            prod_of_num_of_additions,
             target_phantom: PhantomData,
-        })
+            memory_handle: ProductHandle::default(),
+        }
+        .tracked();
+        scope.finish(Some(&res.limbs));
+        Ok(res)
+    }
+
+    /// Subtract unreduced elements
+    ///
+    /// A multiple of p whose limbs exceed the bounds of the limbs of `other` is added, so that
+    /// the limbs of the difference stay nonnegative. Each limb of this pad is below twice the
+    /// bound of the corresponding limb of `other`. Return `SynthesisError::Unsatisfiable` if
+    /// the difference could not be soundly reduced; `try_sub` returns the typed error.
+    pub fn sub(&self, other: &Self) -> Result<Self, SynthesisError> {
+        Ok(self.try_sub(other)?)
+    }
+
+    /// Subtract unreduced elements as `sub` does
+    ///
+    /// Return `NonNativeFieldError::Overflow` if the difference could not be soundly reduced.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn try_sub(&self, other: &Self) -> Result<Self, NonNativeFieldError> {
+        let scope = GraphScope::start("sub", &self.cs, &[&self.limbs[..], &other.limbs[..]]);
+        let params = get_params::<TargetField, BaseField>(&self.cs)?;
+        let prod_of_num_of_additions = self.prod_of_num_of_additions.double()
+            + other.prod_of_num_of_additions.double().double()
+            + BaseField::one();
+        self.check_bound(prod_of_num_of_additions, "sub")?;
+
+        let (bits_per_unreduced_top_limb, bits_per_unreduced_non_top_limb) =
+            Self::unreduced_limb_sizes(&params, other.prod_of_num_of_additions);
+
+        // `2^bits` in every limb, completed by the normal form of the remainder modulo p
+        let num_limbs = self.limbs.len();
+        let mut pad = Vec::with_capacity(num_limbs);
+        let mut pad_value = TargetField::zero();
+        for i in 0..num_limbs {
+            let bits = if i == 0 {
+                bits_per_unreduced_top_limb
+            } else {
+                bits_per_unreduced_non_top_limb
+            };
+            let shift = (num_limbs - 1 - i) * params.bits_per_non_top_limb;
+            pad.push(BaseField::from(2u64).pow(&[bits as u64]));
+            pad_value += &TargetField::from(2u64).pow(&[(bits + shift) as u64]);
+        }
+        let remainder =
+            AllocatedNonNativeFieldVar::<TargetField, BaseField, Opt>::get_limbs_representations(
+                &-pad_value,
+                Some(&self.cs),
+            )?;
+        for (limb, remainder_limb) in pad.iter_mut().rev().zip(remainder.iter().rev()) {
+            *limb += remainder_limb;
+        }
+
+        let mut new_limbs: Vec<AllocatedFp<BaseField>> = Vec::new();
+
+        for ((l1, l2), pad_limb) in self.limbs.iter().zip(other.limbs.iter()).zip(pad.iter()) {
+            new_limbs.push(l1.add_constant(*pad_limb).sub(l2));
+        }
+
+        let res = Self {
+            cs: self.cs.clone(),
+            limbs: new_limbs,
+            prod_of_num_of_additions,
+            target_phantom: PhantomData,
+            memory_handle: ProductHandle::default(),
+        }
+        .tracked();
+        scope.finish(Some(&res.limbs));
+        Ok(res)
     }
 }
 
-impl<TargetField: PrimeField, BaseField: PrimeField>
-    NonNativeFieldMulResultVar<TargetField, BaseField>
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode>
+    NonNativeFieldMulResultVar<TargetField, BaseField, Opt>
 {
     /// Create a zero `NonNativeFieldMulResultVar` (used for additions)
     pub fn zero() -> Self {
@@ -1457,31 +3407,217 @@ impl<TargetField: PrimeField, BaseField: PrimeField>
         Self::Constant(v)
     }
 
+    /// Lift `elem` into the unreduced form, so that it can be added to products before they
+    /// are reduced
+    pub fn from_reduced(
+        elem: &NonNativeFieldVar<TargetField, BaseField, Opt>,
+    ) -> Result<Self, SynthesisError> {
+        match elem {
+            NonNativeFieldVar::Constant(c) => Ok(Self::Constant(*c)),
+            NonNativeFieldVar::Var(v) => {
+                AllocatedNonNativeFieldMulResultVar::from_reduced(v).map(Self::Var)
+            }
+        }
+    }
+
+    /// Add `other` to `self` as the `+` operator does, reducing the operands first if the sum
+    /// would exceed the bound that the reduction can soundly handle, but return the errors of
+    /// the reduction instead of panicking
+    pub fn add_or_reduce(&self, other: &Self) -> Result<Self, SynthesisError> {
+        match (self, other) {
+            (Self::Constant(c1), Self::Constant(c2)) => Ok(Self::Constant(*c1 + c2)),
+            (Self::Constant(c), Self::Var(v)) | (Self::Var(v), Self::Constant(c)) => {
+                v.add_constant_or_reduce(c).map(Self::Var)
+            }
+            (Self::Var(v1), Self::Var(v2)) => v1.add_or_reduce(v2).map(Self::Var),
+        }
+    }
+
+    /// Add the constant `other` to `self` as `add_or_reduce` does
+    pub fn add_constant_or_reduce(&self, other: &TargetField) -> Result<Self, SynthesisError> {
+        self.add_or_reduce(&Self::Constant(*other))
+    }
+
     /// Reduce the `NonNativeFieldMulResultVar` back to NonNativeFieldVar
     #[tracing::instrument(target = "r1cs")]
-    pub fn reduce(&self) -> Result<NonNativeFieldVar<TargetField, BaseField>, SynthesisError> {
+    pub fn reduce(&self) -> Result<NonNativeFieldVar<TargetField, BaseField, Opt>, SynthesisError> {
         match self {
             Self::Constant(c) => Ok(NonNativeFieldVar::Constant(*c)),
             Self::Var(v) => Ok(NonNativeFieldVar::Var(v.reduce()?)),
         }
     }
+
+    /// Return how many more products of elements in the normal form can be added to `self`
+    /// before it has to be reduced, saturating at `usize::MAX`
+    pub fn headroom(&self) -> Result<usize, SynthesisError> {
+        match self {
+            Self::Constant(_) => Ok(usize::MAX),
+            Self::Var(v) => v.headroom(),
+        }
+    }
+
+    /// Enforce that the `NonNativeFieldMulResultVar` reduces to `dst`, without allocating
+    /// the reduced value
+    #[tracing::instrument(target = "r1cs")]
+    pub fn reduce_into(
+        &self,
+        dst: &AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>,
+    ) -> Result<(), SynthesisError> {
+        match self {
+            Self::Constant(c) => {
+                let c = AllocatedNonNativeFieldVar::new_constant(dst.cs.clone(), c)?;
+                Reducer::<TargetField, BaseField, Opt>::enforce_equal_to_normal_form(dst, &c)
+            }
+            Self::Var(v) => v.reduce_into(dst),
+        }
+    }
+}
+
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode>
+    CondSelectGadget<BaseField>
+    for AllocatedNonNativeFieldMulResultVar<TargetField, BaseField, Opt>
+{
+    #[tracing::instrument(target = "r1cs")]
+    fn conditionally_select(
+        cond: &Boolean<BaseField>,
+        true_value: &Self,
+        false_value: &Self,
+    ) -> Result<Self, SynthesisError> {
+        let mut limbs_sel = Vec::<AllocatedFp<BaseField>>::with_capacity(true_value.limbs.len());
+
+        for (x, y) in true_value.limbs.iter().zip(&false_value.limbs) {
+            limbs_sel.push(AllocatedFp::<BaseField>::conditionally_select(cond, x, y)?);
+        }
+
+        Ok(Self {
+            cs: true_value.cs.clone(),
+            limbs: limbs_sel,
+            prod_of_num_of_additions: max(
+                true_value.prod_of_num_of_additions,
+                false_value.prod_of_num_of_additions,
+            ),
+            target_phantom: PhantomData,
+            memory_handle: ProductHandle::default(),
+        }
+        .tracked())
+    }
+}
+
+/// Selecting the unreduced products lets branching logic share a single reduction, instead of
+/// reducing both branches and selecting afterwards
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode>
+    CondSelectGadget<BaseField> for NonNativeFieldMulResultVar<TargetField, BaseField, Opt>
+{
+    #[tracing::instrument(target = "r1cs")]
+    fn conditionally_select(
+        cond: &Boolean<BaseField>,
+        true_value: &Self,
+        false_value: &Self,
+    ) -> Result<Self, SynthesisError> {
+        match (cond, true_value, false_value) {
+            (Boolean::Constant(true), _, _) => Ok(true_value.clone()),
+            (Boolean::Constant(false), _, _) => Ok(false_value.clone()),
+            (_, Self::Constant(c1), Self::Constant(c2)) if c1 == c2 => Ok(true_value.clone()),
+            _ => {
+                let cs = cond.cs();
+                let true_value = match true_value {
+                    Self::Constant(f) => {
+                        AllocatedNonNativeFieldMulResultVar::new_constant(cs.clone(), f)?
+                    }
+                    Self::Var(v) => v.clone(),
+                };
+                let false_value = match false_value {
+                    Self::Constant(f) => AllocatedNonNativeFieldMulResultVar::new_constant(cs, f)?,
+                    Self::Var(v) => v.clone(),
+                };
+                cond.select(&true_value, &false_value).map(Self::Var)
+            }
+        }
+    }
+}
+
+/// Products are compared through their difference, so that `a * b == c * d` costs a single
+/// reduction instead of one per side
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode> EqGadget<BaseField>
+    for NonNativeFieldMulResultVar<TargetField, BaseField, Opt>
+{
+    #[tracing::instrument(target = "r1cs")]
+    fn is_eq(&self, other: &Self) -> Result<Boolean<BaseField>, SynthesisError> {
+        match (self, other) {
+            (Self::Constant(c1), Self::Constant(c2)) => Ok(Boolean::Constant(c1 == c2)),
+            (Self::Constant(c), Self::Var(v)) | (Self::Var(v), Self::Constant(c)) => {
+                NonNativeFieldVar::Var(v.reduce()?).is_eq(&NonNativeFieldVar::Constant(*c))
+            }
+            (Self::Var(v1), Self::Var(v2)) => {
+                NonNativeFieldVar::Var(v1.sub(v2)?.reduce()?).is_eq(&NonNativeFieldVar::zero())
+            }
+        }
+    }
+
+    #[tracing::instrument(target = "r1cs")]
+    fn conditional_enforce_equal(
+        &self,
+        other: &Self,
+        should_enforce: &Boolean<BaseField>,
+    ) -> Result<(), SynthesisError> {
+        if let Boolean::Constant(false) = should_enforce {
+            return Ok(());
+        }
+
+        match (self, other) {
+            (Self::Constant(c1), Self::Constant(c2)) => {
+                if c1 != c2 {
+                    should_enforce.enforce_equal(&Boolean::FALSE)?;
+                }
+                Ok(())
+            }
+            (Self::Constant(c), Self::Var(v)) | (Self::Var(v), Self::Constant(c)) => v
+                .reduce()?
+                .conditional_enforce_equal_to_constant(c, should_enforce),
+            (Self::Var(v1), Self::Var(v2)) => v1
+                .sub(v2)?
+                .reduce()?
+                .conditional_enforce_equal_to_constant(&TargetField::zero(), should_enforce),
+        }
+    }
+
+    #[tracing::instrument(target = "r1cs")]
+    fn conditional_enforce_not_equal(
+        &self,
+        other: &Self,
+        should_enforce: &Boolean<BaseField>,
+    ) -> Result<(), SynthesisError> {
+        if let Boolean::Constant(false) = should_enforce {
+            return Ok(());
+        }
+
+        match (self, other) {
+            (Self::Constant(c1), Self::Constant(c2)) => {
+                if c1 == c2 {
+                    should_enforce.enforce_equal(&Boolean::FALSE)?;
+                }
+                Ok(())
+            }
+            (Self::Constant(c), Self::Var(v)) | (Self::Var(v), Self::Constant(c)) => {
+                NonNativeFieldVar::Var(v.reduce()?)
+                    .conditional_enforce_not_equal(&NonNativeFieldVar::Constant(*c), should_enforce)
+            }
+            (Self::Var(v1), Self::Var(v2)) => NonNativeFieldVar::Var(v1.sub(v2)?.reduce()?)
+                .conditional_enforce_not_equal(&NonNativeFieldVar::zero(), should_enforce),
+        }
+    }
 }
 
 impl_bounded_ops!(
-    NonNativeFieldMulResultVar<TargetField, BaseField>,
+    NonNativeFieldMulResultVar<TargetField, BaseField, Opt>,
     TargetField,
     Add,
     add,
     AddAssign,
     add_assign,
-    |this: &'a NonNativeFieldMulResultVar<TargetField, BaseField>, other: &'a NonNativeFieldMulResultVar<TargetField, BaseField>| {
-        use NonNativeFieldMulResultVar::*;
-        match (this, other) {
-            (Constant(c1), Constant(c2)) => Constant(*c1 + c2),
-            (Constant(c), Var(v)) | (Var(v), Constant(c)) => Var(v.add_constant(c).unwrap()),
-            (Var(v1), Var(v2)) => Var(v1.add(v2).unwrap()),
-        }
+    |this: &'a NonNativeFieldMulResultVar<TargetField, BaseField, Opt>, other: &'a NonNativeFieldMulResultVar<TargetField, BaseField, Opt>| {
+        this.add_or_reduce(other).unwrap()
     },
-    |this: &'a NonNativeFieldMulResultVar<TargetField, BaseField>, other: TargetField| { this + &NonNativeFieldMulResultVar::Constant(other) },
-    (TargetField: PrimeField, BaseField: PrimeField),
+    |this: &'a NonNativeFieldMulResultVar<TargetField, BaseField, Opt>, other: TargetField| { this + &NonNativeFieldMulResultVar::Constant(other) },
+    (TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode),
 );
@@ -0,0 +1,55 @@
+use crate::uint::NonNativeUintVar;
+use ark_ff::PrimeField;
+use ark_r1cs_std::boolean::Boolean;
+use ark_relations::r1cs::SynthesisError;
+
+/// A Paillier public key with the modulus `N`, whose ciphertexts are integers modulo `N^2`
+///
+/// With the generator `g = N + 1`, the encryption of `m` with the randomness `r` is
+/// `(1 + m·N) · r^N mod N^2`, so the product of two ciphertexts encrypts the sum of their
+/// plaintexts modulo `N`, and the `k`-th power of a ciphertext encrypts `k` times its
+/// plaintext. The gadgets only compute these ciphertexts: they do not check that their inputs
+/// are below `N^2` or prime to `N`, which the homomorphisms do not need.
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct PaillierPublicKeyVar<BaseField: PrimeField> {
+    /// The modulus `N`
+    pub n: NonNativeUintVar<BaseField>,
+    /// The modulus of the ciphertexts, `N^2`, computed from `n` in the circuit
+    pub n_squared: NonNativeUintVar<BaseField>,
+}
+
+impl<BaseField: PrimeField> PaillierPublicKeyVar<BaseField> {
+    /// The key of the modulus `n`, e.g., a public input of the circuit, whose square takes
+    /// twice as many limbs
+    #[tracing::instrument(target = "r1cs")]
+    pub fn new(n: NonNativeUintVar<BaseField>) -> Result<Self, SynthesisError> {
+        let n_squared = n.mul(&n)?;
+        Ok(Self { n, n_squared })
+    }
+
+    /// Compute the ciphertext of the sum of the plaintexts of `a` and `b`, `a · b mod N^2`
+    ///
+    /// This is one `mul_mod` modulo `N^2`.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn add_ciphertexts(
+        &self,
+        a: &NonNativeUintVar<BaseField>,
+        b: &NonNativeUintVar<BaseField>,
+    ) -> Result<NonNativeUintVar<BaseField>, SynthesisError> {
+        a.mul_mod(b, &self.n_squared)
+    }
+
+    /// Compute the ciphertext of `k` times the plaintext of `c`, `c^k mod N^2`, for the
+    /// little-endian bits of the scalar `k`
+    ///
+    /// This is `pow_mod` modulo `N^2`, one squaring and one multiplication per bit of `k`.
+    #[tracing::instrument(target = "r1cs", skip(k))]
+    pub fn scalar_mul_ciphertext(
+        &self,
+        c: &NonNativeUintVar<BaseField>,
+        k: &[Boolean<BaseField>],
+    ) -> Result<NonNativeUintVar<BaseField>, SynthesisError> {
+        c.pow_mod(k, &self.n_squared)
+    }
+}
@@ -1,15 +1,26 @@
-use crate::NonNativeFieldParams;
+use crate::{error::NonNativeFieldError, EqualityLayout, NonNativeFieldParams};
 use ark_ff::PrimeField;
-use ark_relations::r1cs::ConstraintSystemRef;
+use ark_r1cs_std::boolean::Boolean;
+use ark_relations::r1cs::{ConstraintSystemRef, Variable};
 use ark_std::{
     any::{Any, TypeId},
     boxed::Box,
-    cmp::min,
-    collections::BTreeMap,
+    cmp::{max, min},
+    collections::{BTreeMap, BTreeSet},
+    fmt::Debug,
+    marker::PhantomData,
+    vec::Vec,
 };
+use num_bigint::BigUint;
+use num_traits::{One, ToPrimitive, Zero};
 
 /// The type for a cache map for parameters
 pub type ParamsMap = BTreeMap<(usize, usize), NonNativeFieldParams>;
+
+/// The process-wide cache of parameters, shared by the threads synthesizing in parallel
+#[cfg(feature = "std")]
+static SHARED_PARAMS: std::sync::RwLock<ParamsMap> = std::sync::RwLock::new(BTreeMap::new());
+
 #[derive(Clone)]
 /// Statistics for hit rate of cache
 pub struct HitRate {
@@ -50,42 +61,553 @@ impl HitRate {
         }
     }
 
+    /// Obtain the numbers of hits and misses, if the statistics are activated
+    pub fn get<BaseField: PrimeField>(
+        cs: &ConstraintSystemRef<BaseField>,
+    ) -> Option<(usize, usize)> {
+        match cs {
+            ConstraintSystemRef::None => None,
+            ConstraintSystemRef::CS(v) => {
+                let cs_sys = v.borrow();
+                let big_map = cs_sys.cache_map.borrow();
+                big_map
+                    .get(&TypeId::of::<HitRate>())
+                    .and_then(|rate| rate.downcast_ref::<HitRate>())
+                    .map(|stat| (stat.hit, stat.miss))
+            }
+        }
+    }
+
+    /// Obtain the hit rate in percent, rounded down, if the statistics are activated
+    /// and there has been at least one lookup
+    pub fn percentage<BaseField: PrimeField>(cs: &ConstraintSystemRef<BaseField>) -> Option<usize> {
+        Self::get(cs).and_then(|(hit, miss)| (hit * 100).checked_div(hit + miss))
+    }
+
+    /// Write the statistics to `writer`, if they are activated
+    #[cfg(feature = "std")]
+    pub fn write<BaseField: PrimeField, W: std::io::Write>(
+        cs: &ConstraintSystemRef<BaseField>,
+        mut writer: W,
+    ) -> std::io::Result<()> {
+        if let Some((hit, miss)) = Self::get(cs) {
+            match Self::percentage(cs) {
+                Some(percentage) => writeln!(
+                    writer,
+                    "Hit: {}, Miss: {}, Hit Rate = {}%",
+                    hit, miss, percentage
+                )?,
+                None => writeln!(writer, "Hit: {}, Miss: {}", hit, miss)?,
+            }
+        }
+        Ok(())
+    }
+
     /// Print out the statistics
     #[cfg(feature = "std")]
     pub fn print<BaseField: PrimeField>(cs: &ConstraintSystemRef<BaseField>) {
+        // like `println!`, this only fails if stdout is gone
+        let _ = Self::write(cs, std::io::stdout());
+    }
+}
+
+/// A flag in the cache map that lets the reductions express their single-use intermediates,
+/// such as the carries pushed from a limb to the next one, as symbolic linear combinations
+/// instead of new witnesses
+///
+/// The constraint system inlines each of them into its only use when it is finalized, which
+/// saves one witness, one constraint, and two nonzero entries per intermediate. The limbs of
+/// the elements, which may feed many constraints, are still allocated as witnesses, so the
+/// matrix weight only decreases. The elements of the mode `DensityOpt` are optimized this way
+/// whether the flag is set or not.
+#[derive(Clone)]
+pub struct DensityOptimization;
+
+impl DensityOptimization {
+    /// Enable the optimization for a constraint system
+    pub fn enable<BaseField: PrimeField>(cs: &ConstraintSystemRef<BaseField>) {
+        if let ConstraintSystemRef::CS(v) = cs {
+            let cs_sys = v.borrow_mut();
+            let mut big_map = cs_sys.cache_map.borrow_mut();
+            big_map.insert(
+                TypeId::of::<DensityOptimization>(),
+                Box::new(DensityOptimization),
+            );
+        }
+    }
+
+    /// Check whether the optimization is enabled for a constraint system
+    pub fn is_enabled<BaseField: PrimeField>(cs: &ConstraintSystemRef<BaseField>) -> bool {
         match cs {
-            ConstraintSystemRef::None => (),
+            ConstraintSystemRef::None => false,
             ConstraintSystemRef::CS(v) => {
                 let cs_sys = v.borrow();
                 let big_map = cs_sys.cache_map.borrow();
-                let hit_rate = big_map.get(&TypeId::of::<HitRate>());
-
-                if hit_rate.is_some() {
-                    match hit_rate.unwrap().downcast_ref::<HitRate>() {
-                        Some(stat) => {
-                            let hit_rate = (*stat).clone();
-                            println!(
-                                "Hit: {}, Miss: {}, Hit Rate = {}",
-                                hit_rate.hit,
-                                hit_rate.miss,
-                                (hit_rate.hit as f64) / ((hit_rate.hit + hit_rate.miss) as f64)
-                            );
+                big_map.contains_key(&TypeId::of::<DensityOptimization>())
+            }
+        }
+    }
+}
+
+/// A cache in the cache map of the bit decompositions of normal forms, keyed by the variables
+/// of their limbs, so that decomposing the same element twice, e.g., for a scalar
+/// multiplication and for a range check, allocates the bits only once
+///
+/// The cached bits refer to the constraint system, whose memory is therefore not freed as long
+/// as the cache is enabled. Call `clear` once the synthesis is done.
+#[derive(Clone)]
+pub struct BitDecompositionCache<BaseField: PrimeField> {
+    bits: BTreeMap<Vec<Variable>, Vec<Boolean<BaseField>>>,
+}
+
+impl<BaseField: PrimeField> BitDecompositionCache<BaseField> {
+    /// Enable the cache for a constraint system
+    pub fn enable(cs: &ConstraintSystemRef<BaseField>) {
+        if let ConstraintSystemRef::CS(v) = cs {
+            let cs_sys = v.borrow_mut();
+            let mut big_map = cs_sys.cache_map.borrow_mut();
+            big_map.entry(TypeId::of::<Self>()).or_insert_with(|| {
+                Box::new(Self {
+                    bits: BTreeMap::new(),
+                })
+            });
+        }
+    }
+
+    /// Check whether the cache is enabled for a constraint system
+    pub fn is_enabled(cs: &ConstraintSystemRef<BaseField>) -> bool {
+        match cs {
+            ConstraintSystemRef::None => false,
+            ConstraintSystemRef::CS(v) => {
+                let cs_sys = v.borrow();
+                let big_map = cs_sys.cache_map.borrow();
+                big_map.contains_key(&TypeId::of::<Self>())
+            }
+        }
+    }
+
+    /// Drop the cached bits and disable the cache
+    pub fn clear(cs: &ConstraintSystemRef<BaseField>) {
+        if let ConstraintSystemRef::CS(v) = cs {
+            let cs_sys = v.borrow_mut();
+            let mut big_map = cs_sys.cache_map.borrow_mut();
+            big_map.remove(&TypeId::of::<Self>());
+        }
+    }
+
+    /// Look up the bits of the normal form with the limbs `key`
+    pub(crate) fn load(
+        cs: &ConstraintSystemRef<BaseField>,
+        key: &[Variable],
+    ) -> Option<Vec<Boolean<BaseField>>> {
+        match cs {
+            ConstraintSystemRef::None => None,
+            ConstraintSystemRef::CS(v) => {
+                let cs_sys = v.borrow();
+                let big_map = cs_sys.cache_map.borrow();
+                big_map
+                    .get(&TypeId::of::<Self>())
+                    .and_then(|cache| cache.downcast_ref::<Self>())
+                    .and_then(|cache| cache.bits.get(key))
+                    .cloned()
+            }
+        }
+    }
+
+    /// Remember `bits` as the decomposition of the normal form with the limbs `key`, if the
+    /// cache is enabled
+    pub(crate) fn store(
+        cs: &ConstraintSystemRef<BaseField>,
+        key: Vec<Variable>,
+        bits: &[Boolean<BaseField>],
+    ) {
+        if let ConstraintSystemRef::CS(v) = cs {
+            let cs_sys = v.borrow();
+            let mut big_map = cs_sys.cache_map.borrow_mut();
+            if let Some(cache) = big_map
+                .get_mut(&TypeId::of::<Self>())
+                .and_then(|cache| cache.downcast_mut::<Self>())
+            {
+                cache.bits.insert(key, bits.to_vec());
+            }
+        }
+    }
+}
+
+/// The limbs of the zero and the one of a constraint system, which are allocated once and
+/// shared by all the elements that need them as allocated constants
+///
+/// Only the limb variables are kept, since the elements refer back to the constraint system.
+/// They are keyed by the parameters they were allocated with, so that they are not shared
+/// across a change of the parameters.
+pub(crate) struct IdentityCache<TargetField: PrimeField, BaseField: PrimeField> {
+    params: NonNativeFieldParams,
+    zero: Option<Vec<Variable>>,
+    one: Option<Vec<Variable>>,
+    field_phantom: PhantomData<(TargetField, BaseField)>,
+}
+
+impl<TargetField: PrimeField, BaseField: PrimeField> IdentityCache<TargetField, BaseField> {
+    /// Look up the limbs of the one if `is_one` is set, or of the zero otherwise, allocated
+    /// with `params`
+    pub(crate) fn load(
+        cs: &ConstraintSystemRef<BaseField>,
+        params: &NonNativeFieldParams,
+        is_one: bool,
+    ) -> Option<Vec<Variable>> {
+        match cs {
+            ConstraintSystemRef::None => None,
+            ConstraintSystemRef::CS(v) => {
+                let cs_sys = v.borrow();
+                let big_map = cs_sys.cache_map.borrow();
+                big_map
+                    .get(&TypeId::of::<Self>())
+                    .and_then(|cache| cache.downcast_ref::<Self>())
+                    .filter(|cache| cache.params.has_same_limbs(params))
+                    .and_then(|cache| {
+                        if is_one {
+                            cache.one.clone()
+                        } else {
+                            cache.zero.clone()
                         }
-                        None => (),
-                    }
+                    })
+            }
+        }
+    }
+
+    /// Remember `limbs` as those of the one if `is_one` is set, or of the zero otherwise,
+    /// allocated with `params`, forgetting the limbs allocated with other parameters
+    pub(crate) fn store(
+        cs: &ConstraintSystemRef<BaseField>,
+        params: &NonNativeFieldParams,
+        is_one: bool,
+        limbs: Vec<Variable>,
+    ) {
+        if let ConstraintSystemRef::CS(v) = cs {
+            let cs_sys = v.borrow();
+            let mut big_map = cs_sys.cache_map.borrow_mut();
+            if let Some(cache) = big_map
+                .entry(TypeId::of::<Self>())
+                .or_insert_with(|| {
+                    Box::new(Self {
+                        params: params.clone(),
+                        zero: None,
+                        one: None,
+                        field_phantom: PhantomData,
+                    })
+                })
+                .downcast_mut::<Self>()
+            {
+                if !cache.params.has_same_limbs(params) {
+                    cache.params = params.clone();
+                    cache.zero = None;
+                    cache.one = None;
+                }
+                if is_one {
+                    cache.one = Some(limbs);
+                } else {
+                    cache.zero = Some(limbs);
                 }
             }
         }
     }
 }
 
+/// The constants that elements have been enforced to equal, keyed by the variables of their
+/// limbs, so that the operations can special-case such elements as they do constants
+///
+/// Replacing an element by its constant is sound because the equality is enforced, and an
+/// assignment with another value does not satisfy the constraint system anyway.
+pub(crate) struct KnownConstants<TargetField: PrimeField, BaseField: PrimeField> {
+    values: BTreeMap<Vec<Variable>, TargetField>,
+    base_phantom: PhantomData<BaseField>,
+}
+
+impl<TargetField: PrimeField, BaseField: PrimeField> KnownConstants<TargetField, BaseField> {
+    /// Look up the constant that the element with the limbs `key` is enforced to equal
+    pub(crate) fn load(
+        cs: &ConstraintSystemRef<BaseField>,
+        key: &[Variable],
+    ) -> Option<TargetField> {
+        match cs {
+            ConstraintSystemRef::None => None,
+            ConstraintSystemRef::CS(v) => {
+                let cs_sys = v.borrow();
+                let big_map = cs_sys.cache_map.borrow();
+                big_map
+                    .get(&TypeId::of::<Self>())
+                    .and_then(|cache| cache.downcast_ref::<Self>())
+                    .and_then(|cache| cache.values.get(key))
+                    .copied()
+            }
+        }
+    }
+
+    /// Remember that the element with the limbs `key` is enforced to equal `value`
+    pub(crate) fn store(
+        cs: &ConstraintSystemRef<BaseField>,
+        key: Vec<Variable>,
+        value: TargetField,
+    ) {
+        if let ConstraintSystemRef::CS(v) = cs {
+            let cs_sys = v.borrow();
+            let mut big_map = cs_sys.cache_map.borrow_mut();
+            if let Some(cache) = big_map
+                .entry(TypeId::of::<Self>())
+                .or_insert_with(|| {
+                    Box::new(Self {
+                        values: BTreeMap::new(),
+                        base_phantom: PhantomData,
+                    })
+                })
+                .downcast_mut::<Self>()
+            {
+                cache.values.insert(key, value);
+            }
+        }
+    }
+}
+
+/// An element or unreduced product whose limbs grew beyond the fraction of the largest size
+/// set in `OverflowWarnings`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OverflowWarning {
+    /// The operation that produced the element
+    pub operation: &'static str,
+    /// The name of the innermost entered `tracing` span, if any
+    ///
+    /// The namespaces of `ns!` are `tracing` spans, which are only entered with a subscriber, so
+    /// this is `None` without one. `num_constraints` locates the warning in either case.
+    pub span: Option<&'static str>,
+    /// The number of constraints of the constraint system when the warning was emitted, i.e.,
+    /// the index of the first constraint that the next operations emit
+    pub num_constraints: usize,
+    /// The number of bits of the largest limb
+    pub bits: usize,
+    /// The largest number of bits of a limb that the reductions can soundly handle
+    pub max_bits: usize,
+}
+
+/// A hook in the cache map that warns about elements and unreduced products whose limbs grow
+/// beyond a fraction of the largest size that the reductions can soundly handle
+///
+/// Such elements force a reduction soon, and such products make the next additions fail, so
+/// the warnings point at the hot spots of reduction pressure. Each warning is emitted as a
+/// `tracing` event, which subscribers print within the entered spans, i.e., the namespaces,
+/// and passed to the callback, if any.
+#[derive(Clone, Copy, Debug)]
+pub struct OverflowWarnings {
+    /// The fraction of the largest size, in percent, beyond which the limbs are reported
+    pub threshold_percent: usize,
+    /// A function called with each warning
+    pub callback: Option<fn(&OverflowWarning)>,
+}
+
+impl OverflowWarnings {
+    /// Enable the warnings for a constraint system
+    pub fn enable<BaseField: PrimeField>(&self, cs: &ConstraintSystemRef<BaseField>) {
+        if let ConstraintSystemRef::CS(v) = cs {
+            let cs_sys = v.borrow_mut();
+            let mut big_map = cs_sys.cache_map.borrow_mut();
+            big_map.insert(TypeId::of::<OverflowWarnings>(), Box::new(*self));
+        }
+    }
+
+    /// Disable the warnings for a constraint system
+    pub fn disable<BaseField: PrimeField>(cs: &ConstraintSystemRef<BaseField>) {
+        if let ConstraintSystemRef::CS(v) = cs {
+            let cs_sys = v.borrow_mut();
+            let mut big_map = cs_sys.cache_map.borrow_mut();
+            big_map.remove(&TypeId::of::<OverflowWarnings>());
+        }
+    }
+
+    /// Obtain the warnings enabled for a constraint system, if any
+    pub fn get<BaseField: PrimeField>(cs: &ConstraintSystemRef<BaseField>) -> Option<Self> {
+        match cs {
+            ConstraintSystemRef::None => None,
+            ConstraintSystemRef::CS(v) => {
+                let cs_sys = v.borrow();
+                let big_map = cs_sys.cache_map.borrow();
+                big_map
+                    .get(&TypeId::of::<OverflowWarnings>())
+                    .and_then(|warnings| warnings.downcast_ref::<OverflowWarnings>())
+                    .copied()
+            }
+        }
+    }
+
+    /// Warn about the result of `operation` in `cs` if its largest limb has more than the
+    /// threshold of `max_bits` bits
+    ///
+    /// The callers obtain the warnings with `get` first, so that they compute the sizes of the
+    /// limbs only when the warnings are enabled.
+    pub(crate) fn check<BaseField: PrimeField>(
+        &self,
+        cs: &ConstraintSystemRef<BaseField>,
+        operation: &'static str,
+        bits: usize,
+        max_bits: usize,
+    ) {
+        if bits * 100 > self.threshold_percent * max_bits {
+            tracing::warn!(
+                target: "r1cs",
+                operation,
+                bits,
+                max_bits,
+                "nonnative limbs close to the largest size that can be reduced"
+            );
+
+            if let Some(callback) = self.callback {
+                callback(&OverflowWarning {
+                    operation,
+                    span: tracing::Span::current()
+                        .metadata()
+                        .map(|metadata| metadata.name()),
+                    num_constraints: cs.num_constraints(),
+                    bits,
+                    max_bits,
+                });
+            }
+        }
+    }
+}
+
+/// The measure of the constraint system that the gadgets optimize for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OptimizationGoal {
+    /// The number of constraints
+    Constraints,
+    /// The weight of the matrices, as with `DensityOptimization`
+    Weight,
+}
+
+/// The optimization mode of the nonnative field variables, given as their type parameter `Opt`
+///
+/// Elements of different modes have different types, so an element is converted with
+/// `to_mode` before it is mixed with those of another mode in an operation, which generates no
+/// constraints. The mode is only read by the reductions, to decide at compile time whether
+/// their intermediates are allocated or kept as linear combinations. The parameters and the
+/// caches of a constraint system are shared by the elements of both modes. The gadgets built
+/// on the elements, such as the curves and the accumulator, take those of the default mode.
+pub trait OptimizationMode:
+    'static + Clone + Copy + Debug + Default + PartialEq + Eq + Send + Sync
+{
+    /// The measure that the reductions of the elements of this mode optimize for
+    const GOAL: OptimizationGoal;
+}
+
+/// The default optimization mode, which optimizes for the number of constraints unless
+/// `DensityOptimization` is enabled for the constraint system, e.g., by a `NonNativeConfig`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ConstraintsOpt;
+
+impl OptimizationMode for ConstraintsOpt {
+    const GOAL: OptimizationGoal = OptimizationGoal::Constraints;
+}
+
+/// The optimization mode for the weight of the matrices, as with `DensityOptimization`, which
+/// applies whatever the configuration of the constraint system
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DensityOpt;
+
+impl OptimizationMode for DensityOpt {
+    const GOAL: OptimizationGoal = OptimizationGoal::Weight;
+}
+
+/// The configuration of the gadgets for a constraint system
+///
+/// `install` stores it in the cache map, where the gadgets consult it, and also sets the
+/// flags of `DensityOptimization` and `BitDecompositionCache` accordingly. The reductions are
+/// steered by two options only: `optimization_goal` selects whether their limbs are allocated
+/// or kept as linear combinations, and `max_surfeit` how many additions they let through.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NonNativeConfig {
+    /// The measure to optimize for
+    pub optimization_goal: OptimizationGoal,
+    /// The largest surfeit, i.e., `ceil(log2(num_of_additions + 1))`, that an element may
+    /// reach through additions before it is reduced, on top of the bound given by the
+    /// parameters. `None` lets the elements grow as long as the parameters allow.
+    pub max_surfeit: Option<usize>,
+    /// Whether to cache the bit decompositions of the normal forms
+    pub cache_bit_decompositions: bool,
+}
+
+impl Default for NonNativeConfig {
+    fn default() -> Self {
+        Self {
+            optimization_goal: OptimizationGoal::Constraints,
+            max_surfeit: None,
+            cache_bit_decompositions: false,
+        }
+    }
+}
+
+impl NonNativeConfig {
+    /// Install the configuration for a constraint system
+    pub fn install<BaseField: PrimeField>(&self, cs: &ConstraintSystemRef<BaseField>) {
+        if let ConstraintSystemRef::CS(v) = cs {
+            {
+                let cs_sys = v.borrow_mut();
+                let mut big_map = cs_sys.cache_map.borrow_mut();
+                big_map.insert(TypeId::of::<NonNativeConfig>(), Box::new(self.clone()));
+                if self.optimization_goal == OptimizationGoal::Constraints {
+                    big_map.remove(&TypeId::of::<DensityOptimization>());
+                }
+            }
+
+            if self.optimization_goal == OptimizationGoal::Weight {
+                DensityOptimization::enable(cs);
+            }
+            if self.cache_bit_decompositions {
+                BitDecompositionCache::enable(cs);
+            } else {
+                BitDecompositionCache::clear(cs);
+            }
+        }
+    }
+
+    /// Obtain the configuration of a constraint system, which reflects the flags set
+    /// without a configuration if none has been installed
+    pub fn get<BaseField: PrimeField>(cs: &ConstraintSystemRef<BaseField>) -> Self {
+        Self {
+            optimization_goal: if DensityOptimization::is_enabled(cs) {
+                OptimizationGoal::Weight
+            } else {
+                OptimizationGoal::Constraints
+            },
+            max_surfeit: Self::max_surfeit(cs),
+            cache_bit_decompositions: BitDecompositionCache::is_enabled(cs),
+        }
+    }
+
+    /// Obtain the `max_surfeit` of the configuration installed for a constraint system
+    ///
+    /// This is what the additions consult, so it only looks the configuration up, without
+    /// assembling the rest of it.
+    pub(crate) fn max_surfeit<BaseField: PrimeField>(
+        cs: &ConstraintSystemRef<BaseField>,
+    ) -> Option<usize> {
+        match cs {
+            ConstraintSystemRef::None => None,
+            ConstraintSystemRef::CS(v) => {
+                let cs_sys = v.borrow();
+                let big_map = cs_sys.cache_map.borrow();
+                big_map
+                    .get(&TypeId::of::<NonNativeConfig>())
+                    .and_then(|config| config.downcast_ref::<NonNativeConfig>())
+                    .and_then(|config| config.max_surfeit)
+            }
+        }
+    }
+}
+
 /// Obtain the parameters from a `ConstraintSystem`'s cache or generate a new one
-#[must_use]
 pub fn get_params<TargetField: PrimeField, BaseField: PrimeField>(
     cs: &ConstraintSystemRef<BaseField>,
-) -> NonNativeFieldParams {
+) -> Result<NonNativeFieldParams, NonNativeFieldError> {
     match cs {
-        ConstraintSystemRef::None => gen_params::<TargetField, BaseField>(),
+        ConstraintSystemRef::None => get_shared_params::<TargetField, BaseField>(),
         ConstraintSystemRef::CS(v) => {
             let cs_sys = v.borrow_mut();
             let mut big_map = cs_sys.cache_map.borrow_mut();
@@ -97,9 +619,9 @@ pub fn get_params<TargetField: PrimeField, BaseField: PrimeField>(
                     if let Some(params) = params {
                         let params = params.clone();
                         HitRate::update(&mut *big_map, true);
-                        params
+                        Ok(params)
                     } else {
-                        let params = gen_params::<TargetField, BaseField>();
+                        let params = get_shared_params::<TargetField, BaseField>()?;
 
                         let mut small_map = (*map).clone();
                         small_map.insert(
@@ -109,10 +631,10 @@ pub fn get_params<TargetField: PrimeField, BaseField: PrimeField>(
                         big_map.insert(TypeId::of::<ParamsMap>(), Box::new(small_map));
 
                         HitRate::update(&mut *big_map, false);
-                        params
+                        Ok(params)
                     }
                 } else {
-                    let params = gen_params::<TargetField, BaseField>();
+                    let params = get_shared_params::<TargetField, BaseField>()?;
 
                     let mut small_map = ParamsMap::new();
                     small_map.insert(
@@ -122,10 +644,10 @@ pub fn get_params<TargetField: PrimeField, BaseField: PrimeField>(
 
                     big_map.insert(TypeId::of::<ParamsMap>(), Box::new(small_map));
                     HitRate::update(&mut *big_map, false);
-                    params
+                    Ok(params)
                 }
             } else {
-                let params = gen_params::<TargetField, BaseField>();
+                let params = get_shared_params::<TargetField, BaseField>()?;
 
                 let mut small_map = ParamsMap::new();
                 small_map.insert(
@@ -135,22 +657,288 @@ pub fn get_params<TargetField: PrimeField, BaseField: PrimeField>(
 
                 big_map.insert(TypeId::of::<ParamsMap>(), Box::new(small_map));
                 HitRate::update(&mut *big_map, false);
-                params
+                Ok(params)
             }
         }
     }
 }
 
+/// Check that `params` suit a `target_bits`-bit target field in a `base_bits`-bit base field
+/// with at least `min_limbs` limbs, and with at least one limb in the layout of the equality
+/// checks
+pub(crate) fn validate_params(
+    params: &NonNativeFieldParams,
+    target_bits: usize,
+    base_bits: usize,
+    min_limbs: usize,
+) -> Result<(), NonNativeFieldError> {
+    let reason = if params.num_limbs < min_limbs {
+        Some("too few limbs")
+    } else if params.bits_per_top_limb > params.bits_per_non_top_limb {
+        Some("the top limb is larger than the other limbs")
+    } else if params.bits_per_top_limb
+        + params.num_limbs.saturating_sub(1) * params.bits_per_non_top_limb
+        < target_bits
+    {
+        Some("the limbs do not cover the target field")
+    } else if 2 * (params.bits_per_non_top_limb + 5) >= base_bits {
+        Some("the limbs are too large for the reductions")
+    } else {
+        None
+    };
+
+    match (reason, params.equality_layout) {
+        (Some(reason), _) => Err(NonNativeFieldError::InvalidParameters {
+            params: params.clone(),
+            reason,
+        }),
+        // the equality checks only need limbs that cover the target field
+        (None, Some(layout)) => validate_params(&layout.into(), target_bits, base_bits, 1),
+        (None, None) => Ok(()),
+    }
+}
+
+/// Obtain the parameters that a `ConstraintSystem`'s cache holds for the sizes of the two
+/// fields, if any, without searching for them
+pub fn get_cached_params<TargetField: PrimeField, BaseField: PrimeField>(
+    cs: &ConstraintSystemRef<BaseField>,
+) -> Option<NonNativeFieldParams> {
+    match cs {
+        ConstraintSystemRef::None => None,
+        ConstraintSystemRef::CS(v) => {
+            let cs_sys = v.borrow();
+            let big_map = cs_sys.cache_map.borrow();
+            big_map
+                .get(&TypeId::of::<ParamsMap>())
+                .and_then(|map| map.downcast_ref::<ParamsMap>())
+                .and_then(|map| {
+                    map.get(&(BaseField::size_in_bits(), TargetField::size_in_bits()))
+                        .cloned()
+                })
+        }
+    }
+}
+
+/// The sizes of the fields, keyed like `ParamsMap`, for which elements have been allocated in
+/// a `ConstraintSystem`, whose parameters can thus no longer change
+#[derive(Default)]
+struct ParamsInUse(BTreeSet<(usize, usize)>);
+
+/// Record that an element has been allocated with the parameters of `cs` for the sizes of the
+/// two fields
+pub(crate) fn mark_params_in_use<TargetField: PrimeField, BaseField: PrimeField>(
+    cs: &ConstraintSystemRef<BaseField>,
+) {
+    if let ConstraintSystemRef::CS(v) = cs {
+        let cs_sys = v.borrow();
+        let mut big_map = cs_sys.cache_map.borrow_mut();
+        if let Some(in_use) = big_map
+            .entry(TypeId::of::<ParamsInUse>())
+            .or_insert_with(|| Box::new(ParamsInUse::default()))
+            .downcast_mut::<ParamsInUse>()
+        {
+            in_use
+                .0
+                .insert((BaseField::size_in_bits(), TargetField::size_in_bits()));
+        }
+    }
+}
+
+/// Store `params` in a `ConstraintSystem`'s cache, so that the gadgets use them instead of
+/// the searched parameters for the sizes of the two fields
+///
+/// The cache is keyed by the sizes of the fields, so the parameters apply to every target
+/// field of the same size. Return `NonNativeFieldError::InvalidParameters` if the limbs do not
+/// cover the target field, the top limb is larger than the others, or the limbs are too large
+/// for the reducer.
+///
+/// **The parameters must be set before the first element is allocated.** Once an element has
+/// been allocated with the parameters for the sizes of the two fields, its limbs are only
+/// meaningful in their layout, so replacing them with different parameters returns
+/// `NonNativeFieldError::ParametersInUse`. Parameters with the same limbs as those in use can
+/// still be set, which only replaces the layout of the equality checks.
+pub fn set_params<TargetField: PrimeField, BaseField: PrimeField>(
+    cs: &ConstraintSystemRef<BaseField>,
+    params: NonNativeFieldParams,
+) -> Result<(), NonNativeFieldError> {
+    validate_params(
+        &params,
+        TargetField::size_in_bits(),
+        BaseField::size_in_bits(),
+        2,
+    )?;
+
+    if let ConstraintSystemRef::CS(v) = cs {
+        let key = (BaseField::size_in_bits(), TargetField::size_in_bits());
+        let cs_sys = v.borrow();
+        let mut big_map = cs_sys.cache_map.borrow_mut();
+
+        let in_use = big_map
+            .get(&TypeId::of::<ParamsInUse>())
+            .and_then(|in_use| in_use.downcast_ref::<ParamsInUse>())
+            .map_or(false, |in_use| in_use.0.contains(&key));
+
+        if let Some(small_map) = big_map
+            .entry(TypeId::of::<ParamsMap>())
+            .or_insert_with(|| Box::new(ParamsMap::new()))
+            .downcast_mut::<ParamsMap>()
+        {
+            match small_map.get(&key) {
+                Some(current) if in_use && !current.has_same_limbs(&params) => {
+                    return Err(NonNativeFieldError::ParametersInUse {
+                        current: current.clone(),
+                        requested: params,
+                    });
+                }
+                _ => {
+                    small_map.insert(key, params);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Set the layout of the equality checks in the parameters of a `ConstraintSystem`, which then
+/// compare the limbs of both sides in `layout` instead of their normal forms
+///
+/// The products keep using the limbs of the parameters, so a finer layout that suits the range
+/// checks better, e.g., limbs whose sizes are multiples of the bits of a lookup table, can be
+/// used for the equality checks alone, even after elements have been allocated. Return
+/// `NonNativeFieldError::InvalidParameters` if the limbs do not cover the target field or are
+/// too large for the base field.
+pub fn set_equality_params<TargetField: PrimeField, BaseField: PrimeField>(
+    cs: &ConstraintSystemRef<BaseField>,
+    layout: EqualityLayout,
+) -> Result<(), NonNativeFieldError> {
+    let mut params = get_params::<TargetField, BaseField>(cs)?;
+    params.equality_layout = Some(layout);
+    set_params::<TargetField, BaseField>(cs, params)
+}
+
+/// Obtain the layout of the equality checks in the parameters of a `ConstraintSystem`, if they
+/// have one
+pub fn get_equality_params<TargetField: PrimeField, BaseField: PrimeField>(
+    cs: &ConstraintSystemRef<BaseField>,
+) -> Option<EqualityLayout> {
+    get_params::<TargetField, BaseField>(cs)
+        .ok()
+        .and_then(|params| params.equality_layout)
+}
+
+/// Obtain the parameters from the process-wide cache or generate a new one
+///
+/// Lookups only take a shared lock, so that threads synthesizing different constraint systems
+/// do not contend once the parameters are cached. Without `std`, the parameters are generated.
+pub fn get_shared_params<TargetField: PrimeField, BaseField: PrimeField>(
+) -> Result<NonNativeFieldParams, NonNativeFieldError> {
+    #[cfg(feature = "std")]
+    {
+        let key = (BaseField::size_in_bits(), TargetField::size_in_bits());
+
+        if let Ok(map) = SHARED_PARAMS.read() {
+            if let Some(params) = map.get(&key) {
+                return Ok(params.clone());
+            }
+        }
+
+        let params = gen_params::<TargetField, BaseField>()?;
+        if let Ok(mut map) = SHARED_PARAMS.write() {
+            map.insert(key, params.clone());
+        }
+        Ok(params)
+    }
+
+    #[cfg(not(feature = "std"))]
+    gen_params::<TargetField, BaseField>()
+}
+
 /// Generate the new params
-#[must_use]
-pub fn gen_params<TargetField: PrimeField, BaseField: PrimeField>() -> NonNativeFieldParams {
+///
+/// Return `NonNativeFieldError::NoParameters` if no suitable parameters exist for the two
+/// fields.
+pub fn gen_params<TargetField: PrimeField, BaseField: PrimeField>(
+) -> Result<NonNativeFieldParams, NonNativeFieldError> {
+    let mut problem = ParamsSearching::new(BaseField::size_in_bits(), TargetField::size_in_bits());
+    problem.solve();
+    problem.params()
+}
+
+/// Generate the parameters that minimize the predicted constraints of `workload`
+///
+/// Return `NonNativeFieldError::NoParameters` if no suitable parameters exist for the two
+/// fields.
+pub fn gen_params_for_workload<TargetField: PrimeField, BaseField: PrimeField>(
+    workload: &WorkloadProfile,
+) -> Result<NonNativeFieldParams, NonNativeFieldError> {
+    let mut problem = ParamsSearching::new(BaseField::size_in_bits(), TargetField::size_in_bits());
+    problem.solve_for_workload(workload);
+    problem.params()
+}
+
+/// Generate the parameters with the layout of the equality checks that is predicted to be the
+/// cheapest with range checks of `lookup_bits`-bit lookups, or of Boolean constraints if
+/// `None`, which is no layout if comparing the normal forms is the cheapest
+///
+/// Return `NonNativeFieldError::NoParameters` if no suitable parameters exist for the two
+/// fields.
+pub fn gen_equality_params<TargetField: PrimeField, BaseField: PrimeField>(
+    lookup_bits: Option<usize>,
+) -> Result<NonNativeFieldParams, NonNativeFieldError> {
     let mut problem = ParamsSearching::new(BaseField::size_in_bits(), TargetField::size_in_bits());
     problem.solve();
+    let mut params = problem.params()?;
+    params.equality_layout = problem.solve_equality_layout(lookup_bits);
+    Ok(params)
+}
+
+/// The expected mix of the operations of a circuit, as relative weights
+///
+/// The default is a workload of multiplications only, which `ParamsSearching::new` starts
+/// with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WorkloadProfile {
+    /// The weight of the additions and subtractions
+    pub additions: usize,
+    /// The weight of the multiplications
+    pub multiplications: usize,
+    /// The weight of the equality checks
+    pub equalities: usize,
+}
+
+impl Default for WorkloadProfile {
+    fn default() -> Self {
+        Self {
+            additions: 0,
+            multiplications: 1,
+            equalities: 0,
+        }
+    }
+}
+
+/// The constraints that `ParamsSearching::predicted_costs` predicts for the operations of a
+/// workload, attributed to the operations as in a `ConstraintReport`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PredictedCosts {
+    /// The constraints of the normal forms that the equality checks allocate
+    pub allocations: usize,
+    /// The constraints of the products of the limbs
+    pub multiplications: usize,
+    /// The constraints of the reductions after the additions, before and after the
+    /// multiplications, and before the equality checks
+    pub reductions: usize,
+    /// The constraints of the comparisons of the limbs of the equality checks
+    pub equalities: usize,
+}
 
-    NonNativeFieldParams {
-        num_limbs: problem.num_of_limbs,
-        bits_per_top_limb: problem.top_limb_size.unwrap(),
-        bits_per_non_top_limb: problem.non_top_limb_size.unwrap(),
+impl PredictedCosts {
+    /// The constraints of all the operations
+    pub fn total(&self) -> usize {
+        self.allocations
+            .saturating_add(self.multiplications)
+            .saturating_add(self.reductions)
+            .saturating_add(self.equalities)
     }
 }
 
@@ -172,6 +960,10 @@ pub struct ParamsSearching {
     pub top_limb_size: Option<usize>,
     /// Size of a non-top limb
     pub non_top_limb_size: Option<usize>,
+
+    // Objective
+    /// The workload whose predicted constraints select the sizes of the limbs
+    pub workload: WorkloadProfile,
 }
 
 impl ParamsSearching {
@@ -185,16 +977,36 @@ impl ParamsSearching {
             num_of_limbs: 2,
             top_limb_size: None,
             non_top_limb_size: None,
+            workload: WorkloadProfile::default(),
+        }
+    }
+
+    /// Obtain the parameters of the solution, or `NonNativeFieldError::NoParameters` if there
+    /// is none
+    pub fn params(&self) -> Result<NonNativeFieldParams, NonNativeFieldError> {
+        match (self.top_limb_size, self.non_top_limb_size) {
+            (Some(bits_per_top_limb), Some(bits_per_non_top_limb)) => Ok(NonNativeFieldParams {
+                num_limbs: self.num_of_limbs,
+                bits_per_top_limb,
+                bits_per_non_top_limb,
+                equality_layout: None,
+            }),
+            _ => Err(NonNativeFieldError::NoParameters {
+                target_bits: self.target_field_prime_bit_length,
+                base_bits: self.base_field_prime_length,
+            }),
         }
     }
 
     /// Solve the search problem
+    ///
+    /// This takes the first number of limbs, from two upwards, for which some layout works, and
+    /// the layout of them with the fewest predicted constraints of `self.workload`.
     pub fn solve(&mut self) {
         loop {
             let Self {
                 base_field_prime_length,
                 target_field_prime_bit_length,
-                num_of_additions_after_mul,
                 num_of_limbs,
                 ..
             } = self.clone();
@@ -213,8 +1025,11 @@ impl ParamsSearching {
                     if (ark_std::log2(num_of_limbs * min_overhead * min_overhead) as usize) + 3
                         >= base_field_prime_length
                     {
-                        #[cfg(feature = "std")]
-                        dbg!(format!("The program has tested up to {} limbs; at this point, we can conclude that no suitable parameters exist", num_of_limbs));
+                        tracing::debug!(
+                            target: "r1cs",
+                            "The program has tested up to {} limbs; at this point, we can conclude that no suitable parameters exist",
+                            num_of_limbs
+                        );
                         self.top_limb_size = None;
                         self.non_top_limb_size = None;
                         return;
@@ -222,20 +1037,215 @@ impl ParamsSearching {
                 }
             }
 
-            let top_limb = 1 + (num_of_additions_after_mul + 1) * (num_of_additions_after_mul + 1);
-            let log_top_limb = ark_std::log2(top_limb) as usize;
-            let log_sub_top_limb = ark_std::log2(top_limb * 2) as usize;
-            let log_other_limbs_upper_bound = ark_std::log2(top_limb * num_of_limbs) as usize;
+            if self.solve_with_num_of_limbs(num_of_limbs).is_some() {
+                return;
+            }
+            self.num_of_limbs += 1;
+        }
+    }
+
+    /// Solve the search problem for `workload`
+    ///
+    /// This sets `self.workload`, and starting from the solution of `solve`, also tries up to
+    /// twice as many limbs, and keeps the layout with the fewest `predicted_constraints`.
+    /// Narrower limbs leave more room for the additions, which addition-heavy workloads such
+    /// as folding profit from.
+    pub fn solve_for_workload(&mut self, workload: &WorkloadProfile) {
+        self.workload = *workload;
+        self.solve();
+        let min_num_of_limbs = self.num_of_limbs;
+        let mut best = match self.predicted_constraints(workload) {
+            Some(cost) => (cost, self.clone()),
+            None => return,
+        };
+
+        for num_of_limbs in (min_num_of_limbs + 1)..=(2 * min_num_of_limbs) {
+            if self.solve_with_num_of_limbs(num_of_limbs).is_none() {
+                continue;
+            }
+            if let Some(cost) = self.predicted_constraints(workload) {
+                if cost < best.0 {
+                    best = (cost, self.clone());
+                }
+            }
+        }
+
+        *self = best.1;
+    }
+
+    /// Predict the constraints of `workload`, with the weights of its operations as their
+    /// numbers, for the current layout, or `None` if there is none
+    ///
+    /// This is the total of `predicted_costs`.
+    pub fn predicted_constraints(&self, workload: &WorkloadProfile) -> Option<usize> {
+        self.predicted_costs(workload).map(|costs| costs.total())
+    }
+
+    /// Predict the constraints of the operations of `workload`, with the weights of its
+    /// operations as their numbers, for the current layout, or `None` if there is none
+    ///
+    /// The model follows the bit decompositions and the constraints of the gadgets, with range
+    /// checks of Boolean constraints, for a circuit in which every operand of the
+    /// multiplications and the equality checks is a sum of products, with the additions
+    /// spread evenly over the operands. The additions raise the surfeit of the operands, which
+    /// implies the reductions:
+    /// - a `post_add_reduce` whenever the surfeit exceeds what `can_safely_push` allows, which
+    ///   decomposes all the limbs;
+    /// - a `pre_mul_reduce` of one or both operands of a multiplication if the unreduced limbs
+    ///   of the product would exceed the base field, which pushes an operand to the top;
+    /// - the reduction of the product, whose limbs grow with the surfeit;
+    /// - the reduction of both sides of an equality check into their normal forms.
+    ///
+    /// The folding of the product limbs for pseudo-Mersenne moduli is not modeled.
+    pub fn predicted_costs(&self, workload: &WorkloadProfile) -> Option<PredictedCosts> {
+        let model = self.cost_model()?;
+        let num_of_limbs = self.num_of_limbs;
+
+        let num_of_operands = 2 * (workload.multiplications + workload.equalities);
+        let additions_per_operand = BigUint::from(workload.additions / max(num_of_operands, 1));
+        let additions = model.product_additions(&additions_per_operand)?;
+
+        let (sum_reductions, operand) = model.sum(&additions, &additions_per_operand);
+        let (pre_mul_reductions, prod) = model.pre_mul_reduce(&operand, &operand)?;
+        let (product_reduction, _) = model.reduce_product(&prod);
+
+        Some(PredictedCosts {
+            allocations: 2 * workload.equalities * model.normal_form_allocation(None),
+            multiplications: workload.multiplications * (2 * num_of_limbs - 1),
+            reductions: num_of_operands
+                .saturating_mul(sum_reductions)
+                .saturating_add(workload.multiplications * (pre_mul_reductions + product_reduction))
+                .saturating_add(
+                    2 * workload.equalities * model.enforce_normal_form(&operand, None),
+                ),
+            equalities: workload.equalities * num_of_limbs,
+        })
+    }
+
+    /// Search for a layout of the equality checks that is predicted to be cheaper than
+    /// comparing the normal forms in the current layout, with range checks of `lookup_bits`-bit
+    /// lookups, or of Boolean constraints if `None`
+    ///
+    /// The candidates have limbs of multiples of `lookup_bits` bits, which the lookups cover
+    /// without the extra lookup of a partial chunk, or of any size with Boolean constraints.
+    pub fn solve_equality_layout(&self, lookup_bits: Option<usize>) -> Option<EqualityLayout> {
+        let step = match lookup_bits {
+            Some(lookup_bits) if lookup_bits > 0 => lookup_bits,
+            _ => 1,
+        };
+
+        let mut best = (
+            self.predicted_equality_constraints(None, lookup_bits)?,
+            None,
+        );
+        let mut limb_size = step;
+        while 2 * (limb_size + 5) < self.base_field_prime_length {
+            let num_limbs = (self.target_field_prime_bit_length + limb_size - 1) / limb_size;
+            let layout = EqualityLayout {
+                num_limbs,
+                bits_per_top_limb: self.target_field_prime_bit_length - (num_limbs - 1) * limb_size,
+                bits_per_non_top_limb: limb_size,
+            };
+            if let Some(cost) = self.predicted_equality_constraints(Some(&layout), lookup_bits) {
+                if cost < best.0 {
+                    best = (cost, Some(layout));
+                }
+            }
+            limb_size += step;
+        }
+
+        best.1
+    }
+
+    /// Predict the constraints of an equality check of two products of products in the
+    /// current layout, comparing their limbs in `layout`, or their normal forms if `None`,
+    /// with range checks of `lookup_bits`-bit lookups, or of Boolean constraints if `None`
+    ///
+    /// Without a layout, both sides are allocated in the normal form and enforced to be
+    /// congruent to it, which pushes their surfeit to the top with bit decompositions. With
+    /// one, both sides are allocated in `layout` and enforced to be congruent to it with the
+    /// range checks of `k` and of the carries of `enforce_congruent`. Return `None` if there is
+    /// no current layout, or if the groups of `enforce_congruent` do not fit into the base
+    /// field.
+    pub fn predicted_equality_constraints(
+        &self,
+        layout: Option<&EqualityLayout>,
+        lookup_bits: Option<usize>,
+    ) -> Option<usize> {
+        let model = self.cost_model()?;
+        let additions = model.product_additions(&BigUint::zero())?;
+
+        match layout {
+            None => Some(
+                2 * (model.normal_form_allocation(lookup_bits)
+                    + model.enforce_normal_form(&additions, lookup_bits))
+                    + self.num_of_limbs,
+            ),
+            Some(layout) => {
+                let congruence = model.enforce_congruent(
+                    overhead(&(additions + 1u32)),
+                    layout,
+                    self.target_field_prime_bit_length,
+                    lookup_bits,
+                )?;
+                let range_checks = model.range_check(layout.bits_per_top_limb, lookup_bits)
+                    + layout.num_limbs.saturating_sub(1)
+                        * model.range_check(layout.bits_per_non_top_limb, lookup_bits);
+                Some(2 * (range_checks + congruence) + layout.num_limbs)
+            }
+        }
+    }
+
+    /// The model of the costs of the gadgets for the current layout, if there is one
+    fn cost_model(&self) -> Option<CostModel> {
+        Some(CostModel {
+            base_field_prime_length: self.base_field_prime_length,
+            num_of_limbs: self.num_of_limbs,
+            top_limb_size: self.top_limb_size?,
+            non_top_limb_size: self.non_top_limb_size?,
+        })
+    }
+
+    /// Search for the cheapest layout with exactly `num_of_limbs` limbs, and return its cost
+    ///
+    /// The cost is the number of additions that the layout must absorb after a
+    /// multiplication, which the search raises until the layout's own cost fits. Among the
+    /// layouts that work, the one with the fewest `predicted_constraints` of `self.workload`
+    /// is chosen, so that the reductions after the additions and the products count towards
+    /// the choice, and the fewest additions after a multiplication break ties. Return `None`,
+    /// with the limb sizes cleared, if no layout works.
+    pub fn solve_with_num_of_limbs(&mut self, num_of_limbs: usize) -> Option<usize> {
+        self.num_of_limbs = num_of_limbs;
+        self.num_of_additions_after_mul = 1;
+        self.top_limb_size = None;
+        self.non_top_limb_size = None;
+
+        if num_of_limbs < 2 {
+            return None;
+        }
 
-            let mut flag = false;
-            let mut cost = 0usize;
-            let mut current_top_limb_size = None;
-            let mut current_non_top_limb_size = None;
+        loop {
+            let Self {
+                base_field_prime_length,
+                target_field_prime_bit_length,
+                num_of_additions_after_mul,
+                num_of_limbs,
+                ..
+            } = self.clone();
 
+            let mut candidates = Vec::new();
             for top_limb_size in 0..min(base_field_prime_length, target_field_prime_bit_length) {
                 let non_top_limb_size =
-                    (target_field_prime_bit_length - top_limb_size + self.num_of_limbs - 1 - 1)
-                        / (self.num_of_limbs - 1);
+                    (target_field_prime_bit_length - top_limb_size + num_of_limbs - 1 - 1)
+                        / (num_of_limbs - 1);
+
+                // the top limb only needs the bits that the non-top limbs leave over, so that its
+                // range checks and the cost below use its exact width
+                let top_limb_size = min(
+                    top_limb_size,
+                    target_field_prime_bit_length
+                        .saturating_sub((num_of_limbs - 1) * non_top_limb_size),
+                );
 
                 // top limb must be smaller; otherwise, the top limb is too long
                 if top_limb_size > non_top_limb_size {
@@ -246,61 +1256,366 @@ impl ParamsSearching {
                 if 2 * (top_limb_size + 5) >= base_field_prime_length {
                     break;
                 }
-                if 2 * (non_top_limb_size + 5) >= base_field_prime_length {
-                    continue;
-                }
 
-                // computation on the top limb works
-                if 2 * (top_limb_size + 1) + log_top_limb + non_top_limb_size
-                    >= 2 * (non_top_limb_size + 1) + log_sub_top_limb
+                if let Some(this_cost) =
+                    self.layout_cost(top_limb_size, non_top_limb_size, num_of_additions_after_mul)
                 {
-                    if 2 * (top_limb_size + 1) + log_top_limb + non_top_limb_size
-                        >= base_field_prime_length - 1
-                    {
-                        continue;
-                    }
-                } else if 2 * (non_top_limb_size + 1) + log_sub_top_limb
-                    >= base_field_prime_length - 1
-                {
-                    continue;
+                    candidates.push((this_cost, top_limb_size, non_top_limb_size));
                 }
+            }
 
-                // computation on the non-top limb works
-                if 2 * non_top_limb_size + log_other_limbs_upper_bound > base_field_prime_length - 1
-                {
-                    continue;
-                }
+            let cost = candidates
+                .iter()
+                .map(|(this_cost, _, _)| *this_cost)
+                .min()?;
+            if cost > num_of_additions_after_mul {
+                self.num_of_additions_after_mul = cost;
+                continue;
+            }
 
-                let this_cost = 2 * (top_limb_size + 1)
-                    + log_top_limb
-                    + non_top_limb_size
-                    + 1
-                    + (2 * num_of_limbs - 3)
-                        * (2 * (non_top_limb_size + 1) + log_other_limbs_upper_bound)
-                    - target_field_prime_bit_length;
-
-                if !flag || this_cost < cost {
-                    flag = true;
-                    cost = this_cost;
-                    current_top_limb_size = Some(top_limb_size);
-                    current_non_top_limb_size = Some(non_top_limb_size);
-                }
+            // among the layouts that also work with their own additions after a multiplication,
+            // the workload selects the one with the fewest predicted constraints, which counts
+            // the reductions after the additions and the multiplications
+            let workload = self.workload;
+            let (cost, top_limb_size, non_top_limb_size) = candidates
+                .into_iter()
+                .filter(|(this_cost, top_limb_size, non_top_limb_size)| {
+                    self.layout_cost(
+                        *top_limb_size,
+                        *non_top_limb_size,
+                        max(*this_cost, num_of_additions_after_mul),
+                    )
+                    .is_some()
+                })
+                .min_by_key(|(this_cost, top_limb_size, non_top_limb_size)| {
+                    let mut layout = self.clone();
+                    layout.top_limb_size = Some(*top_limb_size);
+                    layout.non_top_limb_size = Some(*non_top_limb_size);
+                    let predicted = layout.predicted_constraints(&workload);
+                    (predicted.unwrap_or(usize::MAX), *this_cost)
+                })?;
+
+            self.top_limb_size = Some(top_limb_size);
+            self.non_top_limb_size = Some(non_top_limb_size);
+            return Some(cost);
+        }
+    }
+
+    /// The number of additions after a multiplication in the layout with limbs of
+    /// `top_limb_size` and `non_top_limb_size` bits, if the products of elements with
+    /// `num_of_additions_after_mul` additions fit into the base field
+    fn layout_cost(
+        &self,
+        top_limb_size: usize,
+        non_top_limb_size: usize,
+        num_of_additions_after_mul: usize,
+    ) -> Option<usize> {
+        let Self {
+            base_field_prime_length,
+            target_field_prime_bit_length,
+            num_of_limbs,
+            ..
+        } = *self;
+
+        let top_limb = 1 + (num_of_additions_after_mul + 1) * (num_of_additions_after_mul + 1);
+        let log_top_limb = ark_std::log2(top_limb) as usize;
+        let log_sub_top_limb = ark_std::log2(top_limb * 2) as usize;
+        let log_other_limbs_upper_bound = ark_std::log2(top_limb * num_of_limbs) as usize;
+
+        if 2 * (non_top_limb_size + 5) >= base_field_prime_length {
+            return None;
+        }
+
+        // computation on the top limb works
+        if 2 * (top_limb_size + 1) + log_top_limb + non_top_limb_size
+            >= 2 * (non_top_limb_size + 1) + log_sub_top_limb
+        {
+            if 2 * (top_limb_size + 1) + log_top_limb + non_top_limb_size
+                >= base_field_prime_length - 1
+            {
+                return None;
             }
+        } else if 2 * (non_top_limb_size + 1) + log_sub_top_limb >= base_field_prime_length - 1 {
+            return None;
+        }
 
-            if !flag {
-                self.num_of_limbs += 1;
-                self.num_of_additions_after_mul = 1;
-                continue;
+        // computation on the non-top limb works
+        if 2 * non_top_limb_size + log_other_limbs_upper_bound > base_field_prime_length - 1 {
+            return None;
+        }
+
+        Some(
+            2 * (top_limb_size + 1)
+                + log_top_limb
+                + non_top_limb_size
+                + 1
+                + (2 * num_of_limbs - 3)
+                    * (2 * (non_top_limb_size + 1) + log_other_limbs_upper_bound)
+                - target_field_prime_bit_length,
+        )
+    }
+}
+
+/// The bits of `x`, plus one unless it is a power of two, as in `overhead!`
+fn overhead(x: &BigUint) -> usize {
+    let num_bits = x.bits() as usize;
+    if !x.is_zero() && (x & (x - 1u32)).is_zero() {
+        num_bits
+    } else {
+        num_bits + 1
+    }
+}
+
+/// The constraints of the gadgets for a layout, with range checks of Boolean constraints,
+/// where the elements are given by their `num_of_additions_over_normal_form`
+struct CostModel {
+    base_field_prime_length: usize,
+    num_of_limbs: usize,
+    top_limb_size: usize,
+    non_top_limb_size: usize,
+}
+
+impl CostModel {
+    /// `Reducer::limb_to_bits` of `num_bits` bits
+    fn limb_to_bits(&self, num_bits: usize) -> usize {
+        min(self.base_field_prime_length - 1, num_bits) + 1
+    }
+
+    /// `range_check::enforce_range` of `num_bits` bits with `lookup_bits`-bit lookups, or
+    /// with Boolean constraints if `None`
+    fn range_check(&self, num_bits: usize, lookup_bits: Option<usize>) -> usize {
+        match lookup_bits {
+            Some(lookup_bits) if lookup_bits > 0 => {
+                let num_bits = min(self.base_field_prime_length - 1, num_bits);
+                let partial_chunk = if num_bits % lookup_bits == 0 { 0 } else { 1 };
+                (num_bits + lookup_bits - 1) / lookup_bits + partial_chunk + 1
             }
+            _ => self.limb_to_bits(num_bits),
+        }
+    }
 
-            if cost > num_of_additions_after_mul {
-                self.num_of_additions_after_mul = cost;
-                continue;
+    /// The surfeit of the reductions of an element with `additions` additions
+    fn surfeit(additions: &BigUint) -> usize {
+        overhead(&(additions + 1u32)) + 1
+    }
+
+    /// `AllocatedNonNativeFieldMulResultVar::unreduced_limb_sizes`
+    fn unreduced_limb_sizes(&self, prod_of_num_of_additions: &BigUint) -> (usize, usize) {
+        let top = max(
+            2 * (self.top_limb_size + 1)
+                + overhead(prod_of_num_of_additions)
+                + self.non_top_limb_size
+                + 1,
+            2 * (self.non_top_limb_size + 1) + overhead(&(prod_of_num_of_additions << 1)) + 1,
+        );
+        let non_top = 2 * (self.non_top_limb_size + 1)
+            + overhead(&(prod_of_num_of_additions * self.num_of_limbs));
+        (top, non_top)
+    }
+
+    /// `Reducer::push_to_the_top`, or `Reducer::push_to_the_top_keep_top` if `keep_top`
+    fn push_to_the_top(&self, additions: &BigUint, keep_top: bool) -> usize {
+        let surfeit = Self::surfeit(additions);
+        let mut cost = self.limb_to_bits(self.non_top_limb_size + surfeit)
+            + (self.num_of_limbs - 2) * self.limb_to_bits(self.non_top_limb_size + surfeit + 1)
+            + (self.num_of_limbs - 1);
+        if !keep_top {
+            cost += self.limb_to_bits(self.top_limb_size + surfeit + 1);
+        }
+        cost
+    }
+
+    /// `Reducer::push_and_reduce_the_top`, and the additions of the result
+    fn push_and_reduce_the_top(&self, additions: &BigUint) -> (usize, BigUint) {
+        let num_top_bits = min(
+            self.base_field_prime_length - 1,
+            self.top_limb_size + Self::surfeit(additions) + 1,
+        );
+        (
+            self.push_to_the_top(additions, false) + self.num_of_limbs,
+            BigUint::from(num_top_bits - self.top_limb_size),
+        )
+    }
+
+    /// `Reducer::reduce_all_limbs`, and the additions of the result
+    fn reduce_all_limbs(&self, additions: &BigUint) -> (usize, BigUint) {
+        let surfeit = Self::surfeit(additions);
+        let top = min(
+            self.base_field_prime_length - 1,
+            self.top_limb_size + surfeit,
+        );
+        let non_top = min(
+            self.base_field_prime_length - 1,
+            self.non_top_limb_size + surfeit,
+        );
+        (
+            (top + 1) + (self.num_of_limbs - 1) * (non_top + 1) + self.num_of_limbs,
+            BigUint::from(
+                (top - self.top_limb_size)
+                    + (self.num_of_limbs - 1) * (non_top - self.non_top_limb_size),
+            ),
+        )
+    }
+
+    /// The `post_add_reduce`s of a sum of `num_of_additions + 1` terms with `additions`
+    /// additions each, added one after another, and the additions of the sum
+    fn sum(&self, additions: &BigUint, num_of_additions: &BigUint) -> (usize, BigUint) {
+        // `can_safely_push` allows the sums whose `overhead!(num_of_additions + 1)`, which is
+        // one more than their surfeit, is at most `max_overhead`
+        let max_overhead = self
+            .base_field_prime_length
+            .saturating_sub(self.non_top_limb_size + 3);
+        let limit = (BigUint::one() << max_overhead) >> 1u32;
+        let step = additions + 1u32;
+        let additions_until_reduction = |start: &BigUint| {
+            if max_overhead == 0 || start + 1u32 > limit {
+                BigUint::one()
+            } else {
+                (&limit - 1u32 - start) / &step + 1u32
             }
+        };
 
-            self.top_limb_size = current_top_limb_size;
-            self.non_top_limb_size = current_non_top_limb_size;
-            break;
+        let first = additions_until_reduction(additions);
+        if num_of_additions < &first {
+            return (0, additions + num_of_additions * &step);
         }
+        let (first_reduction, reduced) = self.reduce_all_limbs(&(additions + &first * &step));
+
+        // the later reductions start from the additions of a reduced sum
+        let rest = num_of_additions - &first;
+        let period = additions_until_reduction(&reduced);
+        let (reduction, reduced_again) = self.reduce_all_limbs(&(&reduced + &period * &step));
+        let num_of_reductions = &rest / &period;
+        let cost = first_reduction.saturating_add(
+            reduction.saturating_mul(num_of_reductions.to_usize().unwrap_or(usize::MAX)),
+        );
+
+        let last = if num_of_reductions.is_zero() {
+            reduced
+        } else {
+            reduced_again
+        };
+        (cost, last + (rest % period) * step)
+    }
+
+    /// `Reducer::pre_mul_reduce` of operands with `x` and `y` additions, and the product of
+    /// their additions plus one, or `None` if the reductions cannot make the product fit
+    fn pre_mul_reduce(&self, x: &BigUint, y: &BigUint) -> Option<(usize, BigUint)> {
+        let (mut x, mut y) = (x.clone(), y.clone());
+        let mut cost = 0;
+        loop {
+            let prod = (&x + 1u32) * (&y + 1u32);
+            let (top, non_top) = self.unreduced_limb_sizes(&prod);
+            if max(top, non_top) < self.base_field_prime_length {
+                return Some((cost, prod));
+            }
+
+            let larger = if x >= y { &mut x } else { &mut y };
+            let (reduction, additions) = self.push_and_reduce_the_top(larger);
+            if additions >= *larger {
+                return None;
+            }
+            cost += reduction;
+            *larger = additions;
+        }
+    }
+
+    /// `AllocatedNonNativeFieldMulResultVar::reduce`, and the additions of the result
+    fn reduce_product(&self, prod_of_num_of_additions: &BigUint) -> (usize, BigUint) {
+        let (top, non_top) = self.unreduced_limb_sizes(prod_of_num_of_additions);
+        let top = min(self.base_field_prime_length - 1, top);
+        let non_top = min(self.base_field_prime_length - 1, non_top);
+        let num_of_non_top_limbs = 2 * self.num_of_limbs - 3;
+
+        // the first addition of every bit within the normal form is free
+        let num_of_normal_form_bits =
+            self.top_limb_size + (self.num_of_limbs - 1) * self.non_top_limb_size;
+        (
+            num_of_non_top_limbs * (non_top + 1) + (top + 1) + self.num_of_limbs,
+            BigUint::from(num_of_non_top_limbs * non_top + top - num_of_normal_form_bits),
+        )
+    }
+
+    /// The additions of a product of two operands that are sums of such products with
+    /// `additions_per_operand` additions, or `None` if they do not fit into a product
+    fn product_additions(&self, additions_per_operand: &BigUint) -> Option<BigUint> {
+        // they settle after a few rounds, as they only depend on the bits of the previous ones
+        let mut additions = BigUint::one();
+        for _ in 0..4 {
+            let (_, operand) = self.sum(&additions, additions_per_operand);
+            let (_, prod) = self.pre_mul_reduce(&operand, &operand)?;
+            additions = self.reduce_product(&prod).1;
+        }
+        Some(additions)
+    }
+
+    /// The range checks of a normal form allocated as a witness
+    fn normal_form_allocation(&self, lookup_bits: Option<usize>) -> usize {
+        (self.num_of_limbs - 1) * self.range_check(self.non_top_limb_size, lookup_bits)
+            + self.range_check(self.top_limb_size, lookup_bits)
+    }
+
+    /// `Reducer::enforce_normal_form` of an element with `additions` additions, against a
+    /// normal form allocated as a witness, where the pushes decompose the limbs into bits
+    fn enforce_normal_form(&self, additions: &BigUint, lookup_bits: Option<usize>) -> usize {
+        let k_bits = (self.base_field_prime_length - 1)
+            - max(self.top_limb_size, self.non_top_limb_size)
+            - 1;
+        self.push_to_the_top(additions, true)
+            + self.range_check(k_bits, lookup_bits)
+            + self.num_of_limbs
+            + self.push_to_the_top(&(additions + 2u32), true)
+            + self.num_of_limbs
+    }
+
+    /// `layout::enforce_congruent` of an element with limbs of `surfeit` bits over the current
+    /// layout against limbs in `layout`, or `None` if its groups do not fit into the base field
+    fn enforce_congruent(
+        &self,
+        surfeit: usize,
+        layout: &EqualityLayout,
+        target_field_prime_bit_length: usize,
+        lookup_bits: Option<usize>,
+    ) -> Option<usize> {
+        let num_covered_bits =
+            self.top_limb_size + (self.num_of_limbs - 1) * self.non_top_limb_size;
+        let num_layout_bits =
+            layout.bits_per_top_limb + (layout.num_limbs - 1) * layout.bits_per_non_top_limb;
+        let k_bits = (max(num_covered_bits + surfeit, num_layout_bits) + 1)
+            .saturating_sub(target_field_prime_bit_length);
+
+        // the limbs, the offset `2^k_bits · p`, and `k · p` split into the current layout, and
+        // the limbs in `layout`
+        let term_bits = max(
+            max(
+                self.non_top_limb_size + surfeit,
+                self.non_top_limb_size + k_bits + 1,
+            ),
+            max(layout.bits_per_top_limb, layout.bits_per_non_top_limb),
+        );
+        let num_terms = 3 * self.num_of_limbs + layout.num_limbs;
+        let sum_bits = term_bits + overhead(&BigUint::from(num_terms));
+        let max_span = self.base_field_prime_length.checked_sub(sum_bits + 5)?;
+
+        let mut positions: Vec<usize> = (0..self.num_of_limbs)
+            .map(|i| i * self.non_top_limb_size)
+            .chain((0..layout.num_limbs).map(|i| i * layout.bits_per_non_top_limb))
+            .collect();
+        positions.sort_unstable();
+        positions.dedup();
+        let mut group_starts: Vec<usize> = Vec::new();
+        for position in positions {
+            match group_starts.last() {
+                Some(start) if position - start <= max_span => {}
+                _ => group_starts.push(position),
+            }
+        }
+        let num_groups = group_starts.len();
+
+        Some(
+            self.range_check(k_bits + 1, lookup_bits)
+                + (num_groups - 1) * self.range_check(sum_bits + 1, lookup_bits)
+                + num_groups,
+        )
     }
 }
@@ -0,0 +1,206 @@
+use crate::{NonNativeFieldMulResultVar, NonNativeFieldVar};
+use ark_ff::{PrimeField, Zero};
+use ark_r1cs_std::fields::FieldVar;
+use ark_relations::r1cs::SynthesisError;
+use ark_std::{cmp::max, vec, vec::Vec};
+
+/// Invert all of `elems`, which must be nonzero, with a single inversion
+pub(crate) fn batch_inverse<TargetField: PrimeField, BaseField: PrimeField>(
+    elems: &[NonNativeFieldVar<TargetField, BaseField>],
+) -> Result<Vec<NonNativeFieldVar<TargetField, BaseField>>, SynthesisError> {
+    // prefix[i] is the product of the elements before the i-th one
+    let mut prefix = Vec::with_capacity(elems.len());
+    let mut cur = NonNativeFieldVar::one();
+    for elem in elems.iter() {
+        prefix.push(cur.clone());
+        cur *= elem;
+    }
+
+    let mut inv = cur.inverse()?;
+    let mut res = vec![NonNativeFieldVar::zero(); elems.len()];
+    for i in (0..elems.len()).rev() {
+        res[i] = &inv * &prefix[i];
+        inv *= &elems[i];
+    }
+
+    Ok(res)
+}
+
+impl<TargetField: PrimeField, BaseField: PrimeField> NonNativeFieldVar<TargetField, BaseField> {
+    /// Evaluate at `x` the polynomial of the lowest degree through `points`, whose
+    /// x-coordinates must be distinct
+    ///
+    /// This uses the Lagrange form `Σ y_i Π_{j≠i} (x - x_j) / (x_i - x_j)`, so `x` may be one
+    /// of the x-coordinates. The denominators share a single inversion, and the weighted sum
+    /// of the `y_i` is reduced once.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn lagrange_interpolate_and_eval(
+        points: &[(Self, Self)],
+        x: &Self,
+    ) -> Result<Self, SynthesisError> {
+        let n = points.len();
+
+        // the denominators Π_{j≠i} (x_i - x_j)
+        let mut denominators = Vec::with_capacity(n);
+        for (i, (x_i, _)) in points.iter().enumerate() {
+            let mut denominator = Self::one();
+            for (j, (x_j, _)) in points.iter().enumerate() {
+                if i != j {
+                    denominator *= &(x_i - x_j);
+                }
+            }
+            denominators.push(denominator);
+        }
+        let inverses = batch_inverse(&denominators)?;
+
+        // the numerators Π_{j≠i} (x - x_j), from the prefix and suffix products of (x - x_j)
+        let differences: Vec<Self> = points.iter().map(|(x_j, _)| x - x_j).collect();
+        let mut numerators = Vec::with_capacity(n);
+        let mut cur = Self::one();
+        for difference in differences.iter() {
+            numerators.push(cur.clone());
+            cur *= difference;
+        }
+        let mut cur = Self::one();
+        for i in (0..n).rev() {
+            numerators[i] *= &cur;
+            cur *= &differences[i];
+        }
+
+        let mut res = NonNativeFieldMulResultVar::zero();
+        for ((numerator, inverse), (_, y_i)) in
+            numerators.iter().zip(inverses.iter()).zip(points.iter())
+        {
+            res += &y_i.mul_without_reduce(&(numerator * inverse))?;
+        }
+        res.reduce()
+    }
+
+    /// Compute `x^n - 1`, the vanishing polynomial of the multiplicative subgroup of order `n`
+    #[tracing::instrument(target = "r1cs")]
+    pub fn eval_vanishing_poly(x: &Self, n: u64) -> Result<Self, SynthesisError> {
+        Ok(x.pow_by_constant(&[n])? - Self::one())
+    }
+
+    /// Compute `x^n - 1` and its derivative `n·x^{n-1}`, which is the denominator of the
+    /// barycentric weights over the multiplicative subgroup of order `n`
+    ///
+    /// Both share the square-and-multiply chain of `x^{n-1}`.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn eval_vanishing_poly_with_derivative(
+        x: &Self,
+        n: u64,
+    ) -> Result<(Self, Self), SynthesisError> {
+        if n == 0 {
+            return Ok((Self::zero(), Self::zero()));
+        }
+
+        let x_pow_n_minus_one = x.pow_by_constant(&[n - 1])?;
+        let vanishing = &x_pow_n_minus_one * x - Self::one();
+        let derivative = x_pow_n_minus_one * TargetField::from(n);
+
+        Ok((vanishing, derivative))
+    }
+}
+
+/// A univariate polynomial over the simulated field, given by its coefficients, the constant
+/// term first
+///
+/// Leading coefficients that are constant zeros are dropped, so `degree` is exact for constant
+/// coefficients and an upper bound otherwise.
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct DensePolynomialVar<TargetField: PrimeField, BaseField: PrimeField> {
+    /// The coefficients, the constant term first
+    pub coeffs: Vec<NonNativeFieldVar<TargetField, BaseField>>,
+}
+
+impl<TargetField: PrimeField, BaseField: PrimeField> DensePolynomialVar<TargetField, BaseField> {
+    /// Create a polynomial from its coefficients, the constant term first
+    pub fn from_coefficients_vec(coeffs: Vec<NonNativeFieldVar<TargetField, BaseField>>) -> Self {
+        let mut res = Self { coeffs };
+        res.truncate_leading_zeros();
+        res
+    }
+
+    /// Create a polynomial from its coefficients, the constant term first
+    pub fn from_coefficients_slice(coeffs: &[NonNativeFieldVar<TargetField, BaseField>]) -> Self {
+        Self::from_coefficients_vec(coeffs.to_vec())
+    }
+
+    /// The zero polynomial, which has no coefficients
+    pub fn zero() -> Self {
+        Self { coeffs: Vec::new() }
+    }
+
+    /// Whether the polynomial has no coefficients
+    pub fn is_zero(&self) -> bool {
+        self.coeffs.is_empty()
+    }
+
+    /// The degree, which is zero for the zero polynomial
+    pub fn degree(&self) -> usize {
+        self.coeffs.len().saturating_sub(1)
+    }
+
+    fn truncate_leading_zeros(&mut self) {
+        while let Some(NonNativeFieldVar::Constant(c)) = self.coeffs.last() {
+            if !c.is_zero() {
+                break;
+            }
+            self.coeffs.pop();
+        }
+    }
+
+    /// Evaluate the polynomial at `x` with Horner's rule
+    ///
+    /// Each coefficient is added to the unreduced product of the previous step, so every
+    /// step costs a single reduction.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn evaluate(
+        &self,
+        x: &NonNativeFieldVar<TargetField, BaseField>,
+    ) -> Result<NonNativeFieldVar<TargetField, BaseField>, SynthesisError> {
+        let mut coeffs = self.coeffs.iter().rev();
+        let mut res = match coeffs.next() {
+            Some(coeff) => coeff.clone(),
+            None => return Ok(NonNativeFieldVar::zero()),
+        };
+
+        for coeff in coeffs {
+            let prod = res.mul_without_reduce(x)?;
+            res = (prod + &NonNativeFieldMulResultVar::from_reduced(coeff)?).reduce()?;
+        }
+
+        Ok(res)
+    }
+
+    /// Add `other` to the polynomial
+    #[tracing::instrument(target = "r1cs")]
+    pub fn add(&self, other: &Self) -> Self {
+        let len = max(self.coeffs.len(), other.coeffs.len());
+        let mut coeffs = Vec::with_capacity(len);
+        for i in 0..len {
+            coeffs.push(match (self.coeffs.get(i), other.coeffs.get(i)) {
+                (Some(a), Some(b)) => a + b,
+                (Some(a), None) | (None, Some(a)) => a.clone(),
+                (None, None) => unreachable!(),
+            });
+        }
+        Self::from_coefficients_vec(coeffs)
+    }
+
+    /// Subtract `other` from the polynomial
+    #[tracing::instrument(target = "r1cs")]
+    pub fn sub(&self, other: &Self) -> Self {
+        let negated = other.coeffs.iter().map(|coeff| -coeff).collect();
+        self.add(&Self { coeffs: negated })
+    }
+
+    /// Multiply every coefficient by `scalar`
+    #[tracing::instrument(target = "r1cs")]
+    pub fn mul_by_scalar(&self, scalar: &NonNativeFieldVar<TargetField, BaseField>) -> Self {
+        let coeffs = self.coeffs.iter().map(|coeff| coeff * scalar).collect();
+        Self::from_coefficients_vec(coeffs)
+    }
+}
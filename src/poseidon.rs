@@ -0,0 +1,289 @@
+use crate::{error::NonNativeFieldError, NonNativeFieldMulResultVar, NonNativeFieldVar};
+use ark_ff::PrimeField;
+use ark_r1cs_std::fields::FieldVar;
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use ark_std::{vec, vec::Vec};
+
+/// Parameters of a Poseidon permutation over the simulated `TargetField`
+///
+/// The parameters are supplied by the caller and must match the ones used by the
+/// (non-circuit) protocol whose hashes or challenges are being recomputed.
+#[derive(Clone, Debug)]
+pub struct PoseidonParameters<TargetField: PrimeField> {
+    /// Number of rounds in which the S-box is applied to the full state
+    pub full_rounds: usize,
+    /// Number of rounds in which the S-box is applied to the first element only
+    pub partial_rounds: usize,
+    /// Exponent of the S-box
+    pub alpha: u64,
+    /// Maximally distance separating matrix, `(rate + capacity) x (rate + capacity)`
+    pub mds: Vec<Vec<TargetField>>,
+    /// Round constants, one vector of length `rate + capacity` per round
+    pub ark: Vec<Vec<TargetField>>,
+    /// Number of state elements absorbed or squeezed per permutation
+    pub rate: usize,
+    /// Number of state elements that are never directly absorbed into or squeezed from
+    pub capacity: usize,
+}
+
+impl<TargetField: PrimeField> PoseidonParameters<TargetField> {
+    /// Create the parameters, checking that the dimensions of `mds` and `ark` are consistent
+    ///
+    /// Return `NonNativeFieldError::InvalidPoseidonParameters` if the number of full rounds is
+    /// odd, `mds` is not a square matrix of size `rate + capacity`, or `ark` does not have one
+    /// row of that size per round.
+    pub fn new(
+        full_rounds: usize,
+        partial_rounds: usize,
+        alpha: u64,
+        mds: Vec<Vec<TargetField>>,
+        ark: Vec<Vec<TargetField>>,
+        rate: usize,
+        capacity: usize,
+    ) -> Result<Self, NonNativeFieldError> {
+        let width = rate + capacity;
+        let reason = if full_rounds % 2 != 0 {
+            Some("the number of full rounds must be even")
+        } else if mds.len() != width || mds.iter().any(|row| row.len() != width) {
+            Some("the MDS matrix must have rate + capacity rows and columns")
+        } else if ark.len() != full_rounds + partial_rounds
+            || ark.iter().any(|row| row.len() != width)
+        {
+            Some("the round constants must have one row of rate + capacity elements per round")
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            return Err(NonNativeFieldError::InvalidPoseidonParameters { reason });
+        }
+
+        Ok(Self {
+            full_rounds,
+            partial_rounds,
+            alpha,
+            mds,
+            ark,
+            rate,
+            capacity,
+        })
+    }
+}
+
+/// The mode of a duplex sponge
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DuplexSpongeMode {
+    /// The sponge is absorbing; the next element goes to the given index of the rate part
+    Absorbing {
+        /// Index of the rate part into which the next element is absorbed
+        next_absorb_index: usize,
+    },
+    /// The sponge is squeezing; the next element comes from the given index of the rate part
+    Squeezing {
+        /// Index of the rate part from which the next element is squeezed
+        next_squeeze_index: usize,
+    },
+}
+
+/// A Poseidon duplex sponge whose state consists of `NonNativeFieldVar`s
+#[derive(Clone, Debug)]
+pub struct PoseidonSpongeVar<TargetField: PrimeField, BaseField: PrimeField> {
+    /// Reference to the constraint system
+    pub cs: ConstraintSystemRef<BaseField>,
+    /// The permutation parameters
+    pub parameters: PoseidonParameters<TargetField>,
+    /// The sponge state; the first `capacity` elements are the capacity part
+    pub state: Vec<NonNativeFieldVar<TargetField, BaseField>>,
+    /// The current mode of the sponge
+    pub mode: DuplexSpongeMode,
+}
+
+impl<TargetField: PrimeField, BaseField: PrimeField> PoseidonSpongeVar<TargetField, BaseField> {
+    /// Create a new sponge with an all-zero state
+    pub fn new(
+        cs: ConstraintSystemRef<BaseField>,
+        parameters: &PoseidonParameters<TargetField>,
+    ) -> Self {
+        let width = parameters.rate + parameters.capacity;
+
+        Self {
+            cs,
+            parameters: parameters.clone(),
+            state: vec![NonNativeFieldVar::zero(); width],
+            mode: DuplexSpongeMode::Absorbing {
+                next_absorb_index: 0,
+            },
+        }
+    }
+
+    fn apply_ark(&mut self, round: usize) {
+        for (elem, c) in self.state.iter_mut().zip(self.parameters.ark[round].iter()) {
+            *elem += *c;
+        }
+    }
+
+    fn apply_s_box(&mut self, is_full_round: bool) -> Result<(), SynthesisError> {
+        let alpha = [self.parameters.alpha];
+        if is_full_round {
+            for elem in self.state.iter_mut() {
+                *elem = elem.pow_by_constant(&alpha)?;
+            }
+        } else {
+            self.state[0] = self.state[0].pow_by_constant(&alpha)?;
+        }
+        Ok(())
+    }
+
+    /// The MDS multiplication reduces each output element only once,
+    /// accumulating the products with the (constant) matrix entries unreduced.
+    fn apply_mds(&mut self) -> Result<(), SynthesisError> {
+        let mut new_state = Vec::with_capacity(self.state.len());
+        for row in self.parameters.mds.iter() {
+            let mut acc = NonNativeFieldMulResultVar::<TargetField, BaseField>::zero();
+            for (m, elem) in row.iter().zip(self.state.iter()) {
+                acc += &elem.mul_without_reduce(&NonNativeFieldVar::Constant(*m))?;
+            }
+            new_state.push(acc.reduce()?);
+        }
+        self.state = new_state;
+        Ok(())
+    }
+
+    /// Apply the Poseidon permutation to the state
+    #[tracing::instrument(target = "r1cs")]
+    pub fn permute(&mut self) -> Result<(), SynthesisError> {
+        let full_rounds_over_2 = self.parameters.full_rounds / 2;
+        let partial_rounds = self.parameters.partial_rounds;
+
+        for round in 0..(self.parameters.full_rounds + partial_rounds) {
+            let is_full_round =
+                round < full_rounds_over_2 || round >= full_rounds_over_2 + partial_rounds;
+
+            self.apply_ark(round);
+            self.apply_s_box(is_full_round)?;
+            self.apply_mds()?;
+        }
+
+        Ok(())
+    }
+
+    fn absorb_internal(
+        &mut self,
+        mut rate_start_index: usize,
+        elements: &[NonNativeFieldVar<TargetField, BaseField>],
+    ) -> Result<(), SynthesisError> {
+        let rate = self.parameters.rate;
+        let capacity = self.parameters.capacity;
+        let mut remaining = elements;
+
+        loop {
+            // the remaining elements fit into the current rate part
+            if rate_start_index + remaining.len() <= rate {
+                for (i, elem) in remaining.iter().enumerate() {
+                    self.state[capacity + rate_start_index + i] += elem;
+                }
+                self.mode = DuplexSpongeMode::Absorbing {
+                    next_absorb_index: rate_start_index + remaining.len(),
+                };
+                return Ok(());
+            }
+
+            // otherwise, fill up the rate part and permute
+            let num_absorbed = rate - rate_start_index;
+            for (i, elem) in remaining.iter().enumerate().take(num_absorbed) {
+                self.state[capacity + rate_start_index + i] += elem;
+            }
+            self.permute()?;
+
+            remaining = &remaining[num_absorbed..];
+            rate_start_index = 0;
+        }
+    }
+
+    fn squeeze_internal(
+        &mut self,
+        mut rate_start_index: usize,
+        output: &mut [NonNativeFieldVar<TargetField, BaseField>],
+    ) -> Result<(), SynthesisError> {
+        let rate = self.parameters.rate;
+        let capacity = self.parameters.capacity;
+        let mut remaining = output;
+
+        loop {
+            // the remaining outputs can be taken from the current rate part
+            if rate_start_index + remaining.len() <= rate {
+                let start = capacity + rate_start_index;
+                remaining.clone_from_slice(&self.state[start..start + remaining.len()]);
+                self.mode = DuplexSpongeMode::Squeezing {
+                    next_squeeze_index: rate_start_index + remaining.len(),
+                };
+                return Ok(());
+            }
+
+            // otherwise, take what is left of the rate part and permute
+            let num_squeezed = rate - rate_start_index;
+            let start = capacity + rate_start_index;
+            remaining[..num_squeezed].clone_from_slice(&self.state[start..start + num_squeezed]);
+            if remaining.len() != rate {
+                self.permute()?;
+            }
+
+            remaining = &mut remaining[num_squeezed..];
+            rate_start_index = 0;
+        }
+    }
+
+    /// Absorb nonnative field elements into the sponge
+    #[tracing::instrument(target = "r1cs")]
+    pub fn absorb(
+        &mut self,
+        elements: &[NonNativeFieldVar<TargetField, BaseField>],
+    ) -> Result<(), SynthesisError> {
+        if elements.is_empty() {
+            return Ok(());
+        }
+
+        match self.mode {
+            DuplexSpongeMode::Absorbing { next_absorb_index } => {
+                let mut absorb_index = next_absorb_index;
+                if absorb_index == self.parameters.rate {
+                    self.permute()?;
+                    absorb_index = 0;
+                }
+                self.absorb_internal(absorb_index, elements)
+            }
+            DuplexSpongeMode::Squeezing { .. } => {
+                self.permute()?;
+                self.absorb_internal(0, elements)
+            }
+        }
+    }
+
+    /// Squeeze `num_elements` nonnative field elements out of the sponge
+    #[tracing::instrument(target = "r1cs")]
+    pub fn squeeze(
+        &mut self,
+        num_elements: usize,
+    ) -> Result<Vec<NonNativeFieldVar<TargetField, BaseField>>, SynthesisError> {
+        let mut output = vec![NonNativeFieldVar::zero(); num_elements];
+        if num_elements == 0 {
+            return Ok(output);
+        }
+
+        match self.mode {
+            DuplexSpongeMode::Absorbing { .. } => {
+                self.permute()?;
+                self.squeeze_internal(0, &mut output)?;
+            }
+            DuplexSpongeMode::Squeezing { next_squeeze_index } => {
+                let mut squeeze_index = next_squeeze_index;
+                if squeeze_index == self.parameters.rate {
+                    self.permute()?;
+                    squeeze_index = 0;
+                }
+                self.squeeze_internal(squeeze_index, &mut output)?;
+            }
+        }
+
+        Ok(output)
+    }
+}
@@ -0,0 +1,211 @@
+use crate::reduce::{biguint_to_field, field_to_biguint, Reducer};
+use ark_ff::PrimeField;
+use ark_r1cs_std::{alloc::AllocVar, fields::fp::AllocatedFp, R1CSVar};
+use ark_relations::{
+    lc,
+    r1cs::{ConstraintSystemRef, LinearCombination, SynthesisError, Variable},
+};
+use ark_std::{any::TypeId, boxed::Box, cmp::min, collections::BTreeMap, rc::Rc, vec::Vec};
+use num_bigint::BigUint;
+use num_traits::One;
+
+/// A gadget enforcing that a limb fits into a number of bits
+///
+/// It serves the range checks whose bits are not needed otherwise, i.e., those of the
+/// allocations and of the quotients of the reductions to the normal form.
+pub trait RangeCheckGadget<BaseField: PrimeField> {
+    /// Enforce that `limb` is in `[0, 2^num_bits)`
+    fn enforce_range(
+        &self,
+        limb: &AllocatedFp<BaseField>,
+        num_bits: usize,
+    ) -> Result<(), SynthesisError>;
+}
+
+/// A table of a constraint system with lookup arguments, which contains the integers in
+/// `[0, 2^k)`
+pub trait RangeTable<BaseField: PrimeField> {
+    /// The number of bits `k` of the table
+    fn num_bits(&self) -> usize;
+
+    /// Enforce that `value` is in the table
+    fn enforce_lookup(&self, value: &AllocatedFp<BaseField>) -> Result<(), SynthesisError>;
+}
+
+/// The range check for constraint systems without lookups, which decomposes the limb into
+/// bits with a Boolean constraint each
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BooleanRangeCheck;
+
+impl<BaseField: PrimeField> RangeCheckGadget<BaseField> for BooleanRangeCheck {
+    fn enforce_range(
+        &self,
+        limb: &AllocatedFp<BaseField>,
+        num_bits: usize,
+    ) -> Result<(), SynthesisError> {
+        Reducer::<BaseField, BaseField>::limb_to_bits(limb, num_bits).map(|_| ())
+    }
+}
+
+/// The range check with `k`-bit lookups into a `RangeTable`
+///
+/// A limb of `n` bits is split into `ceil(n / k)` chunks of `k` bits, each of which is looked
+/// up in the table. If the most significant chunk has `r < k` bits, it is also looked up
+/// after a multiplication by `2^(k - r)`, which bounds it by `2^r`. This replaces the `n`
+/// Boolean constraints by at most `ceil(n / k) + 1` lookups and one linear constraint.
+#[derive(Clone, Debug)]
+pub struct LookupRangeCheck<T> {
+    /// The table of the constraint system
+    pub table: T,
+}
+
+impl<BaseField: PrimeField, T: RangeTable<BaseField>> RangeCheckGadget<BaseField>
+    for LookupRangeCheck<T>
+{
+    fn enforce_range(
+        &self,
+        limb: &AllocatedFp<BaseField>,
+        num_bits: usize,
+    ) -> Result<(), SynthesisError> {
+        let cs = limb.cs.clone();
+        let table_bits = self.table.num_bits();
+        if table_bits == 0 {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+
+        let num_bits = min(BaseField::size_in_bits() - 1, num_bits);
+        let num_chunks = (num_bits + table_bits - 1) / table_bits;
+        let limb_value = field_to_biguint(&limb.value().unwrap_or_default());
+        let mask = (BigUint::one() << table_bits) - BigUint::one();
+
+        let mut chunks_lc = LinearCombination::zero();
+        let mut coeff = BaseField::one();
+        let mut chunks = Vec::with_capacity(num_chunks);
+        for i in 0..num_chunks {
+            let chunk_value: BaseField =
+                biguint_to_field(&((&limb_value >> (i * table_bits)) & &mask));
+            let chunk =
+                AllocatedFp::<BaseField>::new_witness(ark_relations::ns!(cs, "chunk"), || {
+                    Ok(chunk_value)
+                })?;
+            self.table.enforce_lookup(&chunk)?;
+
+            chunks_lc += (coeff, chunk.variable);
+            coeff *= &BaseField::from(2u64).pow(&[table_bits as u64]);
+            chunks.push(chunk);
+        }
+
+        let top_bits = num_bits - (num_chunks.saturating_sub(1)) * table_bits;
+        if let Some(top_chunk) = chunks.last() {
+            if top_bits < table_bits {
+                let shifted = top_chunk
+                    .mul_constant(BaseField::from(2u64).pow(&[(table_bits - top_bits) as u64]));
+                self.table.enforce_lookup(&shifted)?;
+            }
+        }
+
+        let limb_lc = LinearCombination::from((BaseField::one(), limb.variable));
+        cs.enforce_constraint(lc!(), lc!(), limb_lc - chunks_lc)?;
+
+        Ok(())
+    }
+}
+
+/// The range check installed in the cache map of a constraint system
+struct InstalledRangeCheck<BaseField: PrimeField> {
+    gadget: Rc<dyn RangeCheckGadget<BaseField>>,
+}
+
+/// The limbs range-checked in a constraint system, with the smallest number of bits that each
+/// one has been checked to, so that the limbs checked again by layered gadgets are skipped
+#[derive(Default)]
+struct InternedRangeChecks {
+    num_bits: BTreeMap<Variable, usize>,
+}
+
+impl InternedRangeChecks {
+    /// Check whether `limb` has already been checked to at most `num_bits` bits
+    fn contains<BaseField: PrimeField>(limb: &AllocatedFp<BaseField>, num_bits: usize) -> bool {
+        match &limb.cs {
+            ConstraintSystemRef::None => false,
+            ConstraintSystemRef::CS(v) => {
+                let cs_sys = v.borrow();
+                let big_map = cs_sys.cache_map.borrow();
+                big_map
+                    .get(&TypeId::of::<Self>())
+                    .and_then(|interned| interned.downcast_ref::<Self>())
+                    .and_then(|interned| interned.num_bits.get(&limb.variable))
+                    .map_or(false, |checked_bits| *checked_bits <= num_bits)
+            }
+        }
+    }
+
+    /// Remember that `limb` has been checked to `num_bits` bits
+    fn insert<BaseField: PrimeField>(limb: &AllocatedFp<BaseField>, num_bits: usize) {
+        if let ConstraintSystemRef::CS(v) = &limb.cs {
+            let cs_sys = v.borrow();
+            let mut big_map = cs_sys.cache_map.borrow_mut();
+            if let Some(interned) = big_map
+                .entry(TypeId::of::<Self>())
+                .or_insert_with(|| Box::new(Self::default()))
+                .downcast_mut::<Self>()
+            {
+                let checked_bits = interned.num_bits.entry(limb.variable).or_insert(num_bits);
+                *checked_bits = min(*checked_bits, num_bits);
+            }
+        }
+    }
+}
+
+/// Use `gadget` for the range checks whose bits are not needed in `cs`, instead of the
+/// default `BooleanRangeCheck`
+pub fn set_range_check<BaseField: PrimeField>(
+    cs: &ConstraintSystemRef<BaseField>,
+    gadget: impl RangeCheckGadget<BaseField> + 'static,
+) {
+    if let ConstraintSystemRef::CS(v) = cs {
+        let cs_sys = v.borrow_mut();
+        let mut big_map = cs_sys.cache_map.borrow_mut();
+        big_map.insert(
+            TypeId::of::<InstalledRangeCheck<BaseField>>(),
+            Box::new(InstalledRangeCheck {
+                gadget: Rc::new(gadget),
+            }),
+        );
+    }
+}
+
+/// Enforce that `limb` is in `[0, 2^num_bits)` with the range check set for its constraint
+/// system, if any, or with `BooleanRangeCheck`
+///
+/// A limb that has already been checked to at most `num_bits` bits in its constraint system
+/// is not checked again.
+pub fn enforce_range<BaseField: PrimeField>(
+    limb: &AllocatedFp<BaseField>,
+    num_bits: usize,
+) -> Result<(), SynthesisError> {
+    if InternedRangeChecks::contains(limb, num_bits) {
+        return Ok(());
+    }
+
+    // the gadget is taken out of the cache map first, since it generates constraints
+    let gadget = match &limb.cs {
+        ConstraintSystemRef::None => None,
+        ConstraintSystemRef::CS(v) => {
+            let cs_sys = v.borrow();
+            let big_map = cs_sys.cache_map.borrow();
+            big_map
+                .get(&TypeId::of::<InstalledRangeCheck<BaseField>>())
+                .and_then(|installed| installed.downcast_ref::<InstalledRangeCheck<BaseField>>())
+                .map(|installed| installed.gadget.clone())
+        }
+    };
+
+    match gadget {
+        Some(gadget) => gadget.enforce_range(limb, num_bits)?,
+        None => BooleanRangeCheck.enforce_range(limb, num_bits)?,
+    }
+
+    InternedRangeChecks::insert(limb, num_bits);
+    Ok(())
+}
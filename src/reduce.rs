@@ -1,4 +1,10 @@
-use crate::params::get_params;
+use crate::graph::GraphScope;
+use crate::params::{
+    get_params, BitDecompositionCache, ConstraintsOpt, DensityOptimization, NonNativeConfig,
+    OptimizationGoal, OptimizationMode, OverflowWarnings,
+};
+use crate::range_check;
+use crate::report::{MemoryStats, Operation, OperationScope};
 use crate::{overhead, AllocatedNonNativeFieldVar};
 use ark_ff::{biginteger::BigInteger, fields::FpParameters, BitIteratorBE};
 use ark_ff::{One, PrimeField, Zero};
@@ -10,40 +16,126 @@ use ark_r1cs_std::{
 };
 use ark_relations::{
     lc,
-    r1cs::{LinearCombination, Result as R1CSResult},
+    r1cs::{
+        ConstraintSystemRef, LinearCombination, Result as R1CSResult, SynthesisError, Variable,
+    },
 };
 use ark_std::{
+    any::TypeId,
+    boxed::Box,
     cmp::{max, min},
+    collections::BTreeMap,
     marker::PhantomData,
     vec,
     vec::Vec,
 };
 use num_bigint::BigUint;
 
+/// The normal forms computed in a constraint system, keyed by the variables of the reduced limbs
+struct NormalFormCache<TargetField: PrimeField, BaseField: PrimeField> {
+    normal_forms: BTreeMap<Vec<Variable>, Vec<(Option<BaseField>, Variable)>>,
+    target_phantom: PhantomData<TargetField>,
+}
+
+impl<TargetField: PrimeField, BaseField: PrimeField> Default
+    for NormalFormCache<TargetField, BaseField>
+{
+    fn default() -> Self {
+        Self {
+            normal_forms: BTreeMap::new(),
+            target_phantom: PhantomData,
+        }
+    }
+}
+
+/// The state before a reduction, from which a `tracing` event describing the reduction is
+/// emitted once it is done
+///
+/// Without the `trace-reductions` feature, this records nothing and emits nothing.
+pub(crate) struct ReductionTrace<BaseField: PrimeField> {
+    #[cfg(feature = "trace-reductions")]
+    operation: &'static str,
+    #[cfg(feature = "trace-reductions")]
+    cs: ConstraintSystemRef<BaseField>,
+    #[cfg(feature = "trace-reductions")]
+    surfeit_before: usize,
+    #[cfg(feature = "trace-reductions")]
+    num_constraints_before: usize,
+    base_phantom: PhantomData<BaseField>,
+}
+
+impl<BaseField: PrimeField> ReductionTrace<BaseField> {
+    /// Record the state before a reduction of an element with `num_of_additions` additions
+    /// over the normal form
+    #[allow(unused_variables)]
+    pub(crate) fn start(
+        operation: &'static str,
+        cs: &ConstraintSystemRef<BaseField>,
+        num_of_additions: BaseField,
+    ) -> Self {
+        Self {
+            #[cfg(feature = "trace-reductions")]
+            operation,
+            #[cfg(feature = "trace-reductions")]
+            cs: cs.clone(),
+            #[cfg(feature = "trace-reductions")]
+            surfeit_before: overhead!(num_of_additions + BaseField::one()),
+            #[cfg(feature = "trace-reductions")]
+            num_constraints_before: cs.num_constraints(),
+            base_phantom: PhantomData,
+        }
+    }
+
+    /// Emit the event for a reduction that produced `num_limbs` limbs with `num_of_additions`
+    /// additions over the normal form
+    #[allow(unused_variables)]
+    pub(crate) fn finish(self, num_limbs: usize, num_of_additions: BaseField) {
+        #[cfg(feature = "trace-reductions")]
+        tracing::info!(
+            target: "r1cs",
+            operation = self.operation,
+            num_limbs,
+            surfeit_before = self.surfeit_before,
+            surfeit_after = overhead!(num_of_additions + BaseField::one()),
+            constraints_added = self.cs.num_constraints() - self.num_constraints_before,
+            "nonnative reduction"
+        );
+    }
+}
+
 /// the collections of methods for reducing the presentations
-pub struct Reducer<TargetField: PrimeField, BaseField: PrimeField> {
+pub struct Reducer<
+    TargetField: PrimeField,
+    BaseField: PrimeField,
+    Opt: OptimizationMode = ConstraintsOpt,
+> {
     pub target_phantom: PhantomData<TargetField>,
     pub base_phantom: PhantomData<BaseField>,
+    pub opt_phantom: PhantomData<Opt>,
 }
 
-impl<TargetField: PrimeField, BaseField: PrimeField> Reducer<TargetField, BaseField> {
+impl<TargetField: PrimeField, BaseField: PrimeField, Opt: OptimizationMode>
+    Reducer<TargetField, BaseField, Opt>
+{
     /// an internal method for checking whether a push operation can be completed for the current gadget;
     /// if not, `reduce_all_limbs`, which reduces without using a push, is used.
     /// this is part of the post-add reduction.
-    pub fn can_safely_push(elem: &AllocatedNonNativeFieldVar<TargetField, BaseField>) -> bool {
-        let params = get_params::<TargetField, BaseField>(&elem.cs);
+    pub fn can_safely_push(
+        elem: &AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>,
+    ) -> R1CSResult<bool> {
+        let params = get_params::<TargetField, BaseField>(&elem.cs)?;
 
         let log = overhead!(elem.num_of_additions_over_normal_form + BaseField::one()) + 1;
-        BaseField::size_in_bits() > params.bits_per_non_top_limb + log + 1
+        Ok(BaseField::size_in_bits() > params.bits_per_non_top_limb + log + 1)
     }
 
     /// an internal method for checking whether the current two elements are ready to multiply;
     /// if not, they would be reduced. This is part of the pre-mul reduction.
     pub fn can_safely_mul(
-        elem: &AllocatedNonNativeFieldVar<TargetField, BaseField>,
-        other: &AllocatedNonNativeFieldVar<TargetField, BaseField>,
-    ) -> bool {
-        let params = get_params::<TargetField, BaseField>(&elem.cs);
+        elem: &AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>,
+        other: &AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>,
+    ) -> R1CSResult<bool> {
+        let params = get_params::<TargetField, BaseField>(&elem.cs)?;
 
         let prod_of_num_of_additions = (elem.num_of_additions_over_normal_form + BaseField::one())
             * (other.num_of_additions_over_normal_form + BaseField::one());
@@ -53,12 +145,8 @@ impl<TargetField: PrimeField, BaseField: PrimeField> Reducer<TargetField, BaseFi
 
         let log_top_limb = overhead!(prod_of_num_of_additions);
         let log_sub_top_limb = overhead!(prod_of_num_of_additions.double());
-        let log_other_limbs_upper_bound = overhead!(prod_of_num_of_additions.mul(
-            &BaseField::from_repr(<BaseField as PrimeField>::BigInt::from(
-                (params.num_limbs) as u64
-            ))
-            .unwrap()
-        ));
+        let log_other_limbs_upper_bound =
+            overhead!(prod_of_num_of_additions.mul(&BaseField::from(params.num_limbs as u64)));
 
         let bits_per_unreduced_top_limb = max(
             2 * (bits_per_top_limb + 1) + log_top_limb + bits_per_non_top_limb + 1,
@@ -67,8 +155,57 @@ impl<TargetField: PrimeField, BaseField: PrimeField> Reducer<TargetField, BaseFi
         let bits_per_unreduced_non_top_limb =
             2 * (bits_per_non_top_limb + 1) + log_other_limbs_upper_bound;
 
-        max(bits_per_unreduced_top_limb, bits_per_unreduced_non_top_limb)
-            < BaseField::size_in_bits()
+        Ok(
+            max(bits_per_unreduced_top_limb, bits_per_unreduced_non_top_limb)
+                < BaseField::size_in_bits(),
+        )
+    }
+
+    /// Allocate a limb that equals the linear combination `lc`, whose value is `value`, as a
+    /// new witness with one constraint for the equality
+    ///
+    /// The limbs of an element may feed many constraints, so a witness keeps the matrices
+    /// lighter than a copy of `lc` in each of them.
+    pub fn alloc_limb_from_lc(
+        cs: &ConstraintSystemRef<BaseField>,
+        value: BaseField,
+        lc: LinearCombination<BaseField>,
+    ) -> R1CSResult<AllocatedFp<BaseField>> {
+        MemoryStats::record_limbs(cs, 1);
+        let limb =
+            AllocatedFp::<BaseField>::new_witness(ark_relations::ns!(cs, "limb"), || Ok(value))?;
+        let limb_lc = LinearCombination::from((BaseField::one(), limb.variable));
+        cs.enforce_constraint(lc!(), lc!(), limb_lc - lc)?;
+
+        Ok(limb)
+    }
+
+    /// Allocate an intermediate value that equals the linear combination `lc`, whose value is
+    /// `value`, and that feeds a single constraint, such as the carry of a limb into the next
+    /// one
+    ///
+    /// If the optimization mode `Opt` is `DensityOpt`, or the density optimization is enabled
+    /// for the constraint system, the value is the symbolic linear combination itself, which
+    /// the constraint system inlines into its only use when it is finalized. This saves the
+    /// witness, the constraint of the equality, and two nonzero entries. Otherwise, it is
+    /// allocated as `alloc_limb_from_lc` does.
+    pub fn alloc_intermediate_from_lc(
+        cs: &ConstraintSystemRef<BaseField>,
+        value: BaseField,
+        lc: LinearCombination<BaseField>,
+    ) -> R1CSResult<AllocatedFp<BaseField>> {
+        if Opt::GOAL == OptimizationGoal::Constraints && !DensityOptimization::is_enabled(cs) {
+            return Self::alloc_limb_from_lc(cs, value, lc);
+        }
+
+        MemoryStats::record_limbs(cs, 1);
+        let variable = cs.new_lc(lc)?;
+        let value = if cs.is_in_setup_mode() {
+            None
+        } else {
+            Some(value)
+        };
+        Ok(AllocatedFp::new(value, variable, cs.clone()))
     }
 
     /// convert limbs to bits (take at most `BaseField::size_in_bits() - 1` bits)
@@ -116,10 +253,18 @@ impl<TargetField: PrimeField, BaseField: PrimeField> Reducer<TargetField, BaseFi
 
     /// Use the `sum of resides` method to reduce the representations, without firstly pushing it to the top
     pub fn reduce_all_limbs(
-        elem: &mut AllocatedNonNativeFieldVar<TargetField, BaseField>,
+        elem: &mut AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>,
     ) -> R1CSResult<()> {
-        let cs = elem.cs.clone();
-        let params = get_params::<TargetField, BaseField>(&cs);
+        let ns = ark_relations::ns!(elem.cs, "nonnative::reduce");
+        let cs = ns.cs();
+        let _scope = OperationScope::start(&cs, Operation::Reduction);
+        let scope = GraphScope::start("reduce_all_limbs", &cs, &[&elem.limbs[..]]);
+        let trace = ReductionTrace::start(
+            "reduce_all_limbs",
+            &cs,
+            elem.num_of_additions_over_normal_form,
+        );
+        let params = get_params::<TargetField, BaseField>(&cs)?;
 
         // almost only used for mandatory reduce, since the values are not pushed first (pushing first provides better efficiency)
         let mut limb_bits = Vec::new();
@@ -148,11 +293,10 @@ impl<TargetField: PrimeField, BaseField: PrimeField> Reducer<TargetField, BaseFi
             + surfeit
         {
             powers_of_2_mod_p.push(
-                AllocatedNonNativeFieldVar::<TargetField, BaseField>::get_limbs_representations(
+                AllocatedNonNativeFieldVar::<TargetField, BaseField, Opt>::get_limbs_representations(
                     &cur,
                     Some(&cs),
-                )
-                .unwrap(),
+                )?,
             );
             cur.double_in_place();
         }
@@ -225,33 +369,28 @@ impl<TargetField: PrimeField, BaseField: PrimeField> Reducer<TargetField, BaseFi
         }
 
         let mut new_limbs_gadget = Vec::<AllocatedFp<BaseField>>::new();
-        for (value, lc) in limbs_value.iter().zip(limbs_lc.iter()) {
-            let value_gadget =
-                AllocatedFp::<BaseField>::new_witness(elem.cs.clone(), || Ok(value))?;
-
-            let value_lc = LinearCombination::from((BaseField::one(), value_gadget.variable));
-
-            cs.enforce_constraint(lc!(), lc!(), value_lc - lc).unwrap();
-
-            new_limbs_gadget.push(value_gadget.clone());
+        for (value, lc) in limbs_value.iter().zip(limbs_lc.into_iter()) {
+            new_limbs_gadget.push(Self::alloc_limb_from_lc(&cs, *value, lc)?);
         }
 
         elem.limbs = new_limbs_gadget;
         elem.num_of_additions_over_normal_form = additions;
+        trace.finish(elem.limbs.len(), additions);
+        scope.finish(Some(&elem.limbs));
 
         Ok(())
     }
 
     /// A subprocedure of the common reduction, which firstly pushes the representations to the top
     pub fn push_to_the_top(
-        elem: &mut AllocatedNonNativeFieldVar<TargetField, BaseField>,
+        elem: &mut AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>,
     ) -> R1CSResult<(
         Vec<Boolean<BaseField>>,
         Vec<BaseField>,
         Vec<LinearCombination<BaseField>>,
     )> {
         let cs = elem.cs.clone();
-        let params = get_params::<TargetField, BaseField>(&cs);
+        let params = get_params::<TargetField, BaseField>(&cs)?;
 
         let surfeit = overhead!(elem.num_of_additions_over_normal_form + BaseField::one()) + 1;
         let surfeit_plus_one = surfeit + 1; // one more bit is added as a result of pushing
@@ -313,14 +452,7 @@ impl<TargetField: PrimeField, BaseField: PrimeField> Reducer<TargetField, BaseFi
                 coeff.double_in_place();
             }
 
-            add =
-                AllocatedFp::<BaseField>::new_witness(ark_relations::ns!(cs, "alloc_add"), || {
-                    Ok(add_value)
-                })?;
-
-            let add_lc = LinearCombination::from((BaseField::one(), add.variable));
-
-            cs.enforce_constraint(lc!(), lc!(), add_lc - lc)?;
+            add = Self::alloc_intermediate_from_lc(&cs, add_value, lc)?;
         }
 
         let mut overhead_bits = Vec::<Boolean<BaseField>>::new();
@@ -366,10 +498,10 @@ impl<TargetField: PrimeField, BaseField: PrimeField> Reducer<TargetField, BaseFi
 
     /// A subprocedure of the common reduction, which pushes the representations to the top and keeps the new top unreduced.
     pub fn push_to_the_top_keep_top(
-        elem: &mut AllocatedNonNativeFieldVar<TargetField, BaseField>,
+        elem: &mut AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>,
     ) -> R1CSResult<(Vec<BaseField>, Vec<LinearCombination<BaseField>>)> {
         let cs = elem.cs.clone();
-        let params = get_params::<TargetField, BaseField>(&cs);
+        let params = get_params::<TargetField, BaseField>(&cs)?;
 
         let surfeit = overhead!(elem.num_of_additions_over_normal_form + BaseField::one()) + 1;
         let surfeit_plus_one = surfeit + 1; // one more bit is added as a result of pushing
@@ -431,10 +563,7 @@ impl<TargetField: PrimeField, BaseField: PrimeField> Reducer<TargetField, BaseFi
                 coeff.double_in_place();
             }
 
-            add = AllocatedFp::<BaseField>::new_witness(cs.clone(), || Ok(add_value))?;
-
-            let add_lc = LinearCombination::from((BaseField::one(), add.variable));
-            cs.enforce_constraint(lc!(), lc!(), add_lc - lc)?;
+            add = Self::alloc_intermediate_from_lc(&cs, add_value, lc)?;
         }
 
         // top limb
@@ -456,12 +585,20 @@ impl<TargetField: PrimeField, BaseField: PrimeField> Reducer<TargetField, BaseFi
 
     /// A full reduction procedure, which pushes the representations to the top first and then reduces it
     pub fn push_and_reduce_the_top(
-        elem: &mut AllocatedNonNativeFieldVar<TargetField, BaseField>,
+        elem: &mut AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>,
     ) -> R1CSResult<()> {
         let surfeit = overhead!(elem.num_of_additions_over_normal_form + BaseField::one()) + 1;
-        let cs = elem.cs.clone();
+        let ns = ark_relations::ns!(elem.cs, "nonnative::reduce");
+        let cs = ns.cs();
+        let _scope = OperationScope::start(&cs, Operation::Reduction);
+        let scope = GraphScope::start("push_and_reduce_the_top", &cs, &[&elem.limbs[..]]);
+        let trace = ReductionTrace::start(
+            "push_and_reduce_the_top",
+            &cs,
+            elem.num_of_additions_over_normal_form,
+        );
 
-        let params = get_params::<TargetField, BaseField>(&cs);
+        let params = get_params::<TargetField, BaseField>(&cs)?;
 
         // push
         let (overhead_bits, mut limbs_value, mut limbs_lc) = Self::push_to_the_top(elem)?;
@@ -474,11 +611,10 @@ impl<TargetField: PrimeField, BaseField: PrimeField> Reducer<TargetField, BaseFi
             + surfeit;
         for _ in 0..=loop_length {
             powers_of_2_mod_p.push(
-                AllocatedNonNativeFieldVar::<TargetField, BaseField>::get_limbs_representations(
+                AllocatedNonNativeFieldVar::<TargetField, BaseField, Opt>::get_limbs_representations(
                     &cur,
                     Some(&cs),
-                )
-                .unwrap(),
+                )?,
             );
             cur.double_in_place();
         }
@@ -503,32 +639,53 @@ impl<TargetField: PrimeField, BaseField: PrimeField> Reducer<TargetField, BaseFi
         }
 
         let mut new_limbs_gadget = Vec::<AllocatedFp<BaseField>>::new();
-        for (value, lc) in limbs_value.iter().zip(limbs_lc.iter()) {
-            let value_gadget = AllocatedFp::<BaseField>::new_witness(
-                ark_relations::ns!(cs, "limb_initial_value_alloc"),
-                || Ok(value),
-            )?;
-
-            let value_lc = (BaseField::one(), value_gadget.variable);
-
-            let final_lc = LinearCombination::from(value_lc) - lc.clone();
-
-            cs.enforce_constraint(lc!(), lc!(), final_lc).unwrap();
-
-            new_limbs_gadget.push(value_gadget.clone());
+        for (value, lc) in limbs_value.iter().zip(limbs_lc.into_iter()) {
+            new_limbs_gadget.push(Self::alloc_limb_from_lc(&cs, *value, lc)?);
         }
 
         elem.limbs = new_limbs_gadget;
         elem.num_of_additions_over_normal_form = additions;
+        trace.finish(elem.limbs.len(), additions);
+        scope.finish(Some(&elem.limbs));
 
         Ok(())
     }
 
+    /// Check whether `elem` may stay unreduced after an addition, i.e., whether it is within
+    /// the maximal surfeit of the configuration and can still be pushed
+    pub(crate) fn can_stay_unreduced(
+        elem: &AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>,
+    ) -> R1CSResult<bool> {
+        let within_max_surfeit = match NonNativeConfig::max_surfeit(&elem.cs) {
+            // `overhead!` is one more than the surfeit `ceil(log2(num_of_additions + 1))`
+            Some(max_surfeit) => {
+                overhead!(elem.num_of_additions_over_normal_form + BaseField::one()) - 1
+                    <= max_surfeit
+            }
+            None => true,
+        };
+
+        Ok(within_max_surfeit && Self::can_safely_push(elem)?)
+    }
+
     /// Reduction to be enforced after additions
     pub fn post_add_reduce(
-        elem: &mut AllocatedNonNativeFieldVar<TargetField, BaseField>,
+        elem: &mut AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>,
     ) -> R1CSResult<()> {
-        if Self::can_safely_push(elem) {
+        if Self::can_stay_unreduced(elem)? {
+            // the limbs of the sum, including the carry, have to stay below the size for which
+            // `can_safely_push` holds
+            if let Some(warnings) = OverflowWarnings::get(&elem.cs) {
+                let params = get_params::<TargetField, BaseField>(&elem.cs)?;
+                warnings.check(
+                    &elem.cs,
+                    "add",
+                    params.bits_per_non_top_limb
+                        + overhead!(elem.num_of_additions_over_normal_form + BaseField::one())
+                        + 1,
+                    BaseField::size_in_bits() - 2,
+                );
+            }
             Ok(())
         } else {
             Self::reduce_all_limbs(elem)
@@ -537,20 +694,21 @@ impl<TargetField: PrimeField, BaseField: PrimeField> Reducer<TargetField, BaseFi
 
     /// Reduction used before multiplication to reduce the representations in a way that allows efficient multiplication
     pub fn pre_mul_reduce(
-        elem: &mut AllocatedNonNativeFieldVar<TargetField, BaseField>,
-        elem_other: &mut AllocatedNonNativeFieldVar<TargetField, BaseField>,
+        elem: &mut AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>,
+        elem_other: &mut AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>,
     ) -> R1CSResult<()> {
-        let params = get_params::<TargetField, BaseField>(&elem.cs);
+        let params = get_params::<TargetField, BaseField>(&elem.cs)?;
 
         if (2 * params.bits_per_top_limb + params.bits_per_non_top_limb + 1
             > BaseField::size_in_bits() - 1)
             || (2 * params.bits_per_non_top_limb + ark_std::log2(params.num_limbs) as usize
                 > BaseField::size_in_bits() - 1)
         {
-            panic!("The current limb parameters do not support multiplication.");
+            // the current limb parameters do not support multiplication
+            return Err(SynthesisError::Unsatisfiable);
         }
 
-        while !Self::can_safely_mul(elem, elem_other) {
+        while !Self::can_safely_mul(elem, elem_other)? {
             if elem.num_of_additions_over_normal_form
                 >= elem_other.num_of_additions_over_normal_form
             {
@@ -563,30 +721,364 @@ impl<TargetField: PrimeField, BaseField: PrimeField> Reducer<TargetField, BaseFi
         Ok(())
     }
 
+    /// The bounds of the limbs of `elem` as the columns of a carry-save sum, i.e.,
+    /// `(num_of_additions + 1) * (2^bits - 1)` for a limb of `bits` bits, the top first
+    pub(crate) fn column_bounds(
+        elem: &AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>,
+    ) -> R1CSResult<Vec<BigUint>> {
+        let params = get_params::<TargetField, BaseField>(&elem.cs)?;
+        let num_of_terms =
+            field_to_biguint(&(elem.num_of_additions_over_normal_form + BaseField::one()));
+
+        Ok((0..params.num_limbs)
+            .map(|i| {
+                let bits = if i == 0 {
+                    params.bits_per_top_limb
+                } else {
+                    params.bits_per_non_top_limb
+                };
+                &num_of_terms * ((BigUint::one() << bits) - 1u64)
+            })
+            .collect())
+    }
+
+    /// Check whether the columns of a carry-save sum whose values are at most `bounds`, the
+    /// top first, still fit into `BaseField` when the carries of the columns below are added
+    pub(crate) fn can_resolve_carries(
+        cs: &ConstraintSystemRef<BaseField>,
+        bounds: &[BigUint],
+    ) -> R1CSResult<bool> {
+        let params = get_params::<TargetField, BaseField>(cs)?;
+
+        let mut carry = BigUint::zero();
+        for bound in bounds.iter().skip(1).rev() {
+            let column = bound + &carry;
+            if column.bits() as usize > BaseField::size_in_bits() - 1 {
+                return Ok(false);
+            }
+            carry = column >> params.bits_per_non_top_limb;
+        }
+        let top_column = &bounds[0] + &carry;
+
+        Ok(top_column.bits() as usize <= BaseField::size_in_bits() - 1)
+    }
+
+    /// Resolve the carries of the columns of a carry-save sum, the top first, whose values are
+    /// at most `bounds`, in one pass from the lowest column to the top, and reduce the bits
+    /// carried out of the top limb with the `sum of residues` method
+    ///
+    /// Each column, with the carry of the column below, is decomposed into as many bits as its
+    /// bound takes, so `can_resolve_carries` has to hold for `bounds`.
+    pub(crate) fn resolve_carries(
+        cs: &ConstraintSystemRef<BaseField>,
+        columns: &[AllocatedFp<BaseField>],
+        bounds: &[BigUint],
+    ) -> R1CSResult<AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>> {
+        let ns = ark_relations::ns!(cs, "nonnative::reduce");
+        let cs = ns.cs();
+        let _scope = OperationScope::start(&cs, Operation::Reduction);
+        let scope = GraphScope::start("resolve_carries", &cs, &[columns]);
+        let params = get_params::<TargetField, BaseField>(&cs)?;
+        // the top column holds at most as many terms in the normal form
+        let num_of_terms = &bounds[0] / ((BigUint::one() << params.bits_per_top_limb) - 1u64);
+        let trace = ReductionTrace::start(
+            "resolve_carries",
+            &cs,
+            biguint_to_field::<BaseField>(&num_of_terms) - BaseField::one(),
+        );
+
+        let mut limbs_value = Vec::<BaseField>::new();
+        let mut limbs_lc = Vec::<LinearCombination<BaseField>>::new();
+        let mut overhead_bits = Vec::<Boolean<BaseField>>::new();
+        let mut carry: Option<AllocatedFp<BaseField>> = None;
+        let mut carry_bound = BigUint::zero();
+
+        for (i, (column, bound)) in columns.iter().zip(bounds.iter()).enumerate().rev() {
+            let column = match &carry {
+                Some(carry) => column.add(carry),
+                None => column.clone(),
+            };
+            let bound = bound + &carry_bound;
+            let bits_per_limb = if i == 0 {
+                params.bits_per_top_limb
+            } else {
+                params.bits_per_non_top_limb
+            };
+
+            let column_bits = Self::limb_to_bits(&column, bound.bits() as usize)?;
+
+            let mut value = BaseField::zero();
+            let mut lc = LinearCombination::<BaseField>::zero();
+            let mut coeff = BaseField::one();
+            for bit in column_bits.iter().rev().take(bits_per_limb) {
+                if bit.value().unwrap_or_default() {
+                    value += &coeff;
+                }
+                lc = &lc + bit.lc() * coeff;
+                coeff.double_in_place();
+            }
+            limbs_value.push(value);
+            limbs_lc.push(lc);
+
+            if i == 0 {
+                overhead_bits.extend(column_bits.iter().rev().skip(bits_per_limb).cloned());
+            } else if column_bits.len() > bits_per_limb {
+                let mut carry_value = BaseField::zero();
+                let mut carry_lc = LinearCombination::<BaseField>::zero();
+                coeff = BaseField::one();
+                for bit in column_bits.iter().rev().skip(bits_per_limb) {
+                    if bit.value().unwrap_or_default() {
+                        carry_value += &coeff;
+                    }
+                    carry_lc = &carry_lc + bit.lc() * coeff;
+                    coeff.double_in_place();
+                }
+                carry = Some(Self::alloc_intermediate_from_lc(
+                    &cs,
+                    carry_value,
+                    carry_lc,
+                )?);
+                carry_bound = bound >> bits_per_limb;
+            } else {
+                carry = None;
+                carry_bound = BigUint::zero();
+            }
+        }
+
+        limbs_value.reverse();
+        limbs_lc.reverse();
+
+        // reduce
+        let num_of_limb_bits =
+            (params.num_limbs - 1) * params.bits_per_non_top_limb + params.bits_per_top_limb;
+        let mut power_of_2 = TargetField::from(2u64).pow(&[num_of_limb_bits as u64]);
+        let mut additions = BaseField::zero();
+        for bit in overhead_bits.iter() {
+            additions += &BaseField::one();
+            let power_of_2_limbs =
+                AllocatedNonNativeFieldVar::<TargetField, BaseField, Opt>::get_limbs_representations(
+                    &power_of_2,
+                    Some(&cs),
+                )?;
+
+            if bit.value().unwrap_or_default() {
+                for (j, power_of_2_limb) in power_of_2_limbs.iter().enumerate() {
+                    limbs_value[j] += power_of_2_limb;
+                }
+            }
+
+            for (j, power_of_2_coeff) in power_of_2_limbs.iter().enumerate() {
+                limbs_lc[j] = &limbs_lc[j] + &(bit.lc() * *power_of_2_coeff);
+            }
+            power_of_2.double_in_place();
+        }
+
+        let mut new_limbs_gadget = Vec::<AllocatedFp<BaseField>>::new();
+        for (value, lc) in limbs_value.iter().zip(limbs_lc.into_iter()) {
+            new_limbs_gadget.push(Self::alloc_limb_from_lc(&cs, *value, lc)?);
+        }
+
+        trace.finish(new_limbs_gadget.len(), additions);
+        scope.finish(Some(&new_limbs_gadget));
+
+        Ok(AllocatedNonNativeFieldVar {
+            cs,
+            limbs: new_limbs_gadget,
+            num_of_additions_over_normal_form: additions,
+            is_in_the_normal_form: false,
+            target_phantom: PhantomData,
+        })
+    }
+
+    /// Replace `elem` by its normal form if the same limbs have been reduced before
+    fn load_normal_form(
+        elem: &mut AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>,
+    ) -> bool {
+        let cs = elem.cs.clone();
+        if let ConstraintSystemRef::CS(v) = &cs {
+            let key: Vec<Variable> = elem.limbs.iter().map(|limb| limb.variable).collect();
+
+            let cs_sys = v.borrow();
+            let big_map = cs_sys.cache_map.borrow();
+            let cached = big_map
+                .get(&TypeId::of::<NormalFormCache<TargetField, BaseField>>())
+                .and_then(|map| map.downcast_ref::<NormalFormCache<TargetField, BaseField>>())
+                .and_then(|map| map.normal_forms.get(&key));
+
+            if let Some(limbs) = cached {
+                elem.limbs = limbs
+                    .iter()
+                    .map(|(value, variable)| AllocatedFp::new(*value, *variable, cs.clone()))
+                    .collect();
+                elem.num_of_additions_over_normal_form = BaseField::zero();
+                elem.is_in_the_normal_form = true;
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Remember `elem` as the normal form of the limbs `key`
+    fn store_normal_form(
+        key: Vec<Variable>,
+        elem: &AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>,
+    ) {
+        if let ConstraintSystemRef::CS(v) = &elem.cs {
+            // only the values and variables are stored, as a `ConstraintSystemRef`
+            // in its own cache map would never be freed
+            let limbs = elem
+                .limbs
+                .iter()
+                .map(|limb| (limb.value().ok(), limb.variable))
+                .collect();
+
+            let cs_sys = v.borrow();
+            let mut big_map = cs_sys.cache_map.borrow_mut();
+            if let Some(cache) = big_map
+                .entry(TypeId::of::<NormalFormCache<TargetField, BaseField>>())
+                .or_insert_with(|| Box::new(NormalFormCache::<TargetField, BaseField>::default()))
+                .downcast_mut::<NormalFormCache<TargetField, BaseField>>()
+            {
+                cache.normal_forms.insert(key, limbs);
+            }
+        }
+    }
+
     /// Reduction to the normal form
+    ///
+    /// The normal form is memoized in the constraint system's cache map, keyed by the limb
+    /// variables, so that reducing the same element again does not emit constraints.
     pub fn pre_eq_reduce(
-        elem: &mut AllocatedNonNativeFieldVar<TargetField, BaseField>,
+        elem: &mut AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>,
     ) -> R1CSResult<()> {
-        let cs = elem.cs.clone();
-        let params = get_params::<TargetField, BaseField>(&cs);
+        if elem.is_in_the_normal_form || Self::load_normal_form(elem) {
+            return Ok(());
+        }
+
+        let _scope = OperationScope::start(&elem.cs, Operation::Reduction);
+        let scope = GraphScope::start("pre_eq_reduce", &elem.cs, &[&elem.limbs[..]]);
+        let key = elem.limbs.iter().map(|limb| limb.variable).collect();
+
+        let value = elem.value().unwrap_or_default();
+        let normal_form_gadget =
+            AllocatedNonNativeFieldVar::<TargetField, BaseField, Opt>::new_witness(
+                elem.cs.clone(),
+                || Ok(value),
+            )?;
 
-        if elem.is_in_the_normal_form {
+        Self::enforce_normal_form(elem, normal_form_gadget)?;
+        Self::store_normal_form(key, elem);
+        scope.finish(Some(&elem.limbs));
+        Ok(())
+    }
+
+    /// Enforce that `elem` equals `normal_form`, which must be in the normal form
+    ///
+    /// If no normal form of `elem` is known, `elem` is checked against `normal_form`
+    /// directly, which saves allocating a normal form of its own, and `normal_form` is
+    /// remembered as its normal form. Otherwise, the limbs are compared.
+    pub(crate) fn enforce_equal_to_normal_form(
+        elem: &AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>,
+        normal_form: &AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>,
+    ) -> R1CSResult<()> {
+        let mut elem_normal = elem.clone();
+        if elem_normal.is_in_the_normal_form || Self::load_normal_form(&mut elem_normal) {
+            for (left, right) in elem_normal.limbs.iter().zip(normal_form.limbs.iter()) {
+                left.conditional_enforce_equal(right, &Boolean::TRUE)?;
+            }
             return Ok(());
         }
 
+        let _scope = OperationScope::start(&elem.cs, Operation::Reduction);
+        let key = elem.limbs.iter().map(|limb| limb.variable).collect();
+        Self::enforce_normal_form(&mut elem_normal, normal_form.clone())?;
+        Self::store_normal_form(key, &elem_normal);
+        Ok(())
+    }
+
+    /// Reduction to the normal form that also outputs the bits of the limbs, big limb and
+    /// most significant bit first. Each limb of the normal form is decomposed only once,
+    /// and the decomposition also serves as the range check of the normal form.
+    pub fn pre_eq_reduce_with_bits(
+        elem: &mut AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>,
+    ) -> R1CSResult<Vec<Boolean<BaseField>>> {
+        let cs = elem.cs.clone();
+        let _scope = OperationScope::start(&cs, Operation::Reduction);
+        let params = get_params::<TargetField, BaseField>(&cs)?;
+
+        let num_bits_of_limb = |i: usize| {
+            if i == 0 {
+                params.bits_per_top_limb
+            } else {
+                params.bits_per_non_top_limb
+            }
+        };
+
+        let mut bits = Vec::new();
+
+        if elem.is_in_the_normal_form || Self::load_normal_form(elem) {
+            let key: Vec<Variable> = elem.limbs.iter().map(|limb| limb.variable).collect();
+            if let Some(bits) = BitDecompositionCache::load(&cs, &key) {
+                return Ok(bits);
+            }
+
+            for (i, limb) in elem.limbs.iter().enumerate() {
+                bits.extend(Self::limb_to_bits(limb, num_bits_of_limb(i))?);
+            }
+            BitDecompositionCache::store(&cs, key, &bits);
+            return Ok(bits);
+        }
+
         let value = elem.value().unwrap_or_default();
-        let normal_form_representations =
-            AllocatedNonNativeFieldVar::<TargetField, BaseField>::get_limbs_representations(
+        let representations =
+            AllocatedNonNativeFieldVar::<TargetField, BaseField, Opt>::get_limbs_representations(
                 &value,
                 Some(&cs),
             )?;
-        let normal_form_gadget =
-            AllocatedNonNativeFieldVar::<TargetField, BaseField>::new_witness(cs.clone(), || {
-                Ok(value)
-            })?;
+
+        let mut limbs = Vec::with_capacity(representations.len());
+        for (i, representation) in representations.iter().enumerate() {
+            let limb = AllocatedFp::<BaseField>::new_witness(
+                ark_relations::ns!(cs, "normal_form_limb"),
+                || Ok(representation),
+            )?;
+            bits.extend(Self::limb_to_bits(&limb, num_bits_of_limb(i))?);
+            limbs.push(limb);
+        }
+
+        let key = elem.limbs.iter().map(|limb| limb.variable).collect();
+        let normal_form_gadget = AllocatedNonNativeFieldVar::<TargetField, BaseField, Opt> {
+            cs,
+            limbs,
+            num_of_additions_over_normal_form: BaseField::one(),
+            is_in_the_normal_form: false,
+            target_phantom: PhantomData,
+        };
+
+        Self::enforce_normal_form(elem, normal_form_gadget)?;
+        Self::store_normal_form(key, elem);
+
+        let normal_form_key = elem.limbs.iter().map(|limb| limb.variable).collect();
+        BitDecompositionCache::store(&elem.cs, normal_form_key, &bits);
+        Ok(bits)
+    }
+
+    /// Enforce that `elem` equals `normal_form_gadget` modulo p and replace `elem` by it
+    pub(crate) fn enforce_normal_form(
+        elem: &mut AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>,
+        normal_form_gadget: AllocatedNonNativeFieldVar<TargetField, BaseField, Opt>,
+    ) -> R1CSResult<()> {
+        let cs = elem.cs.clone();
+        let params = get_params::<TargetField, BaseField>(&cs)?;
+
+        let normal_form_representations: Vec<BaseField> = normal_form_gadget
+            .limbs
+            .iter()
+            .map(|limb| limb.value().unwrap_or_default())
+            .collect();
 
         let p_representations =
-            AllocatedNonNativeFieldVar::<TargetField, BaseField>::get_limbs_representations_from_big_int(
+            AllocatedNonNativeFieldVar::<TargetField, BaseField, Opt>::get_limbs_representations_from_big_int(
                 &<TargetField as PrimeField>::Params::MODULUS,
                 Some(&cs),
             )?;
@@ -594,7 +1086,7 @@ impl<TargetField: PrimeField, BaseField: PrimeField> Reducer<TargetField, BaseFi
         for limb in &p_representations {
             p_gadget_limbs.push(AllocatedFp::<BaseField>::new_constant(cs.clone(), limb)?);
         }
-        let p_gadget = AllocatedNonNativeFieldVar::<TargetField, BaseField> {
+        let p_gadget = AllocatedNonNativeFieldVar::<TargetField, BaseField, Opt> {
             cs: cs.clone(),
             limbs: p_gadget_limbs,
             num_of_additions_over_normal_form: BaseField::one(),
@@ -605,53 +1097,20 @@ impl<TargetField: PrimeField, BaseField: PrimeField> Reducer<TargetField, BaseFi
         let (elem_pushed_to_the_top_limbs_value, elem_pushed_to_the_top_limbs_lc) =
             Self::push_to_the_top_keep_top(elem)?;
 
-        let limbs_to_bigint = |limbs: Vec<BaseField>| {
-            let mut val = BigUint::zero();
-            let mut big_cur = BigUint::one();
-            let two = BigUint::from(2u32);
-            for limb in limbs.iter().rev() {
-                let limb_repr = limb.into_repr().to_bits();
-                let mut small_cur = big_cur.clone();
-                for limb_bit in limb_repr.iter().rev() {
-                    if *limb_bit {
-                        val += &small_cur;
-                    }
-                    small_cur *= 2u32;
-                }
-                big_cur *= two.pow(params.bits_per_non_top_limb as u32);
-            }
-
-            val
-        };
-
-        let bigint_to_basefield = |bigint: BigUint| {
-            let mut val = BaseField::zero();
-            let mut cur = BaseField::one();
-            let bytes = bigint.to_bytes_be();
-
-            let basefield_256 =
-                BaseField::from_repr(<BaseField as PrimeField>::BigInt::from(256)).unwrap();
-
-            for byte in bytes.iter().rev() {
-                let bytes_basefield = BaseField::from(*byte);
-                val += cur * bytes_basefield;
-
-                cur *= &basefield_256;
-            }
-
-            val
-        };
-
-        let elem_bigint = limbs_to_bigint(elem_pushed_to_the_top_limbs_value);
-        let normal_bigint = limbs_to_bigint(normal_form_representations);
-        let p_bigint = limbs_to_bigint(p_representations);
+        let elem_bigint = limbs_to_biguint(
+            &elem_pushed_to_the_top_limbs_value,
+            params.bits_per_non_top_limb,
+        );
+        let normal_bigint =
+            limbs_to_biguint(&normal_form_representations, params.bits_per_non_top_limb);
+        let p_bigint = limbs_to_biguint(&p_representations, params.bits_per_non_top_limb);
 
-        let k = bigint_to_basefield((elem_bigint - normal_bigint) / p_bigint);
+        let k = biguint_to_field::<BaseField>(&((elem_bigint - normal_bigint) / p_bigint));
         let k_gadget = AllocatedFp::<BaseField>::new_witness(cs.clone(), || Ok(k))?;
 
         // k should be smaller than 2^ ((BaseField::size_in_bits() - 1) - max(bits_per_top_limb, bits_per_non_top_limb) - 1)
         // aka, k only has at most (BaseField::size_in_bits() - 1) - max(bits_per_top_limb, bits_per_non_top_limb) - 1 bits.
-        Self::limb_to_bits(
+        range_check::enforce_range(
             &k_gadget,
             (BaseField::size_in_bits() - 1)
                 - max(params.bits_per_top_limb, params.bits_per_non_top_limb)
@@ -662,7 +1121,7 @@ impl<TargetField: PrimeField, BaseField: PrimeField> Reducer<TargetField, BaseFi
         for limb in &p_gadget.limbs {
             kp_gadget_limbs.push(limb.mul(&k_gadget));
         }
-        let kp_gadget = AllocatedNonNativeFieldVar::<TargetField, BaseField> {
+        let kp_gadget = AllocatedNonNativeFieldVar::<TargetField, BaseField, Opt> {
             cs: elem.cs.clone(),
             limbs: kp_gadget_limbs,
             num_of_additions_over_normal_form: elem.num_of_additions_over_normal_form,
@@ -670,7 +1129,25 @@ impl<TargetField: PrimeField, BaseField: PrimeField> Reducer<TargetField, BaseFi
             target_phantom: PhantomData,
         };
 
-        let mut normal_form_plus_kp_gadget = normal_form_gadget.add(&kp_gadget)?;
+        // the sum is added limb by limb rather than with `add`, whose `post_add_reduce` may move
+        // it to another representative, e.g., under a maximal surfeit, which the limbs of
+        // `elem` pushed to the top would no longer match
+        let mut normal_form_plus_kp_gadget =
+            AllocatedNonNativeFieldVar::<TargetField, BaseField, Opt> {
+                cs: cs.clone(),
+                limbs: normal_form_gadget
+                    .limbs
+                    .iter()
+                    .zip(kp_gadget.limbs.iter())
+                    .map(|(normal_form_limb, kp_limb)| normal_form_limb.add(kp_limb))
+                    .collect(),
+                num_of_additions_over_normal_form: normal_form_gadget
+                    .num_of_additions_over_normal_form
+                    + kp_gadget.num_of_additions_over_normal_form
+                    + BaseField::one(),
+                is_in_the_normal_form: false,
+                target_phantom: PhantomData,
+            };
         let (_, normal_form_plus_kp_limbs_lc) =
             Self::push_to_the_top_keep_top(&mut normal_form_plus_kp_gadget)?;
 
@@ -688,3 +1165,48 @@ impl<TargetField: PrimeField, BaseField: PrimeField> Reducer<TargetField, BaseFi
         Ok(())
     }
 }
+
+/// Convert a big integer into a `BigUint`
+pub(crate) fn bigint_to_biguint<B: BigInteger>(bigint: &B) -> BigUint {
+    let mut bytes = Vec::with_capacity(bigint.as_ref().len() * 8);
+    for limb in bigint.as_ref() {
+        bytes.extend_from_slice(&limb.to_le_bytes());
+    }
+    BigUint::from_bytes_le(&bytes)
+}
+
+/// Convert a prime field element into a `BigUint`
+pub(crate) fn field_to_biguint<F: PrimeField>(elem: &F) -> BigUint {
+    bigint_to_biguint(&elem.into_repr())
+}
+
+/// Convert a `BigUint` into a prime field element, reducing modulo the field's prime if needed
+pub(crate) fn biguint_to_field<F: PrimeField>(elem: &BigUint) -> F {
+    F::from_le_bytes_mod_order(&elem.to_bytes_le())
+}
+
+/// The form `p = 2^k - c` of the modulus of `TargetField` as `(k, c)`, if `c` has at most
+/// `k / 2` bits, e.g., for `2^255 - 19` and for the base field of secp256k1
+pub(crate) fn pseudo_mersenne_form<TargetField: PrimeField>() -> Option<(usize, BigUint)> {
+    let modulus = bigint_to_biguint(&<TargetField::Params as FpParameters>::MODULUS);
+    let k = TargetField::size_in_bits();
+    let c = (BigUint::one() << k) - modulus;
+    if c.bits() as usize <= k / 2 {
+        Some((k, c))
+    } else {
+        None
+    }
+}
+
+/// Compute the integer represented by the limbs (big limb first), treating each limb as an integer
+pub(crate) fn limbs_to_biguint<F: PrimeField>(
+    limbs: &[F],
+    bits_per_non_top_limb: usize,
+) -> BigUint {
+    let mut val = BigUint::zero();
+    for limb in limbs.iter() {
+        val <<= bits_per_non_top_limb;
+        val += field_to_biguint(limb);
+    }
+    val
+}
@@ -0,0 +1,609 @@
+#[cfg(feature = "std")]
+use crate::params::HitRate;
+use crate::params::{get_params, ParamsSearching, PredictedCosts, WorkloadProfile};
+use ark_ff::PrimeField;
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use ark_std::{
+    any::TypeId, boxed::Box, cell::Cell, cmp::max, collections::BTreeMap, rc::Rc, vec::Vec,
+};
+
+/// The kinds of nonnative operations that a `ConstraintReport` breaks the costs down into
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Operation {
+    /// Allocating an element
+    Allocation,
+    /// Multiplying the limbs of two elements, without the reduction of the product
+    Multiplication,
+    /// Reducing an element or the result of a multiplication
+    Reduction,
+    /// Enforcing the equality of elements, without the reductions to the normal forms
+    Equality,
+}
+
+impl Operation {
+    /// All the kinds of operations, in the order of the report
+    pub const ALL: [Operation; 4] = [
+        Operation::Allocation,
+        Operation::Multiplication,
+        Operation::Reduction,
+        Operation::Equality,
+    ];
+
+    /// The name of the operation in exported profiles
+    pub fn name(&self) -> &'static str {
+        match self {
+            Operation::Allocation => "allocation",
+            Operation::Multiplication => "multiplication",
+            Operation::Reduction => "reduction",
+            Operation::Equality => "equality",
+        }
+    }
+}
+
+/// The cost of the operations of one kind
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OperationCost {
+    /// The number of operations performed
+    pub num_calls: usize,
+    /// The number of constraints generated
+    pub num_constraints: usize,
+    /// The number of instance and witness variables allocated
+    pub num_variables: usize,
+}
+
+/// A breakdown of the constraints and variables generated by a block of nonnative operations
+///
+/// Each constraint is attributed to the innermost operation generating it, e.g., the
+/// reductions inside a multiplication count as reductions. The constraints generated outside
+/// of the listed operations, such as those of native gadgets, only count towards the totals.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConstraintReport {
+    /// The total number of constraints generated
+    pub num_constraints: usize,
+    /// The total number of instance and witness variables allocated
+    pub num_variables: usize,
+    /// The costs of the allocations
+    pub allocations: OperationCost,
+    /// The costs of the multiplications
+    pub multiplications: OperationCost,
+    /// The costs of the reductions
+    pub reductions: OperationCost,
+    /// The costs of the equality checks
+    pub equalities: OperationCost,
+}
+
+/// A prediction of the cost model of `ParamsSearching` for one kind of operations that
+/// `ConstraintReport::check_prediction` found to differ from the measured constraints beyond
+/// the tolerance
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CostDiscrepancy {
+    /// The workload of the measured operations
+    pub workload: WorkloadProfile,
+    /// The kind of the operations whose constraints differ
+    pub operation: Operation,
+    /// The constraints predicted for the operations of this kind
+    pub predicted: usize,
+    /// The constraints measured for the operations of this kind
+    pub actual: usize,
+}
+
+/// The costs recorded in the cache map while a `ConstraintReport` is being measured
+#[derive(Clone, Default)]
+struct Recorder {
+    costs: BTreeMap<Operation, OperationCost>,
+    /// The constraints and variables of the nested operations, for each open operation
+    open: Vec<(usize, usize)>,
+}
+
+impl ConstraintReport {
+    /// Run `f` on `cs` and report the constraints and variables that it generated
+    ///
+    /// Reports can be nested, in which case the operations of the inner block also count
+    /// towards the outer report.
+    pub fn measure<BaseField: PrimeField, T>(
+        cs: &ConstraintSystemRef<BaseField>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+    ) -> Result<(T, Self), SynthesisError> {
+        let outer = Self::swap_recorder(cs, Some(Recorder::default()));
+        let num_constraints_before = cs.num_constraints();
+        let num_variables_before = num_variables(cs);
+
+        let res = f();
+
+        let num_constraints = cs.num_constraints() - num_constraints_before;
+        let num_variables = num_variables(cs) - num_variables_before;
+        let recorder = Self::swap_recorder(cs, None).unwrap_or_default();
+
+        if let Some(mut outer) = outer {
+            for (operation, cost) in recorder.costs.iter() {
+                let outer_cost = outer.costs.entry(*operation).or_default();
+                outer_cost.num_calls += cost.num_calls;
+                outer_cost.num_constraints += cost.num_constraints;
+                outer_cost.num_variables += cost.num_variables;
+            }
+            if let Some(last) = outer.open.last_mut() {
+                last.0 += num_constraints;
+                last.1 += num_variables;
+            }
+            Self::swap_recorder(cs, Some(outer));
+        }
+
+        let cost = |operation| recorder.costs.get(&operation).cloned().unwrap_or_default();
+        let report = Self {
+            num_constraints,
+            num_variables,
+            allocations: cost(Operation::Allocation),
+            multiplications: cost(Operation::Multiplication),
+            reductions: cost(Operation::Reduction),
+            equalities: cost(Operation::Equality),
+        };
+
+        Ok((res?, report))
+    }
+
+    /// The cost of the operations of the kind `operation`
+    pub fn cost(&self, operation: Operation) -> &OperationCost {
+        match operation {
+            Operation::Allocation => &self.allocations,
+            Operation::Multiplication => &self.multiplications,
+            Operation::Reduction => &self.reductions,
+            Operation::Equality => &self.equalities,
+        }
+    }
+
+    /// Compare the constraints of the multiplications, of the reductions, and of the equality
+    /// checks of the report, one kind at a time, with those that
+    /// `ParamsSearching::predicted_costs` predicts for the same numbers of multiplications and
+    /// equality checks, with the parameters of `cs`
+    ///
+    /// The additions are not recorded, so the prediction assumes that the operands are
+    /// products of products, without additions, and range checks of Boolean constraints. The
+    /// allocations are not compared, as they include the witnesses of the circuit. Return the
+    /// discrepancies, which are also logged as warnings, of the kinds where either number
+    /// exceeds the other by more than `tolerance_percent` percent of it, so that tests catch
+    /// the regressions of the cost model and of the gadgets alike.
+    pub fn check_prediction<TargetField: PrimeField, BaseField: PrimeField>(
+        &self,
+        cs: &ConstraintSystemRef<BaseField>,
+        tolerance_percent: usize,
+    ) -> Result<Vec<CostDiscrepancy>, SynthesisError> {
+        let params = get_params::<TargetField, BaseField>(cs)?;
+        let mut problem =
+            ParamsSearching::new(BaseField::size_in_bits(), TargetField::size_in_bits());
+        problem.num_of_limbs = params.num_limbs;
+        problem.top_limb_size = Some(params.bits_per_top_limb);
+        problem.non_top_limb_size = Some(params.bits_per_non_top_limb);
+
+        let workload = WorkloadProfile {
+            additions: 0,
+            multiplications: self.multiplications.num_calls,
+            equalities: self.equalities.num_calls,
+        };
+        let predicted = match params.equality_layout {
+            None => problem.predicted_costs(&workload),
+            // the equality checks in a layout of their own need no normal forms, and all their
+            // constraints count as those of the equality checks
+            Some(layout) => problem
+                .predicted_costs(&WorkloadProfile {
+                    equalities: 0,
+                    ..workload
+                })
+                .zip(problem.predicted_equality_constraints(Some(&layout), None))
+                .map(|(costs, equality)| PredictedCosts {
+                    equalities: workload.equalities * equality,
+                    ..costs
+                }),
+        }
+        .ok_or(SynthesisError::Unsatisfiable)?;
+
+        let exceeds =
+            |larger: usize, smaller: usize| 100 * larger > (100 + tolerance_percent) * smaller;
+        let mut discrepancies = Vec::new();
+        for (operation, predicted) in [
+            (Operation::Multiplication, predicted.multiplications),
+            (Operation::Reduction, predicted.reductions),
+            (Operation::Equality, predicted.equalities),
+        ]
+        .iter()
+        {
+            let actual = self.cost(*operation).num_constraints;
+            if !exceeds(*predicted, actual) && !exceeds(actual, *predicted) {
+                continue;
+            }
+
+            tracing::warn!(
+                target: "r1cs",
+                "predicted {} constraints of the {}s for {} multiplications and {} equality checks, but measured {}",
+                predicted,
+                operation.name(),
+                workload.multiplications,
+                workload.equalities,
+                actual
+            );
+            discrepancies.push(CostDiscrepancy {
+                workload,
+                operation: *operation,
+                predicted: *predicted,
+                actual,
+            });
+        }
+        Ok(discrepancies)
+    }
+
+    /// Replace the recorder in the cache map of `cs` and return the previous one
+    fn swap_recorder<BaseField: PrimeField>(
+        cs: &ConstraintSystemRef<BaseField>,
+        recorder: Option<Recorder>,
+    ) -> Option<Recorder> {
+        match cs {
+            ConstraintSystemRef::None => None,
+            ConstraintSystemRef::CS(v) => {
+                let cs_sys = v.borrow();
+                let mut big_map = cs_sys.cache_map.borrow_mut();
+                let previous = big_map
+                    .remove(&TypeId::of::<Recorder>())
+                    .and_then(|recorder| recorder.downcast::<Recorder>().ok())
+                    .map(|recorder| *recorder);
+                if let Some(recorder) = recorder {
+                    big_map.insert(TypeId::of::<Recorder>(), Box::new(recorder));
+                }
+                previous
+            }
+        }
+    }
+}
+
+/// The number of instance and witness variables of `cs`
+fn num_variables<BaseField: PrimeField>(cs: &ConstraintSystemRef<BaseField>) -> usize {
+    cs.num_instance_variables() + cs.num_witness_variables()
+}
+
+/// An operation in progress, whose cost is recorded when it is dropped, if a
+/// `ConstraintReport` is being measured, and to which `MemoryStats` attributes the allocations
+/// made outside of the tracing spans, if it is enabled
+pub(crate) struct OperationScope<BaseField: PrimeField> {
+    cs: ConstraintSystemRef<BaseField>,
+    operation: Operation,
+    num_constraints_before: usize,
+    num_variables_before: usize,
+    is_measured: bool,
+    is_tracked: bool,
+}
+
+impl<BaseField: PrimeField> OperationScope<BaseField> {
+    /// Start an operation on `cs`
+    pub(crate) fn start(cs: &ConstraintSystemRef<BaseField>, operation: Operation) -> Option<Self> {
+        if let ConstraintSystemRef::CS(v) = cs {
+            let cs_sys = v.borrow();
+            let mut big_map = cs_sys.cache_map.borrow_mut();
+            let is_measured = match big_map
+                .get_mut(&TypeId::of::<Recorder>())
+                .and_then(|recorder| recorder.downcast_mut::<Recorder>())
+            {
+                Some(recorder) => {
+                    recorder.open.push((0, 0));
+                    true
+                }
+                None => false,
+            };
+            let is_tracked = match big_map
+                .get_mut(&TypeId::of::<MemoryRecorder>())
+                .and_then(|recorder| recorder.downcast_mut::<MemoryRecorder>())
+            {
+                Some(recorder) => {
+                    recorder.operations.push(operation);
+                    true
+                }
+                None => false,
+            };
+
+            if is_measured || is_tracked {
+                return Some(Self {
+                    cs: cs.clone(),
+                    operation,
+                    num_constraints_before: cs.num_constraints(),
+                    num_variables_before: num_variables(cs),
+                    is_measured,
+                    is_tracked,
+                });
+            }
+        }
+        None
+    }
+}
+
+impl<BaseField: PrimeField> Drop for OperationScope<BaseField> {
+    fn drop(&mut self) {
+        let num_constraints = self.cs.num_constraints() - self.num_constraints_before;
+        let num_variables = num_variables(&self.cs) - self.num_variables_before;
+
+        if let ConstraintSystemRef::CS(v) = &self.cs {
+            let cs_sys = v.borrow();
+            let mut big_map = cs_sys.cache_map.borrow_mut();
+            if let Some(recorder) = big_map
+                .get_mut(&TypeId::of::<Recorder>())
+                .and_then(|recorder| recorder.downcast_mut::<Recorder>())
+                .filter(|_| self.is_measured)
+            {
+                let (nested_constraints, nested_variables) =
+                    recorder.open.pop().unwrap_or_default();
+                let cost = recorder.costs.entry(self.operation).or_default();
+                cost.num_calls += 1;
+                cost.num_constraints += num_constraints - nested_constraints;
+                cost.num_variables += num_variables - nested_variables;
+
+                if let Some(last) = recorder.open.last_mut() {
+                    last.0 += num_constraints;
+                    last.1 += num_variables;
+                }
+            }
+            if let Some(recorder) = big_map
+                .get_mut(&TypeId::of::<MemoryRecorder>())
+                .and_then(|recorder| recorder.downcast_mut::<MemoryRecorder>())
+                .filter(|_| self.is_tracked)
+            {
+                recorder.operations.pop();
+            }
+        }
+    }
+}
+
+/// The allocations attributed to one namespace by `MemoryStats`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AllocationChurn {
+    /// The number of limb variables allocated for elements
+    pub num_limb_variables: usize,
+    /// The number of unreduced products created, including their clones
+    pub num_products: usize,
+}
+
+/// Statistics on the memory held by the nonnative gadgets of a constraint system, recorded
+/// while they are enabled with `enable`
+///
+/// The unreduced products, i.e., the `AllocatedNonNativeFieldMulResultVar`s, are tracked from
+/// their creation to their drop, since retaining them is the usual cause of memory blowups.
+/// Each product holds a `ProductHandle` for this, which is empty if the statistics are not
+/// enabled when it is created. The churn is attributed to the name of the innermost tracing
+/// span, i.e., of the `ns!` namespace or of the instrumented gadget, which requires a
+/// subscriber such as `ConstraintLayer` since the namespaces are tracing spans. Without one,
+/// it is attributed to the name of the innermost nonnative operation, e.g., `"reduction"`,
+/// or to `"other"` outside of them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MemoryStats {
+    /// The number of limb variables allocated for elements, which only grows
+    pub num_limb_variables: usize,
+    /// The number of unreduced products alive
+    pub live_products: usize,
+    /// The largest number of unreduced products alive at once
+    pub peak_live_products: usize,
+    /// The number of limbs held by the unreduced products alive
+    pub live_product_limbs: usize,
+    /// The largest number of limbs held by the unreduced products alive at once
+    pub peak_live_product_limbs: usize,
+    /// The allocations of each namespace
+    pub churn: BTreeMap<&'static str, AllocationChurn>,
+}
+
+/// The unreduced products alive, shared by the `MemoryStats` of a constraint system and the
+/// handles of the products, so that the products are dropped without touching the
+/// constraint system
+#[derive(Debug, Default)]
+struct LiveProducts {
+    num_products: Cell<usize>,
+    num_limbs: Cell<usize>,
+}
+
+/// The statistics recorded in the cache map while `MemoryStats` are enabled
+#[derive(Default)]
+struct MemoryRecorder {
+    stats: MemoryStats,
+    live: Rc<LiveProducts>,
+    /// The nonnative operations in progress, innermost last
+    operations: Vec<Operation>,
+}
+
+impl MemoryRecorder {
+    /// The churn of the innermost tracing span, or of the innermost operation without one
+    fn current_churn(&mut self) -> &mut AllocationChurn {
+        let operation = self.operations.last().map_or("other", Operation::name);
+        let name = tracing::Span::current()
+            .metadata()
+            .map_or(operation, |metadata| metadata.name());
+        self.stats.churn.entry(name).or_default()
+    }
+}
+
+/// The handle by which the `MemoryStats` of a constraint system count an unreduced product as
+/// alive until it is dropped
+///
+/// The default handle is empty, as is that of a product created while the statistics are not
+/// enabled, and is not counted.
+#[derive(Debug, Default)]
+pub struct ProductHandle {
+    live: Option<Rc<LiveProducts>>,
+    num_limbs: usize,
+}
+
+impl Drop for ProductHandle {
+    fn drop(&mut self) {
+        if let Some(live) = &self.live {
+            live.num_products
+                .set(live.num_products.get().saturating_sub(1));
+            live.num_limbs
+                .set(live.num_limbs.get().saturating_sub(self.num_limbs));
+        }
+    }
+}
+
+impl MemoryStats {
+    /// Start recording the statistics of `cs`, unless they are already being recorded
+    pub fn enable<BaseField: PrimeField>(cs: &ConstraintSystemRef<BaseField>) {
+        if let ConstraintSystemRef::CS(v) = cs {
+            let cs_sys = v.borrow();
+            let mut big_map = cs_sys.cache_map.borrow_mut();
+            big_map
+                .entry(TypeId::of::<MemoryRecorder>())
+                .or_insert_with(|| Box::new(MemoryRecorder::default()));
+        }
+    }
+
+    /// Stop recording the statistics of `cs` and drop them
+    pub fn disable<BaseField: PrimeField>(cs: &ConstraintSystemRef<BaseField>) {
+        if let ConstraintSystemRef::CS(v) = cs {
+            let cs_sys = v.borrow();
+            let mut big_map = cs_sys.cache_map.borrow_mut();
+            big_map.remove(&TypeId::of::<MemoryRecorder>());
+        }
+    }
+
+    /// Obtain the statistics recorded for `cs` so far, if they are enabled
+    pub fn get<BaseField: PrimeField>(cs: &ConstraintSystemRef<BaseField>) -> Option<Self> {
+        match cs {
+            ConstraintSystemRef::None => None,
+            ConstraintSystemRef::CS(v) => {
+                let cs_sys = v.borrow();
+                let big_map = cs_sys.cache_map.borrow();
+                big_map
+                    .get(&TypeId::of::<MemoryRecorder>())
+                    .and_then(|recorder| recorder.downcast_ref::<MemoryRecorder>())
+                    .map(|recorder| Self {
+                        live_products: recorder.live.num_products.get(),
+                        live_product_limbs: recorder.live.num_limbs.get(),
+                        ..recorder.stats.clone()
+                    })
+            }
+        }
+    }
+
+    /// Record the allocation of `num_limbs` limb variables for an element
+    pub(crate) fn record_limbs<BaseField: PrimeField>(
+        cs: &ConstraintSystemRef<BaseField>,
+        num_limbs: usize,
+    ) {
+        Self::update(cs, |recorder| {
+            recorder.stats.num_limb_variables += num_limbs;
+            recorder.current_churn().num_limb_variables += num_limbs;
+        });
+    }
+
+    /// Record the creation of an unreduced product with `num_limbs` limbs, and return the
+    /// handle that counts it as alive until it is dropped
+    pub(crate) fn record_product<BaseField: PrimeField>(
+        cs: &ConstraintSystemRef<BaseField>,
+        num_limbs: usize,
+    ) -> ProductHandle {
+        let mut handle = ProductHandle::default();
+        Self::update(cs, |recorder| {
+            let live = recorder.live.clone();
+            live.num_products.set(live.num_products.get() + 1);
+            live.num_limbs.set(live.num_limbs.get() + num_limbs);
+
+            let stats = &mut recorder.stats;
+            stats.peak_live_products = max(stats.peak_live_products, live.num_products.get());
+            stats.peak_live_product_limbs =
+                max(stats.peak_live_product_limbs, live.num_limbs.get());
+            recorder.current_churn().num_products += 1;
+
+            handle = ProductHandle {
+                live: Some(live),
+                num_limbs,
+            };
+        });
+        handle
+    }
+
+    /// Apply `f` to the recorder of `cs`, if the statistics are enabled
+    fn update<BaseField: PrimeField>(
+        cs: &ConstraintSystemRef<BaseField>,
+        f: impl FnOnce(&mut MemoryRecorder),
+    ) {
+        if let ConstraintSystemRef::CS(v) = cs {
+            let cs_sys = v.borrow();
+            let mut big_map = cs_sys.cache_map.borrow_mut();
+            if let Some(recorder) = big_map
+                .get_mut(&TypeId::of::<MemoryRecorder>())
+                .and_then(|recorder| recorder.downcast_mut::<MemoryRecorder>())
+            {
+                f(recorder);
+            }
+        }
+    }
+}
+
+/// The formats of the profiles written by `export_profile`
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProfileFormat {
+    /// A JSON object, with an object per operation
+    Json,
+    /// `metric,value` rows, with the metrics of the operations named `operation.field`
+    Csv,
+}
+
+/// Write `report`, and the hit rate of the parameter cache of `cs` if the statistics are
+/// activated, to `writer`
+#[cfg(feature = "std")]
+pub fn export_profile<BaseField: PrimeField, W: std::io::Write>(
+    cs: &ConstraintSystemRef<BaseField>,
+    report: &ConstraintReport,
+    format: ProfileFormat,
+    mut writer: W,
+) -> std::io::Result<()> {
+    let hit_rate = HitRate::get(cs);
+
+    match format {
+        ProfileFormat::Json => {
+            write!(
+                writer,
+                "{{\"num_constraints\":{},\"num_variables\":{},\"operations\":{{",
+                report.num_constraints, report.num_variables
+            )?;
+            for (i, operation) in Operation::ALL.iter().enumerate() {
+                let cost = report.cost(*operation);
+                if i > 0 {
+                    write!(writer, ",")?;
+                }
+                write!(
+                    writer,
+                    "\"{}\":{{\"num_calls\":{},\"num_constraints\":{},\"num_variables\":{}}}",
+                    operation.name(),
+                    cost.num_calls,
+                    cost.num_constraints,
+                    cost.num_variables
+                )?;
+            }
+            write!(writer, "}},\"params_cache\":")?;
+            match hit_rate {
+                Some((hit, miss)) => write!(writer, "{{\"hit\":{},\"miss\":{}}}", hit, miss)?,
+                None => write!(writer, "null")?,
+            }
+            writeln!(writer, "}}")
+        }
+        ProfileFormat::Csv => {
+            writeln!(writer, "metric,value")?;
+            writeln!(writer, "num_constraints,{}", report.num_constraints)?;
+            writeln!(writer, "num_variables,{}", report.num_variables)?;
+            for operation in Operation::ALL.iter() {
+                let cost = report.cost(*operation);
+                writeln!(writer, "{}.num_calls,{}", operation.name(), cost.num_calls)?;
+                writeln!(
+                    writer,
+                    "{}.num_constraints,{}",
+                    operation.name(),
+                    cost.num_constraints
+                )?;
+                writeln!(
+                    writer,
+                    "{}.num_variables,{}",
+                    operation.name(),
+                    cost.num_variables
+                )?;
+            }
+            if let Some((hit, miss)) = hit_rate {
+                writeln!(writer, "params_cache.hit,{}", hit)?;
+                writeln!(writer, "params_cache.miss,{}", miss)?;
+            }
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use crate::NonNativeFieldVar;
+use ark_ec::ProjectiveCurve;
+use ark_ff::PrimeField;
+use ark_r1cs_std::{
+    alloc::{AllocVar, AllocationMode},
+    bits::ToBitsGadget,
+    boolean::Boolean,
+    groups::CurveVar,
+    R1CSVar,
+};
+use ark_relations::r1cs::{ConstraintSystemRef, Namespace, SynthesisError};
+use ark_std::{borrow::Borrow, vec::Vec};
+
+/// A simulated scalar of a curve whose group is native to `BaseField`, as the input of the
+/// scalar multiplications of the curve gadgets of ark-r1cs-std
+///
+/// `CurveVar::scalar_mul_le` takes the bits of the scalar, and the bits of the normal form of
+/// a `NonNativeFieldVar` may encode `k + r` for the modulus `r`, which is a different scalar
+/// for points outside the subgroup of order `r`. The bits of `to_bits_le` are enforced to be
+/// those of the canonical integer, and there are exactly as many as the bits of the modulus,
+/// so the scalar multiplication has a fixed number of steps. The cheaper bits of
+/// `to_non_unique_bits_le` may be used when the base is known to be in the subgroup.
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct ScalarVar<ScalarField: PrimeField, BaseField: PrimeField> {
+    /// The scalar
+    pub scalar: NonNativeFieldVar<ScalarField, BaseField>,
+}
+
+impl<ScalarField: PrimeField, BaseField: PrimeField> ScalarVar<ScalarField, BaseField> {
+    /// Wrap the simulated `scalar`
+    pub fn new(scalar: NonNativeFieldVar<ScalarField, BaseField>) -> Self {
+        Self { scalar }
+    }
+
+    /// Compute `self · base` with the scalar multiplication of the curve gadget `GG`
+    #[tracing::instrument(target = "r1cs", skip(base))]
+    pub fn scalar_mul<C, GG>(&self, base: &GG) -> Result<GG, SynthesisError>
+    where
+        C: ProjectiveCurve<ScalarField = ScalarField>,
+        GG: CurveVar<C, BaseField>,
+    {
+        base.scalar_mul_le(self.to_bits_le()?.iter())
+    }
+}
+
+impl<ScalarField: PrimeField, BaseField: PrimeField> From<NonNativeFieldVar<ScalarField, BaseField>>
+    for ScalarVar<ScalarField, BaseField>
+{
+    fn from(scalar: NonNativeFieldVar<ScalarField, BaseField>) -> Self {
+        Self::new(scalar)
+    }
+}
+
+impl<ScalarField: PrimeField, BaseField: PrimeField> ToBitsGadget<BaseField>
+    for ScalarVar<ScalarField, BaseField>
+{
+    /// Output the `ScalarField::size_in_bits()` little-endian bits of the canonical integer of
+    /// the scalar
+    #[tracing::instrument(target = "r1cs")]
+    fn to_bits_le(&self) -> Result<Vec<Boolean<BaseField>>, SynthesisError> {
+        // the bits of a constant omit the leading zeros, and those of a variable cover all the
+        // limbs, whose bits above the modulus are zero once the bits are canonical
+        let mut bits = self.scalar.to_canonical_bits_le()?;
+        bits.resize(ScalarField::size_in_bits(), Boolean::FALSE);
+        Ok(bits)
+    }
+
+    #[tracing::instrument(target = "r1cs")]
+    fn to_non_unique_bits_le(&self) -> Result<Vec<Boolean<BaseField>>, SynthesisError> {
+        self.scalar.to_non_unique_bits_le()
+    }
+}
+
+impl<ScalarField: PrimeField, BaseField: PrimeField> R1CSVar<BaseField>
+    for ScalarVar<ScalarField, BaseField>
+{
+    type Value = ScalarField;
+
+    fn cs(&self) -> ConstraintSystemRef<BaseField> {
+        self.scalar.cs()
+    }
+
+    fn value(&self) -> Result<Self::Value, SynthesisError> {
+        self.scalar.value()
+    }
+}
+
+impl<ScalarField: PrimeField, BaseField: PrimeField> AllocVar<ScalarField, BaseField>
+    for ScalarVar<ScalarField, BaseField>
+{
+    fn new_variable<T: Borrow<ScalarField>>(
+        cs: impl Into<Namespace<BaseField>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        NonNativeFieldVar::new_variable(cs, f, mode).map(Self::new)
+    }
+}
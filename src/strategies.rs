@@ -0,0 +1,126 @@
+use crate::reduce::{bigint_to_biguint, biguint_to_field};
+use crate::{NonNativeFieldParams, NonNativeFieldVar};
+use ark_ff::{FpParameters, PrimeField};
+use ark_r1cs_std::{alloc::AllocVar, fields::FieldVar};
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use ark_std::{vec, vec::Vec};
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+use proptest::{prelude::*, sample::select};
+
+/// The values of `TargetField` at which the limb arithmetic is the most likely to go wrong
+///
+/// These are 0, 1, 2, `p - 2`, `p - 1`, and the two halves of `p`. With `params`, these also
+/// include the values around the boundaries of the limbs, and those whose lowest limbs have
+/// all their bits set, as long as they are below `p`.
+pub fn edge_cases<TargetField: PrimeField>(
+    params: Option<&NonNativeFieldParams>,
+) -> Vec<TargetField> {
+    let modulus = bigint_to_biguint(&<TargetField::Params as FpParameters>::MODULUS);
+    let mut values = vec![
+        BigUint::zero(),
+        BigUint::one(),
+        BigUint::from(2u64),
+        &modulus - 2u64,
+        &modulus - 1u64,
+        (&modulus - 1u64) >> 1,
+        (&modulus + 1u64) >> 1,
+    ];
+
+    if let Some(params) = params {
+        let mut all_ones = BigUint::zero();
+        for i in 0..params.num_limbs {
+            let position = i * params.bits_per_non_top_limb;
+            let boundary = BigUint::one() << position;
+            values.push(&boundary - 1u64);
+            values.push(&boundary + 1u64);
+            values.push(boundary);
+
+            let num_bits = if i == params.num_limbs - 1 {
+                params.bits_per_top_limb
+            } else {
+                params.bits_per_non_top_limb
+            };
+            all_ones |= ((BigUint::one() << num_bits) - 1u64) << position;
+            values.push(all_ones.clone());
+        }
+    }
+
+    values.sort();
+    values.dedup();
+    values
+        .iter()
+        .filter(|value| *value < &modulus)
+        .map(biguint_to_field)
+        .collect()
+}
+
+/// A strategy for `TargetField` elements, which are drawn from `edge_cases` half of the time
+/// and uniformly otherwise
+pub fn target_field<TargetField: PrimeField>(
+    params: Option<&NonNativeFieldParams>,
+) -> BoxedStrategy<TargetField> {
+    // enough bytes for the bias of the reduction modulo p to be negligible
+    let num_bytes = (TargetField::size_in_bits() + 7) / 8 + 16;
+    let uniform = proptest::collection::vec(any::<u8>(), num_bytes)
+        .prop_map(|bytes| TargetField::from_le_bytes_mod_order(&bytes));
+
+    prop_oneof![select(edge_cases::<TargetField>(params)), uniform].boxed()
+}
+
+/// A `TargetField` element for the arguments of `proptest!`, drawn by `target_field` with the
+/// parameters, if any
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TargetValue<TargetField: PrimeField>(pub TargetField);
+
+impl<TargetField: PrimeField> Arbitrary for TargetValue<TargetField> {
+    type Parameters = Option<NonNativeFieldParams>;
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(params: Self::Parameters) -> Self::Strategy {
+        target_field(params.as_ref()).prop_map(TargetValue).boxed()
+    }
+}
+
+/// A sum of `terms`, which are allocated as witnesses and added one after another, so that
+/// the surfeit of the intermediate results grows up to the reductions
+#[derive(Clone, Debug)]
+pub struct SurfeitSum<TargetField: PrimeField> {
+    /// The terms of the sum
+    pub terms: Vec<TargetField>,
+}
+
+impl<TargetField: PrimeField> SurfeitSum<TargetField> {
+    /// The largest number of terms drawn by the strategy, which exceeds the additions that
+    /// the default parameters allow without a reduction
+    pub const MAX_TERMS: usize = 64;
+
+    /// Allocate the terms in `cs` and add them up, and output the sum together with its
+    /// native value
+    pub fn allocate<BaseField: PrimeField>(
+        &self,
+        cs: ConstraintSystemRef<BaseField>,
+    ) -> Result<(NonNativeFieldVar<TargetField, BaseField>, TargetField), SynthesisError> {
+        let mut sum = NonNativeFieldVar::zero();
+        let mut native = TargetField::zero();
+        for term in self.terms.iter() {
+            let term_var =
+                NonNativeFieldVar::new_witness(ark_relations::ns!(cs, "term"), || Ok(*term))?;
+            sum += &term_var;
+            native += term;
+        }
+
+        Ok((sum, native))
+    }
+}
+
+impl<TargetField: PrimeField> Arbitrary for SurfeitSum<TargetField> {
+    type Parameters = Option<NonNativeFieldParams>;
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(params: Self::Parameters) -> Self::Strategy {
+        proptest::collection::vec(target_field(params.as_ref()), 1..=Self::MAX_TERMS)
+            .prop_map(|terms| Self { terms })
+            .boxed()
+    }
+}
@@ -0,0 +1,123 @@
+use ark_ff::{
+    biginteger::BigInteger64 as BigInteger,
+    fields::{FftParameters, Fp64, Fp64Parameters, FpParameters},
+    PrimeField,
+};
+use ark_std::{vec, vec::Vec};
+
+/// The 13-bit Mersenne prime field, with modulus `2^13 - 1`
+pub type F13 = Fp64<F13Parameters>;
+
+/// The parameters of `F13`
+pub struct F13Parameters;
+
+impl Fp64Parameters for F13Parameters {}
+
+impl FftParameters for F13Parameters {
+    type BigInt = BigInteger;
+
+    const TWO_ADICITY: u32 = 1;
+
+    #[rustfmt::skip]
+    const TWO_ADIC_ROOT_OF_UNITY: BigInteger = BigInteger([4095]);
+}
+
+impl FpParameters for F13Parameters {
+    #[rustfmt::skip]
+    const MODULUS: BigInteger = BigInteger([8191]);
+
+    const MODULUS_BITS: u32 = 13;
+
+    const CAPACITY: u32 = Self::MODULUS_BITS - 1;
+
+    const REPR_SHAVE_BITS: u32 = 51;
+
+    #[rustfmt::skip]
+    const R: BigInteger = BigInteger([4096]);
+
+    #[rustfmt::skip]
+    const R2: BigInteger = BigInteger([2048]);
+
+    const INV: u64 = 4504149450301441;
+
+    /// 17, in the Montgomery form
+    #[rustfmt::skip]
+    const GENERATOR: BigInteger = BigInteger([4104]);
+
+    #[rustfmt::skip]
+    const MODULUS_MINUS_ONE_DIV_TWO: BigInteger = BigInteger([4095]);
+
+    #[rustfmt::skip]
+    const T: BigInteger = BigInteger([4095]);
+
+    #[rustfmt::skip]
+    const T_MINUS_ONE_DIV_TWO: BigInteger = BigInteger([2047]);
+}
+
+/// The 61-bit Mersenne prime field, with modulus `2^61 - 1`
+pub type F61 = Fp64<F61Parameters>;
+
+/// The parameters of `F61`
+pub struct F61Parameters;
+
+impl Fp64Parameters for F61Parameters {}
+
+impl FftParameters for F61Parameters {
+    type BigInt = BigInteger;
+
+    const TWO_ADICITY: u32 = 1;
+
+    #[rustfmt::skip]
+    const TWO_ADIC_ROOT_OF_UNITY: BigInteger = BigInteger([2305843009213693943]);
+}
+
+impl FpParameters for F61Parameters {
+    #[rustfmt::skip]
+    const MODULUS: BigInteger = BigInteger([2305843009213693951]);
+
+    const MODULUS_BITS: u32 = 61;
+
+    const CAPACITY: u32 = Self::MODULUS_BITS - 1;
+
+    const REPR_SHAVE_BITS: u32 = 3;
+
+    #[rustfmt::skip]
+    const R: BigInteger = BigInteger([8]);
+
+    #[rustfmt::skip]
+    const R2: BigInteger = BigInteger([64]);
+
+    const INV: u64 = 2305843009213693953;
+
+    /// 37, in the Montgomery form
+    #[rustfmt::skip]
+    const GENERATOR: BigInteger = BigInteger([296]);
+
+    #[rustfmt::skip]
+    const MODULUS_MINUS_ONE_DIV_TWO: BigInteger = BigInteger([1152921504606846975]);
+
+    #[rustfmt::skip]
+    const T: BigInteger = BigInteger([1152921504606846975]);
+
+    #[rustfmt::skip]
+    const T_MINUS_ONE_DIV_TWO: BigInteger = BigInteger([576460752303423487]);
+}
+
+/// The elements of a field that sit on bit boundaries: `0`, `1`, `-1`, `-2`, and `2^k - 1`,
+/// `2^k`, `2^k + 1` for every `k` below the modulus size
+///
+/// With a small target field, these values hit every limb boundary, so that feeding them and
+/// their pairwise sums and products through the gadgets exercises the carries deterministically.
+pub fn boundary_elements<F: PrimeField>() -> Vec<F> {
+    let mut res = vec![F::zero(), F::one(), -F::one(), -F::one().double()];
+
+    let mut power_of_2 = F::one();
+    for _ in 1..F::size_in_bits() {
+        power_of_2.double_in_place();
+        res.push(power_of_2 - &F::one());
+        res.push(power_of_2);
+        res.push(power_of_2 + &F::one());
+    }
+
+    res
+}
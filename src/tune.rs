@@ -0,0 +1,215 @@
+use crate::params::{set_params, ParamsSearching, WorkloadProfile};
+use crate::{NonNativeFieldParams, NonNativeFieldVar};
+use ark_ff::PrimeField;
+use ark_r1cs_std::{alloc::AllocVar, eq::EqGadget, R1CSVar};
+use ark_relations::r1cs::{ConstraintSystem, SynthesisError};
+use ark_std::vec::Vec;
+use std::time::{Duration, Instant};
+
+/// The measure that `tune` minimizes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TuneObjective {
+    /// The number of constraints
+    Constraints,
+    /// The number of instance and witness variables
+    Variables,
+    /// The wall-clock time of the synthesis, which includes the witness generation
+    SynthesisTime,
+    /// The number of nonzero entries of the matrices `A`, `B`, and `C`
+    Density,
+}
+
+/// The configuration of `tune`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TuneConfig {
+    /// The operations of the microbenchmark
+    pub workload: WorkloadProfile,
+    /// The largest number of limbs of the candidates
+    pub max_num_limbs: usize,
+    /// The measure to minimize
+    pub objective: TuneObjective,
+}
+
+impl Default for TuneConfig {
+    fn default() -> Self {
+        Self {
+            workload: WorkloadProfile {
+                additions: 16,
+                multiplications: 8,
+                equalities: 1,
+            },
+            max_num_limbs: 8,
+            objective: TuneObjective::Constraints,
+        }
+    }
+}
+
+/// The costs of the microbenchmark of `tune` with some parameters
+#[derive(Clone, Debug)]
+pub struct Measurement {
+    /// The parameters
+    pub params: NonNativeFieldParams,
+    /// The number of constraints
+    pub num_constraints: usize,
+    /// The number of instance and witness variables
+    pub num_variables: usize,
+    /// The wall-clock time of the synthesis
+    pub synthesis_time: Duration,
+    /// The number of nonzero entries of the matrix `A`
+    pub a_num_non_zero: usize,
+    /// The number of nonzero entries of the matrix `B`
+    pub b_num_non_zero: usize,
+    /// The number of nonzero entries of the matrix `C`
+    pub c_num_non_zero: usize,
+}
+
+impl Measurement {
+    /// The value of `objective` for the measurement
+    pub fn cost(&self, objective: TuneObjective) -> u128 {
+        match objective {
+            TuneObjective::Constraints => self.num_constraints as u128,
+            TuneObjective::Variables => self.num_variables as u128,
+            TuneObjective::SynthesisTime => self.synthesis_time.as_nanos(),
+            TuneObjective::Density => self.density() as u128,
+        }
+    }
+
+    /// The number of nonzero entries of the three matrices
+    pub fn density(&self) -> usize {
+        self.a_num_non_zero + self.b_num_non_zero + self.c_num_non_zero
+    }
+}
+
+/// The candidate parameters for `TargetField` over `BaseField` with up to `max_num_limbs`
+/// limbs
+///
+/// For every number of limbs with a working layout, these are the layout of the search and
+/// those whose non-top limbs are one bit narrower or wider, as long as they still cover
+/// `TargetField`.
+pub fn candidates<TargetField: PrimeField, BaseField: PrimeField>(
+    max_num_limbs: usize,
+) -> Vec<NonNativeFieldParams> {
+    let target_bits = TargetField::size_in_bits();
+    let mut problem = ParamsSearching::new(BaseField::size_in_bits(), target_bits);
+
+    let mut res = Vec::new();
+    for num_limbs in 2..=max_num_limbs {
+        if problem.solve_with_num_of_limbs(num_limbs).is_none() {
+            continue;
+        }
+        let non_top_limb_size = match problem.non_top_limb_size {
+            Some(non_top_limb_size) => non_top_limb_size,
+            None => continue,
+        };
+
+        for bits_per_non_top_limb in [
+            non_top_limb_size.saturating_sub(1),
+            non_top_limb_size,
+            non_top_limb_size + 1,
+        ]
+        .iter()
+        {
+            let lower_bits = (num_limbs - 1) * bits_per_non_top_limb;
+            if lower_bits >= target_bits || target_bits - lower_bits > *bits_per_non_top_limb {
+                continue;
+            }
+            res.push(NonNativeFieldParams {
+                num_limbs,
+                bits_per_top_limb: target_bits - lower_bits,
+                bits_per_non_top_limb: *bits_per_non_top_limb,
+                equality_layout: None,
+            });
+        }
+    }
+    res
+}
+
+/// Synthesize the microbenchmark of `workload` with `params` and measure its costs
+///
+/// The microbenchmark multiplies an accumulator `multiplications` times, with the additions
+/// spread evenly before the multiplications, and then checks its equality with a witness of
+/// its value `equalities` times. The operands are `-1` and `-2`, whose limbs are full. The
+/// density is that of the matrices after the symbolic linear combinations are inlined. Return
+/// `SynthesisError::Unsatisfiable` if `set_params` rejects `params` or the honest witness does
+/// not satisfy the constraint system, which only shows that the gadgets are complete with
+/// `params`: it proves nothing about their soundness, which rests on the bounds that
+/// `set_params` checks.
+pub fn measure<TargetField: PrimeField, BaseField: PrimeField>(
+    params: &NonNativeFieldParams,
+    workload: &WorkloadProfile,
+) -> Result<Measurement, SynthesisError> {
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+    set_params::<TargetField, BaseField>(&cs, params.clone())?;
+
+    let start = Instant::now();
+    let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "a"),
+        || Ok(-TargetField::one()),
+    )?;
+    let b = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "b"),
+        || Ok(-TargetField::from(2u64)),
+    )?;
+
+    let num_rounds = if workload.multiplications == 0 && workload.additions > 0 {
+        1
+    } else {
+        workload.multiplications
+    };
+    let additions_per_round = workload.additions / num_rounds.max(1);
+    let mut acc = a.clone();
+    for round in 0..num_rounds {
+        for _ in 0..additions_per_round {
+            acc += &b;
+        }
+        if round < workload.multiplications {
+            acc *= &b;
+        }
+    }
+    for _ in 0..workload.equalities {
+        let value = acc.value()?;
+        let expected = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+            ark_relations::ns!(cs, "expected"),
+            || Ok(value),
+        )?;
+        acc.enforce_equal(&expected)?;
+    }
+    let synthesis_time = start.elapsed();
+
+    if !cs.is_satisfied()? {
+        return Err(SynthesisError::Unsatisfiable);
+    }
+
+    cs.inline_all_lcs();
+    let matrices = cs.to_matrices().ok_or(SynthesisError::Unsatisfiable)?;
+
+    Ok(Measurement {
+        params: params.clone(),
+        num_constraints: cs.num_constraints(),
+        num_variables: cs.num_instance_variables() + cs.num_witness_variables(),
+        synthesis_time,
+        a_num_non_zero: matrices.a_num_non_zero,
+        b_num_non_zero: matrices.b_num_non_zero,
+        c_num_non_zero: matrices.c_num_non_zero,
+    })
+}
+
+/// Measure the `candidates` with the microbenchmark of `config`, and output the best
+/// parameters by the objective together with all the measurements
+///
+/// Unlike the analytic model of `ParamsSearching`, this captures the costs of the actual
+/// gadgets and of the machine. Return `SynthesisError::Unsatisfiable` if no candidate works.
+pub fn tune<TargetField: PrimeField, BaseField: PrimeField>(
+    config: &TuneConfig,
+) -> Result<(NonNativeFieldParams, Vec<Measurement>), SynthesisError> {
+    let measurements: Vec<Measurement> = candidates::<TargetField, BaseField>(config.max_num_limbs)
+        .iter()
+        .filter_map(|params| measure::<TargetField, BaseField>(params, &config.workload).ok())
+        .collect();
+
+    let best = measurements
+        .iter()
+        .min_by_key(|measurement| measurement.cost(config.objective))
+        .ok_or(SynthesisError::Unsatisfiable)?;
+    Ok((best.params.clone(), measurements))
+}
@@ -0,0 +1,723 @@
+use crate::reduce::Reducer;
+use ark_ff::{BitIteratorBE, PrimeField};
+use ark_r1cs_std::{
+    alloc::{AllocVar, AllocationMode},
+    bits::{ToBitsGadget, ToBytesGadget},
+    boolean::Boolean,
+    eq::EqGadget,
+    fields::{fp::FpVar, FieldVar},
+    select::CondSelectGadget,
+    uint8::UInt8,
+    R1CSVar,
+};
+use ark_relations::r1cs::{ConstraintSystemRef, Namespace, SynthesisError};
+use ark_std::{borrow::Borrow, cmp::min, vec::Vec};
+use num_bigint::BigUint;
+use num_traits::Zero;
+
+/// The number of 64-bit limbs in a 256-bit integer
+const NUM_LIMBS: usize = 4;
+
+/// A 256-bit unsigned integer with EVM semantics, i.e., arithmetic wraps around modulo `2^256`
+///
+/// The integer is represented by four 64-bit limbs in little-endian order, each of which is a
+/// `BaseField` element range-checked at allocation. The native value is the `[u64; 4]` of those
+/// limbs, as in the common `U256` implementations. The products in `wrapping_mul` require
+/// `BaseField` to have more than 132 bits; otherwise, it returns `SynthesisError::Unsatisfiable`.
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct U256Var<BaseField: PrimeField> {
+    /// The 64-bit limbs, least significant first
+    pub limbs: Vec<FpVar<BaseField>>,
+}
+
+/// Decompose `v`, which must have at most `num_bits` bits, into little-endian bits
+pub(crate) fn to_bits_le_bounded<BaseField: PrimeField>(
+    v: &FpVar<BaseField>,
+    num_bits: usize,
+) -> Result<Vec<Boolean<BaseField>>, SynthesisError> {
+    match v {
+        FpVar::Constant(c) => {
+            let mut bits: Vec<bool> = BitIteratorBE::new(c.into_repr()).collect();
+            bits.reverse();
+            Ok(bits
+                .into_iter()
+                .take(num_bits)
+                .map(Boolean::constant)
+                .collect())
+        }
+        FpVar::Var(v) => {
+            let mut bits = Reducer::<BaseField, BaseField>::limb_to_bits(v, num_bits)?;
+            bits.reverse();
+            Ok(bits)
+        }
+    }
+}
+
+/// Recompose little-endian bits into a `BaseField` element
+pub(crate) fn from_bits_le<BaseField: PrimeField>(bits: &[Boolean<BaseField>]) -> FpVar<BaseField> {
+    let mut res = FpVar::<BaseField>::zero();
+    let mut coeff = BaseField::one();
+    for bit in bits {
+        res += FpVar::from(bit.clone()) * coeff;
+        coeff.double_in_place();
+    }
+    res
+}
+
+/// Split `v`, which must have at most `num_bits` bits, into the lowest 64 bits and the rest
+pub(crate) fn split_limb<BaseField: PrimeField>(
+    v: &FpVar<BaseField>,
+    num_bits: usize,
+) -> Result<(FpVar<BaseField>, Vec<Boolean<BaseField>>), SynthesisError> {
+    let bits = to_bits_le_bounded(v, num_bits)?;
+    Ok((from_bits_le(&bits[..64]), bits[64..].to_vec()))
+}
+
+/// Compute the integer represented by 64-bit little-endian limbs
+pub(crate) fn u64_limbs_to_biguint<BaseField: PrimeField>(
+    limbs: &[FpVar<BaseField>],
+) -> Result<BigUint, SynthesisError> {
+    let mut res = BigUint::zero();
+    for limb in limbs.iter().rev() {
+        res <<= 64;
+        res += limb.value()?.into_repr().as_ref()[0];
+    }
+    Ok(res)
+}
+
+/// Allocate `num_limbs` range-checked 64-bit little-endian limbs of the witness `value`
+pub(crate) fn alloc_limbs<BaseField: PrimeField>(
+    cs: &ConstraintSystemRef<BaseField>,
+    value: Result<BigUint, SynthesisError>,
+    num_limbs: usize,
+) -> Result<Vec<FpVar<BaseField>>, SynthesisError> {
+    let digits = value.map(|v| v.to_u64_digits());
+    let mut limbs = Vec::with_capacity(num_limbs);
+    for i in 0..num_limbs {
+        let limb = FpVar::new_witness(ark_relations::ns!(cs, "limb"), || {
+            digits
+                .clone()
+                .map(|d| BaseField::from(d.get(i).copied().unwrap_or(0)))
+        })?;
+        to_bits_le_bounded(&limb, 64)?;
+        limbs.push(limb);
+    }
+    Ok(limbs)
+}
+
+/// Compute `a * b + c` as `a.len() + b.len()` normalized 64-bit little-endian limbs, where
+/// `c` has fewer limbs than the product
+pub(crate) fn mul_add_limbs<BaseField: PrimeField>(
+    a: &[FpVar<BaseField>],
+    b: &[FpVar<BaseField>],
+    c: &[FpVar<BaseField>],
+) -> Result<Vec<FpVar<BaseField>>, SynthesisError> {
+    mul_add_low_limbs(a, b, c, a.len() + b.len())
+}
+
+/// Compute the lowest `num_limbs` normalized 64-bit little-endian limbs of `a * b + c`, i.e.,
+/// `a * b + c mod 2^(64 * num_limbs)`, without the columns above them
+pub(crate) fn mul_add_low_limbs<BaseField: PrimeField>(
+    a: &[FpVar<BaseField>],
+    b: &[FpVar<BaseField>],
+    c: &[FpVar<BaseField>],
+    num_limbs: usize,
+) -> Result<Vec<FpVar<BaseField>>, SynthesisError> {
+    // each column is at most min(a.len(), b.len()) products of 64-bit limbs, one limb of c,
+    // and a carry, so it is below (min(a.len(), b.len()) + 1) * 2^128
+    let column_bits = 128 + ark_std::log2(min(a.len(), b.len()) + 1) as usize;
+    if BaseField::size_in_bits() <= column_bits + 1 {
+        return Err(SynthesisError::Unsatisfiable);
+    }
+
+    let mut carry = FpVar::<BaseField>::zero();
+    let mut limbs = Vec::with_capacity(num_limbs);
+    for k in 0..num_limbs {
+        let mut column = carry;
+        if let Some(c) = c.get(k) {
+            column += c;
+        }
+        for (i, a) in a.iter().enumerate().take(k + 1) {
+            if let Some(b) = b.get(k - i) {
+                column += a * b;
+            }
+        }
+
+        let (limb, high) = split_limb(&column, column_bits)?;
+        limbs.push(limb);
+        carry = from_bits_le(&high);
+    }
+
+    Ok(limbs)
+}
+
+/// Split the 64-bit `limb` into its lowest `offset` bits and the rest, as witnesses whose
+/// recomposition and sizes are enforced
+fn split_at_bit<BaseField: PrimeField>(
+    limb: &FpVar<BaseField>,
+    offset: usize,
+) -> Result<(FpVar<BaseField>, FpVar<BaseField>), SynthesisError> {
+    if offset == 0 {
+        return Ok((FpVar::zero(), limb.clone()));
+    }
+
+    let value = limb.value().map(|v| v.into_repr().as_ref()[0]);
+    let mask = (1u64 << offset) - 1;
+    let power_of_2 = BaseField::from(1u64 << offset);
+    if let FpVar::Constant(_) = limb {
+        let value = value?;
+        return Ok((
+            FpVar::Constant(BaseField::from(value & mask)),
+            FpVar::Constant(BaseField::from(value >> offset)),
+        ));
+    }
+
+    let cs = limb.cs();
+    let low = FpVar::new_witness(ark_relations::ns!(cs, "low"), || {
+        value.map(|v| BaseField::from(v & mask))
+    })?;
+    let high = FpVar::new_witness(ark_relations::ns!(cs, "high"), || {
+        value.map(|v| BaseField::from(v >> offset))
+    })?;
+    to_bits_le_bounded(&low, offset)?;
+    to_bits_le_bounded(&high, 64 - offset)?;
+    (&low + &high * power_of_2).enforce_equal(limb)?;
+
+    Ok((low, high))
+}
+
+/// Compute `a + b + carry` on 64-bit little-endian limbs of the same length, returning the
+/// wrapped sum and the carry-out bit
+pub(crate) fn add_with_carry_limbs<BaseField: PrimeField>(
+    a: &[FpVar<BaseField>],
+    b: &[FpVar<BaseField>],
+    carry: FpVar<BaseField>,
+) -> Result<(Vec<FpVar<BaseField>>, Boolean<BaseField>), SynthesisError> {
+    let mut carry = carry;
+    let mut carry_out = Boolean::constant(false);
+    let mut limbs = Vec::with_capacity(a.len());
+    for (a, b) in a.iter().zip(b.iter()) {
+        let (limb, high) = split_limb(&(a + b + &carry), 65)?;
+        limbs.push(limb);
+        carry_out = high[0].clone();
+        carry = FpVar::from(carry_out.clone());
+    }
+
+    Ok((limbs, carry_out))
+}
+
+/// Compute `a - b` on 64-bit little-endian limbs of the same length, returning the wrapped
+/// difference and whether no borrow occurred, i.e., whether `a >= b`
+pub(crate) fn sub_with_borrow_limbs<BaseField: PrimeField>(
+    a: &[FpVar<BaseField>],
+    b: &[FpVar<BaseField>],
+) -> Result<(Vec<FpVar<BaseField>>, Boolean<BaseField>), SynthesisError> {
+    // a - b = a + (2^(64n) - 1 - b) + 1 mod 2^(64n), and the carry-out is set iff a >= b
+    let max_limb = BaseField::from(u64::max_value());
+    let not_b: Vec<FpVar<BaseField>> = b
+        .iter()
+        .map(|limb| FpVar::Constant(max_limb) - limb)
+        .collect();
+    add_with_carry_limbs(a, &not_b, FpVar::one())
+}
+
+/// Divide the integer `value` given by normalized 64-bit little-endian limbs by `modulus`,
+/// and output the limbs of the quotient `q` and the remainder `r`, which has as many limbs as
+/// `modulus`
+///
+/// Both are witnesses, with `value = q * modulus + r` enforced limb by limb and
+/// `r < modulus` enforced, which requires `modulus` to be nonzero.
+pub(crate) fn div_rem_limbs<BaseField: PrimeField>(
+    value_limbs: &[FpVar<BaseField>],
+    modulus: &[FpVar<BaseField>],
+) -> Result<(Vec<FpVar<BaseField>>, Vec<FpVar<BaseField>>), SynthesisError> {
+    let cs = value_limbs.cs().or(modulus.cs());
+    let value = u64_limbs_to_biguint(value_limbs);
+    let modulus_value = u64_limbs_to_biguint(modulus);
+    let division = value.and_then(|v| {
+        let m = modulus_value?;
+        if m.is_zero() {
+            Err(SynthesisError::Unsatisfiable)
+        } else {
+            Ok((&v / &m, &v % &m))
+        }
+    });
+
+    if cs == ConstraintSystemRef::None {
+        let (q, r) = division?;
+        let constant_limbs = |v: BigUint, num_limbs: usize| {
+            let digits = v.to_u64_digits();
+            (0..num_limbs)
+                .map(|i| FpVar::Constant(BaseField::from(digits.get(i).copied().unwrap_or(0))))
+                .collect::<Vec<_>>()
+        };
+        return Ok((
+            constant_limbs(q, value_limbs.len()),
+            constant_limbs(r, modulus.len()),
+        ));
+    }
+
+    let q = alloc_limbs(&cs, division.clone().map(|(q, _)| q), value_limbs.len())?;
+    let r = alloc_limbs(&cs, division.map(|(_, r)| r), modulus.len())?;
+
+    let rhs = mul_add_limbs(&q, modulus, &r)?;
+    let (rhs_low, rhs_high) = rhs.split_at(value_limbs.len());
+    rhs_low.enforce_equal(value_limbs)?;
+    for limb in rhs_high {
+        limb.enforce_equal(&FpVar::zero())?;
+    }
+    sub_with_borrow_limbs(&r, modulus)?
+        .1
+        .enforce_equal(&Boolean::FALSE)?;
+
+    Ok((q, r))
+}
+
+impl<BaseField: PrimeField> U256Var<BaseField> {
+    /// Construct a constant
+    pub fn constant(value: [u64; NUM_LIMBS]) -> Self {
+        Self {
+            limbs: value
+                .iter()
+                .map(|limb| FpVar::Constant(BaseField::from(*limb)))
+                .collect(),
+        }
+    }
+
+    /// The constant zero
+    pub fn zero() -> Self {
+        Self::constant([0u64; NUM_LIMBS])
+    }
+
+    /// Compute `self - other`, returning the wrapped difference and whether no borrow occurred
+    fn sub_with_borrow(&self, other: &Self) -> Result<(Self, Boolean<BaseField>), SynthesisError> {
+        let (limbs, no_borrow) = sub_with_borrow_limbs(&self.limbs, &other.limbs)?;
+        Ok((Self { limbs }, no_borrow))
+    }
+
+    /// Compute `self + other mod 2^256`
+    #[tracing::instrument(target = "r1cs")]
+    pub fn wrapping_add(&self, other: &Self) -> Result<Self, SynthesisError> {
+        Ok(Self {
+            limbs: add_with_carry_limbs(&self.limbs, &other.limbs, FpVar::zero())?.0,
+        })
+    }
+
+    /// Compute `self - other mod 2^256`
+    #[tracing::instrument(target = "r1cs")]
+    pub fn wrapping_sub(&self, other: &Self) -> Result<Self, SynthesisError> {
+        Ok(self.sub_with_borrow(other)?.0)
+    }
+
+    /// Compute `self * other mod 2^256`
+    #[tracing::instrument(target = "r1cs")]
+    pub fn wrapping_mul(&self, other: &Self) -> Result<Self, SynthesisError> {
+        Ok(Self {
+            limbs: mul_add_low_limbs(&self.limbs, &other.limbs, &[], NUM_LIMBS)?,
+        })
+    }
+
+    /// Divide the integer `value` given by normalized 64-bit little-endian limbs by `modulus`
+    fn div_rem_by(
+        value_limbs: &[FpVar<BaseField>],
+        modulus: &Self,
+    ) -> Result<(Vec<FpVar<BaseField>>, Self), SynthesisError> {
+        let (q, r) = div_rem_limbs(value_limbs, &modulus.limbs)?;
+        Ok((q, Self { limbs: r }))
+    }
+
+    /// Compute `(self + other) mod modulus` for a `modulus` that may be a witness, as the EVM
+    /// `ADDMOD`, except that a zero `modulus` is unsatisfiable
+    #[tracing::instrument(target = "r1cs")]
+    pub fn add_mod(&self, other: &Self, modulus: &Self) -> Result<Self, SynthesisError> {
+        let sum = mul_add_limbs(&self.limbs, &[FpVar::one()], &other.limbs)?;
+        Ok(Self::div_rem_by(&sum, modulus)?.1)
+    }
+
+    /// Compute `(self * other) mod modulus` for a `modulus` that may be a witness, as the EVM
+    /// `MULMOD`, except that a zero `modulus` is unsatisfiable
+    ///
+    /// This enforces `self * other = q * modulus + r` over the integers, with the 512-bit
+    /// quotient `q` and the remainder `r` range-checked and `r < modulus`.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn mul_mod(&self, other: &Self, modulus: &Self) -> Result<Self, SynthesisError> {
+        let product = mul_add_limbs(&self.limbs, &other.limbs, &[])?;
+        Ok(Self::div_rem_by(&product, modulus)?.1)
+    }
+
+    /// Compute the Euclidean division of `self` by `divisor`, i.e., the quotient `q` and the
+    /// remainder `r` with `self = q * divisor + r` and `r < divisor`
+    ///
+    /// Unlike the EVM `DIV` and `MOD`, a zero `divisor` is unsatisfiable.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn div_rem(&self, divisor: &Self) -> Result<(Self, Self), SynthesisError> {
+        let (q, r) = Self::div_rem_by(&self.limbs, divisor)?;
+        Ok((Self { limbs: q }, r))
+    }
+
+    /// Compute `self >> k`, the quotient of `self` by `2^k`
+    ///
+    /// Every limb from the one holding the bit `k` is split there into a low and a high part,
+    /// which are witnesses with their recomposition and sizes enforced, and the result is
+    /// assembled from the parts.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn shr(&self, k: usize) -> Result<Self, SynthesisError> {
+        if k >= NUM_LIMBS * 64 {
+            return Ok(Self::zero());
+        }
+
+        let (shift, offset) = (k / 64, k % 64);
+        let parts = self.limbs[shift..]
+            .iter()
+            .map(|limb| split_at_bit(limb, offset))
+            .collect::<Result<Vec<_>, SynthesisError>>()?;
+
+        // the low part of the next limb lands right above the high part of this one
+        let carry_coeff = BaseField::from(1u128 << (64 - offset));
+        let mut limbs = Vec::with_capacity(NUM_LIMBS);
+        for i in 0..NUM_LIMBS {
+            limbs.push(match (parts.get(i), parts.get(i + 1)) {
+                (Some((_, high)), Some((low, _))) => high + low * carry_coeff,
+                (Some((_, high)), None) => high.clone(),
+                _ => FpVar::zero(),
+            });
+        }
+
+        Ok(Self { limbs })
+    }
+
+    /// Compute `self mod 2^k`, the lowest `k` bits of `self`
+    ///
+    /// The limb holding the bit `k` is split there as in `shr`, and the limbs above it are
+    /// dropped.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn truncate_to_bits(&self, k: usize) -> Result<Self, SynthesisError> {
+        if k >= NUM_LIMBS * 64 {
+            return Ok(self.clone());
+        }
+
+        let (shift, offset) = (k / 64, k % 64);
+        let mut limbs = self.limbs[..shift].to_vec();
+        limbs.push(split_at_bit(&self.limbs[shift], offset)?.0);
+        limbs.resize(NUM_LIMBS, FpVar::zero());
+
+        Ok(Self { limbs })
+    }
+
+    /// Output `self < other`
+    #[tracing::instrument(target = "r1cs")]
+    pub fn is_lt(&self, other: &Self) -> Result<Boolean<BaseField>, SynthesisError> {
+        Ok(self.sub_with_borrow(other)?.1.not())
+    }
+
+    /// Output `self <= other`
+    #[tracing::instrument(target = "r1cs")]
+    pub fn is_le(&self, other: &Self) -> Result<Boolean<BaseField>, SynthesisError> {
+        other.is_ge(self)
+    }
+
+    /// Output `self > other`
+    #[tracing::instrument(target = "r1cs")]
+    pub fn is_gt(&self, other: &Self) -> Result<Boolean<BaseField>, SynthesisError> {
+        other.is_lt(self)
+    }
+
+    /// Output `self >= other`
+    #[tracing::instrument(target = "r1cs")]
+    pub fn is_ge(&self, other: &Self) -> Result<Boolean<BaseField>, SynthesisError> {
+        Ok(self.sub_with_borrow(other)?.1)
+    }
+
+    /// Construct from 32 little-endian bytes
+    pub fn from_bytes_le(bytes: &[UInt8<BaseField>]) -> Result<Self, SynthesisError> {
+        if bytes.len() != NUM_LIMBS * 8 {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+
+        let bits = bytes.to_bits_le()?;
+        Ok(Self {
+            limbs: bits.chunks(64).map(from_bits_le).collect(),
+        })
+    }
+
+    /// Construct from 32 big-endian bytes, the byte order of EVM words
+    pub fn from_bytes_be(bytes: &[UInt8<BaseField>]) -> Result<Self, SynthesisError> {
+        let mut bytes = bytes.to_vec();
+        bytes.reverse();
+        Self::from_bytes_le(&bytes)
+    }
+
+    /// Output the 32 big-endian bytes, the byte order of EVM words
+    #[tracing::instrument(target = "r1cs")]
+    pub fn to_bytes_be(&self) -> Result<Vec<UInt8<BaseField>>, SynthesisError> {
+        let mut bytes = self.to_bytes()?;
+        bytes.reverse();
+        Ok(bytes)
+    }
+}
+
+impl<BaseField: PrimeField> R1CSVar<BaseField> for U256Var<BaseField> {
+    type Value = [u64; NUM_LIMBS];
+
+    fn cs(&self) -> ConstraintSystemRef<BaseField> {
+        self.limbs.as_slice().cs()
+    }
+
+    fn value(&self) -> Result<Self::Value, SynthesisError> {
+        let mut res = [0u64; NUM_LIMBS];
+        for (r, limb) in res.iter_mut().zip(self.limbs.iter()) {
+            *r = limb.value()?.into_repr().as_ref()[0];
+        }
+        Ok(res)
+    }
+}
+
+impl<BaseField: PrimeField> AllocVar<[u64; NUM_LIMBS], BaseField> for U256Var<BaseField> {
+    fn new_variable<T: Borrow<[u64; NUM_LIMBS]>>(
+        cs: impl Into<Namespace<BaseField>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let ns = cs.into();
+        let cs = ns.cs();
+
+        if mode == AllocationMode::Constant {
+            return Ok(Self::constant(*f()?.borrow()));
+        }
+
+        let value = f().map(|v| *v.borrow());
+        let mut limbs = Vec::with_capacity(NUM_LIMBS);
+        for i in 0..NUM_LIMBS {
+            let limb = FpVar::new_variable(
+                ark_relations::ns!(cs, "limb"),
+                || value.map(|v| BaseField::from(v[i])),
+                mode,
+            )?;
+            to_bits_le_bounded(&limb, 64)?;
+            limbs.push(limb);
+        }
+
+        Ok(Self { limbs })
+    }
+}
+
+impl<BaseField: PrimeField> EqGadget<BaseField> for U256Var<BaseField> {
+    #[tracing::instrument(target = "r1cs")]
+    fn is_eq(&self, other: &Self) -> Result<Boolean<BaseField>, SynthesisError> {
+        self.limbs.is_eq(&other.limbs)
+    }
+
+    #[tracing::instrument(target = "r1cs")]
+    fn conditional_enforce_equal(
+        &self,
+        other: &Self,
+        should_enforce: &Boolean<BaseField>,
+    ) -> Result<(), SynthesisError> {
+        self.limbs
+            .conditional_enforce_equal(&other.limbs, should_enforce)
+    }
+}
+
+impl<BaseField: PrimeField> CondSelectGadget<BaseField> for U256Var<BaseField> {
+    #[tracing::instrument(target = "r1cs")]
+    fn conditionally_select(
+        cond: &Boolean<BaseField>,
+        true_value: &Self,
+        false_value: &Self,
+    ) -> Result<Self, SynthesisError> {
+        let mut limbs = Vec::with_capacity(NUM_LIMBS);
+        for (x, y) in true_value.limbs.iter().zip(false_value.limbs.iter()) {
+            limbs.push(FpVar::conditionally_select(cond, x, y)?);
+        }
+        Ok(Self { limbs })
+    }
+}
+
+impl<BaseField: PrimeField> ToBitsGadget<BaseField> for U256Var<BaseField> {
+    #[tracing::instrument(target = "r1cs")]
+    fn to_bits_le(&self) -> Result<Vec<Boolean<BaseField>>, SynthesisError> {
+        let mut bits = Vec::with_capacity(NUM_LIMBS * 64);
+        for limb in self.limbs.iter() {
+            bits.extend(to_bits_le_bounded(limb, 64)?);
+        }
+        Ok(bits)
+    }
+}
+
+impl<BaseField: PrimeField> ToBytesGadget<BaseField> for U256Var<BaseField> {
+    /// Output the 32 little-endian bytes
+    #[tracing::instrument(target = "r1cs")]
+    fn to_bytes(&self) -> Result<Vec<UInt8<BaseField>>, SynthesisError> {
+        Ok(self
+            .to_bits_le()?
+            .chunks(8)
+            .map(UInt8::from_bits_le)
+            .collect())
+    }
+}
+
+/// A 256-bit signed integer in two's complement with EVM semantics, on top of `U256Var`
+///
+/// Addition, subtraction, and multiplication are those of the underlying unsigned integer,
+/// since two's complement wraps around modulo `2^256` the same way. The comparisons flip the
+/// sign bits, which maps `[-2^255, 2^255)` monotonically onto `[0, 2^256)`. The native value is
+/// the `[u64; 4]` of the two's complement, as for `U256Var`.
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct I256Var<BaseField: PrimeField> {
+    /// The two's complement, as an unsigned integer
+    pub value: U256Var<BaseField>,
+}
+
+impl<BaseField: PrimeField> I256Var<BaseField> {
+    /// Interpret the unsigned integer `value` as a two's complement
+    pub fn new(value: U256Var<BaseField>) -> Self {
+        Self { value }
+    }
+
+    /// Construct a constant from a two's complement
+    pub fn constant(value: [u64; NUM_LIMBS]) -> Self {
+        Self::new(U256Var::constant(value))
+    }
+
+    /// The constant zero
+    pub fn zero() -> Self {
+        Self::new(U256Var::zero())
+    }
+
+    /// Construct `-magnitude` if `is_negative` is set, and `magnitude` otherwise
+    ///
+    /// The magnitude is not checked to be at most `2^255`, so larger ones wrap around.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn from_sign_and_magnitude(
+        is_negative: &Boolean<BaseField>,
+        magnitude: &U256Var<BaseField>,
+    ) -> Result<Self, SynthesisError> {
+        let negated = U256Var::zero().wrapping_sub(magnitude)?;
+        Ok(Self::new(is_negative.select(&negated, magnitude)?))
+    }
+
+    /// Output whether `self` is negative, i.e., its sign bit
+    #[tracing::instrument(target = "r1cs")]
+    pub fn is_negative(&self) -> Result<Boolean<BaseField>, SynthesisError> {
+        Ok(to_bits_le_bounded(&self.value.limbs[NUM_LIMBS - 1], 64)?[63].clone())
+    }
+
+    /// Compute `-self mod 2^256`, so that `-(-2^255)` wraps to `-2^255`
+    #[tracing::instrument(target = "r1cs")]
+    pub fn wrapping_neg(&self) -> Result<Self, SynthesisError> {
+        Ok(Self::new(U256Var::zero().wrapping_sub(&self.value)?))
+    }
+
+    /// Output the magnitude `|self|`, which is at most `2^255` and thus fits in `U256Var`
+    #[tracing::instrument(target = "r1cs")]
+    pub fn abs(&self) -> Result<U256Var<BaseField>, SynthesisError> {
+        let negated = self.wrapping_neg()?;
+        self.is_negative()?.select(&negated.value, &self.value)
+    }
+
+    /// Compute `self + other mod 2^256`
+    #[tracing::instrument(target = "r1cs")]
+    pub fn wrapping_add(&self, other: &Self) -> Result<Self, SynthesisError> {
+        Ok(Self::new(self.value.wrapping_add(&other.value)?))
+    }
+
+    /// Compute `self - other mod 2^256`
+    #[tracing::instrument(target = "r1cs")]
+    pub fn wrapping_sub(&self, other: &Self) -> Result<Self, SynthesisError> {
+        Ok(Self::new(self.value.wrapping_sub(&other.value)?))
+    }
+
+    /// Compute `self * other mod 2^256`
+    #[tracing::instrument(target = "r1cs")]
+    pub fn wrapping_mul(&self, other: &Self) -> Result<Self, SynthesisError> {
+        Ok(Self::new(self.value.wrapping_mul(&other.value)?))
+    }
+
+    /// Flip the sign bit, which adds `2^255` and maps the signed order onto the unsigned one
+    fn flip_sign(&self) -> Result<U256Var<BaseField>, SynthesisError> {
+        let mut limbs = self.value.limbs.clone();
+        let top = &limbs[NUM_LIMBS - 1] + FpVar::Constant(BaseField::from(1u64 << 63));
+        limbs[NUM_LIMBS - 1] = split_limb(&top, 65)?.0;
+        Ok(U256Var { limbs })
+    }
+
+    /// Output `self < other`, as the EVM `SLT`
+    #[tracing::instrument(target = "r1cs")]
+    pub fn is_lt(&self, other: &Self) -> Result<Boolean<BaseField>, SynthesisError> {
+        self.flip_sign()?.is_lt(&other.flip_sign()?)
+    }
+
+    /// Output `self <= other`
+    #[tracing::instrument(target = "r1cs")]
+    pub fn is_le(&self, other: &Self) -> Result<Boolean<BaseField>, SynthesisError> {
+        other.is_ge(self)
+    }
+
+    /// Output `self > other`, as the EVM `SGT`
+    #[tracing::instrument(target = "r1cs")]
+    pub fn is_gt(&self, other: &Self) -> Result<Boolean<BaseField>, SynthesisError> {
+        other.is_lt(self)
+    }
+
+    /// Output `self >= other`
+    #[tracing::instrument(target = "r1cs")]
+    pub fn is_ge(&self, other: &Self) -> Result<Boolean<BaseField>, SynthesisError> {
+        self.flip_sign()?.is_ge(&other.flip_sign()?)
+    }
+}
+
+impl<BaseField: PrimeField> R1CSVar<BaseField> for I256Var<BaseField> {
+    type Value = [u64; NUM_LIMBS];
+
+    fn cs(&self) -> ConstraintSystemRef<BaseField> {
+        self.value.cs()
+    }
+
+    fn value(&self) -> Result<Self::Value, SynthesisError> {
+        self.value.value()
+    }
+}
+
+impl<BaseField: PrimeField> AllocVar<[u64; NUM_LIMBS], BaseField> for I256Var<BaseField> {
+    fn new_variable<T: Borrow<[u64; NUM_LIMBS]>>(
+        cs: impl Into<Namespace<BaseField>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        Ok(Self::new(U256Var::new_variable(cs, f, mode)?))
+    }
+}
+
+impl<BaseField: PrimeField> EqGadget<BaseField> for I256Var<BaseField> {
+    #[tracing::instrument(target = "r1cs")]
+    fn is_eq(&self, other: &Self) -> Result<Boolean<BaseField>, SynthesisError> {
+        self.value.is_eq(&other.value)
+    }
+
+    #[tracing::instrument(target = "r1cs")]
+    fn conditional_enforce_equal(
+        &self,
+        other: &Self,
+        should_enforce: &Boolean<BaseField>,
+    ) -> Result<(), SynthesisError> {
+        self.value
+            .conditional_enforce_equal(&other.value, should_enforce)
+    }
+}
+
+impl<BaseField: PrimeField> CondSelectGadget<BaseField> for I256Var<BaseField> {
+    #[tracing::instrument(target = "r1cs")]
+    fn conditionally_select(
+        cond: &Boolean<BaseField>,
+        true_value: &Self,
+        false_value: &Self,
+    ) -> Result<Self, SynthesisError> {
+        Ok(Self::new(U256Var::conditionally_select(
+            cond,
+            &true_value.value,
+            &false_value.value,
+        )?))
+    }
+}
@@ -0,0 +1,232 @@
+use crate::u256::{
+    add_with_carry_limbs, alloc_limbs, div_rem_limbs, mul_add_limbs, sub_with_borrow_limbs,
+    to_bits_le_bounded, u64_limbs_to_biguint,
+};
+use ark_ff::PrimeField;
+use ark_r1cs_std::{
+    alloc::AllocVar, bits::ToBitsGadget, boolean::Boolean, eq::EqGadget, fields::fp::FpVar,
+    select::CondSelectGadget, R1CSVar,
+};
+use ark_relations::r1cs::{ConstraintSystemRef, Namespace, SynthesisError};
+use ark_std::vec::Vec;
+use num_bigint::BigUint;
+
+/// An unsigned integer of a fixed number of 64-bit limbs, e.g., an RSA modulus, whose
+/// arithmetic is modulo another such integer that may be a witness
+///
+/// The integer is represented by 64-bit limbs in little-endian order, each of which is a
+/// `BaseField` element range-checked at allocation, as in `U256Var`. The number of limbs is
+/// chosen at allocation, so that a 2048-bit modulus takes 32 limbs. The products of
+/// `mul_mod` require `BaseField` to have more than `130 + log2(num_limbs)` bits; otherwise, it
+/// returns `SynthesisError::Unsatisfiable`.
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct NonNativeUintVar<BaseField: PrimeField> {
+    /// The 64-bit limbs, least significant first
+    pub limbs: Vec<FpVar<BaseField>>,
+}
+
+impl<BaseField: PrimeField> NonNativeUintVar<BaseField> {
+    /// Construct the constant `value` with `num_limbs` limbs, or return
+    /// `SynthesisError::Unsatisfiable` if it does not fit
+    pub fn constant(value: &BigUint, num_limbs: usize) -> Result<Self, SynthesisError> {
+        let digits = value.to_u64_digits();
+        if digits.len() > num_limbs {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+
+        Ok(Self {
+            limbs: (0..num_limbs)
+                .map(|i| FpVar::Constant(BaseField::from(digits.get(i).copied().unwrap_or(0))))
+                .collect(),
+        })
+    }
+
+    /// Allocate the witness `value` with `num_limbs` range-checked limbs
+    ///
+    /// A value that does not fit into the limbs leaves the constraint system unsatisfied.
+    pub fn new_witness(
+        cs: impl Into<Namespace<BaseField>>,
+        f: impl FnOnce() -> Result<BigUint, SynthesisError>,
+        num_limbs: usize,
+    ) -> Result<Self, SynthesisError> {
+        let ns = cs.into();
+        let cs = ns.cs();
+        Ok(Self {
+            limbs: alloc_limbs(&cs, f(), num_limbs)?,
+        })
+    }
+
+    /// Allocate the public input `value` with `num_limbs` limbs, e.g., a modulus that is an
+    /// input of the circuit
+    pub fn new_input(
+        cs: impl Into<Namespace<BaseField>>,
+        f: impl FnOnce() -> Result<BigUint, SynthesisError>,
+        num_limbs: usize,
+    ) -> Result<Self, SynthesisError> {
+        let ns = cs.into();
+        let cs = ns.cs();
+        let digits = f().map(|v| v.to_u64_digits());
+        let mut limbs = Vec::with_capacity(num_limbs);
+        for i in 0..num_limbs {
+            let limb = FpVar::new_input(ark_relations::ns!(cs, "limb"), || {
+                digits
+                    .clone()
+                    .map(|d| BaseField::from(d.get(i).copied().unwrap_or(0)))
+            })?;
+            to_bits_le_bounded(&limb, 64)?;
+            limbs.push(limb);
+        }
+
+        Ok(Self { limbs })
+    }
+
+    /// The number of 64-bit limbs
+    pub fn num_limbs(&self) -> usize {
+        self.limbs.len()
+    }
+
+    /// The limbs of `self` padded with zeros to `num_limbs`
+    fn padded_limbs(&self, num_limbs: usize) -> Vec<FpVar<BaseField>> {
+        let mut limbs = self.limbs.clone();
+        if limbs.len() < num_limbs {
+            limbs.resize(num_limbs, FpVar::Constant(BaseField::zero()));
+        }
+        limbs
+    }
+
+    /// Compute `(self + other) mod modulus` for a `modulus` that may be a witness, with as
+    /// many limbs as `modulus`
+    ///
+    /// A zero `modulus` is unsatisfiable.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn add_mod(&self, other: &Self, modulus: &Self) -> Result<Self, SynthesisError> {
+        let num_limbs = self.num_limbs().max(other.num_limbs());
+        let (mut sum, carry) = add_with_carry_limbs(
+            &self.padded_limbs(num_limbs),
+            &other.padded_limbs(num_limbs),
+            FpVar::Constant(BaseField::zero()),
+        )?;
+        sum.push(FpVar::from(carry));
+
+        let (_, r) = div_rem_limbs(&sum, &modulus.limbs)?;
+        Ok(Self { limbs: r })
+    }
+
+    /// Compute `self * other` over the integers, with `self.num_limbs() + other.num_limbs()`
+    /// limbs, e.g., the square of a Paillier modulus
+    #[tracing::instrument(target = "r1cs")]
+    pub fn mul(&self, other: &Self) -> Result<Self, SynthesisError> {
+        Ok(Self {
+            limbs: mul_add_limbs(&self.limbs, &other.limbs, &[])?,
+        })
+    }
+
+    /// Compute `(self * other) mod modulus` for a `modulus` that may be a witness, with as
+    /// many limbs as `modulus`
+    ///
+    /// This enforces `self * other = q * modulus + r` over the integers, limb by limb, with the
+    /// quotient `q` and the remainder `r` range-checked witnesses and `r < modulus` enforced,
+    /// so a zero `modulus` is unsatisfiable.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn mul_mod(&self, other: &Self, modulus: &Self) -> Result<Self, SynthesisError> {
+        let product = mul_add_limbs(&self.limbs, &other.limbs, &[])?;
+        let (_, r) = div_rem_limbs(&product, &modulus.limbs)?;
+        Ok(Self { limbs: r })
+    }
+
+    /// Compute `self^exponent mod modulus` for the little-endian bits of `exponent`, with
+    /// square-and-multiply
+    ///
+    /// The shape of the constraints only depends on the number of bits. For the RSA
+    /// verification exponent `65537`, this is 17 squarings and 17 multiplications.
+    #[tracing::instrument(target = "r1cs", skip(exponent))]
+    pub fn pow_mod(
+        &self,
+        exponent: &[Boolean<BaseField>],
+        modulus: &Self,
+    ) -> Result<Self, SynthesisError> {
+        let mut res = Self::constant(&BigUint::from(1u64), modulus.num_limbs())?;
+        for bit in exponent.iter().rev() {
+            res = res.mul_mod(&res, modulus)?;
+            let product = res.mul_mod(self, modulus)?;
+            res = Self::conditionally_select(bit, &product, &res)?;
+        }
+        Ok(res)
+    }
+
+    /// Output `self < other`
+    #[tracing::instrument(target = "r1cs")]
+    pub fn is_lt(&self, other: &Self) -> Result<Boolean<BaseField>, SynthesisError> {
+        let num_limbs = self.num_limbs().max(other.num_limbs());
+        Ok(sub_with_borrow_limbs(
+            &self.padded_limbs(num_limbs),
+            &other.padded_limbs(num_limbs),
+        )?
+        .1
+        .not())
+    }
+}
+
+impl<BaseField: PrimeField> R1CSVar<BaseField> for NonNativeUintVar<BaseField> {
+    type Value = BigUint;
+
+    fn cs(&self) -> ConstraintSystemRef<BaseField> {
+        self.limbs.as_slice().cs()
+    }
+
+    fn value(&self) -> Result<Self::Value, SynthesisError> {
+        u64_limbs_to_biguint(&self.limbs)
+    }
+}
+
+impl<BaseField: PrimeField> EqGadget<BaseField> for NonNativeUintVar<BaseField> {
+    #[tracing::instrument(target = "r1cs")]
+    fn is_eq(&self, other: &Self) -> Result<Boolean<BaseField>, SynthesisError> {
+        let num_limbs = self.num_limbs().max(other.num_limbs());
+        self.padded_limbs(num_limbs)
+            .is_eq(&other.padded_limbs(num_limbs))
+    }
+
+    #[tracing::instrument(target = "r1cs")]
+    fn conditional_enforce_equal(
+        &self,
+        other: &Self,
+        should_enforce: &Boolean<BaseField>,
+    ) -> Result<(), SynthesisError> {
+        let num_limbs = self.num_limbs().max(other.num_limbs());
+        self.padded_limbs(num_limbs)
+            .conditional_enforce_equal(&other.padded_limbs(num_limbs), should_enforce)
+    }
+}
+
+impl<BaseField: PrimeField> CondSelectGadget<BaseField> for NonNativeUintVar<BaseField> {
+    #[tracing::instrument(target = "r1cs")]
+    fn conditionally_select(
+        cond: &Boolean<BaseField>,
+        true_value: &Self,
+        false_value: &Self,
+    ) -> Result<Self, SynthesisError> {
+        let num_limbs = true_value.num_limbs().max(false_value.num_limbs());
+        let mut limbs = Vec::with_capacity(num_limbs);
+        for (x, y) in true_value
+            .padded_limbs(num_limbs)
+            .iter()
+            .zip(false_value.padded_limbs(num_limbs).iter())
+        {
+            limbs.push(FpVar::conditionally_select(cond, x, y)?);
+        }
+        Ok(Self { limbs })
+    }
+}
+
+impl<BaseField: PrimeField> ToBitsGadget<BaseField> for NonNativeUintVar<BaseField> {
+    #[tracing::instrument(target = "r1cs")]
+    fn to_bits_le(&self) -> Result<Vec<Boolean<BaseField>>, SynthesisError> {
+        let mut bits = Vec::with_capacity(self.num_limbs() * 64);
+        for limb in self.limbs.iter() {
+            bits.extend(to_bits_le_bounded(limb, 64)?);
+        }
+        Ok(bits)
+    }
+}
@@ -0,0 +1,306 @@
+use crate::error::NonNativeFieldError;
+use crate::range_check;
+use crate::reduce::biguint_to_field;
+use crate::u256::from_bits_le;
+use crate::NonNativeFieldVar;
+use ark_ff::{FpParameters, PrimeField};
+use ark_r1cs_std::{
+    alloc::AllocVar,
+    boolean::Boolean,
+    eq::EqGadget,
+    fields::{
+        fp::{AllocatedFp, FpVar},
+        FieldVar,
+    },
+    R1CSVar,
+};
+use ark_relations::{
+    lc,
+    r1cs::{ConstraintSystemRef, SynthesisError},
+};
+use ark_std::vec::Vec;
+use num_bigint::{BigInt, BigUint};
+use num_traits::{One, Zero};
+
+/// A digit of the windowed non-adjacent form of a scalar
+///
+/// A nonzero digit is odd, and its absolute value is `2 · magnitude + 1`. A zero digit has
+/// all its flags and magnitude bits unset.
+#[derive(Clone, Debug)]
+pub struct WnafDigitVar<BaseField: PrimeField> {
+    /// Whether the digit is nonzero
+    pub is_nonzero: Boolean<BaseField>,
+    /// Whether the digit is negative
+    pub is_negative: Boolean<BaseField>,
+    /// The little-endian bits of `(|digit| - 1) / 2`, of which there are `window - 2`
+    pub magnitude: Vec<Boolean<BaseField>>,
+}
+
+/// The windowed non-adjacent form of a scalar with window `window`, i.e., its signed-digit
+/// representation `sum_i digits[i] · 2^i` with odd digits in `(-2^(window - 1), 2^(window - 1))`,
+/// of which at most one is nonzero in any `window` consecutive digits
+///
+/// The form of a scalar with `m` bits has `m + 1` digits. It is unique, so the constraints of
+/// the recoding pin every digit down. Scalar multiplications with the form need a table of the
+/// odd multiples of the point, and one addition per `window` digits instead of one per bit.
+#[derive(Clone, Debug)]
+pub struct WnafVar<BaseField: PrimeField> {
+    /// The window
+    pub window: usize,
+    /// The digits, least significant first
+    pub digits: Vec<WnafDigitVar<BaseField>>,
+}
+
+/// Compute the `num_digits` digits of the windowed non-adjacent form of the scalar with
+/// little-endian `bits`
+fn wnaf_digits(bits: &[bool], window: usize, num_digits: usize) -> Vec<i64> {
+    let mut k = BigUint::zero();
+    for bit in bits.iter().rev() {
+        k <<= 1;
+        if *bit {
+            k += 1u64;
+        }
+    }
+
+    let modulus = 1i64 << window;
+    let mut digits = Vec::with_capacity(num_digits);
+    for _ in 0..num_digits {
+        let low = k.to_u64_digits().first().copied().unwrap_or(0);
+        let mut digit = 0;
+        if low & 1 == 1 {
+            digit = (low % (modulus as u64)) as i64;
+            if digit >= modulus / 2 {
+                digit -= modulus;
+                k += (-digit) as u64;
+            } else {
+                k -= digit as u64;
+            }
+        }
+        digits.push(digit);
+        k >>= 1;
+    }
+    digits
+}
+
+impl<BaseField: PrimeField> WnafVar<BaseField> {
+    /// Recode the scalar with little-endian `bits` into the windowed non-adjacent form with
+    /// window `window`
+    ///
+    /// The digits are witnessed, and the constraints enforce that they sum to the scalar, are
+    /// odd or zero, and are sparse, which costs about `window` constraints per digit. The sum is
+    /// checked over the integers in chunks of digits that fit into `BaseField` with the signed
+    /// carries between them, which are range-checked to `window + 1` bits, so the scalar may
+    /// be wider than `BaseField`. `window` must be between 2 and 32, and leave room for a chunk
+    /// in `BaseField`; otherwise, this returns `NonNativeFieldError::InvalidWindow`.
+    #[tracing::instrument(target = "r1cs")]
+    pub fn from_bits_le(
+        bits: &[Boolean<BaseField>],
+        window: usize,
+    ) -> Result<Self, NonNativeFieldError> {
+        let capacity = <BaseField::Params as FpParameters>::CAPACITY as usize;
+        if window < 2 || window > 32 {
+            return Err(NonNativeFieldError::InvalidWindow {
+                window,
+                reason: "the window must be between 2 and 32",
+            });
+        }
+        if window + 4 > capacity {
+            return Err(NonNativeFieldError::InvalidWindow {
+                window,
+                reason: "a chunk of digits must fit into the base field",
+            });
+        }
+
+        let num_digits = bits.len() + 1;
+        let values: Result<Vec<i64>, SynthesisError> = bits
+            .iter()
+            .map(|bit| bit.value())
+            .collect::<Result<Vec<_>, _>>()
+            .map(|bits| wnaf_digits(&bits, window, num_digits));
+
+        let cs = bits.cs();
+        if cs.is_none() {
+            let digits = values?
+                .into_iter()
+                .map(|digit| {
+                    let magnitude = (digit.abs().max(1) - 1) / 2;
+                    WnafDigitVar {
+                        is_nonzero: Boolean::constant(digit != 0),
+                        is_negative: Boolean::constant(digit < 0),
+                        magnitude: (0..window - 2)
+                            .map(|i| Boolean::constant((magnitude >> i) & 1 == 1))
+                            .collect(),
+                    }
+                })
+                .collect();
+            return Ok(Self { window, digits });
+        }
+
+        let mut digits = Vec::with_capacity(num_digits);
+        for i in 0..num_digits {
+            let digit = || values.clone().map(|values| values[i]);
+            let is_nonzero = Boolean::new_witness(ark_relations::ns!(cs, "is_nonzero"), || {
+                digit().map(|digit| digit != 0)
+            })?;
+            let is_negative = Boolean::new_witness(ark_relations::ns!(cs, "is_negative"), || {
+                digit().map(|digit| digit < 0)
+            })?;
+            let mut magnitude = Vec::with_capacity(window - 2);
+            for j in 0..window - 2 {
+                magnitude.push(Boolean::new_witness(
+                    ark_relations::ns!(cs, "magnitude"),
+                    || digit().map(|digit| (((digit.abs().max(1) - 1) / 2) >> j) & 1 == 1),
+                )?);
+            }
+
+            // a zero digit has no sign or magnitude
+            cs.enforce_constraint(is_negative.lc(), is_nonzero.not().lc(), lc!())?;
+            for bit in magnitude.iter() {
+                cs.enforce_constraint(bit.lc(), is_nonzero.not().lc(), lc!())?;
+            }
+
+            digits.push(WnafDigitVar {
+                is_nonzero,
+                is_negative,
+                magnitude,
+            });
+        }
+
+        // at most one nonzero digit in any window consecutive digits
+        for (i, digit) in digits.iter().enumerate() {
+            for other in digits.iter().skip(i + 1).take(window - 1) {
+                cs.enforce_constraint(digit.is_nonzero.lc(), other.is_nonzero.lc(), lc!())?;
+            }
+        }
+
+        let res = Self { window, digits };
+        res.enforce_sum(bits)?;
+        Ok(res)
+    }
+
+    /// Recode the canonical bits of the simulated `scalar`, which are enforced to be smaller
+    /// than the modulus of `TargetField`, into the windowed non-adjacent form with window
+    /// `window`, as in `from_bits_le`
+    pub fn from_nonnative<TargetField: PrimeField>(
+        scalar: &NonNativeFieldVar<TargetField, BaseField>,
+        window: usize,
+    ) -> Result<Self, NonNativeFieldError> {
+        Self::from_bits_le(&scalar.to_canonical_bits_le()?, window)
+    }
+
+    /// Compute the signed digits in `BaseField`, with two constraints per digit
+    fn signed_digits(&self) -> Result<Vec<FpVar<BaseField>>, SynthesisError> {
+        self.digits
+            .iter()
+            .map(|digit| {
+                let magnitude = from_bits_le(&digit.magnitude);
+                let abs = magnitude.double()? + BaseField::one();
+                let negation = FpVar::from(digit.is_negative.clone()) * &abs;
+                let signed = abs - negation.double()?;
+                Ok(FpVar::from(digit.is_nonzero.clone()) * signed)
+            })
+            .collect()
+    }
+
+    /// Enforce `sum_i digits[i] · 2^i = sum_i bits[i] · 2^i` over the integers
+    ///
+    /// The digits and the bits are compared in chunks of `CAPACITY - window - 3` positions. A
+    /// chunk of the difference, plus the carry from the chunk below, equals the carry into the
+    /// chunk above times `2^chunk_size`, where the carries lie in `[-2^window, 2^window)`. The
+    /// absolute value of each side stays below `2^(CAPACITY - 1)`, so the equations hold over
+    /// the integers, and the last chunk carries nothing, which telescopes into the sum. The
+    /// partial sums of the form agree with the scalar modulo every power of two, so the
+    /// honest carries are at most `2^(window - 1)` in absolute value.
+    fn enforce_sum(&self, bits: &[Boolean<BaseField>]) -> Result<(), SynthesisError> {
+        let capacity = <BaseField::Params as FpParameters>::CAPACITY as usize;
+        let chunk_size = capacity - self.window - 3;
+        let signed_digits = self.signed_digits()?;
+        let cs = self.cs().or(bits.cs());
+        let carry_offset = BaseField::from(2u64).pow(&[self.window as u64]);
+
+        let digit_values: Result<Vec<i64>, SynthesisError> = self.value();
+        let bit_values: Result<Vec<bool>, SynthesisError> =
+            bits.iter().map(|bit| bit.value()).collect();
+
+        let mut carry_in = FpVar::<BaseField>::zero();
+        let mut start = 0;
+        while start < signed_digits.len() {
+            let end = (start + chunk_size).min(signed_digits.len());
+
+            let mut difference = carry_in.clone();
+            let mut coeff = BaseField::one();
+            for (i, digit) in signed_digits.iter().enumerate().take(end).skip(start) {
+                difference += digit * coeff;
+                if let Some(bit) = bits.get(i) {
+                    difference -= FpVar::from(bit.clone()) * coeff;
+                }
+                coeff.double_in_place();
+            }
+
+            if end == signed_digits.len() {
+                difference.enforce_equal(&FpVar::zero())?;
+                break;
+            }
+
+            // the difference of the sums below `end`, divided by `2^end`, offset by `2^window`
+            let carry_value = || -> Result<BaseField, SynthesisError> {
+                let digits = digit_values.clone()?;
+                let bits = bit_values.clone()?;
+                let mut sum = BigInt::zero();
+                for (i, digit) in digits.iter().enumerate().take(end).rev() {
+                    sum <<= 1;
+                    sum += *digit;
+                    if bits.get(i).copied().unwrap_or(false) {
+                        sum -= 1;
+                    }
+                }
+                let carry = (sum >> end) + (BigInt::one() << self.window);
+                let carry = carry.to_biguint().ok_or(SynthesisError::Unsatisfiable)?;
+                Ok(biguint_to_field(&carry))
+            };
+            let offset_carry =
+                AllocatedFp::new_witness(ark_relations::ns!(cs, "carry"), carry_value)?;
+            range_check::enforce_range(&offset_carry, self.window + 1)?;
+            let carry_out = FpVar::from(offset_carry) - carry_offset;
+
+            let shift = BaseField::from(2u64).pow(&[(end - start) as u64]);
+            difference.enforce_equal(&(&carry_out * shift))?;
+            carry_in = carry_out;
+            start = end;
+        }
+        Ok(())
+    }
+}
+
+impl<BaseField: PrimeField> R1CSVar<BaseField> for WnafVar<BaseField> {
+    type Value = Vec<i64>;
+
+    fn cs(&self) -> ConstraintSystemRef<BaseField> {
+        let mut res = ConstraintSystemRef::None;
+        for digit in self.digits.iter() {
+            res = res.or(digit.is_nonzero.cs());
+        }
+        res
+    }
+
+    fn value(&self) -> Result<Self::Value, SynthesisError> {
+        self.digits
+            .iter()
+            .map(|digit| {
+                if !digit.is_nonzero.value()? {
+                    return Ok(0);
+                }
+                let mut magnitude = 0i64;
+                for bit in digit.magnitude.iter().rev() {
+                    magnitude = 2 * magnitude + bit.value()? as i64;
+                }
+                let abs = 2 * magnitude + 1;
+                Ok(if digit.is_negative.value()? {
+                    -abs
+                } else {
+                    abs
+                })
+            })
+            .collect()
+    }
+}
@@ -6,7 +6,11 @@ use ark_mnt4_753::MNT4_753;
 use ark_mnt6_298::MNT6_298;
 use ark_mnt6_753::MNT6_753;
 
-use ark_nonnative_field::NonNativeFieldVar;
+use ark_nonnative_field::{
+    kzg,
+    poseidon::{PoseidonParameters, PoseidonSpongeVar},
+    NonNativeFieldVar,
+};
 use ark_r1cs_std::{alloc::AllocVar, eq::EqGadget, fields::FieldVar, R1CSVar};
 use ark_relations::r1cs::{ConstraintSystem, ConstraintSystemRef};
 use rand::RngCore;
@@ -536,6 +540,706 @@ fn inverse_stress_test<TargetField: PrimeField, BaseField: PrimeField, R: RngCor
     }
 }
 
+fn native_poseidon_permute<F: PrimeField>(params: &PoseidonParameters<F>, state: &mut Vec<F>) {
+    let full_rounds_over_2 = params.full_rounds / 2;
+    for round in 0..(params.full_rounds + params.partial_rounds) {
+        for (elem, c) in state.iter_mut().zip(params.ark[round].iter()) {
+            *elem += c;
+        }
+
+        if round < full_rounds_over_2 || round >= full_rounds_over_2 + params.partial_rounds {
+            for elem in state.iter_mut() {
+                *elem = elem.pow(&[params.alpha]);
+            }
+        } else {
+            state[0] = state[0].pow(&[params.alpha]);
+        }
+
+        let new_state: Vec<F> = params
+            .mds
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .zip(state.iter())
+                    .fold(F::zero(), |acc, (m, elem)| acc + &(*m * elem))
+            })
+            .collect();
+        *state = new_state;
+    }
+}
+
+fn poseidon_sponge_test<TargetField: PrimeField, BaseField: PrimeField, R: RngCore>(
+    cs: ConstraintSystemRef<BaseField>,
+    rng: &mut R,
+) {
+    let (rate, capacity, full_rounds, partial_rounds) = (2, 1, 2, 2);
+    let mds: Vec<Vec<TargetField>> = (0..rate + capacity)
+        .map(|_| {
+            (0..rate + capacity)
+                .map(|_| TargetField::rand(rng))
+                .collect()
+        })
+        .collect();
+    let ark: Vec<Vec<TargetField>> = (0..full_rounds + partial_rounds)
+        .map(|_| {
+            (0..rate + capacity)
+                .map(|_| TargetField::rand(rng))
+                .collect()
+        })
+        .collect();
+    let params =
+        PoseidonParameters::new(full_rounds, partial_rounds, 5, mds, ark, rate, capacity).unwrap();
+
+    let inputs_native: Vec<TargetField> = (0..3).map(|_| TargetField::rand(rng)).collect();
+    let inputs: Vec<NonNativeFieldVar<TargetField, BaseField>> = inputs_native
+        .iter()
+        .map(|input| {
+            NonNativeFieldVar::new_witness(ark_relations::ns!(cs, "input"), || Ok(*input)).unwrap()
+        })
+        .collect();
+
+    // absorb three elements with rate 2, then squeeze one
+    let mut state = vec![TargetField::zero(); rate + capacity];
+    state[1] += &inputs_native[0];
+    state[2] += &inputs_native[1];
+    native_poseidon_permute(&params, &mut state);
+    state[1] += &inputs_native[2];
+    native_poseidon_permute(&params, &mut state);
+    let expected = state[1];
+
+    let mut sponge = PoseidonSpongeVar::new(cs.clone(), &params);
+    sponge.absorb(&inputs).unwrap();
+    let output = sponge.squeeze(1).unwrap();
+
+    assert!(
+        output[0].value().unwrap().eq(&expected),
+        "poseidon sponge output does not match the native computation"
+    );
+    assert!(cs.is_satisfied().unwrap());
+
+    // parameters whose dimensions do not match are rejected
+    assert!(PoseidonParameters::new(
+        full_rounds,
+        partial_rounds,
+        5,
+        params.mds.clone(),
+        params.ark[1..].to_vec(),
+        rate,
+        capacity
+    )
+    .is_err());
+    assert!(PoseidonParameters::new(
+        full_rounds + 1,
+        partial_rounds - 1,
+        5,
+        params.mds.clone(),
+        params.ark.clone(),
+        rate,
+        capacity
+    )
+    .is_err());
+}
+
+fn kzg_scalars_test<TargetField: PrimeField, BaseField: PrimeField, R: RngCore>(
+    cs: ConstraintSystemRef<BaseField>,
+    rng: &mut R,
+) {
+    let alloc = |v: TargetField| {
+        NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+            ark_relations::ns!(cs, "alloc"),
+            || Ok(v),
+        )
+        .unwrap()
+    };
+
+    let coeffs_native: Vec<TargetField> = (0..4).map(|_| TargetField::rand(rng)).collect();
+    let point_native = TargetField::rand(rng);
+    let evaluation_native = coeffs_native
+        .iter()
+        .rev()
+        .fold(TargetField::zero(), |acc, c| acc * &point_native + c);
+
+    let coeffs: Vec<_> = coeffs_native.iter().map(|c| alloc(*c)).collect();
+    let point = alloc(point_native);
+    let evaluation = alloc(evaluation_native);
+    kzg::enforce_evaluation(&coeffs, &point, &evaluation).unwrap();
+
+    let points_native: Vec<TargetField> = (0..3).map(|_| TargetField::rand(rng)).collect();
+    let evaluations_native: Vec<TargetField> = (0..3).map(|_| TargetField::rand(rng)).collect();
+    let challenge_native = TargetField::rand(rng);
+
+    let points: Vec<_> = points_native.iter().map(|p| alloc(*p)).collect();
+    let evaluations: Vec<_> = evaluations_native.iter().map(|e| alloc(*e)).collect();
+    let challenge = alloc(challenge_native);
+    let scalars = kzg::batch_openings(&points, &evaluations, &challenge).unwrap();
+
+    let mut power = TargetField::one();
+    let mut combined_evaluation_native = TargetField::zero();
+    for i in 0..3 {
+        assert!(scalars.commitment_coeffs[i].value().unwrap().eq(&power));
+        assert!(scalars.proof_coeffs[i]
+            .value()
+            .unwrap()
+            .eq(&(power * &points_native[i])));
+        combined_evaluation_native += &(power * &evaluations_native[i]);
+        power *= &challenge_native;
+    }
+    assert!(scalars
+        .combined_evaluation
+        .value()
+        .unwrap()
+        .eq(&combined_evaluation_native));
+    assert!(cs.is_satisfied().unwrap());
+
+    // a wrong opening makes the system unsatisfied, which is checked in a system of its own
+    // since the caller expects `cs` to be satisfied
+    let other_cs = ConstraintSystem::<BaseField>::new_ref();
+    let other_alloc = |v: TargetField| {
+        NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+            ark_relations::ns!(other_cs, "alloc"),
+            || Ok(v),
+        )
+        .unwrap()
+    };
+    let coeffs: Vec<_> = coeffs_native.iter().map(|c| other_alloc(*c)).collect();
+    let point = other_alloc(point_native);
+    let wrong_evaluation = other_alloc(evaluation_native + &TargetField::one());
+    kzg::enforce_evaluation(&coeffs, &point, &wrong_evaluation).unwrap();
+    assert!(!other_cs.is_satisfied().unwrap());
+}
+
+fn constant_folding_test<TargetField: PrimeField, BaseField: PrimeField, R: RngCore>(
+    cs: ConstraintSystemRef<BaseField>,
+    rng: &mut R,
+) {
+    use ark_nonnative_field::NonNativeFieldMulResultVar;
+    use ark_r1cs_std::{boolean::Boolean, select::CondSelectGadget};
+
+    let a_native = TargetField::rand(rng);
+    let b_native = TargetField::rand(rng);
+    let a = NonNativeFieldVar::<TargetField, BaseField>::Constant(a_native);
+    let b = NonNativeFieldVar::<TargetField, BaseField>::Constant(b_native);
+    let cond = Boolean::new_witness(ark_relations::ns!(cs, "alloc cond"), || Ok(true)).unwrap();
+
+    let num_constraints = cs.num_constraints();
+    let num_witness_variables = cs.num_witness_variables();
+
+    match a.mul_without_reduce(&b).unwrap() {
+        NonNativeFieldMulResultVar::Constant(c) => assert_eq!(c, a_native * &b_native),
+        NonNativeFieldMulResultVar::Var(_) => panic!("a product of constants must be a constant"),
+    }
+    match &a - &b {
+        NonNativeFieldVar::Constant(c) => assert_eq!(c, a_native - &b_native),
+        NonNativeFieldVar::Var(_) => panic!("a difference of constants must be a constant"),
+    }
+    assert_eq!(a.is_eq(&b).unwrap().value().unwrap(), a_native == b_native);
+    match NonNativeFieldVar::conditionally_select(&cond, &a, &a).unwrap() {
+        NonNativeFieldVar::Constant(c) => assert_eq!(c, a_native),
+        NonNativeFieldVar::Var(_) => panic!("selecting between equal constants must be a constant"),
+    }
+
+    assert_eq!(cs.num_constraints(), num_constraints);
+    assert_eq!(cs.num_witness_variables(), num_witness_variables);
+
+    // a variable times a constant, in either order
+    let c_native = TargetField::rand(rng);
+    let c = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "alloc c"),
+        || Ok(c_native),
+    )
+    .unwrap();
+    let ca = c.mul_without_reduce(&a).unwrap().reduce().unwrap();
+    let ac = a.mul_without_reduce(&c).unwrap().reduce().unwrap();
+    assert_eq!(ca.value().unwrap(), c_native * &a_native);
+    assert_eq!(ac.value().unwrap(), c_native * &a_native);
+}
+
+fn identity_operands_test<TargetField: PrimeField, BaseField: PrimeField, R: RngCore>(
+    cs: ConstraintSystemRef<BaseField>,
+    rng: &mut R,
+) {
+    let a_native = TargetField::rand(rng);
+    let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "alloc a"),
+        || Ok(a_native),
+    )
+    .unwrap();
+    let zero = NonNativeFieldVar::<TargetField, BaseField>::zero();
+    let one = NonNativeFieldVar::<TargetField, BaseField>::one();
+
+    let num_constraints = cs.num_constraints();
+
+    assert_eq!((&a + &zero).value().unwrap(), a_native);
+    assert_eq!((&zero + &a).value().unwrap(), a_native);
+    assert_eq!((&a - &zero).value().unwrap(), a_native);
+    assert_eq!((&a * &one).value().unwrap(), a_native);
+    assert_eq!((&one * &a).value().unwrap(), a_native);
+    assert!((&a * &zero).value().unwrap().is_zero());
+    assert!(a
+        .mul_without_reduce(&zero)
+        .unwrap()
+        .reduce()
+        .unwrap()
+        .value()
+        .unwrap()
+        .is_zero());
+
+    assert_eq!(cs.num_constraints(), num_constraints);
+
+    // variables proven equal to zero or one are special-cased as the constants are
+    let proven_zero = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "alloc proven zero"),
+        || Ok(TargetField::zero()),
+    )
+    .unwrap();
+    proven_zero.enforce_equal(&zero).unwrap();
+    let proven_one = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "alloc proven one"),
+        || Ok(TargetField::one()),
+    )
+    .unwrap();
+    proven_one.enforce_equal(&one).unwrap();
+
+    let num_constraints = cs.num_constraints();
+
+    assert_eq!((&a + &proven_zero).value().unwrap(), a_native);
+    assert_eq!((&a - &proven_zero).value().unwrap(), a_native);
+    assert_eq!((&a * &proven_one).value().unwrap(), a_native);
+    assert_eq!((&proven_one * &a).value().unwrap(), a_native);
+    assert!((&a * &proven_zero).is_constant());
+    assert!(a
+        .mul_without_reduce(&proven_zero)
+        .unwrap()
+        .reduce()
+        .unwrap()
+        .value()
+        .unwrap()
+        .is_zero());
+
+    assert_eq!(cs.num_constraints(), num_constraints);
+    assert!(cs.is_satisfied().unwrap());
+}
+
+fn reduction_memoization_test<TargetField: PrimeField, BaseField: PrimeField, R: RngCore>(
+    cs: ConstraintSystemRef<BaseField>,
+    rng: &mut R,
+) {
+    let a_native = TargetField::rand(rng);
+    let b_native = TargetField::rand(rng);
+    let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "alloc a"),
+        || Ok(a_native),
+    )
+    .unwrap();
+    let b = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "alloc b"),
+        || Ok(b_native),
+    )
+    .unwrap();
+
+    let sum = &a + &b;
+    let expected = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "alloc expected"),
+        || Ok(a_native + &b_native),
+    )
+    .unwrap();
+
+    let num_constraints = cs.num_constraints();
+    sum.enforce_equal(&expected).unwrap();
+    let num_constraints_first = cs.num_constraints() - num_constraints;
+
+    // both sides have been reduced before, so only the limb equalities remain
+    let num_constraints = cs.num_constraints();
+    sum.enforce_equal(&expected).unwrap();
+    let num_constraints_second = cs.num_constraints() - num_constraints;
+
+    assert!(num_constraints_second < num_constraints_first);
+}
+
+fn lagrange_interpolation_test<TargetField: PrimeField, BaseField: PrimeField, R: RngCore>(
+    cs: ConstraintSystemRef<BaseField>,
+    rng: &mut R,
+) {
+    // the polynomial c_0 + c_1 X + c_2 X^2 + c_3 X^3
+    let coeffs: Vec<TargetField> = (0..4).map(|_| TargetField::rand(rng)).collect();
+    let eval = |x: &TargetField| {
+        coeffs
+            .iter()
+            .rev()
+            .fold(TargetField::zero(), |acc, c| acc * x + c)
+    };
+
+    let mut points = Vec::new();
+    for i in 0..4u64 {
+        let x_native = TargetField::from(i + 1);
+        let x = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+            ark_relations::ns!(cs, "alloc x"),
+            || Ok(x_native),
+        )
+        .unwrap();
+        let y = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+            ark_relations::ns!(cs, "alloc y"),
+            || Ok(eval(&x_native)),
+        )
+        .unwrap();
+        points.push((x, y));
+    }
+
+    let x_native = TargetField::rand(rng);
+    let x = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "alloc point"),
+        || Ok(x_native),
+    )
+    .unwrap();
+    let res = NonNativeFieldVar::lagrange_interpolate_and_eval(&points, &x).unwrap();
+    assert_eq!(res.value().unwrap(), eval(&x_native));
+
+    // evaluating at one of the x-coordinates returns its y-coordinate
+    let res = NonNativeFieldVar::lagrange_interpolate_and_eval(&points, &points[2].0).unwrap();
+    assert_eq!(res.value().unwrap(), points[2].1.value().unwrap());
+
+    assert!(cs.is_satisfied().unwrap());
+}
+
+fn vanishing_poly_test<TargetField: PrimeField, BaseField: PrimeField, R: RngCore>(
+    cs: ConstraintSystemRef<BaseField>,
+    rng: &mut R,
+) {
+    let x_native = TargetField::rand(rng);
+    let x = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "alloc x"),
+        || Ok(x_native),
+    )
+    .unwrap();
+
+    for n in [1u64, 8, 13].iter() {
+        let expected = x_native.pow(&[*n]) - &TargetField::one();
+        let expected_derivative = x_native.pow(&[*n - 1]) * &TargetField::from(*n);
+
+        let vanishing = NonNativeFieldVar::eval_vanishing_poly(&x, *n).unwrap();
+        assert_eq!(vanishing.value().unwrap(), expected);
+
+        let (vanishing, derivative) =
+            NonNativeFieldVar::eval_vanishing_poly_with_derivative(&x, *n).unwrap();
+        assert_eq!(vanishing.value().unwrap(), expected);
+        assert_eq!(derivative.value().unwrap(), expected_derivative);
+    }
+
+    assert!(cs.is_satisfied().unwrap());
+}
+
+fn enforce_equal_many_test<TargetField: PrimeField, BaseField: PrimeField, R: RngCore>(
+    cs: ConstraintSystemRef<BaseField>,
+    rng: &mut R,
+) {
+    let mut lhs = Vec::new();
+    let mut rhs = Vec::new();
+    for _ in 0..5 {
+        let a_native = TargetField::rand(rng);
+        let b_native = TargetField::rand(rng);
+        let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+            ark_relations::ns!(cs, "alloc a"),
+            || Ok(a_native),
+        )
+        .unwrap();
+        let b = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+            ark_relations::ns!(cs, "alloc b"),
+            || Ok(b_native),
+        )
+        .unwrap();
+        lhs.push(&a * &b);
+        rhs.push(NonNativeFieldVar::constant(a_native * &b_native));
+    }
+    lhs.push(NonNativeFieldVar::constant(TargetField::one()));
+    rhs.push(NonNativeFieldVar::constant(TargetField::one()));
+
+    NonNativeFieldVar::enforce_equal_many(&lhs, &rhs).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+
+    // a single differing pair makes the system unsatisfied, which is checked in a system of
+    // its own since the caller expects `cs` to be satisfied
+    let other_cs = ConstraintSystem::<BaseField>::new_ref();
+    let a_native = TargetField::rand(rng);
+    let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(other_cs, "alloc a"),
+        || Ok(a_native),
+    )
+    .unwrap();
+    let wrong = vec![a.clone(), a];
+    let right = vec![
+        NonNativeFieldVar::constant(a_native),
+        NonNativeFieldVar::constant(a_native + &TargetField::one()),
+    ];
+    NonNativeFieldVar::enforce_equal_many(&wrong, &right).unwrap();
+    assert!(!other_cs.is_satisfied().unwrap());
+}
+
+fn inverse_or_zero_test<TargetField: PrimeField, BaseField: PrimeField, R: RngCore>(
+    cs: ConstraintSystemRef<BaseField>,
+    rng: &mut R,
+) {
+    let a_native = TargetField::rand(rng);
+    for native in [a_native, TargetField::zero()].iter() {
+        let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+            ark_relations::ns!(cs, "alloc a"),
+            || Ok(*native),
+        )
+        .unwrap();
+
+        let (inv, is_nonzero) = a.inverse_or_zero().unwrap();
+        assert_eq!(is_nonzero.value().unwrap(), !native.is_zero());
+        assert_eq!(
+            inv.value().unwrap(),
+            native.inverse().unwrap_or_else(TargetField::zero)
+        );
+
+        let (inv, is_nonzero) = NonNativeFieldVar::<TargetField, BaseField>::constant(*native)
+            .inverse_or_zero()
+            .unwrap();
+        assert_eq!(is_nonzero.value().unwrap(), !native.is_zero());
+        assert_eq!(
+            inv.value().unwrap(),
+            native.inverse().unwrap_or_else(TargetField::zero)
+        );
+    }
+
+    assert!(cs.is_satisfied().unwrap());
+}
+
+fn fixed_base_pow_test<TargetField: PrimeField, BaseField: PrimeField, R: RngCore>(
+    cs: ConstraintSystemRef<BaseField>,
+    rng: &mut R,
+) {
+    use ark_r1cs_std::boolean::Boolean;
+
+    let base = TargetField::rand(rng);
+    let exponent = rng.next_u32() as u64;
+
+    // an odd number of bits exercises the unpaired top bit
+    let bits: Vec<Boolean<BaseField>> = (0..33)
+        .map(|i| {
+            Boolean::new_witness(ark_relations::ns!(cs, "alloc bit"), || {
+                Ok((exponent >> i) & 1 == 1)
+            })
+            .unwrap()
+        })
+        .collect();
+
+    let res = NonNativeFieldVar::<TargetField, BaseField>::fixed_base_pow(&base, &bits).unwrap();
+    assert_eq!(res.value().unwrap(), base.pow(&[exponent]));
+    assert!(cs.is_satisfied().unwrap());
+}
+
+fn enforce_all_distinct_test<TargetField: PrimeField, BaseField: PrimeField, R: RngCore>(
+    cs: ConstraintSystemRef<BaseField>,
+    rng: &mut R,
+) {
+    let mut elems: Vec<NonNativeFieldVar<TargetField, BaseField>> = (0..4)
+        .map(|_| {
+            NonNativeFieldVar::new_witness(ark_relations::ns!(cs, "alloc elem"), || {
+                Ok(TargetField::rand(rng))
+            })
+            .unwrap()
+        })
+        .collect();
+    elems.push(NonNativeFieldVar::constant(TargetField::one()));
+
+    NonNativeFieldVar::enforce_all_distinct(&elems).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+
+    // a repeated element makes the system unsatisfied, which is checked in a system of its own
+    // since the caller expects `cs` to be satisfied
+    let other_cs = ConstraintSystem::<BaseField>::new_ref();
+    let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(other_cs, "alloc a"),
+        || Ok(TargetField::rand(rng)),
+    )
+    .unwrap();
+    let repeated = vec![
+        a.clone(),
+        NonNativeFieldVar::constant(TargetField::one()),
+        a,
+    ];
+    NonNativeFieldVar::enforce_all_distinct(&repeated).unwrap();
+    assert!(!other_cs.is_satisfied().unwrap());
+}
+
+fn enforce_in_set_test<TargetField: PrimeField, BaseField: PrimeField, R: RngCore>(
+    cs: ConstraintSystemRef<BaseField>,
+    rng: &mut R,
+) {
+    let set: Vec<TargetField> = (0..4).map(|_| TargetField::rand(rng)).collect();
+    let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "alloc a"),
+        || Ok(set[2]),
+    )
+    .unwrap();
+    a.enforce_in_set(&set).unwrap();
+    NonNativeFieldVar::<TargetField, BaseField>::constant(set[0])
+        .enforce_in_set(&set)
+        .unwrap();
+    assert!(cs.is_satisfied().unwrap());
+    assert!(
+        NonNativeFieldVar::<TargetField, BaseField>::constant(TargetField::rand(rng))
+            .enforce_in_set(&set)
+            .is_err()
+    );
+
+    // an element outside of the set makes the system unsatisfied, which is checked in a system
+    // of its own since the caller expects `cs` to be satisfied
+    let other_cs = ConstraintSystem::<BaseField>::new_ref();
+    let b = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(other_cs, "alloc b"),
+        || Ok(set[0] + &set[1]),
+    )
+    .unwrap();
+    b.enforce_in_set(&set).unwrap();
+    assert!(!other_cs.is_satisfied().unwrap());
+}
+
+fn conditionally_swap_test<TargetField: PrimeField, BaseField: PrimeField, R: RngCore>(
+    cs: ConstraintSystemRef<BaseField>,
+    rng: &mut R,
+) {
+    use ark_r1cs_std::boolean::Boolean;
+
+    let a_native = TargetField::rand(rng);
+    let b_native = TargetField::rand(rng);
+    let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "alloc a"),
+        || Ok(a_native),
+    )
+    .unwrap();
+    let b = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "alloc b"),
+        || Ok(b_native),
+    )
+    .unwrap();
+    let c = NonNativeFieldVar::constant(b_native + &a_native);
+
+    for cond_native in [false, true].iter() {
+        let cond = Boolean::new_witness(ark_relations::ns!(cs, "alloc cond"), || Ok(*cond_native))
+            .unwrap();
+        let expected = |x: TargetField, y: TargetField| if *cond_native { (y, x) } else { (x, y) };
+
+        let (x, y) = NonNativeFieldVar::conditionally_swap(&cond, &a, &b).unwrap();
+        assert_eq!(
+            (x.value().unwrap(), y.value().unwrap()),
+            expected(a_native, b_native)
+        );
+        let (x, y) = NonNativeFieldVar::conditionally_swap(&cond, &c, &a).unwrap();
+        assert_eq!(
+            (x.value().unwrap(), y.value().unwrap()),
+            expected(b_native + &a_native, a_native)
+        );
+
+        // the outputs can be used in further operations
+        let sum = &x + &y;
+        assert_eq!(sum.value().unwrap(), b_native + &a_native + &a_native);
+    }
+}
+
+fn is_odd_test<TargetField: PrimeField, BaseField: PrimeField, R: RngCore>(
+    cs: ConstraintSystemRef<BaseField>,
+    rng: &mut R,
+) {
+    use ark_ff::BigInteger;
+
+    let a_native = TargetField::rand(rng);
+    let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "alloc a"),
+        || Ok(a_native),
+    )
+    .unwrap();
+    assert_eq!(
+        a.is_odd().unwrap().value().unwrap(),
+        a_native.into_repr().is_odd()
+    );
+
+    // the sum is reduced modulo p, so its parity is not the sum of the parities
+    let b = &a + &a;
+    assert_eq!(
+        b.is_odd().unwrap().value().unwrap(),
+        (a_native + &a_native).into_repr().is_odd()
+    );
+
+    let minus_one = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "alloc minus one"),
+        || Ok(-TargetField::from(1u64)),
+    )
+    .unwrap();
+    assert!(!minus_one.is_odd().unwrap().value().unwrap());
+    assert!(
+        NonNativeFieldVar::<TargetField, BaseField>::constant(TargetField::from(1u64))
+            .is_odd()
+            .unwrap()
+            .value()
+            .unwrap()
+    );
+}
+
+fn mux_test<TargetField: PrimeField, BaseField: PrimeField, R: RngCore>(
+    cs: ConstraintSystemRef<BaseField>,
+    rng: &mut R,
+) {
+    use ark_r1cs_std::boolean::Boolean;
+
+    let natives: Vec<TargetField> = (0..8).map(|_| TargetField::rand(rng)).collect();
+    let mut options: Vec<NonNativeFieldVar<TargetField, BaseField>> = natives
+        .iter()
+        .take(6)
+        .map(|native| {
+            NonNativeFieldVar::new_witness(ark_relations::ns!(cs, "alloc option"), || Ok(*native))
+                .unwrap()
+        })
+        .collect();
+    options.push(NonNativeFieldVar::constant(natives[6]));
+    options.push(NonNativeFieldVar::constant(natives[7]));
+
+    for index in 0..8usize {
+        let bits: Vec<Boolean<BaseField>> = (0..3)
+            .map(|i| {
+                Boolean::new_witness(ark_relations::ns!(cs, "alloc bit"), || {
+                    Ok((index >> i) & 1 == 1)
+                })
+                .unwrap()
+            })
+            .collect();
+        let res = NonNativeFieldVar::mux(&bits, &options).unwrap();
+        assert_eq!(res.value().unwrap(), natives[index]);
+
+        let bits: Vec<Boolean<BaseField>> = (0..3)
+            .map(|i| Boolean::constant((index >> i) & 1 == 1))
+            .collect();
+        let res = NonNativeFieldVar::mux(&bits, &options).unwrap();
+        assert_eq!(res.value().unwrap(), natives[index]);
+    }
+
+    assert!(cs.is_satisfied().unwrap());
+}
+
+fn montgomery_repr_test<TargetField: PrimeField, BaseField: PrimeField, R: RngCore>(
+    cs: ConstraintSystemRef<BaseField>,
+    rng: &mut R,
+) {
+    use ark_ff::FpParameters;
+
+    let a_native = TargetField::rand(rng);
+    let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "alloc a"),
+        || Ok(a_native),
+    )
+    .unwrap();
+
+    let r = TargetField::from_repr(<TargetField::Params as FpParameters>::R).unwrap();
+    let mont = a.to_montgomery_repr().unwrap();
+    assert_eq!(mont.value().unwrap(), a_native * &r);
+
+    let back = mont.from_montgomery_repr().unwrap();
+    assert_eq!(back.value().unwrap(), a_native);
+    assert!(cs.is_satisfied().unwrap());
+}
+
 macro_rules! nonnative_test_individual {
     ($test_method:ident, $test_name:ident, $test_target_field:ty, $test_base_field:ty) => {
         paste::item! {
@@ -552,144 +1256,5695 @@ macro_rules! nonnative_test_individual {
                     HitRate::print(&cs_ref);
                 }*/
 
-                for _ in 0..NUM_REPETITIONS {
-                    let cs = ConstraintSystem::<$test_base_field>::new_ref();
-                    $test_method::<$test_target_field, $test_base_field, _>(cs.clone(), rng);
-                    assert!(cs.is_satisfied().unwrap());
-                }
+                for _ in 0..NUM_REPETITIONS {
+                    let cs = ConstraintSystem::<$test_base_field>::new_ref();
+                    $test_method::<$test_target_field, $test_base_field, _>(cs.clone(), rng);
+                    assert!(cs.is_satisfied().unwrap());
+                }
+            }
+        }
+    };
+}
+
+macro_rules! nonnative_test {
+    ($test_name:ident, $test_target_field:ty, $test_base_field:ty) => {
+        nonnative_test_individual!(
+            allocation_test,
+            $test_name,
+            $test_target_field,
+            $test_base_field
+        );
+        nonnative_test_individual!(
+            addition_test,
+            $test_name,
+            $test_target_field,
+            $test_base_field
+        );
+        nonnative_test_individual!(
+            multiplication_test,
+            $test_name,
+            $test_target_field,
+            $test_base_field
+        );
+        nonnative_test_individual!(
+            equality_test,
+            $test_name,
+            $test_target_field,
+            $test_base_field
+        );
+        nonnative_test_individual!(
+            edge_cases_test,
+            $test_name,
+            $test_target_field,
+            $test_base_field
+        );
+        nonnative_test_individual!(
+            distribution_law_test,
+            $test_name,
+            $test_target_field,
+            $test_base_field
+        );
+        nonnative_test_individual!(
+            addition_stress_test,
+            $test_name,
+            $test_target_field,
+            $test_base_field
+        );
+        nonnative_test_individual!(
+            double_stress_test_1,
+            $test_name,
+            $test_target_field,
+            $test_base_field
+        );
+        nonnative_test_individual!(
+            double_stress_test_2,
+            $test_name,
+            $test_target_field,
+            $test_base_field
+        );
+        nonnative_test_individual!(
+            double_stress_test_3,
+            $test_name,
+            $test_target_field,
+            $test_base_field
+        );
+        nonnative_test_individual!(
+            randomized_arithmetic_test,
+            $test_name,
+            $test_target_field,
+            $test_base_field
+        );
+        nonnative_test_individual!(
+            multiplication_stress_test,
+            $test_name,
+            $test_target_field,
+            $test_base_field
+        );
+        nonnative_test_individual!(
+            mul_and_add_stress_test,
+            $test_name,
+            $test_target_field,
+            $test_base_field
+        );
+        nonnative_test_individual!(
+            square_mul_add_stress_test,
+            $test_name,
+            $test_target_field,
+            $test_base_field
+        );
+        nonnative_test_individual!(
+            inverse_stress_test,
+            $test_name,
+            $test_target_field,
+            $test_base_field
+        );
+        nonnative_test_individual!(
+            poseidon_sponge_test,
+            $test_name,
+            $test_target_field,
+            $test_base_field
+        );
+        nonnative_test_individual!(
+            kzg_scalars_test,
+            $test_name,
+            $test_target_field,
+            $test_base_field
+        );
+        nonnative_test_individual!(
+            constant_folding_test,
+            $test_name,
+            $test_target_field,
+            $test_base_field
+        );
+        nonnative_test_individual!(
+            identity_operands_test,
+            $test_name,
+            $test_target_field,
+            $test_base_field
+        );
+        nonnative_test_individual!(
+            reduction_memoization_test,
+            $test_name,
+            $test_target_field,
+            $test_base_field
+        );
+        nonnative_test_individual!(
+            lagrange_interpolation_test,
+            $test_name,
+            $test_target_field,
+            $test_base_field
+        );
+        nonnative_test_individual!(
+            vanishing_poly_test,
+            $test_name,
+            $test_target_field,
+            $test_base_field
+        );
+        nonnative_test_individual!(
+            enforce_equal_many_test,
+            $test_name,
+            $test_target_field,
+            $test_base_field
+        );
+        nonnative_test_individual!(
+            inverse_or_zero_test,
+            $test_name,
+            $test_target_field,
+            $test_base_field
+        );
+        nonnative_test_individual!(
+            fixed_base_pow_test,
+            $test_name,
+            $test_target_field,
+            $test_base_field
+        );
+        nonnative_test_individual!(
+            enforce_all_distinct_test,
+            $test_name,
+            $test_target_field,
+            $test_base_field
+        );
+        nonnative_test_individual!(
+            enforce_in_set_test,
+            $test_name,
+            $test_target_field,
+            $test_base_field
+        );
+        nonnative_test_individual!(
+            conditionally_swap_test,
+            $test_name,
+            $test_target_field,
+            $test_base_field
+        );
+        nonnative_test_individual!(
+            is_odd_test,
+            $test_name,
+            $test_target_field,
+            $test_base_field
+        );
+        nonnative_test_individual!(mux_test, $test_name, $test_target_field, $test_base_field);
+        nonnative_test_individual!(
+            montgomery_repr_test,
+            $test_name,
+            $test_target_field,
+            $test_base_field
+        );
+    };
+}
+
+nonnative_test!(
+    MNT46Small,
+    <MNT4_298 as PairingEngine>::Fr,
+    <MNT6_298 as PairingEngine>::Fr
+);
+nonnative_test!(
+    MNT64Small,
+    <MNT6_298 as PairingEngine>::Fr,
+    <MNT4_298 as PairingEngine>::Fr
+);
+nonnative_test!(
+    MNT46Big,
+    <MNT4_753 as PairingEngine>::Fr,
+    <MNT6_753 as PairingEngine>::Fr
+);
+nonnative_test!(
+    MNT64Big,
+    <MNT6_753 as PairingEngine>::Fr,
+    <MNT4_753 as PairingEngine>::Fr
+);
+nonnative_test!(
+    BLS12MNT4Small,
+    <Bls12_381 as PairingEngine>::Fr,
+    <MNT4_298 as PairingEngine>::Fr
+);
+nonnative_test!(
+    BLS12,
+    <Bls12_381 as PairingEngine>::Fq,
+    <Bls12_381 as PairingEngine>::Fr
+);
+#[cfg(not(ci))]
+nonnative_test!(
+    MNT6BigMNT4Small,
+    <MNT6_753 as PairingEngine>::Fr,
+    <MNT4_298 as PairingEngine>::Fr
+);
+
+#[test]
+fn groth16_prepare_inputs_test() {
+    use ark_ec::{AffineCurve, ProjectiveCurve};
+    use ark_ff::UniformRand;
+    use ark_mnt4_298::{constraints::G1Var, Fr, G1Projective};
+    use ark_nonnative_field::groth16;
+    use ark_r1cs_std::groups::CurveVar;
+
+    let rng = &mut ark_ff::test_rng();
+    let cs = ConstraintSystem::<<MNT6_298 as PairingEngine>::Fr>::new_ref();
+
+    let gamma_abc_g1: Vec<_> = (0..4)
+        .map(|_| G1Projective::rand(rng).into_affine())
+        .collect();
+    let inputs_native: Vec<Fr> = (0..3).map(|_| Fr::rand(rng)).collect();
+
+    let mut inputs: Vec<_> = inputs_native[..2]
+        .iter()
+        .map(|a| {
+            NonNativeFieldVar::new_witness(ark_relations::ns!(cs, "alloc input"), || Ok(*a))
+                .unwrap()
+        })
+        .collect();
+    inputs.push(NonNativeFieldVar::Constant(inputs_native[2]));
+
+    let res = groth16::prepare_inputs::<G1Projective, G1Var, _>(&gamma_abc_g1, &inputs).unwrap();
+
+    let mut expected = gamma_abc_g1[0].into_projective();
+    for (a, base) in inputs_native.iter().zip(gamma_abc_g1.iter().skip(1)) {
+        expected += &base.mul(a.into_repr());
+    }
+
+    assert_eq!(res.value().unwrap(), expected);
+    assert!(cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn u256_wrapping_arithmetic_test() {
+    use ark_nonnative_field::u256::U256Var;
+    use num_bigint::BigUint;
+
+    let rng = &mut ark_ff::test_rng();
+    let cs = ConstraintSystem::<<MNT6_298 as PairingEngine>::Fr>::new_ref();
+
+    let to_biguint = |v: &[u64; 4]| {
+        let mut res = BigUint::from(0u64);
+        for limb in v.iter().rev() {
+            res = (res << 64) + BigUint::from(*limb);
+        }
+        res
+    };
+    let modulus = BigUint::from(1u64) << 256;
+
+    let a_native = [
+        rng.next_u64(),
+        rng.next_u64(),
+        rng.next_u64(),
+        rng.next_u64(),
+    ];
+    let b_native = [u64::max_value(); 4];
+    let a = U256Var::new_witness(ark_relations::ns!(cs, "alloc a"), || Ok(a_native)).unwrap();
+    let b = U256Var::new_witness(ark_relations::ns!(cs, "alloc b"), || Ok(b_native)).unwrap();
+    let (a_big, b_big) = (to_biguint(&a_native), to_biguint(&b_native));
+
+    let sum = a.wrapping_add(&b).unwrap().value().unwrap();
+    assert_eq!(to_biguint(&sum), (&a_big + &b_big) % &modulus);
+
+    let diff = a.wrapping_sub(&b).unwrap().value().unwrap();
+    assert_eq!(to_biguint(&diff), (&a_big + &modulus - &b_big) % &modulus);
+
+    let prod = a.wrapping_mul(&b).unwrap().value().unwrap();
+    assert_eq!(to_biguint(&prod), (&a_big * &b_big) % &modulus);
+
+    assert!(a.is_lt(&b).unwrap().value().unwrap());
+    assert!(!a.is_gt(&b).unwrap().value().unwrap());
+    assert!(a.is_le(&a).unwrap().value().unwrap());
+    assert!(a.is_ge(&a).unwrap().value().unwrap());
+
+    let bytes = a.to_bytes_be().unwrap();
+    let a_again = U256Var::from_bytes_be(&bytes).unwrap();
+    a.enforce_equal(&a_again).unwrap();
+
+    assert!(cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn setup_mode_shape_test() {
+    use ark_r1cs_std::bits::ToBitsGadget;
+    use ark_relations::r1cs::{SynthesisError, SynthesisMode};
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let synthesize = |cs: ConstraintSystemRef<BaseField>,
+                      values: Option<(TargetField, TargetField)>| {
+        let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+            ark_relations::ns!(cs, "alloc a"),
+            || values.map(|v| v.0).ok_or(SynthesisError::AssignmentMissing),
+        )
+        .unwrap();
+        let b = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+            ark_relations::ns!(cs, "alloc b"),
+            || values.map(|v| v.1).ok_or(SynthesisError::AssignmentMissing),
+        )
+        .unwrap();
+
+        let c = &a * &b + &a;
+        let d = c.inverse().unwrap();
+        let _ = d.is_eq(&b).unwrap();
+        let _ = c.to_bits_le().unwrap();
+    };
+
+    let rng = &mut ark_ff::test_rng();
+
+    let setup_cs = ConstraintSystem::<BaseField>::new_ref();
+    setup_cs.set_mode(SynthesisMode::Setup);
+    synthesize(setup_cs.clone(), None);
+
+    let prove_cs = ConstraintSystem::<BaseField>::new_ref();
+    synthesize(
+        prove_cs.clone(),
+        Some((TargetField::rand(rng), TargetField::rand(rng))),
+    );
+
+    assert_eq!(setup_cs.num_constraints(), prove_cs.num_constraints());
+    assert_eq!(
+        setup_cs.num_witness_variables(),
+        prove_cs.num_witness_variables()
+    );
+    assert!(prove_cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn mul_result_overflow_test() {
+    use ark_ff::{Field, UniformRand};
+    use ark_nonnative_field::{error::NonNativeFieldError, NonNativeFieldMulResultVar};
+    use ark_relations::r1cs::SynthesisError;
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+
+    let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "alloc a"),
+        || Ok(TargetField::rand(rng)),
+    )
+    .unwrap();
+    let prod = match a.mul_without_reduce(&a).unwrap() {
+        NonNativeFieldMulResultVar::Var(v) => v,
+        NonNativeFieldMulResultVar::Constant(_) => unreachable!(),
+    };
+
+    // repeatedly doubling the unreduced result must eventually be rejected instead of
+    // producing limbs that no longer fit into the base field
+    let mut cur = prod.clone();
+    let mut rejected = false;
+    for _ in 0..BaseField::size_in_bits() {
+        match cur.try_add(&cur) {
+            Ok(next) => cur = next,
+            Err(NonNativeFieldError::Overflow {
+                operation,
+                bits,
+                max_bits,
+            }) => {
+                assert_eq!(operation, "add");
+                assert!(bits > max_bits);
+                // the untyped addition reports the same overflow as a `SynthesisError`
+                assert_eq!(cur.add(&cur).err(), Some(SynthesisError::Unsatisfiable));
+                // the message names the operation and suggests the reduction
+                let error = NonNativeFieldError::Overflow {
+                    operation,
+                    bits,
+                    max_bits,
+                };
+                assert_eq!(
+                    error.to_string(),
+                    format!(
+                        "`add` would produce {}-bit limbs (max {}); call `reduce()` on the \
+                         operands first to normalize them",
+                        bits, max_bits
+                    )
+                );
+                rejected = true;
+                break;
+            }
+            Err(e) => panic!("unexpected error {}", e),
+        }
+    }
+    assert!(rejected);
+
+    let _ = cur.reduce().unwrap();
+
+    // the operator and `add_or_reduce` reduce the operands instead of failing
+    let mut cur = NonNativeFieldMulResultVar::Var(prod);
+    let mut expected = a.value().unwrap().square();
+    for i in 0..BaseField::size_in_bits() {
+        cur = if i % 2 == 0 {
+            &cur + &cur
+        } else {
+            cur.add_or_reduce(&cur).unwrap()
+        };
+        expected.double_in_place();
+    }
+    assert_eq!(cur.reduce().unwrap().value().unwrap(), expected);
+    assert!(cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn hit_rate_test() {
+    use ark_nonnative_field::params::HitRate;
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+    HitRate::init(&cs);
+    assert_eq!(HitRate::percentage(&cs), None);
+
+    let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "alloc a"),
+        || Ok(TargetField::rand(rng)),
+    )
+    .unwrap();
+    let _ = &a * &a;
+
+    let (hit, miss) = HitRate::get(&cs).unwrap();
+    assert_eq!(miss, 1);
+    assert!(hit > 0);
+    assert_eq!(HitRate::percentage(&cs), Some(hit * 100 / (hit + miss)));
+}
+
+#[test]
+fn shared_params_test() {
+    use ark_nonnative_field::params::{gen_params, get_shared_params};
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let expected = gen_params::<TargetField, BaseField>().unwrap();
+    let handles: Vec<_> = (0..4)
+        .map(|_| std::thread::spawn(|| get_shared_params::<TargetField, BaseField>().unwrap()))
+        .collect();
+
+    for handle in handles {
+        let params = handle.join().unwrap();
+        assert_eq!(params.num_limbs, expected.num_limbs);
+        assert_eq!(params.bits_per_top_limb, expected.bits_per_top_limb);
+        assert_eq!(params.bits_per_non_top_limb, expected.bits_per_non_top_limb);
+    }
+}
+
+#[test]
+fn density_optimization_test() {
+    use ark_nonnative_field::params::DensityOptimization;
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let a_native = TargetField::rand(rng);
+    let b_native = TargetField::rand(rng);
+
+    let mut num_constraints = Vec::new();
+    let mut num_non_zero = Vec::new();
+    for enabled in [false, true].iter() {
+        let cs = ConstraintSystem::<BaseField>::new_ref();
+        if *enabled {
+            DensityOptimization::enable(&cs);
+        }
+        assert_eq!(DensityOptimization::is_enabled(&cs), *enabled);
+
+        let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+            ark_relations::ns!(cs, "alloc a"),
+            || Ok(a_native),
+        )
+        .unwrap();
+        let b = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+            ark_relations::ns!(cs, "alloc b"),
+            || Ok(b_native),
+        )
+        .unwrap();
+
+        let c = &(&a * &b) + &a;
+        assert_eq!(c.value().unwrap(), a_native * &b_native + &a_native);
+        let d = &c * &a;
+        d.enforce_equal(&NonNativeFieldVar::constant(
+            (a_native * &b_native + &a_native) * &a_native,
+        ))
+        .unwrap();
+
+        cs.inline_all_lcs();
+        assert!(cs.is_satisfied().unwrap());
+        let matrices = cs.to_matrices().unwrap();
+        num_constraints.push(cs.num_constraints());
+        num_non_zero
+            .push(matrices.a_num_non_zero + matrices.b_num_non_zero + matrices.c_num_non_zero);
+    }
+
+    // inlining the single-use intermediates lowers both measures
+    assert!(num_constraints[1] < num_constraints[0]);
+    assert!(num_non_zero[1] < num_non_zero[0]);
+}
+
+#[test]
+fn optimization_mode_test() {
+    use ark_ff::UniformRand;
+    use ark_nonnative_field::params::{
+        ConstraintsOpt, DensityOpt, DensityOptimization, OptimizationMode,
+    };
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    // the number of constraints and of nonzero entries of `(a * b + a) * a` in the mode `Opt`
+    fn measure<Opt: OptimizationMode>(
+        a_native: TargetField,
+        b_native: TargetField,
+        enable_density: bool,
+    ) -> (usize, usize) {
+        let cs = ConstraintSystem::<BaseField>::new_ref();
+        if enable_density {
+            DensityOptimization::enable(&cs);
+        }
+
+        let a = NonNativeFieldVar::<TargetField, BaseField, Opt>::new_witness(
+            ark_relations::ns!(cs, "alloc a"),
+            || Ok(a_native),
+        )
+        .unwrap();
+        let b = NonNativeFieldVar::<TargetField, BaseField, Opt>::new_witness(
+            ark_relations::ns!(cs, "alloc b"),
+            || Ok(b_native),
+        )
+        .unwrap();
+
+        let d = &(&(&a * &b) + &a) * &a;
+        assert_eq!(
+            d.value().unwrap(),
+            (a_native * &b_native + &a_native) * &a_native
+        );
+        d.enforce_equal(&NonNativeFieldVar::constant(
+            (a_native * &b_native + &a_native) * &a_native,
+        ))
+        .unwrap();
+
+        cs.inline_all_lcs();
+        assert!(cs.is_satisfied().unwrap());
+        let matrices = cs.to_matrices().unwrap();
+        (
+            cs.num_constraints(),
+            matrices.a_num_non_zero + matrices.b_num_non_zero + matrices.c_num_non_zero,
+        )
+    }
+
+    let rng = &mut ark_ff::test_rng();
+    let a_native = TargetField::rand(rng);
+    let b_native = TargetField::rand(rng);
+
+    let constraints = measure::<ConstraintsOpt>(a_native, b_native, false);
+    let density = measure::<DensityOpt>(a_native, b_native, false);
+    assert!(density.0 < constraints.0);
+    assert!(density.1 < constraints.1);
+
+    // the mode of the type gives the same system as the flag of the constraint system, which
+    // the default mode still follows
+    assert_eq!(measure::<ConstraintsOpt>(a_native, b_native, true), density);
+    assert_eq!(measure::<DensityOpt>(a_native, b_native, true), density);
+}
+
+#[test]
+fn optimization_mode_conversion_test() {
+    use ark_ff::UniformRand;
+    use ark_nonnative_field::params::{ConstraintsOpt, DensityOpt};
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let a_native = TargetField::rand(rng);
+    let b_native = TargetField::rand(rng);
+
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+    let a = NonNativeFieldVar::<TargetField, BaseField, DensityOpt>::new_witness(
+        ark_relations::ns!(cs, "alloc a"),
+        || Ok(a_native),
+    )
+    .unwrap();
+    let b = NonNativeFieldVar::<TargetField, BaseField, ConstraintsOpt>::new_witness(
+        ark_relations::ns!(cs, "alloc b"),
+        || Ok(b_native),
+    )
+    .unwrap();
+
+    // an unreduced result of one mode is used in the other one and back
+    let c = &(&a * &a) + &a;
+    let num_constraints_before = cs.num_constraints();
+    let c_converted = c.to_mode::<ConstraintsOpt>();
+    assert_eq!(cs.num_constraints(), num_constraints_before);
+    assert_eq!(c_converted.value().unwrap(), c.value().unwrap());
+
+    let d = &c_converted * &b;
+    let d_converted = d.to_mode::<DensityOpt>();
+    let e = &d_converted + &a;
+    let e_native = (a_native * &a_native + &a_native) * &b_native + &a_native;
+    assert_eq!(e.value().unwrap(), e_native);
+    e.enforce_equal(&NonNativeFieldVar::constant(e_native))
+        .unwrap();
+    e.to_mode::<ConstraintsOpt>()
+        .enforce_equal(&(&d + &a.to_mode::<ConstraintsOpt>()))
+        .unwrap();
+
+    let constant = NonNativeFieldVar::<TargetField, BaseField, DensityOpt>::constant(a_native);
+    assert!(constant.to_mode::<ConstraintsOpt>().is_constant());
+
+    assert!(cs.is_satisfied().unwrap());
+}
+
+#[cfg(feature = "reference-check")]
+#[test]
+fn reference_check_test() {
+    use ark_ff::{One, UniformRand, Zero};
+    use ark_nonnative_field::AllocatedNonNativeFieldVar;
+    use ark_r1cs_std::fields::fp::AllocatedFp;
+    use ark_relations::r1cs::SynthesisError;
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+
+    let a_native = TargetField::rand(rng);
+    let b_native = TargetField::rand(rng);
+    let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "alloc a"),
+        || Ok(a_native),
+    )
+    .unwrap();
+    let b = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "alloc b"),
+        || Ok(b_native),
+    )
+    .unwrap();
+
+    // every operation below is recomputed and compared at witness time, and the witness is
+    // also compared with the native computation after each step
+    let mut c = &a * &b;
+    let mut c_native = a_native * &b_native;
+    assert_eq!(c.value().unwrap(), c_native);
+    for _ in 0..20 {
+        c = &(&c + &a) - &b;
+        c_native = c_native + &a_native - &b_native;
+        assert_eq!(c.value().unwrap(), c_native);
+    }
+    c = &c + a_native;
+    c = &c - b_native;
+    c_native = c_native + &a_native - &b_native;
+    assert_eq!(c.value().unwrap(), c_native);
+    assert!(cs.is_satisfied().unwrap());
+
+    // an element whose bookkeeping claims limbs in the normal form while they are as large as
+    // the base field allows makes an addition wrap around the base field, which the
+    // reference computation catches
+    let other_cs = ConstraintSystem::<BaseField>::new_ref();
+    let a = AllocatedNonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(other_cs, "alloc a"),
+        || Ok(a_native),
+    )
+    .unwrap();
+    let limbs = a
+        .limbs
+        .iter()
+        .map(|_| {
+            AllocatedFp::new_witness(ark_relations::ns!(other_cs, "alloc limb"), || {
+                Ok(-BaseField::one())
+            })
+            .unwrap()
+        })
+        .collect();
+    let wrong = AllocatedNonNativeFieldVar::<TargetField, BaseField> {
+        cs: other_cs.clone(),
+        limbs,
+        num_of_additions_over_normal_form: BaseField::zero(),
+        is_in_the_normal_form: true,
+        target_phantom: a.target_phantom,
+    };
+    assert_eq!(
+        wrong.add(&wrong).unwrap_err(),
+        SynthesisError::Unsatisfiable
+    );
+}
+
+#[cfg(feature = "test-fields")]
+#[test]
+fn tiny_field_boundary_test() {
+    use ark_nonnative_field::test_fields::{boundary_elements, F13, F61};
+
+    fn check<TargetField: PrimeField>() {
+        type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+        let elements = boundary_elements::<TargetField>();
+        let cs = ConstraintSystem::<BaseField>::new_ref();
+        let vars: Vec<NonNativeFieldVar<TargetField, BaseField>> = elements
+            .iter()
+            .map(|e| {
+                NonNativeFieldVar::new_witness(ark_relations::ns!(cs, "alloc"), || Ok(*e)).unwrap()
+            })
+            .collect();
+
+        for (a_native, a) in elements.iter().zip(vars.iter()) {
+            for (b_native, b) in elements.iter().zip(vars.iter()) {
+                assert_eq!((a + b).value().unwrap(), *a_native + b_native);
+                assert_eq!((a - b).value().unwrap(), *a_native - b_native);
+
+                let prod = a * b;
+                assert_eq!(prod.value().unwrap(), *a_native * b_native);
+                prod.enforce_equal(&NonNativeFieldVar::constant(*a_native * b_native))
+                    .unwrap();
+            }
+        }
+
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    check::<F13>();
+    check::<F61>();
+}
+
+#[test]
+fn tight_top_limb_test() {
+    use ark_nonnative_field::params::gen_params;
+
+    fn check<TargetField: PrimeField, BaseField: PrimeField>() {
+        let params = gen_params::<TargetField, BaseField>().unwrap();
+        let covered =
+            params.bits_per_top_limb + (params.num_limbs - 1) * params.bits_per_non_top_limb;
+
+        // the limbs cover the target field, and the top limb has no bits to spare
+        assert!(covered >= TargetField::size_in_bits());
+        assert!(params.bits_per_top_limb == 0 || covered == TargetField::size_in_bits());
+    }
+
+    check::<<MNT4_298 as PairingEngine>::Fr, <MNT6_298 as PairingEngine>::Fr>();
+    check::<<MNT6_298 as PairingEngine>::Fr, <MNT4_298 as PairingEngine>::Fr>();
+    check::<<MNT4_753 as PairingEngine>::Fr, <MNT6_753 as PairingEngine>::Fr>();
+    check::<<Bls12_381 as PairingEngine>::Fr, <MNT4_298 as PairingEngine>::Fr>();
+    check::<<Bls12_381 as PairingEngine>::Fq, <Bls12_381 as PairingEngine>::Fr>();
+}
+
+#[cfg(feature = "test-fields")]
+#[test]
+fn small_base_field_test() {
+    use ark_nonnative_field::{params::gen_params, test_fields::F61};
+
+    type TargetField = <Bls12_381 as PairingEngine>::Fr;
+
+    // a 255-bit field over a 61-bit one needs many limbs
+    let params = gen_params::<TargetField, F61>().unwrap();
+    assert!(params.num_limbs > 8);
+
+    let rng = &mut ark_ff::test_rng();
+    let cs = ConstraintSystem::<F61>::new_ref();
+
+    let a_native = TargetField::rand(rng);
+    let b_native = TargetField::rand(rng);
+    let a = NonNativeFieldVar::<TargetField, F61>::new_witness(
+        ark_relations::ns!(cs, "alloc a"),
+        || Ok(a_native),
+    )
+    .unwrap();
+    let b = NonNativeFieldVar::<TargetField, F61>::new_witness(
+        ark_relations::ns!(cs, "alloc b"),
+        || Ok(b_native),
+    )
+    .unwrap();
+
+    let c = &(&a * &b) + &a;
+    assert_eq!(c.value().unwrap(), a_native * &b_native + &a_native);
+    c.enforce_equal(&NonNativeFieldVar::constant(
+        a_native * &b_native + &a_native,
+    ))
+    .unwrap();
+    assert!(cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn on_curve_test() {
+    use ark_ec::AffineCurve;
+    use ark_nonnative_field::curves::{enforce_on_curve, enforce_on_te_curve};
+
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+
+    let g = ark_bls12_381::G1Affine::prime_subgroup_generator();
+    let x = NonNativeFieldVar::new_witness(ark_relations::ns!(cs, "x"), || Ok(g.x)).unwrap();
+    let y = NonNativeFieldVar::new_witness(ark_relations::ns!(cs, "y"), || Ok(g.y)).unwrap();
+    enforce_on_curve::<ark_bls12_381::g1::Parameters, _>(&x, &y).unwrap();
+
+    let h = ark_ed_on_bls12_381::EdwardsAffine::prime_subgroup_generator();
+    let x = NonNativeFieldVar::new_witness(ark_relations::ns!(cs, "x"), || Ok(h.x)).unwrap();
+    let y = NonNativeFieldVar::new_witness(ark_relations::ns!(cs, "y"), || Ok(h.y)).unwrap();
+    enforce_on_te_curve::<ark_ed_on_bls12_381::EdwardsParameters, _>(&x, &y).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+
+    // a point off the curve makes the system unsatisfied
+    let y = NonNativeFieldVar::new_witness(ark_relations::ns!(cs, "y"), || Ok(h.x)).unwrap();
+    enforce_on_te_curve::<ark_ed_on_bls12_381::EdwardsParameters, _>(&x, &y).unwrap();
+    assert!(!cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn subgroup_check_test() {
+    use ark_ec::AffineCurve;
+    use ark_ff::{One, SquareRootField};
+    use ark_nonnative_field::curves::short_weierstrass::NonNativeAffineVar;
+
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+    type P = ark_bls12_381::g1::Parameters;
+
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+
+    let g = ark_bls12_381::G1Affine::prime_subgroup_generator();
+    let point =
+        NonNativeAffineVar::<P, BaseField>::new_witness(ark_relations::ns!(cs, "g"), || Ok(g))
+            .unwrap();
+    point.enforce_on_curve().unwrap();
+    point.enforce_in_prime_order_subgroup().unwrap();
+    #[cfg(feature = "bls12-381")]
+    ark_nonnative_field::curves::bls12_381::enforce_in_g1(&point).unwrap();
+
+    let double = point.double().unwrap();
+    let triple = double.add_unchecked(&point).unwrap();
+    assert_eq!(double.value().unwrap(), g + g);
+    assert_eq!(triple.value().unwrap(), g + g + g);
+    assert!(cs.is_satisfied().unwrap());
+
+    // a point on the curve outside of the subgroup is rejected
+    let mut x = ark_bls12_381::Fq::one();
+    let outside = loop {
+        let rhs = x * &x * &x + &ark_bls12_381::Fq::from(4u64);
+        if let Some(y) = rhs.sqrt() {
+            let candidate = ark_bls12_381::G1Affine::new(x, y, false);
+            if !candidate.is_in_correct_subgroup_assuming_on_curve() {
+                break candidate;
+            }
+        }
+        x += &ark_bls12_381::Fq::one();
+    };
+
+    let other_cs = ConstraintSystem::<BaseField>::new_ref();
+    let point = NonNativeAffineVar::<P, BaseField>::new_witness(
+        ark_relations::ns!(other_cs, "outside"),
+        || Ok(outside),
+    )
+    .unwrap();
+    point.enforce_on_curve().unwrap();
+    assert!(other_cs.is_satisfied().unwrap());
+    point.enforce_in_prime_order_subgroup().unwrap();
+    assert!(!other_cs.is_satisfied().unwrap());
+
+    #[cfg(feature = "bls12-381")]
+    {
+        let other_cs = ConstraintSystem::<BaseField>::new_ref();
+        let point = NonNativeAffineVar::<P, BaseField>::new_witness(
+            ark_relations::ns!(other_cs, "outside"),
+            || Ok(outside),
+        )
+        .unwrap();
+        point.enforce_on_curve().unwrap();
+        ark_nonnative_field::curves::bls12_381::enforce_in_g1(&point).unwrap();
+        assert!(!other_cs.is_satisfied().unwrap());
+    }
+}
+
+#[test]
+fn decompression_test() {
+    use ark_ec::AffineCurve;
+    use ark_ff::BigInteger;
+    use ark_nonnative_field::curves::short_weierstrass::{NonNativeAffineVar, SignConvention};
+    use ark_r1cs_std::boolean::Boolean;
+
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+    type P = ark_bls12_381::g1::Parameters;
+
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+    let g = ark_bls12_381::G1Affine::prime_subgroup_generator();
+    let x = NonNativeFieldVar::new_witness(ark_relations::ns!(cs, "x"), || Ok(g.x)).unwrap();
+
+    for (convention, sign) in [
+        (SignConvention::Parity, g.y.into_repr().is_odd()),
+        (SignConvention::Lexicographic, g.y > -g.y),
+    ]
+    .iter()
+    {
+        let sign_var = Boolean::new_witness(ark_relations::ns!(cs, "sign"), || Ok(*sign)).unwrap();
+        let point =
+            NonNativeAffineVar::<P, BaseField>::decompress(&x, &sign_var, *convention).unwrap();
+        assert_eq!(point.value().unwrap(), g);
+
+        let sign_var = Boolean::new_witness(ark_relations::ns!(cs, "sign"), || Ok(!*sign)).unwrap();
+        let point =
+            NonNativeAffineVar::<P, BaseField>::decompress(&x, &sign_var, *convention).unwrap();
+        assert_eq!(point.value().unwrap(), -g);
+    }
+
+    assert!(cs.is_satisfied().unwrap());
+
+    // the root of the other sign is rejected
+    for (convention, sign) in [
+        (SignConvention::Parity, g.y.into_repr().is_odd()),
+        (SignConvention::Lexicographic, g.y > -g.y),
+    ]
+    .iter()
+    {
+        let other_cs = ConstraintSystem::<BaseField>::new_ref();
+        let x =
+            NonNativeFieldVar::new_witness(ark_relations::ns!(other_cs, "x"), || Ok(g.x)).unwrap();
+        let sign_var =
+            Boolean::new_witness(ark_relations::ns!(other_cs, "sign"), || Ok(!*sign)).unwrap();
+        let point =
+            NonNativeAffineVar::<P, BaseField>::decompress(&x, &sign_var, *convention).unwrap();
+        let y =
+            NonNativeFieldVar::new_witness(ark_relations::ns!(other_cs, "y"), || Ok(g.y)).unwrap();
+        point.y.enforce_equal(&y).unwrap();
+        assert!(!other_cs.is_satisfied().unwrap());
+    }
+}
+
+#[test]
+fn montgomery_ladder_test() {
+    use ark_ec::{AffineCurve, ProjectiveCurve};
+    use ark_nonnative_field::curves::short_weierstrass::NonNativeAffineVar;
+    use ark_r1cs_std::boolean::Boolean;
+
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+    type P = ark_bls12_381::g1::Parameters;
+
+    let rng = &mut ark_ff::test_rng();
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+
+    let g = ark_bls12_381::G1Affine::prime_subgroup_generator();
+    let point =
+        NonNativeAffineVar::<P, BaseField>::new_witness(ark_relations::ns!(cs, "g"), || Ok(g))
+            .unwrap();
+
+    let scalar = rng.next_u32() | (1 << 31);
+    let bits: Vec<Boolean<BaseField>> = (0..32)
+        .map(|i| {
+            Boolean::new_witness(ark_relations::ns!(cs, "bit"), || Ok((scalar >> i) & 1 == 1))
+                .unwrap()
+        })
+        .collect();
+
+    let res = point.scalar_mul_ladder(&bits).unwrap();
+    let expected = g.mul(ark_bls12_381::Fr::from(scalar as u64)).into_affine();
+    assert_eq!(res.value().unwrap(), expected);
+    assert!(cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn ladder_at_x_zero_test() {
+    use ark_ec::models::SWModelParameters;
+    use ark_ec::{short_weierstrass_jacobian::GroupAffine, AffineCurve, ProjectiveCurve};
+    use ark_ff::{SquareRootField, Zero};
+    use ark_nonnative_field::curves::{
+        p256::{Fq, Fr, P256Parameters},
+        short_weierstrass::NonNativeAffineVar,
+    };
+    use ark_r1cs_std::boolean::Boolean;
+
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    // the coefficient b of P-256 is a square, so the curve has points with x = 0, which have
+    // the prime order of the curve since its cofactor is one
+    let rng = &mut ark_ff::test_rng();
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+
+    let y = P256Parameters::COEFF_B.sqrt().unwrap();
+    let p = GroupAffine::<P256Parameters>::new(Fq::zero(), y, false);
+    let point = NonNativeAffineVar::<P256Parameters, BaseField>::new_witness(
+        ark_relations::ns!(cs, "p"),
+        || Ok(p),
+    )
+    .unwrap();
+
+    let scalar = rng.next_u32() | (1 << 31);
+    let bits: Vec<Boolean<BaseField>> = (0..32)
+        .map(|i| {
+            Boolean::new_witness(ark_relations::ns!(cs, "bit"), || Ok((scalar >> i) & 1 == 1))
+                .unwrap()
+        })
+        .collect();
+
+    let res = point.scalar_mul_ladder(&bits).unwrap();
+    let expected = p.mul(Fr::from(scalar as u64)).into_affine();
+    assert_eq!(res.value().unwrap(), expected);
+    assert!(cs.is_satisfied().unwrap());
+}
+
+#[cfg(not(ci))]
+#[test]
+fn x25519_test() {
+    use ark_nonnative_field::curves::curve25519::{x25519, Fq25519};
+    use ark_r1cs_std::boolean::Boolean;
+    use std::str::FromStr;
+
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    // the first test vector of RFC 7748, with the scalar already clamped
+    let scalar: [u64; 4] = [
+        0x9d7c52f06be346a0,
+        0xdd5e46824b15163b,
+        0x185afcc10a4c1462,
+        0x449a44ba44226a50,
+    ];
+    let u_native = Fq25519::from_str(
+        "34426434033919594451155107781188821651316167215306631574996226621102155684838",
+    )
+    .unwrap();
+    let expected = Fq25519::from_str(
+        "37325765543539916631701301279660700968428932651319597985674090122993663859395",
+    )
+    .unwrap();
+
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+    let bits: Vec<Boolean<BaseField>> = (0..255)
+        .map(|i| {
+            Boolean::new_witness(ark_relations::ns!(cs, "bit"), || {
+                Ok((scalar[i / 64] >> (i % 64)) & 1 == 1)
+            })
+            .unwrap()
+        })
+        .collect();
+    let u = NonNativeFieldVar::new_witness(ark_relations::ns!(cs, "u"), || Ok(u_native)).unwrap();
+
+    let res = x25519(&bits, &u).unwrap();
+    assert_eq!(res.value().unwrap(), expected);
+    assert!(cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn p256_presets_test() {
+    use ark_nonnative_field::{
+        curves::p256::{Fq, PARAMS_OVER_BLS12_381_FR, PARAMS_OVER_BN254_FR, PARAMS_OVER_MNT_298},
+        params::{gen_params, set_params, ParamsSearching},
+        NonNativeFieldParams,
+    };
+
+    let same = |a: &NonNativeFieldParams, b: &NonNativeFieldParams| {
+        a.num_limbs == b.num_limbs
+            && a.bits_per_top_limb == b.bits_per_top_limb
+            && a.bits_per_non_top_limb == b.bits_per_non_top_limb
+    };
+
+    let searched = gen_params::<Fq, <Bls12_381 as PairingEngine>::Fr>().unwrap();
+    assert!(same(&searched, &PARAMS_OVER_BLS12_381_FR));
+    let searched = gen_params::<Fq, <MNT6_298 as PairingEngine>::Fr>().unwrap();
+    assert!(same(&searched, &PARAMS_OVER_MNT_298));
+
+    let mut problem = ParamsSearching::new(254, 256);
+    problem.solve();
+    assert_eq!(problem.num_of_limbs, PARAMS_OVER_BN254_FR.num_limbs);
+    assert_eq!(
+        problem.top_limb_size,
+        Some(PARAMS_OVER_BN254_FR.bits_per_top_limb)
+    );
+    assert_eq!(
+        problem.non_top_limb_size,
+        Some(PARAMS_OVER_BN254_FR.bits_per_non_top_limb)
+    );
+
+    // a preset is accepted as long as its limbs cover the target field and fit the reducer
+    let cs = ConstraintSystem::<<Bls12_381 as PairingEngine>::Fr>::new_ref();
+    assert!(set_params::<Fq, _>(&cs, PARAMS_OVER_MNT_298).is_ok());
+    let too_small = NonNativeFieldParams {
+        num_limbs: 3,
+        bits_per_top_limb: 60,
+        bits_per_non_top_limb: 94,
+        equality_layout: None,
+    };
+    assert!(set_params::<Fq, _>(&cs, too_small).is_err());
+}
+
+#[cfg(not(ci))]
+#[test]
+fn p256_ecdsa_test() {
+    use ark_ec::short_weierstrass_jacobian::GroupAffine;
+    use ark_nonnative_field::{
+        curves::{
+            p256::{verify_ecdsa, Fq, Fr, P256Parameters, PARAMS_OVER_BLS12_381_FR},
+            short_weierstrass::NonNativeAffineVar,
+        },
+        params::set_params,
+    };
+    use std::str::FromStr;
+
+    type BaseField = <Bls12_381 as PairingEngine>::Fr;
+
+    let public_key = GroupAffine::<P256Parameters>::new(
+        Fq::from_str(
+            "89597842475883119309267159982467775324206547854755065074295300459217223600783",
+        )
+        .unwrap(),
+        Fq::from_str(
+            "81914003552677634664438003469761717571825734889263331194638006564787885899298",
+        )
+        .unwrap(),
+        false,
+    );
+    let hash = Fr::from_str(
+        "4349433889956498187559679968577730028570737179671342555900890444052470358575",
+    )
+    .unwrap();
+    let r = Fr::from_str(
+        "27626107455200108227355382468151404150332870750008382101680335691856160719828",
+    )
+    .unwrap();
+    let s = Fr::from_str(
+        "18430354039672683099294632694333233706802676872854864200882134934537169152318",
+    )
+    .unwrap();
+
+    for (s, valid) in [(s, true), (s + &Fr::from(1u64), false)].iter() {
+        let cs = ConstraintSystem::<BaseField>::new_ref();
+        set_params::<Fq, BaseField>(&cs, PARAMS_OVER_BLS12_381_FR).unwrap();
+
+        let public_key_var = NonNativeAffineVar::<P256Parameters, BaseField>::new_witness(
+            ark_relations::ns!(cs, "public_key"),
+            || Ok(public_key),
+        )
+        .unwrap();
+        public_key_var.enforce_on_curve().unwrap();
+        let hash_var =
+            NonNativeFieldVar::new_witness(ark_relations::ns!(cs, "hash"), || Ok(hash)).unwrap();
+        let r_var = NonNativeFieldVar::new_witness(ark_relations::ns!(cs, "r"), || Ok(r)).unwrap();
+        let s_var = NonNativeFieldVar::new_witness(ark_relations::ns!(cs, "s"), || Ok(*s)).unwrap();
+
+        verify_ecdsa(&public_key_var, &hash_var, &r_var, &s_var).unwrap();
+        assert_eq!(cs.is_satisfied().unwrap(), *valid);
+    }
+}
+
+#[test]
+fn pasta_params_test() {
+    use ark_nonnative_field::{
+        curves::pasta::{Fp, Fq, PARAMS},
+        params::{gen_params, ParamsSearching},
+    };
+
+    for params in [
+        gen_params::<Fp, Fq>().unwrap(),
+        gen_params::<Fq, Fp>().unwrap(),
+    ]
+    .iter()
+    {
+        assert_eq!(params.num_limbs, PARAMS.num_limbs);
+        assert_eq!(params.bits_per_top_limb, PARAMS.bits_per_top_limb);
+        assert_eq!(params.bits_per_non_top_limb, PARAMS.bits_per_non_top_limb);
+    }
+
+    // the search stops at the first number of limbs that works, so check that no larger
+    // number of limbs is cheaper
+    let mut problem = ParamsSearching::new(255, 255);
+    assert!(problem.solve_with_num_of_limbs(2).is_none());
+    let cost = problem.solve_with_num_of_limbs(PARAMS.num_limbs).unwrap();
+    assert_eq!(problem.top_limb_size, Some(PARAMS.bits_per_top_limb));
+    assert_eq!(
+        problem.non_top_limb_size,
+        Some(PARAMS.bits_per_non_top_limb)
+    );
+    for num_of_limbs in PARAMS.num_limbs + 1..=10 {
+        let other_cost = problem.solve_with_num_of_limbs(num_of_limbs).unwrap();
+        assert!(other_cost > cost);
+    }
+}
+
+#[test]
+fn pasta_conversion_test() {
+    use ark_ff::UniformRand;
+    use ark_nonnative_field::{
+        curves::pasta::{Fp, FpVarOverFq, Fq, FqVarOverFp, PARAMS},
+        params::set_params,
+    };
+    use ark_r1cs_std::fields::fp::FpVar;
+
+    let mut rng = ark_ff::test_rng();
+
+    // Fp is the smaller field, so its elements embed into Fq
+    let cs = ConstraintSystem::<Fq>::new_ref();
+    set_params::<Fp, Fq>(&cs, PARAMS).unwrap();
+    for _ in 0..10 {
+        let a_native = Fp::rand(&mut rng);
+        let a = FpVarOverFq::new_witness(ark_relations::ns!(cs, "a"), || Ok(a_native)).unwrap();
+        let a_converted = a.to_native().unwrap();
+        assert_eq!(
+            a_converted.value().unwrap(),
+            Fq::from_repr(a_native.into_repr()).unwrap()
+        );
+
+        let b_native = Fq::rand(&mut rng);
+        let b = FpVar::new_witness(ark_relations::ns!(cs, "b"), || Ok(b_native)).unwrap();
+        let b_converted = FpVarOverFq::from_native(&b).unwrap();
+        let b_expected = Fp::from_le_bytes_mod_order(&ark_ff::to_bytes![b_native].unwrap());
+        assert_eq!(b_converted.value().unwrap(), b_expected);
+
+        // the result of the conversion takes part in the arithmetic
+        let c = &a * &b_converted;
+        assert_eq!(c.value().unwrap(), a_native * &b_expected);
+    }
+    assert!(cs.is_satisfied().unwrap());
+
+    // Fq is the larger field, so its elements are reduced into Fp
+    let cs = ConstraintSystem::<Fp>::new_ref();
+    set_params::<Fq, Fp>(&cs, PARAMS).unwrap();
+    for _ in 0..10 {
+        let a_native = Fq::rand(&mut rng);
+        let a = FqVarOverFp::new_witness(ark_relations::ns!(cs, "a"), || Ok(a_native)).unwrap();
+        let a_converted = a.to_native().unwrap();
+        let a_expected = Fp::from_le_bytes_mod_order(&ark_ff::to_bytes![a_native].unwrap());
+        assert_eq!(a_converted.value().unwrap(), a_expected);
+
+        let b_native = Fp::rand(&mut rng);
+        let b = FpVar::new_witness(ark_relations::ns!(cs, "b"), || Ok(b_native)).unwrap();
+        let b_converted = FqVarOverFp::from_native(&b).unwrap();
+        assert_eq!(
+            b_converted.value().unwrap(),
+            Fq::from_repr(b_native.into_repr()).unwrap()
+        );
+    }
+    assert!(cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn u256_mod_arithmetic_test() {
+    use ark_nonnative_field::u256::U256Var;
+    use num_bigint::BigUint;
+
+    let rng = &mut ark_ff::test_rng();
+    let cs = ConstraintSystem::<<MNT6_298 as PairingEngine>::Fr>::new_ref();
+
+    let to_biguint = |v: &[u64; 4]| {
+        let mut res = BigUint::from(0u64);
+        for limb in v.iter().rev() {
+            res = (res << 64) + BigUint::from(*limb);
+        }
+        res
+    };
+
+    let a_native = [
+        rng.next_u64(),
+        rng.next_u64(),
+        rng.next_u64(),
+        rng.next_u64(),
+    ];
+    let b_native = [u64::max_value(); 4];
+    let a = U256Var::new_witness(ark_relations::ns!(cs, "alloc a"), || Ok(a_native)).unwrap();
+    let b = U256Var::new_witness(ark_relations::ns!(cs, "alloc b"), || Ok(b_native)).unwrap();
+    let (a_big, b_big) = (to_biguint(&a_native), to_biguint(&b_native));
+
+    // a large modulus, and a small one whose quotients take all of their limbs
+    let moduli = [
+        [
+            rng.next_u64(),
+            rng.next_u64(),
+            rng.next_u64(),
+            rng.next_u64() >> 1,
+        ],
+        [7, 0, 0, 0],
+    ];
+    for m_native in moduli.iter() {
+        let m = U256Var::new_witness(ark_relations::ns!(cs, "alloc m"), || Ok(*m_native)).unwrap();
+        let m_big = to_biguint(m_native);
+
+        let sum = a.add_mod(&b, &m).unwrap().value().unwrap();
+        assert_eq!(to_biguint(&sum), (&a_big + &b_big) % &m_big);
+
+        let prod = a.mul_mod(&b, &m).unwrap().value().unwrap();
+        assert_eq!(to_biguint(&prod), (&a_big * &b_big) % &m_big);
+    }
+    assert!(cs.is_satisfied().unwrap());
+
+    let zero =
+        U256Var::new_witness(ark_relations::ns!(cs, "alloc zero"), || Ok([0u64; 4])).unwrap();
+    assert!(a.mul_mod(&b, &zero).is_err());
+}
+
+#[test]
+fn uint_rsa_modulus_test() {
+    use ark_nonnative_field::uint::NonNativeUintVar;
+    use ark_r1cs_std::boolean::Boolean;
+    use num_bigint::BigUint;
+
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let mut random = |num_limbs: usize| {
+        let mut res = BigUint::from(0u64);
+        for _ in 0..num_limbs {
+            res = (res << 64) + BigUint::from(rng.next_u64());
+        }
+        res
+    };
+
+    // a 2048-bit modulus that is an input of the circuit, as in RSA accumulators
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+    let m_native = random(32) | (BigUint::from(1u64) << 2047);
+    let a_native = random(32) % &m_native;
+    let b_native = random(32) % &m_native;
+    let m = NonNativeUintVar::new_input(ark_relations::ns!(cs, "m"), || Ok(m_native.clone()), 32)
+        .unwrap();
+    let a = NonNativeUintVar::new_witness(ark_relations::ns!(cs, "a"), || Ok(a_native.clone()), 32)
+        .unwrap();
+    let b = NonNativeUintVar::new_witness(ark_relations::ns!(cs, "b"), || Ok(b_native.clone()), 32)
+        .unwrap();
+
+    let sum = a.add_mod(&b, &m).unwrap();
+    assert_eq!(sum.value().unwrap(), (&a_native + &b_native) % &m_native);
+    let prod = a.mul_mod(&b, &m).unwrap();
+    assert_eq!(prod.value().unwrap(), (&a_native * &b_native) % &m_native);
+    assert!(prod.is_lt(&m).unwrap().value().unwrap());
+
+    // a^5 with the little-endian bits of the exponent
+    let exponent = [true, false, true]
+        .iter()
+        .map(|bit| Boolean::new_witness(ark_relations::ns!(cs, "bit"), || Ok(*bit)).unwrap())
+        .collect::<Vec<_>>();
+    let power = a.pow_mod(&exponent, &m).unwrap();
+    assert_eq!(
+        power.value().unwrap(),
+        a_native.modpow(&BigUint::from(5u64), &m_native)
+    );
+    assert!(cs.is_satisfied().unwrap());
+
+    // a wrong product is rejected
+    let wrong = NonNativeUintVar::new_witness(
+        ark_relations::ns!(cs, "wrong"),
+        || Ok((&a_native * &b_native + 1u64) % &m_native),
+        32,
+    )
+    .unwrap();
+    prod.enforce_equal(&wrong).unwrap();
+    assert!(!cs.is_satisfied().unwrap());
+
+    // a zero modulus has no remainders
+    let other_cs = ConstraintSystem::<BaseField>::new_ref();
+    let zero = NonNativeUintVar::new_witness(
+        ark_relations::ns!(other_cs, "zero"),
+        || Ok(BigUint::from(0u64)),
+        4,
+    )
+    .unwrap();
+    let a = NonNativeUintVar::new_witness(
+        ark_relations::ns!(other_cs, "a"),
+        || Ok(BigUint::from(3u64)),
+        4,
+    )
+    .unwrap();
+    assert!(a.mul_mod(&a, &zero).is_err());
+}
+
+#[test]
+fn paillier_test() {
+    use ark_nonnative_field::{paillier::PaillierPublicKeyVar, uint::NonNativeUintVar};
+    use ark_r1cs_std::boolean::Boolean;
+    use num_bigint::BigUint;
+
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let one = BigUint::from(1u64);
+
+    // the Mersenne primes 2^127 - 1 and 2^89 - 1, and the generator N + 1
+    let p = (&one << 127usize) - 1u64;
+    let q = (&one << 89usize) - 1u64;
+    let n_native = &p * &q;
+    let n_squared_native = &n_native * &n_native;
+    let phi = (&p - 1u64) * (&q - 1u64);
+    let encrypt = |m: &BigUint, r: &BigUint| {
+        ((&one + m * &n_native) * r.modpow(&n_native, &n_squared_native)) % &n_squared_native
+    };
+    // `c^phi = 1 + m·phi·N mod N^2`, so `(c^phi - 1) / N = m·phi mod N`
+    let decrypts_to = |c: &BigUint, m: &BigUint| {
+        (c.modpow(&phi, &n_squared_native) - 1u64) / &n_native == (m * &phi) % &n_native
+    };
+
+    let m1 = BigUint::from(rng.next_u64());
+    let m2 = BigUint::from(rng.next_u64());
+    let k = rng.next_u64();
+    let c1_native = encrypt(&m1, &BigUint::from(rng.next_u64()));
+    let c2_native = encrypt(&m2, &BigUint::from(rng.next_u64()));
+
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+    let n = NonNativeUintVar::new_input(ark_relations::ns!(cs, "n"), || Ok(n_native.clone()), 4)
+        .unwrap();
+    let key = PaillierPublicKeyVar::new(n).unwrap();
+    assert_eq!(key.n_squared.value().unwrap(), n_squared_native);
+    let c1 =
+        NonNativeUintVar::new_witness(ark_relations::ns!(cs, "c1"), || Ok(c1_native.clone()), 8)
+            .unwrap();
+    let c2 =
+        NonNativeUintVar::new_witness(ark_relations::ns!(cs, "c2"), || Ok(c2_native.clone()), 8)
+            .unwrap();
+
+    // the product of the ciphertexts encrypts the sum of the plaintexts
+    let sum = key.add_ciphertexts(&c1, &c2).unwrap();
+    assert_eq!(
+        sum.value().unwrap(),
+        (&c1_native * &c2_native) % &n_squared_native
+    );
+    assert!(decrypts_to(&sum.value().unwrap(), &(&m1 + &m2)));
+
+    // the power of a ciphertext encrypts the multiple of its plaintext
+    let k_bits = (0..64)
+        .map(|i| {
+            Boolean::new_witness(ark_relations::ns!(cs, "k"), || Ok((k >> i) & 1 == 1)).unwrap()
+        })
+        .collect::<Vec<_>>();
+    let multiple = key.scalar_mul_ciphertext(&c1, &k_bits).unwrap();
+    assert_eq!(
+        multiple.value().unwrap(),
+        c1_native.modpow(&BigUint::from(k), &n_squared_native)
+    );
+    assert!(decrypts_to(&multiple.value().unwrap(), &(&m1 * k)));
+    assert!(cs.is_satisfied().unwrap());
+
+    // a ciphertext of another sum is rejected
+    let wrong = NonNativeUintVar::new_witness(
+        ark_relations::ns!(cs, "wrong"),
+        || Ok(encrypt(&(&m1 + &m2 + 1u64), &BigUint::from(rng.next_u64()))),
+        8,
+    )
+    .unwrap();
+    sum.enforce_equal(&wrong).unwrap();
+    assert!(!cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn u256_div_rem_test() {
+    use ark_nonnative_field::u256::U256Var;
+    use num_bigint::BigUint;
+
+    let rng = &mut ark_ff::test_rng();
+    let cs = ConstraintSystem::<<MNT6_298 as PairingEngine>::Fr>::new_ref();
+
+    let to_biguint = |v: &[u64; 4]| {
+        let mut res = BigUint::from(0u64);
+        for limb in v.iter().rev() {
+            res = (res << 64) + BigUint::from(*limb);
+        }
+        res
+    };
+
+    let a_native = [
+        rng.next_u64(),
+        rng.next_u64(),
+        rng.next_u64(),
+        rng.next_u64(),
+    ];
+    let a = U256Var::new_witness(ark_relations::ns!(cs, "alloc a"), || Ok(a_native)).unwrap();
+    let a_big = to_biguint(&a_native);
+
+    let divisors = [
+        [rng.next_u64(), rng.next_u64(), 0, 0],
+        [1, 0, 0, 0],
+        [u64::max_value(); 4],
+    ];
+    for b_native in divisors.iter() {
+        let b = U256Var::new_witness(ark_relations::ns!(cs, "alloc b"), || Ok(*b_native)).unwrap();
+        let b_big = to_biguint(b_native);
+
+        let (q, r) = a.div_rem(&b).unwrap();
+        assert_eq!(to_biguint(&q.value().unwrap()), &a_big / &b_big);
+        assert_eq!(to_biguint(&r.value().unwrap()), &a_big % &b_big);
+    }
+    assert!(cs.is_satisfied().unwrap());
+
+    assert!(a.div_rem(&U256Var::zero()).is_err());
+}
+
+#[test]
+fn i256_arithmetic_test() {
+    use ark_nonnative_field::u256::{I256Var, U256Var};
+    use ark_r1cs_std::boolean::Boolean;
+
+    let cs = ConstraintSystem::<<MNT6_298 as PairingEngine>::Fr>::new_ref();
+
+    let from_i128 = |v: i128| {
+        let sign_extension = if v < 0 { u64::max_value() } else { 0 };
+        [
+            v as u64,
+            ((v as u128) >> 64) as u64,
+            sign_extension,
+            sign_extension,
+        ]
+    };
+    let min_value = [0, 0, 0, 1u64 << 63];
+    let max_value = [
+        u64::max_value(),
+        u64::max_value(),
+        u64::max_value(),
+        u64::max_value() >> 1,
+    ];
+
+    let values: Vec<I256Var<_>> = [
+        min_value,
+        from_i128(-(1i128 << 100)),
+        from_i128(-5),
+        from_i128(0),
+        from_i128(3),
+        from_i128(1i128 << 100),
+        max_value,
+    ]
+    .iter()
+    .map(|v| I256Var::new_witness(ark_relations::ns!(cs, "alloc"), || Ok(*v)).unwrap())
+    .collect();
+
+    // the values are listed in increasing order
+    for (i, a) in values.iter().enumerate() {
+        for (j, b) in values.iter().enumerate() {
+            assert_eq!(a.is_lt(b).unwrap().value().unwrap(), i < j);
+            assert_eq!(a.is_le(b).unwrap().value().unwrap(), i <= j);
+            assert_eq!(a.is_gt(b).unwrap().value().unwrap(), i > j);
+            assert_eq!(a.is_ge(b).unwrap().value().unwrap(), i >= j);
+        }
+        assert_eq!(a.is_negative().unwrap().value().unwrap(), i < 3);
+    }
+
+    let minus_five = &values[2];
+    let three = &values[4];
+    assert_eq!(
+        minus_five.wrapping_add(three).unwrap().value().unwrap(),
+        from_i128(-2)
+    );
+    assert_eq!(
+        three.wrapping_sub(minus_five).unwrap().value().unwrap(),
+        from_i128(8)
+    );
+    assert_eq!(
+        minus_five.wrapping_mul(three).unwrap().value().unwrap(),
+        from_i128(-15)
+    );
+    assert_eq!(
+        minus_five
+            .wrapping_mul(minus_five)
+            .unwrap()
+            .value()
+            .unwrap(),
+        from_i128(25)
+    );
+    assert_eq!(minus_five.abs().unwrap().value().unwrap(), from_i128(5));
+    assert_eq!(values[0].abs().unwrap().value().unwrap(), min_value);
+    assert_eq!(
+        values[0].wrapping_neg().unwrap().value().unwrap(),
+        min_value
+    );
+
+    let magnitude = U256Var::constant(from_i128(5));
+    let negative = Boolean::new_witness(ark_relations::ns!(cs, "sign"), || Ok(true)).unwrap();
+    let rebuilt = I256Var::from_sign_and_magnitude(&negative, &magnitude).unwrap();
+    rebuilt.enforce_equal(minus_five).unwrap();
+
+    assert!(cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn u256_shift_test() {
+    use ark_nonnative_field::u256::U256Var;
+    use num_bigint::BigUint;
+
+    let rng = &mut ark_ff::test_rng();
+    let cs = ConstraintSystem::<<MNT6_298 as PairingEngine>::Fr>::new_ref();
+
+    let to_biguint = |v: &[u64; 4]| {
+        let mut res = BigUint::from(0u64);
+        for limb in v.iter().rev() {
+            res = (res << 64) + BigUint::from(*limb);
+        }
+        res
+    };
+
+    let a_native = [
+        rng.next_u64(),
+        rng.next_u64(),
+        rng.next_u64(),
+        rng.next_u64(),
+    ];
+    let a = U256Var::new_witness(ark_relations::ns!(cs, "alloc a"), || Ok(a_native)).unwrap();
+    let a_big = to_biguint(&a_native);
+
+    for k in [0usize, 1, 63, 64, 100, 192, 255, 256, 300].iter() {
+        let shifted = a.shr(*k).unwrap().value().unwrap();
+        assert_eq!(to_biguint(&shifted), &a_big >> *k);
+
+        let truncated = a.truncate_to_bits(*k).unwrap().value().unwrap();
+        let mask = (BigUint::from(1u64) << *k) - BigUint::from(1u64);
+        assert_eq!(to_biguint(&truncated), &a_big & &mask);
+    }
+
+    assert!(cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn bit_decomposition_cache_test() {
+    use ark_ff::UniformRand;
+    use ark_nonnative_field::params::BitDecompositionCache;
+    use ark_r1cs_std::bits::ToBitsGadget;
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let a_native = TargetField::rand(rng);
+    let b_native = TargetField::rand(rng);
+
+    for enabled in [false, true].iter() {
+        let cs = ConstraintSystem::<BaseField>::new_ref();
+        if *enabled {
+            BitDecompositionCache::enable(&cs);
+        }
+        assert_eq!(BitDecompositionCache::is_enabled(&cs), *enabled);
+
+        let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+            ark_relations::ns!(cs, "alloc a"),
+            || Ok(a_native),
+        )
+        .unwrap();
+        let b = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+            ark_relations::ns!(cs, "alloc b"),
+            || Ok(b_native),
+        )
+        .unwrap();
+        let c = &(&a * &b) + &a;
+
+        for elem in [a, c].iter() {
+            let bits = elem.to_bits_le().unwrap();
+            let num_constraints = cs.num_constraints();
+            let bits_again = elem.to_bits_le().unwrap();
+            assert_eq!(bits.value().unwrap(), bits_again.value().unwrap());
+            assert_eq!(cs.num_constraints() == num_constraints, *enabled);
+        }
+
+        BitDecompositionCache::clear(&cs);
+        assert!(!BitDecompositionCache::is_enabled(&cs));
+        assert!(cs.is_satisfied().unwrap());
+    }
+}
+
+#[test]
+fn reduce_into_test() {
+    use ark_ff::UniformRand;
+    use ark_nonnative_field::{AllocatedNonNativeFieldVar, NonNativeFieldMulResultVar};
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let a_native = TargetField::rand(rng);
+    let b_native = TargetField::rand(rng);
+
+    let mut num_constraints = Vec::new();
+    for (reduce_into, c_native) in [
+        (false, a_native * &b_native),
+        (true, a_native * &b_native),
+        (true, a_native + &b_native),
+    ]
+    .iter()
+    {
+        let cs = ConstraintSystem::<BaseField>::new_ref();
+        let a = AllocatedNonNativeFieldVar::<TargetField, BaseField>::new_witness(
+            ark_relations::ns!(cs, "alloc a"),
+            || Ok(a_native),
+        )
+        .unwrap();
+        let b = AllocatedNonNativeFieldVar::<TargetField, BaseField>::new_witness(
+            ark_relations::ns!(cs, "alloc b"),
+            || Ok(b_native),
+        )
+        .unwrap();
+        let c = AllocatedNonNativeFieldVar::<TargetField, BaseField>::new_witness(
+            ark_relations::ns!(cs, "alloc c"),
+            || Ok(*c_native),
+        )
+        .unwrap();
+
+        let num_constraints_before = cs.num_constraints();
+        let prod = NonNativeFieldMulResultVar::Var(a.mul_without_reduce(&b).unwrap());
+        if *reduce_into {
+            prod.reduce_into(&c).unwrap();
+        } else {
+            let reduced = prod.reduce().unwrap();
+            reduced
+                .enforce_equal(&NonNativeFieldVar::Var(c.clone()))
+                .unwrap();
+        }
+
+        assert_eq!(
+            cs.is_satisfied().unwrap(),
+            *c_native == a_native * &b_native
+        );
+        num_constraints.push(cs.num_constraints() - num_constraints_before);
+    }
+
+    assert!(num_constraints[1] < num_constraints[0]);
+
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+    let c = AllocatedNonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "alloc c"),
+        || Ok(a_native),
+    )
+    .unwrap();
+    NonNativeFieldMulResultVar::constant(a_native)
+        .reduce_into(&c)
+        .unwrap();
+    assert!(cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn accumulator_test() {
+    use ark_ff::UniformRand;
+    use ark_nonnative_field::{
+        accumulator::Accumulator,
+        curves::pasta::{Fp, Fq},
+    };
+
+    let rng = &mut ark_ff::test_rng();
+    let cs = ConstraintSystem::<Fq>::new_ref();
+
+    let mut acc = Accumulator::<Fp, Fq>::new();
+    let mut expected = Fp::from(0u64);
+    for _ in 0..5 {
+        let a_native = Fp::rand(rng);
+        let b_native = Fp::rand(rng);
+        let a = NonNativeFieldVar::new_witness(ark_relations::ns!(cs, "alloc a"), || Ok(a_native))
+            .unwrap();
+        let b = NonNativeFieldVar::new_witness(ark_relations::ns!(cs, "alloc b"), || Ok(b_native))
+            .unwrap();
+        acc.add_product(&a, &b).unwrap();
+        acc.add_product(&a, &NonNativeFieldVar::constant(b_native))
+            .unwrap();
+        expected += &(a_native * &b_native);
+        expected += &(a_native * &b_native);
+    }
+    assert_eq!(acc.num_of_reductions(), 0);
+
+    // both moduli have 255 bits, so products of operands with many additions quickly exhaust
+    // the room of the unreduced limbs
+    let x_native = Fp::rand(rng);
+    let x =
+        NonNativeFieldVar::new_witness(ark_relations::ns!(cs, "alloc x"), || Ok(x_native)).unwrap();
+    let mut sum = x.clone();
+    for _ in 1..500 {
+        sum += &x;
+    }
+    let sum_native = x_native * &Fp::from(500u64);
+
+    for _ in 0..64 {
+        acc.add_product(&sum, &sum).unwrap();
+        expected += &(sum_native * &sum_native);
+        if acc.num_of_reductions() > 1 {
+            break;
+        }
+    }
+    assert!(acc.num_of_reductions() > 1);
+
+    let res = acc.finalize().unwrap();
+    assert_eq!(res.value().unwrap(), expected);
+    res.enforce_equal(&NonNativeFieldVar::constant(expected))
+        .unwrap();
+    assert!(cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn sum_and_product_test() {
+    use ark_ff::UniformRand;
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+
+    let natives: Vec<TargetField> = (0..6).map(|_| TargetField::rand(rng)).collect();
+    let elems: Vec<NonNativeFieldVar<TargetField, BaseField>> = natives
+        .iter()
+        .enumerate()
+        .map(|(i, native)| {
+            if i % 3 == 0 {
+                NonNativeFieldVar::constant(*native)
+            } else {
+                NonNativeFieldVar::new_witness(ark_relations::ns!(cs, "alloc"), || Ok(*native))
+                    .unwrap()
+            }
+        })
+        .collect();
+
+    let sum_native: TargetField = natives.iter().sum();
+    let product_native: TargetField = natives.iter().product();
+
+    let sum: NonNativeFieldVar<TargetField, BaseField> = elems.iter().sum();
+    assert_eq!(sum.value().unwrap(), sum_native);
+    let sum: NonNativeFieldVar<TargetField, BaseField> = elems.clone().into_iter().sum();
+    assert_eq!(sum.value().unwrap(), sum_native);
+
+    let num_constraints = cs.num_constraints();
+    let product: NonNativeFieldVar<TargetField, BaseField> = elems.iter().product();
+    assert_eq!(product.value().unwrap(), product_native);
+    let num_constraints_of_product = cs.num_constraints() - num_constraints;
+
+    // the constants are folded, so only the variables cost multiplications
+    let num_constraints = cs.num_constraints();
+    let naive = elems
+        .iter()
+        .fold(NonNativeFieldVar::one(), |acc, elem| &acc * elem);
+    assert_eq!(naive.value().unwrap(), product_native);
+    assert!(num_constraints_of_product <= cs.num_constraints() - num_constraints);
+
+    let product: NonNativeFieldVar<TargetField, BaseField> = elems.into_iter().product();
+    assert_eq!(product.value().unwrap(), product_native);
+
+    let mut with_zero = vec![NonNativeFieldVar::zero(), sum];
+    with_zero.push(product);
+    let zero: NonNativeFieldVar<TargetField, BaseField> = with_zero.iter().product();
+    assert!(zero.is_constant());
+    assert_eq!(zero.value().unwrap(), TargetField::from(0u64));
+
+    assert!(cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn compound_assignment_test() {
+    use ark_ff::UniformRand;
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+
+    let a_native = TargetField::rand(rng);
+    let b_native = TargetField::rand(rng);
+    let c_native = TargetField::rand(rng);
+    let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "alloc a"),
+        || Ok(a_native),
+    )
+    .unwrap();
+    let b = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "alloc b"),
+        || Ok(b_native),
+    )
+    .unwrap();
+
+    let mut acc = a.clone();
+    let mut acc_native = a_native;
+
+    acc += &b;
+    acc_native += &b_native;
+    acc *= &a;
+    acc_native *= &a_native;
+    acc -= b.clone();
+    acc_native -= &b_native;
+    acc += c_native;
+    acc_native += &c_native;
+    acc *= c_native;
+    acc_native *= &c_native;
+    acc -= c_native;
+    acc_native -= &c_native;
+    acc *= a;
+    acc_native *= &a_native;
+    assert_eq!(acc.value().unwrap(), acc_native);
+
+    acc.enforce_equal(&NonNativeFieldVar::constant(acc_native))
+        .unwrap();
+    assert!(cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn neg_and_reference_operators_test() {
+    use ark_ff::UniformRand;
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+
+    let a_native = TargetField::rand(rng);
+    let b_native = TargetField::rand(rng);
+    let c_native = TargetField::rand(rng);
+    let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "alloc a"),
+        || Ok(a_native),
+    )
+    .unwrap();
+    let b = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "alloc b"),
+        || Ok(b_native),
+    )
+    .unwrap();
+    let c = NonNativeFieldVar::<TargetField, BaseField>::constant(c_native);
+
+    assert_eq!((-&a).value().unwrap(), -a_native);
+    assert_eq!((-a.clone()).value().unwrap(), -a_native);
+    assert_eq!((-&c).value().unwrap(), -c_native);
+    assert!((-&c).is_constant());
+
+    assert_eq!((&a + &b).value().unwrap(), a_native + &b_native);
+    assert_eq!((&a + b.clone()).value().unwrap(), a_native + &b_native);
+    assert_eq!((a.clone() + &b).value().unwrap(), a_native + &b_native);
+    assert_eq!((&a - &c).value().unwrap(), a_native - &c_native);
+    assert_eq!((&c - &a).value().unwrap(), c_native - &a_native);
+    assert_eq!((&a * c_native).value().unwrap(), a_native * &c_native);
+    assert_eq!(
+        (a.clone() * c_native).value().unwrap(),
+        a_native * &c_native
+    );
+    assert_eq!(
+        (-(&a * &b) + &c).value().unwrap(),
+        -(a_native * &b_native) + &c_native
+    );
+
+    assert!(cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn constraint_report_test() {
+    use ark_ff::UniformRand;
+    use ark_nonnative_field::report::ConstraintReport;
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let a_native = TargetField::rand(rng);
+    let b_native = TargetField::rand(rng);
+
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+    let num_constraints_before = cs.num_constraints();
+
+    let ((c, inner), outer) = ConstraintReport::measure(&cs, || {
+        let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+            ark_relations::ns!(cs, "alloc a"),
+            || Ok(a_native),
+        )?;
+        let b = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+            ark_relations::ns!(cs, "alloc b"),
+            || Ok(b_native),
+        )?;
+
+        ConstraintReport::measure(&cs, || {
+            let c = &a * &b;
+            c.enforce_equal(&NonNativeFieldVar::constant(a_native * &b_native))?;
+            Ok(c)
+        })
+    })
+    .unwrap();
+    assert_eq!(c.value().unwrap(), a_native * &b_native);
+
+    assert_eq!(
+        outer.num_constraints,
+        cs.num_constraints() - num_constraints_before
+    );
+    assert_eq!(outer.allocations.num_calls, inner.allocations.num_calls + 2);
+    assert!(outer.num_variables > inner.num_variables);
+
+    assert_eq!(inner.multiplications.num_calls, 1);
+    assert!(inner.multiplications.num_constraints > 0);
+    assert!(inner.reductions.num_calls >= 1);
+    assert!(inner.reductions.num_constraints > 0);
+    assert!(inner.equalities.num_calls >= 1);
+
+    // every constraint comes from one of the operations, and the inner costs are included in
+    // the outer report
+    for report in [&inner, &outer].iter() {
+        assert_eq!(
+            report.num_constraints,
+            report.allocations.num_constraints
+                + report.multiplications.num_constraints
+                + report.reductions.num_constraints
+                + report.equalities.num_constraints
+        );
+    }
+    assert_eq!(outer.multiplications, inner.multiplications);
+    assert_eq!(outer.reductions, inner.reductions);
+
+    assert!(cs.is_satisfied().unwrap());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn export_profile_test() {
+    use ark_ff::UniformRand;
+    use ark_nonnative_field::{
+        params::HitRate,
+        report::{export_profile, ConstraintReport, ProfileFormat},
+    };
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let a_native = TargetField::rand(rng);
+
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+    HitRate::init(&cs);
+    let (_, report) = ConstraintReport::measure(&cs, || {
+        let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+            ark_relations::ns!(cs, "alloc a"),
+            || Ok(a_native),
+        )?;
+        (&a * &a).enforce_equal(&NonNativeFieldVar::constant(a_native * &a_native))
+    })
+    .unwrap();
+    let (hit, miss) = HitRate::get(&cs).unwrap();
+
+    let mut json = Vec::new();
+    export_profile(&cs, &report, ProfileFormat::Json, &mut json).unwrap();
+    let json = String::from_utf8(json).unwrap();
+    assert!(json.starts_with(&format!(
+        "{{\"num_constraints\":{},\"num_variables\":{},",
+        report.num_constraints, report.num_variables
+    )));
+    assert!(json.contains(&format!(
+        "\"multiplication\":{{\"num_calls\":{},\"num_constraints\":{},\"num_variables\":{}}}",
+        report.multiplications.num_calls,
+        report.multiplications.num_constraints,
+        report.multiplications.num_variables
+    )));
+    assert!(json.ends_with(&format!(
+        "\"params_cache\":{{\"hit\":{},\"miss\":{}}}}}\n",
+        hit, miss
+    )));
+
+    let mut csv = Vec::new();
+    export_profile(&cs, &report, ProfileFormat::Csv, &mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "metric,value");
+    assert_eq!(
+        lines[1],
+        format!("num_constraints,{}", report.num_constraints)
+    );
+    assert!(
+        lines.contains(&format!("reduction.num_calls,{}", report.reductions.num_calls).as_str())
+    );
+    assert_eq!(lines.len(), 3 + 4 * 3 + 2);
+
+    let mut hit_rate = Vec::new();
+    HitRate::write(&cs, &mut hit_rate).unwrap();
+    assert!(String::from_utf8(hit_rate)
+        .unwrap()
+        .starts_with(&format!("Hit: {}, Miss: {}", hit, miss)));
+
+    assert!(cs.is_satisfied().unwrap());
+}
+
+ark_nonnative_field::nonnative! {
+    /// P-256 scalars with the preset parameters over the scalar field of BLS12-381
+    mod p256_fr_var: NonNativeFieldVar<
+        ark_nonnative_field::curves::p256::Fr,
+        <Bls12_381 as PairingEngine>::Fr
+    >, params = ark_nonnative_field::curves::p256::PARAMS_OVER_BLS12_381_FR;
+}
+
+ark_nonnative_field::nonnative! {
+    /// MNT4-298 scalars over MNT6-298 with the searched parameters
+    mod mnt4_fr_var: NonNativeFieldVar<
+        <MNT4_298 as PairingEngine>::Fr,
+        <MNT6_298 as PairingEngine>::Fr
+    >;
+}
+
+#[test]
+fn nonnative_macro_test() {
+    use ark_ff::UniformRand;
+    use ark_nonnative_field::params::{get_cached_params, get_params};
+
+    let rng = &mut ark_ff::test_rng();
+
+    let cs = ConstraintSystem::<p256_fr_var::BaseField>::new_ref();
+    let a_native = p256_fr_var::TargetField::rand(rng);
+    let b_native = p256_fr_var::TargetField::rand(rng);
+    assert_eq!(
+        get_cached_params::<p256_fr_var::TargetField, p256_fr_var::BaseField>(&cs),
+        None
+    );
+    let a = p256_fr_var::new_witness(ark_relations::ns!(cs, "alloc a"), || Ok(a_native)).unwrap();
+    // the parameters are stored by the first allocation and found by the later ones
+    assert_eq!(
+        get_cached_params::<p256_fr_var::TargetField, p256_fr_var::BaseField>(&cs),
+        Some(ark_nonnative_field::curves::p256::PARAMS_OVER_BLS12_381_FR)
+    );
+    let b = p256_fr_var::new_input(ark_relations::ns!(cs, "alloc b"), || Ok(b_native)).unwrap();
+    let c: p256_fr_var::Var = p256_fr_var::constant(a_native * &b_native);
+    (&a * &b).enforce_equal(&c).unwrap();
+
+    let params = get_params::<p256_fr_var::TargetField, p256_fr_var::BaseField>(&cs).unwrap();
+    let preset = ark_nonnative_field::curves::p256::PARAMS_OVER_BLS12_381_FR;
+    assert_eq!(params.num_limbs, preset.num_limbs);
+    assert_eq!(params.bits_per_top_limb, preset.bits_per_top_limb);
+    assert_eq!(params.bits_per_non_top_limb, preset.bits_per_non_top_limb);
+    assert!(cs.is_satisfied().unwrap());
+
+    let cs = ConstraintSystem::<mnt4_fr_var::BaseField>::new_ref();
+    let a_native = mnt4_fr_var::TargetField::rand(rng);
+    let a = mnt4_fr_var::new_witness(ark_relations::ns!(cs, "alloc a"), || Ok(a_native)).unwrap();
+    (&a + &mnt4_fr_var::constant(a_native))
+        .enforce_equal(&mnt4_fr_var::constant(a_native + &a_native))
+        .unwrap();
+    assert!(cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn nonnative_config_test() {
+    use ark_ff::UniformRand;
+    use ark_nonnative_field::params::{
+        BitDecompositionCache, DensityOptimization, NonNativeConfig, OptimizationGoal,
+    };
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let a_native = TargetField::rand(rng);
+
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+    assert_eq!(NonNativeConfig::get(&cs), NonNativeConfig::default());
+
+    let config = NonNativeConfig {
+        optimization_goal: OptimizationGoal::Weight,
+        max_surfeit: Some(3),
+        cache_bit_decompositions: true,
+    };
+    config.install(&cs);
+    assert_eq!(NonNativeConfig::get(&cs), config);
+    assert!(DensityOptimization::is_enabled(&cs));
+    assert!(BitDecompositionCache::is_enabled(&cs));
+
+    NonNativeConfig::default().install(&cs);
+    assert_eq!(NonNativeConfig::get(&cs), NonNativeConfig::default());
+    assert!(!DensityOptimization::is_enabled(&cs));
+    assert!(!BitDecompositionCache::is_enabled(&cs));
+
+    // a small maximal surfeit makes long sums reduce along the way
+    let mut num_constraints = Vec::new();
+    for max_surfeit in [None, Some(2)].iter() {
+        let cs = ConstraintSystem::<BaseField>::new_ref();
+        NonNativeConfig {
+            max_surfeit: *max_surfeit,
+            ..NonNativeConfig::default()
+        }
+        .install(&cs);
+
+        let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+            ark_relations::ns!(cs, "alloc a"),
+            || Ok(a_native),
+        )
+        .unwrap();
+        let mut sum = a.clone();
+        let mut sum_native = a_native;
+        for _ in 0..16 {
+            sum += &a;
+            sum_native += &a_native;
+        }
+        assert_eq!(sum.value().unwrap(), sum_native);
+        assert!(cs.is_satisfied().unwrap());
+        num_constraints.push(cs.num_constraints());
+    }
+    assert!(num_constraints[0] < num_constraints[1]);
+}
+
+#[test]
+fn max_surfeit_equality_test() {
+    use ark_ff::UniformRand;
+    use ark_nonnative_field::params::NonNativeConfig;
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let natives: Vec<TargetField> = (0..6).map(|_| TargetField::rand(rng)).collect();
+
+    // the checks against the normal form stay satisfiable when the sums are reduced along
+    // the way and when they are not
+    for num_terms in [2usize, 3, 6].iter() {
+        let cs = ConstraintSystem::<BaseField>::new_ref();
+        NonNativeConfig {
+            max_surfeit: Some(2),
+            ..NonNativeConfig::default()
+        }
+        .install(&cs);
+
+        let vars: Vec<NonNativeFieldVar<TargetField, BaseField>> = natives[..*num_terms]
+            .iter()
+            .map(|native| {
+                NonNativeFieldVar::new_witness(ark_relations::ns!(cs, "alloc"), || Ok(*native))
+                    .unwrap()
+            })
+            .collect();
+        let mut sum = vars[0].clone();
+        for var in vars[1..].iter() {
+            sum += var;
+        }
+        let sum_native: TargetField = natives[..*num_terms].iter().sum();
+
+        let expected = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+            ark_relations::ns!(cs, "alloc expected"),
+            || Ok(sum_native),
+        )
+        .unwrap();
+        sum.enforce_equal(&expected).unwrap();
+        (&sum + &vars[0])
+            .enforce_equal(&(&expected + &vars[0]))
+            .unwrap();
+        assert!(cs.is_satisfied().unwrap());
+    }
+}
+
+#[test]
+fn enforce_equal_to_normal_form_test() {
+    use ark_ff::UniformRand;
+    use ark_r1cs_std::alloc::AllocationMode;
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let a_native = TargetField::rand(rng);
+    let b_native = TargetField::rand(rng);
+
+    // `a * b + a` is not in the normal form, while the constant and the input are
+    let mut num_constraints = Vec::new();
+    for (other_mode, other_native) in [
+        (AllocationMode::Witness, a_native * &b_native + &a_native),
+        (AllocationMode::Constant, a_native * &b_native + &a_native),
+        (AllocationMode::Input, a_native * &b_native + &a_native),
+        (AllocationMode::Constant, a_native),
+    ]
+    .iter()
+    {
+        let cs = ConstraintSystem::<BaseField>::new_ref();
+        let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+            ark_relations::ns!(cs, "alloc a"),
+            || Ok(a_native),
+        )
+        .unwrap();
+        let b = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+            ark_relations::ns!(cs, "alloc b"),
+            || Ok(b_native),
+        )
+        .unwrap();
+        let c = &(&a * &b) + &a;
+        let other = NonNativeFieldVar::<TargetField, BaseField>::new_variable(
+            ark_relations::ns!(cs, "alloc other"),
+            || Ok(*other_native),
+            *other_mode,
+        )
+        .unwrap();
+
+        let num_constraints_before = cs.num_constraints();
+        c.enforce_equal(&other).unwrap();
+        num_constraints.push(cs.num_constraints() - num_constraints_before);
+
+        // the normal form is remembered, so comparing again only compares the limbs
+        let num_constraints_before = cs.num_constraints();
+        c.enforce_equal(&other).unwrap();
+        assert!(cs.num_constraints() - num_constraints_before <= num_constraints[0]);
+
+        assert_eq!(cs.is_satisfied().unwrap(), *other_native != a_native);
+    }
+
+    assert!(num_constraints[1] < num_constraints[0]);
+    assert!(num_constraints[2] < num_constraints[0]);
+}
+
+#[test]
+fn constant_condition_equality_test() {
+    use ark_ff::UniformRand;
+    use ark_r1cs_std::boolean::Boolean;
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let a_native = TargetField::rand(rng);
+    let b_native = a_native + &TargetField::from(1u64);
+
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+    let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "alloc a"),
+        || Ok(a_native),
+    )
+    .unwrap();
+    let b = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "alloc b"),
+        || Ok(b_native),
+    )
+    .unwrap();
+    let c = NonNativeFieldVar::<TargetField, BaseField>::constant(a_native);
+
+    // a false condition generates nothing, even for the elements that would violate the check
+    let num_constraints_before = cs.num_constraints();
+    let num_variables_before = cs.num_witness_variables();
+    a.conditional_enforce_equal(&b, &Boolean::FALSE).unwrap();
+    a.conditional_enforce_not_equal(&c, &Boolean::FALSE)
+        .unwrap();
+    c.conditional_enforce_equal(&b, &Boolean::FALSE).unwrap();
+    assert_eq!(cs.num_constraints(), num_constraints_before);
+    assert_eq!(cs.num_witness_variables(), num_variables_before);
+    assert!(cs.is_satisfied().unwrap());
+
+    // a true condition costs no more than the unconditional check
+    let num_constraints_before = cs.num_constraints();
+    a.conditional_enforce_not_equal(&b, &Boolean::TRUE).unwrap();
+    let conditional = cs.num_constraints() - num_constraints_before;
+    let num_constraints_before = cs.num_constraints();
+    a.enforce_not_equal(&b).unwrap();
+    assert!(conditional <= cs.num_constraints() - num_constraints_before);
+
+    a.conditional_enforce_equal(&c, &Boolean::TRUE).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn constant_select_and_equality_test() {
+    use ark_ff::UniformRand;
+    use ark_r1cs_std::{boolean::Boolean, select::CondSelectGadget};
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let c1 = TargetField::rand(rng);
+    let c2 = TargetField::rand(rng);
+
+    for cond_native in [true, false].iter() {
+        let cs = ConstraintSystem::<BaseField>::new_ref();
+        let cond = Boolean::new_witness(ark_relations::ns!(cs, "alloc cond"), || Ok(*cond_native))
+            .unwrap();
+
+        // the selection of constants is linear in the condition
+        let num_constraints_before = cs.num_constraints();
+        let num_variables_before = cs.num_witness_variables();
+        let selected = NonNativeFieldVar::<TargetField, BaseField>::conditionally_select(
+            &cond,
+            &NonNativeFieldVar::constant(c1),
+            &NonNativeFieldVar::constant(c2),
+        )
+        .unwrap();
+        assert_eq!(cs.num_constraints(), num_constraints_before);
+        assert_eq!(cs.num_witness_variables(), num_variables_before);
+
+        let expected = if *cond_native { c1 } else { c2 };
+        assert_eq!(selected.value().unwrap(), expected);
+
+        // the result is in the normal form, so comparing it with a constant only compares limbs
+        let num_variables_before = cs.num_witness_variables();
+        selected
+            .conditional_enforce_equal(&NonNativeFieldVar::constant(expected), &cond)
+            .unwrap();
+        selected
+            .enforce_equal(&NonNativeFieldVar::constant(expected))
+            .unwrap();
+        assert_eq!(cs.num_witness_variables(), num_variables_before);
+        assert!(cs.is_satisfied().unwrap());
+
+        let other = if *cond_native { c2 } else { c1 };
+        selected
+            .conditional_enforce_equal(&NonNativeFieldVar::constant(other), &cond)
+            .unwrap();
+        assert_eq!(cs.is_satisfied().unwrap(), !*cond_native);
+    }
+}
+
+#[test]
+fn mul_result_select_test() {
+    use ark_ff::UniformRand;
+    use ark_nonnative_field::NonNativeFieldMulResultVar;
+    use ark_r1cs_std::{boolean::Boolean, select::CondSelectGadget};
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let natives: Vec<TargetField> = (0..4).map(|_| TargetField::rand(rng)).collect();
+    let constant = TargetField::rand(rng);
+
+    for cond_native in [true, false].iter() {
+        let mut num_constraints = Vec::new();
+        for select_first in [true, false].iter() {
+            let cs = ConstraintSystem::<BaseField>::new_ref();
+            let vars: Vec<_> = natives
+                .iter()
+                .map(|native| {
+                    NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+                        ark_relations::ns!(cs, "alloc"),
+                        || Ok(*native),
+                    )
+                    .unwrap()
+                })
+                .collect();
+            let cond =
+                Boolean::new_witness(ark_relations::ns!(cs, "alloc cond"), || Ok(*cond_native))
+                    .unwrap();
+
+            let num_constraints_before = cs.num_constraints();
+            let ab = vars[0].mul_without_reduce(&vars[1]).unwrap();
+            let cd = vars[2].mul_without_reduce(&vars[3]).unwrap();
+            let res = if *select_first {
+                NonNativeFieldMulResultVar::conditionally_select(&cond, &ab, &cd)
+                    .unwrap()
+                    .reduce()
+                    .unwrap()
+            } else {
+                NonNativeFieldVar::conditionally_select(
+                    &cond,
+                    &ab.reduce().unwrap(),
+                    &cd.reduce().unwrap(),
+                )
+                .unwrap()
+            };
+            num_constraints.push(cs.num_constraints() - num_constraints_before);
+
+            let expected = if *cond_native {
+                natives[0] * &natives[1]
+            } else {
+                natives[2] * &natives[3]
+            };
+            assert_eq!(res.value().unwrap(), expected);
+
+            // a constant branch is placed in the unreduced form
+            let res = NonNativeFieldMulResultVar::conditionally_select(
+                &cond,
+                &NonNativeFieldMulResultVar::constant(constant),
+                &ab,
+            )
+            .unwrap()
+            .reduce()
+            .unwrap();
+            let expected = if *cond_native {
+                constant
+            } else {
+                natives[0] * &natives[1]
+            };
+            assert_eq!(res.value().unwrap(), expected);
+            assert!(cs.is_satisfied().unwrap());
+        }
+        assert!(num_constraints[0] < num_constraints[1]);
+    }
+}
+
+#[test]
+fn mul_result_equality_test() {
+    use ark_ff::{Field, UniformRand};
+    use ark_r1cs_std::boolean::Boolean;
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let a_native = TargetField::rand(rng);
+    let b_native = TargetField::rand(rng);
+    let c_native = TargetField::rand(rng);
+    let d_native = a_native * &b_native * &c_native.inverse().unwrap();
+
+    for (d_native, equal) in [
+        (d_native, true),
+        (d_native + &TargetField::from(1u64), false),
+    ]
+    .iter()
+    {
+        let mut num_constraints = Vec::new();
+        for compare_products in [true, false].iter() {
+            let cs = ConstraintSystem::<BaseField>::new_ref();
+            let alloc = |native: TargetField| {
+                NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+                    ark_relations::ns!(cs, "alloc"),
+                    || Ok(native),
+                )
+                .unwrap()
+            };
+            let a = alloc(a_native);
+            let b = alloc(b_native);
+            let c = alloc(c_native);
+            let d = alloc(*d_native);
+
+            let ab = a.mul_without_reduce(&b).unwrap();
+            let cd = c.mul_without_reduce(&d).unwrap();
+            let num_constraints_before = cs.num_constraints();
+            if *compare_products {
+                ab.enforce_equal(&cd).unwrap();
+            } else {
+                ab.reduce()
+                    .unwrap()
+                    .enforce_equal(&cd.reduce().unwrap())
+                    .unwrap();
+            }
+            num_constraints.push(cs.num_constraints() - num_constraints_before);
+            assert_eq!(cs.is_satisfied().unwrap(), *equal);
+
+            let cs = ConstraintSystem::<BaseField>::new_ref();
+            let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+                ark_relations::ns!(cs, "alloc a"),
+                || Ok(a_native),
+            )
+            .unwrap();
+            let d = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+                ark_relations::ns!(cs, "alloc d"),
+                || Ok(*d_native),
+            )
+            .unwrap();
+            let ab = a
+                .mul_without_reduce(&NonNativeFieldVar::constant(b_native))
+                .unwrap();
+            let cd = d
+                .mul_without_reduce(&NonNativeFieldVar::constant(c_native))
+                .unwrap();
+            let is_eq = ab.is_eq(&cd).unwrap();
+            assert_eq!(is_eq.value().unwrap(), *equal);
+            ab.conditional_enforce_equal(&cd, &is_eq).unwrap();
+            ab.conditional_enforce_not_equal(&cd, &is_eq.not()).unwrap();
+            ab.conditional_enforce_equal(&cd, &Boolean::FALSE).unwrap();
+            assert!(cs.is_satisfied().unwrap());
+        }
+        assert!(num_constraints[0] < num_constraints[1]);
+    }
+}
+
+#[test]
+fn dense_polynomial_test() {
+    use ark_ff::{UniformRand, Zero};
+    use ark_nonnative_field::poly::DensePolynomialVar;
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+
+    let eval = |coeffs: &[TargetField], x: TargetField| {
+        coeffs
+            .iter()
+            .rev()
+            .fold(TargetField::zero(), |acc, coeff| acc * &x + coeff)
+    };
+    let alloc = |native: TargetField| {
+        NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+            ark_relations::ns!(cs, "alloc"),
+            || Ok(native),
+        )
+        .unwrap()
+    };
+
+    let p_native: Vec<TargetField> = (0..5).map(|_| TargetField::rand(rng)).collect();
+    let q_native: Vec<TargetField> = (0..3).map(|_| TargetField::rand(rng)).collect();
+    let x_native = TargetField::rand(rng);
+    let scalar_native = TargetField::rand(rng);
+
+    let p = DensePolynomialVar::from_coefficients_vec(
+        p_native.iter().map(|coeff| alloc(*coeff)).collect(),
+    );
+    let q = DensePolynomialVar::from_coefficients_vec(
+        q_native
+            .iter()
+            .map(|coeff| NonNativeFieldVar::constant(*coeff))
+            .collect(),
+    );
+    let x = alloc(x_native);
+    let scalar = alloc(scalar_native);
+    assert_eq!(p.degree(), 4);
+    assert_eq!(q.degree(), 2);
+
+    let res = p.evaluate(&x).unwrap();
+    assert_eq!(res.value().unwrap(), eval(&p_native, x_native));
+
+    let res = p.add(&q).evaluate(&x).unwrap();
+    assert_eq!(
+        res.value().unwrap(),
+        eval(&p_native, x_native) + &eval(&q_native, x_native)
+    );
+
+    let res = p.sub(&q).mul_by_scalar(&scalar).evaluate(&x).unwrap();
+    assert_eq!(
+        res.value().unwrap(),
+        (eval(&p_native, x_native) - &eval(&q_native, x_native)) * &scalar_native
+    );
+
+    // constant polynomials are evaluated natively, and leading constant zeros are dropped
+    let res = q.evaluate(&NonNativeFieldVar::constant(x_native)).unwrap();
+    assert!(res.is_constant());
+    assert_eq!(res.value().unwrap(), eval(&q_native, x_native));
+    assert!(q.sub(&q).is_zero());
+    assert!(DensePolynomialVar::<TargetField, BaseField>::zero()
+        .evaluate(&x)
+        .unwrap()
+        .is_constant());
+
+    assert!(cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn mat_vec_mul_test() {
+    use ark_ff::{UniformRand, Zero};
+    use ark_nonnative_field::error::NonNativeFieldError;
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let matrix_native: Vec<Vec<TargetField>> = (0..3)
+        .map(|i| {
+            (0..4)
+                .map(|j| {
+                    if (i + j) % 3 == 0 {
+                        TargetField::zero()
+                    } else {
+                        TargetField::rand(rng)
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    let vector_native: Vec<TargetField> = (0..4).map(|_| TargetField::rand(rng)).collect();
+    let expected: Vec<TargetField> = matrix_native
+        .iter()
+        .map(|row| {
+            row.iter()
+                .zip(vector_native.iter())
+                .fold(TargetField::zero(), |acc, (a, b)| acc + &(*a * b))
+        })
+        .collect();
+
+    let mut num_constraints = Vec::new();
+    for constant_matrix in [false, true].iter() {
+        let cs = ConstraintSystem::<BaseField>::new_ref();
+        let vector: Vec<_> = vector_native
+            .iter()
+            .map(|elem| {
+                NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+                    ark_relations::ns!(cs, "alloc vector"),
+                    || Ok(*elem),
+                )
+                .unwrap()
+            })
+            .collect();
+
+        let num_constraints_before = cs.num_constraints();
+        let res = if *constant_matrix {
+            NonNativeFieldVar::mat_vec_mul_by_constant(&matrix_native, &vector).unwrap()
+        } else {
+            let matrix: Vec<Vec<_>> = matrix_native
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|entry| {
+                            NonNativeFieldVar::new_witness(
+                                ark_relations::ns!(cs, "alloc matrix"),
+                                || Ok(*entry),
+                            )
+                            .unwrap()
+                        })
+                        .collect()
+                })
+                .collect();
+            NonNativeFieldVar::mat_vec_mul(&matrix, &vector).unwrap()
+        };
+        num_constraints.push(cs.num_constraints() - num_constraints_before);
+
+        let res: Vec<_> = res.iter().map(|elem| elem.value().unwrap()).collect();
+        assert_eq!(res, expected);
+        assert!(cs.is_satisfied().unwrap());
+
+        assert!(matches!(
+            NonNativeFieldVar::mat_vec_mul(&[vector.clone()], &vector[1..]),
+            Err(NonNativeFieldError::LengthMismatch {
+                expected: 3,
+                found: 4
+            })
+        ));
+    }
+    assert!(num_constraints[1] < num_constraints[0]);
+
+    // multiplying by a constant is unchanged in value
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+    let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "alloc a"),
+        || Ok(vector_native[0]),
+    )
+    .unwrap();
+    let res = &a * matrix_native[0][1] + &(&a * &a) * matrix_native[1][0];
+    assert_eq!(
+        res.value().unwrap(),
+        vector_native[0] * &matrix_native[0][1]
+            + &(vector_native[0] * &vector_native[0] * &matrix_native[1][0])
+    );
+    assert!(cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn folding_test() {
+    use ark_ff::UniformRand;
+    use ark_nonnative_field::folding::fold;
+    use ark_r1cs_std::boolean::Boolean;
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+
+    let mut u_native: Vec<TargetField> = (0..3).map(|_| TargetField::rand(rng)).collect();
+    let mut u: Vec<_> = u_native
+        .iter()
+        .map(|elem| {
+            NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+                ark_relations::ns!(cs, "alloc u"),
+                || Ok(*elem),
+            )
+            .unwrap()
+        })
+        .collect();
+
+    // the folded vector is folded again, as across the steps of an IVC
+    for _ in 0..10 {
+        let v_native: Vec<TargetField> = (0..3).map(|_| TargetField::rand(rng)).collect();
+        let v: Vec<_> = v_native
+            .iter()
+            .map(|elem| {
+                NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+                    ark_relations::ns!(cs, "alloc v"),
+                    || Ok(*elem),
+                )
+                .unwrap()
+            })
+            .collect();
+
+        // a 128-bit challenge
+        let r_native = rng.next_u64() as u128 | ((rng.next_u64() as u128) << 64);
+        let r_bits: Vec<_> = (0..128)
+            .map(|i| {
+                Boolean::new_witness(ark_relations::ns!(cs, "alloc r"), || {
+                    Ok((r_native >> i) & 1 == 1)
+                })
+                .unwrap()
+            })
+            .collect();
+
+        u = fold(&r_bits, &u, &v).unwrap();
+        for (u_i, v_i) in u_native.iter_mut().zip(v_native.iter()) {
+            *u_i += &(TargetField::from(r_native) * v_i);
+        }
+        let values: Vec<_> = u.iter().map(|elem| elem.value().unwrap()).collect();
+        assert_eq!(values, u_native);
+    }
+
+    assert!(fold(&[], &u, &u[1..]).is_err());
+    assert!(cs.is_satisfied().unwrap());
+}
+
+#[cfg(feature = "bn254")]
+#[test]
+fn bn254_params_test() {
+    use ark_nonnative_field::{
+        curves::bn254::{Fq, Fr, PARAMS},
+        params::{gen_params, ParamsSearching},
+    };
+
+    for params in [
+        gen_params::<Fq, Fr>().unwrap(),
+        gen_params::<Fr, Fq>().unwrap(),
+    ]
+    .iter()
+    {
+        assert_eq!(params.num_limbs, PARAMS.num_limbs);
+        assert_eq!(params.bits_per_top_limb, PARAMS.bits_per_top_limb);
+        assert_eq!(params.bits_per_non_top_limb, PARAMS.bits_per_non_top_limb);
+    }
+
+    let mut problem = ParamsSearching::new(254, 254);
+    assert!(problem.solve_with_num_of_limbs(2).is_none());
+    let cost = problem.solve_with_num_of_limbs(PARAMS.num_limbs).unwrap();
+    for num_of_limbs in PARAMS.num_limbs + 1..=10 {
+        let other_cost = problem.solve_with_num_of_limbs(num_of_limbs).unwrap();
+        assert!(other_cost > cost);
+    }
+}
+
+#[cfg(feature = "bn254")]
+#[test]
+fn bn254_cycle_test() {
+    use ark_ec::models::SWModelParameters;
+    use ark_ff::UniformRand;
+    use ark_nonnative_field::{
+        curves::{
+            bn254::{
+                Bn254G1Parameters, Fq, FqVarOverFr, Fr, FrVarOverFq, GrumpkinParameters, PARAMS,
+            },
+            enforce_on_curve,
+        },
+        params::set_params,
+    };
+    use ark_r1cs_std::fields::fp::FpVar;
+
+    let mut rng = ark_ff::test_rng();
+
+    // the costs of the allocation and the unreduced multiplication follow from the limbs
+    let num_bits = PARAMS.bits_per_top_limb + (PARAMS.num_limbs - 1) * PARAMS.bits_per_non_top_limb;
+    assert_eq!(num_bits, 254);
+
+    // the scalars of Grumpkin simulated over those of BN254, as in the verifiers of folding
+    // schemes over the cycle
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    set_params::<Fq, Fr>(&cs, PARAMS).unwrap();
+    let a_native = Fq::rand(&mut rng);
+    let b_native = Fq::rand(&mut rng);
+
+    let num_constraints_before = cs.num_constraints();
+    let a = FqVarOverFr::new_witness(ark_relations::ns!(cs, "a"), || Ok(a_native)).unwrap();
+    assert_eq!(
+        cs.num_constraints() - num_constraints_before,
+        num_bits + PARAMS.num_limbs
+    );
+    let b = FqVarOverFr::new_witness(ark_relations::ns!(cs, "b"), || Ok(b_native)).unwrap();
+
+    let num_constraints_before = cs.num_constraints();
+    let ab = a.mul_without_reduce(&b).unwrap();
+    assert_eq!(
+        cs.num_constraints() - num_constraints_before,
+        2 * PARAMS.num_limbs - 1
+    );
+    assert_eq!(ab.reduce().unwrap().value().unwrap(), a_native * &b_native);
+
+    // the conversions in both directions
+    let a_converted = a.to_native().unwrap();
+    assert_eq!(
+        a_converted.value().unwrap(),
+        Fr::from_le_bytes_mod_order(&ark_ff::to_bytes![a_native].unwrap())
+    );
+    let c_native = Fr::rand(&mut rng);
+    let c = FpVar::new_witness(ark_relations::ns!(cs, "c"), || Ok(c_native)).unwrap();
+    let c_converted = FqVarOverFr::from_native(&c).unwrap();
+    assert_eq!(
+        c_converted.value().unwrap(),
+        Fq::from_repr(c_native.into_repr()).unwrap()
+    );
+
+    // the generator of BN254 is on the simulated curve
+    let (x, y) = Bn254G1Parameters::AFFINE_GENERATOR_COEFFS;
+    let x = FqVarOverFr::new_witness(ark_relations::ns!(cs, "x"), || Ok(x)).unwrap();
+    let y = FqVarOverFr::new_witness(ark_relations::ns!(cs, "y"), || Ok(y)).unwrap();
+    enforce_on_curve::<Bn254G1Parameters, _>(&x, &y).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+
+    // and the other way around
+    let cs = ConstraintSystem::<Fq>::new_ref();
+    set_params::<Fr, Fq>(&cs, PARAMS).unwrap();
+    let num_constraints_before = cs.num_constraints();
+    let a = FrVarOverFq::new_witness(ark_relations::ns!(cs, "a"), || Ok(c_native)).unwrap();
+    assert_eq!(
+        cs.num_constraints() - num_constraints_before,
+        num_bits + PARAMS.num_limbs
+    );
+    let a_converted = a.to_native().unwrap();
+    assert_eq!(
+        a_converted.value().unwrap(),
+        Fq::from_repr(c_native.into_repr()).unwrap()
+    );
+
+    let (x, y) = GrumpkinParameters::AFFINE_GENERATOR_COEFFS;
+    let x = FrVarOverFq::new_witness(ark_relations::ns!(cs, "x"), || Ok(x)).unwrap();
+    let y = FrVarOverFq::new_witness(ark_relations::ns!(cs, "y"), || Ok(y)).unwrap();
+    enforce_on_curve::<GrumpkinParameters, _>(&x, &y).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+}
+
+#[cfg(feature = "bw6-761")]
+#[test]
+fn bw6_761_params_test() {
+    use ark_nonnative_field::{
+        curves::bw6_761::{
+            Bw6Fq, Fq, Fr, BW6_FQ_OVER_FQ_PARAMS, FQ_OVER_BW6_FQ_PARAMS, FQ_OVER_FR_PARAMS,
+            FR_OVER_FQ_PARAMS,
+        },
+        params::{gen_params, ParamsSearching},
+        NonNativeFieldParams,
+    };
+
+    let check = |params: NonNativeFieldParams, preset: NonNativeFieldParams| {
+        assert_eq!(params.num_limbs, preset.num_limbs);
+        assert_eq!(params.bits_per_top_limb, preset.bits_per_top_limb);
+        assert_eq!(params.bits_per_non_top_limb, preset.bits_per_non_top_limb);
+    };
+    check(gen_params::<Fr, Fq>().unwrap(), FR_OVER_FQ_PARAMS);
+    check(gen_params::<Fq, Fr>().unwrap(), FQ_OVER_FR_PARAMS);
+    check(gen_params::<Bw6Fq, Fq>().unwrap(), BW6_FQ_OVER_FQ_PARAMS);
+    check(gen_params::<Fq, Bw6Fq>().unwrap(), FQ_OVER_BW6_FQ_PARAMS);
+
+    // the simulated field is twice as wide as the native one, so the search has to go through
+    // several numbers of limbs without a solution
+    let mut problem = ParamsSearching::new(377, 761);
+    for num_of_limbs in 2..BW6_FQ_OVER_FQ_PARAMS.num_limbs {
+        assert!(problem.solve_with_num_of_limbs(num_of_limbs).is_none());
+    }
+    assert!(problem
+        .solve_with_num_of_limbs(BW6_FQ_OVER_FQ_PARAMS.num_limbs)
+        .is_some());
+}
+
+#[cfg(feature = "bw6-761")]
+#[test]
+fn bw6_761_constraint_count_test() {
+    use ark_ff::UniformRand;
+    use ark_nonnative_field::{
+        curves::bw6_761::{
+            Bw6Fq, Bw6FqVarOverFq, Fq, FqVarOverBw6Fq, FqVarOverFr, Fr, FrVarOverFq,
+            BW6_FQ_OVER_FQ_PARAMS, FQ_OVER_BW6_FQ_PARAMS, FQ_OVER_FR_PARAMS, FR_OVER_FQ_PARAMS,
+        },
+        params::set_params,
+        NonNativeFieldParams,
+    };
+
+    let mut rng = ark_ff::test_rng();
+
+    macro_rules! check {
+        ($target:ty, $base:ty, $var:ty, $params:expr) => {{
+            let params: NonNativeFieldParams = $params;
+            let cs = ConstraintSystem::<$base>::new_ref();
+            set_params::<$target, $base>(&cs, params.clone()).unwrap();
+
+            let a_native = <$target>::rand(&mut rng);
+            let b_native = <$target>::rand(&mut rng);
+
+            let num_constraints_before = cs.num_constraints();
+            let a = <$var>::new_witness(ark_relations::ns!(cs, "a"), || Ok(a_native)).unwrap();
+            assert_eq!(
+                cs.num_constraints() - num_constraints_before,
+                params.bits_per_top_limb
+                    + (params.num_limbs - 1) * params.bits_per_non_top_limb
+                    + params.num_limbs
+            );
+            let b = <$var>::new_witness(ark_relations::ns!(cs, "b"), || Ok(b_native)).unwrap();
+
+            let num_constraints_before = cs.num_constraints();
+            let ab = a.mul_without_reduce(&b).unwrap();
+            assert_eq!(
+                cs.num_constraints() - num_constraints_before,
+                2 * params.num_limbs - 1
+            );
+            assert_eq!(ab.reduce().unwrap().value().unwrap(), a_native * &b_native);
+            assert!(cs.is_satisfied().unwrap());
+        }};
+    }
+
+    check!(Fr, Fq, FrVarOverFq, FR_OVER_FQ_PARAMS);
+    check!(Fq, Fr, FqVarOverFr, FQ_OVER_FR_PARAMS);
+    check!(Bw6Fq, Fq, Bw6FqVarOverFq, BW6_FQ_OVER_FQ_PARAMS);
+    check!(Fq, Bw6Fq, FqVarOverBw6Fq, FQ_OVER_BW6_FQ_PARAMS);
+}
+
+#[cfg(feature = "mnt753")]
+#[test]
+fn mnt753_params_test() {
+    use ark_ff::{FftParameters, FpParameters};
+    use ark_nonnative_field::{
+        curves::mnt753::{Fq, Fr, PARAMS},
+        params::{gen_params, ParamsSearching},
+    };
+
+    // the fields of the presets are those of the curves
+    assert_eq!(
+        <Fr as PrimeField>::Params::MODULUS,
+        <<MNT4_753 as PairingEngine>::Fr as PrimeField>::Params::MODULUS
+    );
+    assert_eq!(
+        <Fr as PrimeField>::Params::GENERATOR,
+        <<MNT4_753 as PairingEngine>::Fr as PrimeField>::Params::GENERATOR
+    );
+    assert_eq!(
+        <Fr as PrimeField>::Params::TWO_ADIC_ROOT_OF_UNITY,
+        <<MNT4_753 as PairingEngine>::Fr as PrimeField>::Params::TWO_ADIC_ROOT_OF_UNITY
+    );
+    assert_eq!(
+        <Fq as PrimeField>::Params::MODULUS,
+        <<MNT6_753 as PairingEngine>::Fr as PrimeField>::Params::MODULUS
+    );
+    assert_eq!(
+        <Fq as PrimeField>::Params::GENERATOR,
+        <<MNT6_753 as PairingEngine>::Fr as PrimeField>::Params::GENERATOR
+    );
+    assert_eq!(
+        <Fq as PrimeField>::Params::TWO_ADIC_ROOT_OF_UNITY,
+        <<MNT6_753 as PairingEngine>::Fr as PrimeField>::Params::TWO_ADIC_ROOT_OF_UNITY
+    );
+
+    for params in [
+        gen_params::<Fq, Fr>().unwrap(),
+        gen_params::<Fr, Fq>().unwrap(),
+    ]
+    .iter()
+    {
+        assert_eq!(params.num_limbs, PARAMS.num_limbs);
+        assert_eq!(params.bits_per_top_limb, PARAMS.bits_per_top_limb);
+        assert_eq!(params.bits_per_non_top_limb, PARAMS.bits_per_non_top_limb);
+    }
+
+    let mut problem = ParamsSearching::new(753, 753);
+    assert!(problem.solve_with_num_of_limbs(2).is_none());
+    let cost = problem.solve_with_num_of_limbs(PARAMS.num_limbs).unwrap();
+    for num_of_limbs in PARAMS.num_limbs + 1..=10 {
+        let other_cost = problem.solve_with_num_of_limbs(num_of_limbs).unwrap();
+        assert!(other_cost > cost);
+    }
+}
+
+#[cfg(feature = "mnt753")]
+#[test]
+fn mnt753_cycle_test() {
+    use ark_ff::UniformRand;
+    use ark_nonnative_field::{
+        curves::mnt753::{Fq, FqVarOverFr, Fr, FrVarOverFq, PARAMS},
+        params::set_params,
+    };
+
+    let mut rng = ark_ff::test_rng();
+    let num_bits = PARAMS.bits_per_top_limb + (PARAMS.num_limbs - 1) * PARAMS.bits_per_non_top_limb;
+    assert_eq!(num_bits, 753);
+
+    let cs = ConstraintSystem::<Fq>::new_ref();
+    set_params::<Fr, Fq>(&cs, PARAMS).unwrap();
+    let a_native = Fr::rand(&mut rng);
+    let b_native = Fr::rand(&mut rng);
+
+    let num_constraints_before = cs.num_constraints();
+    let a = FrVarOverFq::new_witness(ark_relations::ns!(cs, "a"), || Ok(a_native)).unwrap();
+    assert_eq!(
+        cs.num_constraints() - num_constraints_before,
+        num_bits + PARAMS.num_limbs
+    );
+    let b = FrVarOverFq::new_witness(ark_relations::ns!(cs, "b"), || Ok(b_native)).unwrap();
+
+    let num_constraints_before = cs.num_constraints();
+    let ab = a.mul_without_reduce(&b).unwrap();
+    assert_eq!(
+        cs.num_constraints() - num_constraints_before,
+        2 * PARAMS.num_limbs - 1
+    );
+    assert_eq!(ab.reduce().unwrap().value().unwrap(), a_native * &b_native);
+    assert!(cs.is_satisfied().unwrap());
+
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    set_params::<Fq, Fr>(&cs, PARAMS).unwrap();
+    let a_native = Fq::rand(&mut rng);
+    let b_native = Fq::rand(&mut rng);
+    let a = FqVarOverFr::new_witness(ark_relations::ns!(cs, "a"), || Ok(a_native)).unwrap();
+    let b = FqVarOverFr::new_witness(ark_relations::ns!(cs, "b"), || Ok(b_native)).unwrap();
+    assert_eq!((&a * &b).value().unwrap(), a_native * &b_native);
+    assert!(cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn headroom_test() {
+    use ark_ff::UniformRand;
+    use ark_nonnative_field::{params::NonNativeConfig, Headroom};
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let a_native = TargetField::rand(rng);
+    let b_native = TargetField::rand(rng);
+
+    assert_eq!(
+        NonNativeFieldVar::<TargetField, BaseField>::constant(a_native)
+            .headroom()
+            .unwrap(),
+        Headroom {
+            additions: usize::MAX,
+            additions_before_mul: Some(usize::MAX),
+        }
+    );
+
+    // with a maximal surfeit of `s`, i.e., `ceil(log2(num_of_additions + 1)) <= s`, an element
+    // absorbs `2^s - 1` additions before it is reduced
+    for max_surfeit in [1usize, 2, 3].iter() {
+        let max_additions = (1usize << *max_surfeit) - 1;
+
+        let cs = ConstraintSystem::<BaseField>::new_ref();
+        NonNativeConfig {
+            max_surfeit: Some(*max_surfeit),
+            ..NonNativeConfig::default()
+        }
+        .install(&cs);
+        let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+            ark_relations::ns!(cs, "alloc a"),
+            || Ok(a_native),
+        )
+        .unwrap();
+        assert_eq!(a.headroom().unwrap().additions, max_additions);
+
+        let num_constraints_before = cs.num_constraints();
+        let mut sum = a.clone();
+        for i in 1..=max_additions {
+            sum += &a;
+            assert_eq!(sum.headroom().unwrap().additions, max_additions - i);
+        }
+        assert_eq!(cs.num_constraints(), num_constraints_before);
+        sum += &a;
+        assert!(cs.num_constraints() > num_constraints_before);
+        assert_eq!(
+            sum.value().unwrap(),
+            a_native * &TargetField::from((max_additions + 2) as u64)
+        );
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    // a multiplication is free of reductions exactly as long as the headroom says so
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+    let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "alloc a"),
+        || Ok(a_native),
+    )
+    .unwrap();
+    let b = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "alloc b"),
+        || Ok(b_native),
+    )
+    .unwrap();
+    let fresh_mul_cost = {
+        let num_constraints_before = cs.num_constraints();
+        a.mul_without_reduce(&b).unwrap();
+        cs.num_constraints() - num_constraints_before
+    };
+
+    let mut sum = a.clone();
+    let mut sum_native = a_native;
+    let mut seen_none = false;
+    for _ in 0..BaseField::size_in_bits() {
+        let headroom = sum.headroom().unwrap();
+        let num_constraints_before = cs.num_constraints();
+        let prod = sum.mul_without_reduce(&b).unwrap();
+        let mul_cost = cs.num_constraints() - num_constraints_before;
+        match headroom.additions_before_mul {
+            Some(_) => assert_eq!(mul_cost, fresh_mul_cost),
+            None => {
+                assert!(mul_cost > fresh_mul_cost);
+                seen_none = true;
+            }
+        }
+        assert_eq!(
+            prod.reduce().unwrap().value().unwrap(),
+            sum_native * &b_native
+        );
+        if seen_none {
+            break;
+        }
+
+        sum = &sum + &sum;
+        sum_native = sum_native + &sum_native;
+    }
+    assert!(seen_none);
+    assert!(cs.is_satisfied().unwrap());
+
+    // every product of normal forms added to an unreduced product uses one unit of headroom
+    let ab = a.mul_without_reduce(&b).unwrap();
+    let headroom = ab.headroom().unwrap();
+    assert!(headroom > 0);
+    let ab2 = &ab + &ab;
+    assert_eq!(ab2.headroom().unwrap(), headroom - 1);
+}
+
+#[test]
+fn overflow_warnings_test() {
+    use ark_ff::UniformRand;
+    use ark_nonnative_field::params::{OverflowWarning, OverflowWarnings};
+    use std::sync::Mutex;
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    static WARNINGS: Mutex<Vec<OverflowWarning>> = Mutex::new(Vec::new());
+    fn record(warning: &OverflowWarning) {
+        WARNINGS.lock().unwrap().push(*warning);
+    }
+
+    let rng = &mut ark_ff::test_rng();
+    let a_native = TargetField::rand(rng);
+    let b_native = TargetField::rand(rng);
+
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+    assert!(OverflowWarnings::get(&cs).is_none());
+    OverflowWarnings {
+        threshold_percent: 40,
+        callback: Some(record),
+    }
+    .enable(&cs);
+    assert_eq!(OverflowWarnings::get(&cs).unwrap().threshold_percent, 40);
+
+    let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "alloc a"),
+        || Ok(a_native),
+    )
+    .unwrap();
+    let b = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "alloc b"),
+        || Ok(b_native),
+    )
+    .unwrap();
+    assert!(WARNINGS.lock().unwrap().is_empty());
+
+    // the surfeit of the sum grows with every doubling until it crosses the threshold
+    let mut sum = a.clone();
+    for _ in 0..32 {
+        sum = &sum + &sum;
+    }
+    {
+        let warnings = WARNINGS.lock().unwrap();
+        assert!(!warnings.is_empty());
+        for warning in warnings.iter() {
+            assert_eq!(warning.operation, "add");
+            assert!(warning.bits * 100 > 40 * warning.max_bits);
+            assert!(warning.bits <= warning.max_bits);
+            // without a `tracing` subscriber, the number of constraints locates the warning
+            assert_eq!(warning.span, None);
+            assert!(warning.num_constraints <= cs.num_constraints());
+        }
+    }
+
+    // the products are close to the bound from the start, so only a high threshold tells
+    // them apart from those that are about to fail
+    WARNINGS.lock().unwrap().clear();
+    OverflowWarnings {
+        threshold_percent: 95,
+        callback: Some(record),
+    }
+    .enable(&cs);
+    let mut prod = a.mul_without_reduce(&b).unwrap();
+    let mut prod_native = a_native * &b_native;
+    let mut num_products = 2;
+    prod = &prod + &prod;
+    prod_native = prod_native + &prod_native;
+    assert!(WARNINGS.lock().unwrap().is_empty());
+    while prod.headroom().unwrap() >= num_products {
+        prod = &prod + &prod;
+        prod_native = prod_native + &prod_native;
+        num_products *= 2;
+    }
+    {
+        let warnings = WARNINGS.lock().unwrap();
+        assert!(!warnings.is_empty());
+        for warning in warnings.iter() {
+            assert!(warning.bits * 100 > 95 * warning.max_bits);
+            assert!(warning.bits <= warning.max_bits);
+        }
+    }
+    assert_eq!(prod.reduce().unwrap().value().unwrap(), prod_native);
+    assert!(cs.is_satisfied().unwrap());
+
+    OverflowWarnings::disable(&cs);
+    assert!(OverflowWarnings::get(&cs).is_none());
+}
+
+#[cfg(feature = "expression-graph")]
+#[test]
+fn expression_graph_test() {
+    use ark_ff::UniformRand;
+    use ark_nonnative_field::graph::ExpressionGraph;
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let a_native = TargetField::rand(rng);
+    let b_native = TargetField::rand(rng);
+
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+    let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "alloc a"),
+        || Ok(a_native),
+    )
+    .unwrap();
+
+    let num_constraints_before = cs.num_constraints();
+    let (d, graph) = ExpressionGraph::record(&cs, || {
+        let b = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+            ark_relations::ns!(cs, "alloc b"),
+            || Ok(b_native),
+        )?;
+        let c = a.mul_without_reduce(&b)?.reduce()?;
+        Ok(&c + &a)
+    })
+    .unwrap();
+    assert_eq!(d.value().unwrap(), a_native * &b_native + &a_native);
+    assert!(cs.is_satisfied().unwrap());
+
+    // `a` was allocated before the recording, so it is an input of the graph
+    let operations: Vec<&str> = graph.nodes.iter().map(|node| node.operation).collect();
+    assert_eq!(
+        operations,
+        vec!["alloc", "input", "mul_without_reduce", "reduce", "add"]
+    );
+    let inputs: Vec<Vec<usize>> = graph.nodes.iter().map(|node| node.inputs.clone()).collect();
+    assert_eq!(
+        inputs,
+        vec![vec![], vec![], vec![1, 0], vec![2], vec![3, 1]]
+    );
+    assert!(graph.nodes[0].num_constraints > 0);
+    assert_eq!(graph.nodes[1].num_constraints, 0);
+    assert_eq!(
+        graph.num_constraints(),
+        cs.num_constraints() - num_constraints_before
+    );
+
+    let dot = graph.to_dot();
+    assert!(dot.starts_with("digraph nonnative {"));
+    assert!(dot.contains("n1 -> n2;"));
+    assert!(dot.contains("n0 -> n2;"));
+    assert!(dot.contains("n3 -> n4;"));
+}
+
+#[test]
+fn lookup_range_check_test() {
+    use ark_ff::UniformRand;
+    use ark_nonnative_field::{
+        params::gen_params,
+        range_check::{enforce_range, set_range_check, LookupRangeCheck, RangeTable},
+        reduce::Reducer,
+    };
+    use ark_r1cs_std::fields::fp::AllocatedFp;
+    use ark_relations::r1cs::SynthesisError;
+    use std::{cell::Cell, rc::Rc};
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    // a table of the integers in `[0, 2^k)`, enforced with bits in the absence of lookups
+    struct CountingTable {
+        num_bits: usize,
+        lookups: Rc<Cell<usize>>,
+    }
+
+    impl RangeTable<BaseField> for CountingTable {
+        fn num_bits(&self) -> usize {
+            self.num_bits
+        }
+
+        fn enforce_lookup(&self, value: &AllocatedFp<BaseField>) -> Result<(), SynthesisError> {
+            self.lookups.set(self.lookups.get() + 1);
+            Reducer::<BaseField, BaseField>::limb_to_bits(value, self.num_bits).map(|_| ())
+        }
+    }
+
+    let k = 16;
+    let lookups_of = |num_bits: usize| {
+        let num_chunks = (num_bits + k - 1) / k;
+        if num_bits % k == 0 {
+            num_chunks
+        } else {
+            num_chunks + 1
+        }
+    };
+
+    let rng = &mut ark_ff::test_rng();
+    let a_native = TargetField::rand(rng);
+
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+    let lookups = Rc::new(Cell::new(0));
+    set_range_check(
+        &cs,
+        LookupRangeCheck {
+            table: CountingTable {
+                num_bits: k,
+                lookups: lookups.clone(),
+            },
+        },
+    );
+
+    let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "alloc a"),
+        || Ok(a_native),
+    )
+    .unwrap();
+    assert_eq!(a.value().unwrap(), a_native);
+    assert!(cs.is_satisfied().unwrap());
+
+    let params = gen_params::<TargetField, BaseField>().unwrap();
+    assert_eq!(
+        lookups.get(),
+        (params.num_limbs - 1) * lookups_of(params.bits_per_non_top_limb)
+            + lookups_of(params.bits_per_top_limb)
+    );
+
+    // the top chunk of a 20-bit range check only has 4 bits
+    let limb = AllocatedFp::<BaseField>::new_witness(ark_relations::ns!(cs, "limb"), || {
+        Ok(BaseField::from(1u64 << 20))
+    })
+    .unwrap();
+    enforce_range(&limb, 21).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+    enforce_range(&limb, 20).unwrap();
+    assert!(!cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn nonnative_exponent_pow_test() {
+    use ark_ff::{Field, UniformRand};
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let a_native = TargetField::rand(rng);
+    let e_native = TargetField::rand(rng);
+    let expected = a_native.pow(e_native.into_repr());
+
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+    let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "alloc a"),
+        || Ok(a_native),
+    )
+    .unwrap();
+    let e = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "alloc e"),
+        || Ok(e_native),
+    )
+    .unwrap();
+
+    assert_eq!(a.pow(&e).unwrap().value().unwrap(), expected);
+    assert_eq!(
+        NonNativeFieldVar::constant(a_native)
+            .pow(&e)
+            .unwrap()
+            .value()
+            .unwrap(),
+        expected
+    );
+    assert_eq!(
+        a.pow(&NonNativeFieldVar::constant(e_native))
+            .unwrap()
+            .value()
+            .unwrap(),
+        expected
+    );
+    assert!(cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn limb_layout_conversion_test() {
+    use ark_ff::{BigInteger, UniformRand};
+    use ark_nonnative_field::{AllocatedNonNativeFieldVar, NonNativeFieldParams};
+    use ark_r1cs_std::fields::fp::AllocatedFp;
+    use num_bigint::BigUint;
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let a_native = TargetField::rand(rng);
+
+    // the default layout has 3 limbs, while this one has 4 limbs of at most 72 bits
+    let layout = NonNativeFieldParams {
+        num_limbs: 4,
+        bits_per_top_limb: 82,
+        bits_per_non_top_limb: 72,
+        equality_layout: None,
+    };
+
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+    let a = AllocatedNonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "alloc a"),
+        || Ok(a_native),
+    )
+    .unwrap();
+    // an element that is not in the normal form
+    let b = a.add(&a).unwrap();
+
+    let limbs = b.to_layout(&layout).unwrap();
+    assert_eq!(limbs.len(), 4);
+    let mut value = BigUint::from(0u64);
+    for limb in limbs.iter() {
+        let limb_value = BigUint::from_bytes_le(&limb.value().unwrap().into_repr().to_bytes_le());
+        value = (value << 72usize) + limb_value;
+    }
+    let expected = (a_native + &a_native).into_repr().to_bytes_le();
+    assert_eq!(value, BigUint::from_bytes_le(&expected));
+
+    let c = AllocatedNonNativeFieldVar::<TargetField, BaseField>::from_layout(
+        cs.clone(),
+        &limbs,
+        &layout,
+    )
+    .unwrap();
+    assert_eq!(c.value().unwrap(), a_native + &a_native);
+    c.enforce_equal(&b).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+
+    // the largest canonical integer, `p - 1`, passes the check of its complement
+    let minus_one = AllocatedNonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "alloc -1"),
+        || Ok(-TargetField::from(1u64)),
+    )
+    .unwrap();
+    let _ = minus_one.to_layout(&layout).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+
+    // limbs that differ from the element in the other layout make the system unsatisfied
+    let mut wrong = limbs.clone();
+    wrong[3] = AllocatedFp::new_witness(ark_relations::ns!(cs, "wrong limb"), || {
+        Ok(limbs[3].value().unwrap() + BaseField::from(1u64))
+    })
+    .unwrap();
+    let _ = AllocatedNonNativeFieldVar::<TargetField, BaseField>::from_layout(
+        cs.clone(),
+        &wrong,
+        &layout,
+    )
+    .unwrap()
+    .enforce_equal(&b);
+    assert!(!cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn deferred_reduction_sum_test() {
+    use ark_ff::{Field, One, UniformRand, Zero};
+    use ark_nonnative_field::params::{get_params, NonNativeConfig};
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let natives: Vec<TargetField> = (0..300).map(|_| TargetField::rand(rng)).collect();
+    let constant = TargetField::rand(rng);
+    let expected = natives.iter().fold(constant, |sum, x| sum + x);
+
+    let mut num_constraints = Vec::new();
+    for deferred in [false, true].iter() {
+        let cs = ConstraintSystem::<BaseField>::new_ref();
+        NonNativeConfig {
+            max_surfeit: Some(2),
+            ..NonNativeConfig::default()
+        }
+        .install(&cs);
+
+        let mut elems: Vec<NonNativeFieldVar<TargetField, BaseField>> = natives
+            .iter()
+            .map(|x| {
+                NonNativeFieldVar::new_witness(ark_relations::ns!(cs, "alloc"), || Ok(*x)).unwrap()
+            })
+            .collect();
+        elems.insert(100, NonNativeFieldVar::constant(constant));
+        let num_constraints_before = cs.num_constraints();
+
+        let sum = if *deferred {
+            NonNativeFieldVar::add_many_with_deferred_reduction(&elems).unwrap()
+        } else {
+            NonNativeFieldVar::add_many(&elems).unwrap()
+        };
+        assert_eq!(sum.value().unwrap(), expected);
+        sum.enforce_equal(&NonNativeFieldVar::constant(expected))
+            .unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        num_constraints.push(cs.num_constraints() - num_constraints_before);
+    }
+    // the carries are resolved once instead of reducing whenever the surfeit exceeds 2
+    assert!(num_constraints[1] < num_constraints[0]);
+
+    // the columns take no constraints, so that a sum of 30 times as many terms only takes a few
+    // more bits to resolve the carries
+    let mut num_constraints = Vec::new();
+    for num_terms in [10, 300].iter() {
+        let cs = ConstraintSystem::<BaseField>::new_ref();
+        let elems: Vec<NonNativeFieldVar<TargetField, BaseField>> = natives[..*num_terms]
+            .iter()
+            .map(|x| {
+                NonNativeFieldVar::new_witness(ark_relations::ns!(cs, "alloc"), || Ok(*x)).unwrap()
+            })
+            .collect();
+        let num_constraints_before = cs.num_constraints();
+        let sum = NonNativeFieldVar::add_many_with_deferred_reduction(&elems).unwrap();
+        num_constraints.push(cs.num_constraints() - num_constraints_before);
+        let expected = natives[..*num_terms]
+            .iter()
+            .fold(TargetField::zero(), |sum, x| sum + x);
+        sum.enforce_equal(&NonNativeFieldVar::constant(expected))
+            .unwrap();
+        assert!(cs.is_satisfied().unwrap());
+    }
+    assert!(num_constraints[1] < 2 * num_constraints[0]);
+
+    // terms whose columns would no longer fit into `BaseField` next to each other have their
+    // carries resolved in between
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+    let params = get_params::<TargetField, BaseField>(&cs).unwrap();
+    let num_of_additions = BaseField::from(2u64)
+        .pow(&[(BaseField::size_in_bits() - params.bits_per_non_top_limb - 4) as u64]);
+    let elems: Vec<NonNativeFieldVar<TargetField, BaseField>> = natives[..16]
+        .iter()
+        .map(|x| {
+            match NonNativeFieldVar::new_witness(ark_relations::ns!(cs, "alloc"), || Ok(*x))
+                .unwrap()
+            {
+                NonNativeFieldVar::Var(mut v) => {
+                    // a loose but valid bound on the limbs
+                    v.num_of_additions_over_normal_form = num_of_additions - BaseField::one();
+                    NonNativeFieldVar::Var(v)
+                }
+                NonNativeFieldVar::Constant(_) => unreachable!(),
+            }
+        })
+        .collect();
+    let sum = NonNativeFieldVar::add_many_with_deferred_reduction(&elems).unwrap();
+    let expected = natives[..16]
+        .iter()
+        .fold(TargetField::zero(), |sum, x| sum + x);
+    assert_eq!(sum.value().unwrap(), expected);
+    sum.enforce_equal(&NonNativeFieldVar::constant(expected))
+        .unwrap();
+    assert!(cs.is_satisfied().unwrap());
+
+    assert!(
+        NonNativeFieldVar::<TargetField, BaseField>::add_many_with_deferred_reduction(&[])
+            .unwrap()
+            .value()
+            .unwrap()
+            .is_zero()
+    );
+}
+
+#[test]
+fn dleq_test() {
+    use ark_ec::{AffineCurve, ProjectiveCurve};
+    use ark_ff::{Field, UniformRand};
+    use ark_nonnative_field::{
+        curves::{
+            p256::{Fq, Fr, P256Parameters, PARAMS_OVER_BLS12_381_FR},
+            short_weierstrass::NonNativeAffineVar,
+        },
+        dleq::{challenge_bits, verify_dleq, DleqProofVar},
+        params::set_params,
+        poseidon::PoseidonParameters,
+    };
+    use ark_relations::r1cs::ConstraintSystemRef;
+
+    type BaseField = <Bls12_381 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let (rate, capacity, full_rounds, partial_rounds) = (2, 1, 2, 2);
+    let mds: Vec<Vec<Fq>> = (0..rate + capacity)
+        .map(|_| (0..rate + capacity).map(|_| Fq::rand(rng)).collect())
+        .collect();
+    let ark: Vec<Vec<Fq>> = (0..full_rounds + partial_rounds)
+        .map(|_| (0..rate + capacity).map(|_| Fq::rand(rng)).collect())
+        .collect();
+    let params =
+        PoseidonParameters::new(full_rounds, partial_rounds, 5, mds, ark, rate, capacity).unwrap();
+
+    let g =
+        ark_ec::short_weierstrass_jacobian::GroupAffine::<P256Parameters>::prime_subgroup_generator(
+        );
+    let h = g.mul(Fr::from(7u64)).into_affine();
+    let x = Fr::rand(rng);
+    let a = g.mul(x).into_affine();
+    let b = h.mul(x).into_affine();
+    let r = Fr::rand(rng);
+    let t1 = g.mul(r).into_affine();
+    let t2 = h.mul(r).into_affine();
+
+    // the prover computes the challenge on constants
+    let constant = |point| {
+        NonNativeAffineVar::<P256Parameters, BaseField>::new_constant(
+            ConstraintSystemRef::None,
+            point,
+        )
+        .unwrap()
+    };
+    let points = [g, h, a, b, t1, t2];
+    let point_vars: Vec<_> = points.iter().map(|p| constant(*p)).collect();
+    let bits = challenge_bits(&params, &point_vars.iter().collect::<Vec<_>>()).unwrap();
+    let c = bits.iter().rev().fold(Fr::from(0u64), |c, bit| {
+        c.double() + Fr::from(bit.value().unwrap() as u64)
+    });
+    let s = r - c * x;
+
+    for (s, valid) in [(s, true), (s + Fr::from(1u64), false)].iter() {
+        let cs = ConstraintSystem::<BaseField>::new_ref();
+        set_params::<Fq, BaseField>(&cs, PARAMS_OVER_BLS12_381_FR).unwrap();
+
+        let a_var = NonNativeAffineVar::new_witness(ark_relations::ns!(cs, "a"), || Ok(a)).unwrap();
+        let b_var = NonNativeAffineVar::new_witness(ark_relations::ns!(cs, "b"), || Ok(b)).unwrap();
+        let proof = DleqProofVar {
+            challenge: NonNativeFieldVar::new_witness(ark_relations::ns!(cs, "c"), || Ok(c))
+                .unwrap(),
+            response: NonNativeFieldVar::new_witness(ark_relations::ns!(cs, "s"), || Ok(*s))
+                .unwrap(),
+        };
+
+        verify_dleq(&params, &constant(g), &constant(h), &a_var, &b_var, &proof).unwrap();
+        assert_eq!(cs.is_satisfied().unwrap(), *valid);
+    }
+}
+
+#[test]
+fn cached_identities_test() {
+    use ark_ff::{Field, UniformRand};
+    use ark_nonnative_field::AllocatedNonNativeFieldVar;
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+
+    let variables = |elem: &AllocatedNonNativeFieldVar<TargetField, BaseField>| {
+        elem.limbs
+            .iter()
+            .map(|limb| limb.variable)
+            .collect::<Vec<_>>()
+    };
+    let zero = AllocatedNonNativeFieldVar::<TargetField, BaseField>::zero(cs.clone()).unwrap();
+    let one = AllocatedNonNativeFieldVar::<TargetField, BaseField>::one(cs.clone()).unwrap();
+    assert_eq!(zero.value().unwrap(), TargetField::from(0u64));
+    assert_eq!(one.value().unwrap(), TargetField::from(1u64));
+    assert_ne!(variables(&zero), variables(&one));
+
+    // the later calls share the limbs of the first ones
+    let zero_again = AllocatedNonNativeFieldVar::zero(cs.clone()).unwrap();
+    let one_again = AllocatedNonNativeFieldVar::one(cs.clone()).unwrap();
+    assert_eq!(variables(&zero), variables(&zero_again));
+    assert_eq!(variables(&one), variables(&one_again));
+    assert_eq!(
+        num_lcs,
+        cs.num_instance_variables() + cs.num_witness_variables()
+    );
+
+    let a_native = TargetField::rand(rng);
+    let a = NonNativeFieldVar::new_witness(ark_relations::ns!(cs, "a"), || Ok(a_native)).unwrap();
+    let b = &(&a * &NonNativeFieldVar::one()) - &a.negate().unwrap();
+    b.enforce_equal(&NonNativeFieldVar::constant(a_native.double()))
+        .unwrap();
+    a.mul_equals(&NonNativeFieldVar::Var(one_again), &a)
+        .unwrap();
+    assert!(cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn explicit_params_allocation_test() {
+    use ark_ff::{BigInteger, UniformRand};
+    use ark_nonnative_field::{AllocatedNonNativeFieldVar, NonNativeFieldParams};
+    use ark_r1cs_std::alloc::AllocationMode;
+    use num_bigint::BigUint;
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let a_native = TargetField::rand(rng);
+
+    // a layout fixed outside of the circuit, with 4 limbs of 75 bits instead of 3 limbs
+    let layout = NonNativeFieldParams {
+        num_limbs: 4,
+        bits_per_top_limb: 75,
+        bits_per_non_top_limb: 75,
+        equality_layout: None,
+    };
+
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+    let limbs = AllocatedNonNativeFieldVar::<TargetField, BaseField>::new_limbs_with_params(
+        ark_relations::ns!(cs, "alloc a"),
+        || Ok(a_native),
+        AllocationMode::Witness,
+        &layout,
+    )
+    .unwrap();
+    assert_eq!(limbs.len(), 4);
+    let mut value = BigUint::from(0u64);
+    for limb in limbs.iter() {
+        let limb_value = BigUint::from_bytes_le(&limb.value().unwrap().into_repr().to_bytes_le());
+        value = (value << 75usize) + limb_value;
+    }
+    assert_eq!(
+        value,
+        BigUint::from_bytes_le(&a_native.into_repr().to_bytes_le())
+    );
+
+    // converted to the parameters of the constraint system for the arithmetic
+    let b = AllocatedNonNativeFieldVar::<TargetField, BaseField>::from_layout(
+        cs.clone(),
+        &limbs,
+        &layout,
+    )
+    .unwrap();
+    let doubled = b.add(&b).unwrap();
+    let expected = AllocatedNonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "expected"),
+        || Ok(a_native + &a_native),
+    )
+    .unwrap();
+    doubled.enforce_equal(&expected).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+
+    // the layouts are validated as by `set_params`: a top limb larger than the other ones, a
+    // single limb, and limbs that do not cover the target field are rejected
+    let invalid_layouts = [
+        NonNativeFieldParams {
+            num_limbs: 4,
+            bits_per_top_limb: 82,
+            bits_per_non_top_limb: 72,
+            equality_layout: None,
+        },
+        NonNativeFieldParams {
+            num_limbs: 1,
+            bits_per_top_limb: 100,
+            bits_per_non_top_limb: 100,
+            equality_layout: None,
+        },
+        NonNativeFieldParams {
+            num_limbs: 3,
+            bits_per_top_limb: 75,
+            bits_per_non_top_limb: 75,
+            equality_layout: None,
+        },
+    ];
+    for invalid_layout in invalid_layouts.iter() {
+        let other_cs = ConstraintSystem::<BaseField>::new_ref();
+        assert!(
+            AllocatedNonNativeFieldVar::<TargetField, BaseField>::new_limbs_with_params(
+                ark_relations::ns!(other_cs, "alloc"),
+                || Ok(a_native),
+                AllocationMode::Witness,
+                invalid_layout,
+            )
+            .is_err()
+        );
+    }
+}
+
+#[test]
+fn constant_conversions_test() {
+    use ark_ff::UniformRand;
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let a_native = TargetField::rand(rng);
+
+    let constant = |v: NonNativeFieldVar<TargetField, BaseField>| match v {
+        NonNativeFieldVar::Constant(c) => c,
+        NonNativeFieldVar::Var(_) => panic!("a conversion allocated a variable"),
+    };
+    assert_eq!(constant(NonNativeFieldVar::from(&a_native)), a_native);
+    assert_eq!(constant(7u64.into()), TargetField::from(7u64));
+    assert_eq!(
+        constant(NonNativeFieldVar::from(u128::MAX)),
+        TargetField::from(u128::MAX)
+    );
+    assert_eq!(constant(true.into()), TargetField::from(1u64));
+    assert_eq!(constant(false.into()), TargetField::from(0u64));
+
+    // the conversions mix with the arithmetic on variables
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+    let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "a"),
+        || Ok(a_native),
+    )
+    .unwrap();
+    let b = &(&a * &NonNativeFieldVar::from(3u64)) + &NonNativeFieldVar::from(true);
+    b.enforce_equal(&NonNativeFieldVar::from(
+        &(a_native * &TargetField::from(3u64) + &TargetField::from(1u64)),
+    ))
+    .unwrap();
+    assert!(cs.is_satisfied().unwrap());
+}
+
+#[cfg(feature = "proptest-strategies")]
+#[test]
+fn surfeit_sum_strategy_test() {
+    use ark_nonnative_field::{
+        params::get_params,
+        strategies::{edge_cases, SurfeitSum},
+    };
+    use proptest::{arbitrary::Arbitrary, test_runner::TestRunner};
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let params = get_params::<TargetField, BaseField>(&ConstraintSystemRef::None).unwrap();
+    let cases = edge_cases::<TargetField>(Some(&params));
+    assert!(cases.contains(&TargetField::from(0u64)));
+    assert!(cases.contains(&-TargetField::from(1u64)));
+
+    let mut runner = TestRunner::default();
+    runner
+        .run(&SurfeitSum::arbitrary_with(Some(params)), |case| {
+            let cs = ConstraintSystem::<BaseField>::new_ref();
+            let (sum, native) = case.allocate(cs.clone()).unwrap();
+            sum.enforce_equal(&NonNativeFieldVar::constant(native))
+                .unwrap();
+            assert_eq!(sum.value().unwrap(), native);
+            assert!(cs.is_satisfied().unwrap());
+            Ok(())
+        })
+        .unwrap();
+}
+
+#[test]
+fn range_check_interning_test() {
+    use ark_ff::UniformRand;
+    use ark_nonnative_field::{range_check::enforce_range, AllocatedNonNativeFieldVar};
+    use ark_r1cs_std::fields::fp::AllocatedFp;
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+    let limb = AllocatedFp::<BaseField>::new_witness(ark_relations::ns!(cs, "limb"), || {
+        Ok(BaseField::from(1u64 << 20))
+    })
+    .unwrap();
+
+    enforce_range(&limb, 30).unwrap();
+    let num_constraints = cs.num_constraints();
+    // a check to as many bits or more is skipped
+    enforce_range(&limb, 30).unwrap();
+    enforce_range(&limb, 40).unwrap();
+    assert_eq!(cs.num_constraints(), num_constraints);
+    // a tighter check is not
+    enforce_range(&limb, 21).unwrap();
+    assert!(cs.num_constraints() > num_constraints);
+    assert!(cs.is_satisfied().unwrap());
+
+    // the limbs of an allocated element are checked once, even when a gadget checks them
+    // again to the sizes of the same layout
+    let rng = &mut ark_ff::test_rng();
+    let a = AllocatedNonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "a"),
+        || Ok(TargetField::rand(rng)),
+    )
+    .unwrap();
+    let num_constraints = cs.num_constraints();
+    for limb in a.limbs.iter() {
+        enforce_range(limb, 128).unwrap();
+    }
+    assert_eq!(cs.num_constraints(), num_constraints);
+    assert!(cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn memory_stats_test() {
+    use ark_ff::UniformRand;
+    use ark_nonnative_field::{
+        report::MemoryStats, AllocatedNonNativeFieldMulResultVar, NonNativeFieldMulResultVar,
+    };
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+    assert!(MemoryStats::get(&cs).is_none());
+    MemoryStats::enable(&cs);
+
+    let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "a"),
+        || Ok(TargetField::rand(rng)),
+    )
+    .unwrap();
+    let stats = MemoryStats::get(&cs).unwrap();
+    assert!(stats.num_limb_variables > 0);
+    assert_eq!(stats.live_products, 0);
+
+    // the retained products are alive until they are dropped
+    let products: Vec<_> = (0..5).map(|_| a.mul_without_reduce(&a).unwrap()).collect();
+    let stats = MemoryStats::get(&cs).unwrap();
+    assert_eq!(stats.live_products, 5);
+    assert!(stats.live_product_limbs >= 5);
+    let copy = products[0].clone();
+    assert_eq!(MemoryStats::get(&cs).unwrap().live_products, 6);
+
+    drop(products);
+    drop(copy);
+    let stats = MemoryStats::get(&cs).unwrap();
+    assert_eq!(stats.live_products, 0);
+    assert_eq!(stats.live_product_limbs, 0);
+    assert!(stats.peak_live_products >= 6);
+    assert!(
+        stats
+            .churn
+            .values()
+            .map(|churn| churn.num_products)
+            .sum::<usize>()
+            >= 6
+    );
+    // without a `tracing` subscriber, the churn is attributed to the operations
+    assert!(!stats.churn.contains_key(""));
+    assert!(stats.churn["multiplication"].num_products >= 5);
+
+    MemoryStats::disable(&cs);
+    assert!(MemoryStats::get(&cs).is_none());
+
+    // the products created while the statistics are disabled are not counted, and their limbs
+    // can still be moved out of them
+    let product = a.mul_without_reduce(&a).unwrap();
+    MemoryStats::enable(&cs);
+    if let NonNativeFieldMulResultVar::Var(product) = product {
+        let AllocatedNonNativeFieldMulResultVar { limbs, .. } = product;
+        assert!(!limbs.is_empty());
+    }
+    assert_eq!(MemoryStats::get(&cs).unwrap().live_products, 0);
+    assert!(cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn workload_params_test() {
+    use ark_ff::UniformRand;
+    use ark_nonnative_field::params::{
+        gen_params_for_workload, set_params, ParamsSearching, WorkloadProfile,
+    };
+
+    let mut rng = ark_ff::test_rng();
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let workloads = [
+        WorkloadProfile::default(),
+        WorkloadProfile {
+            additions: 1000,
+            multiplications: 1,
+            equalities: 1,
+        },
+        WorkloadProfile {
+            additions: 1 << 20,
+            multiplications: 1,
+            equalities: 0,
+        },
+        WorkloadProfile {
+            additions: 0,
+            multiplications: 1,
+            equalities: 100,
+        },
+    ];
+    for workload in workloads.iter() {
+        // the chosen layout is never predicted to be worse than the first one that works
+        let mut first = ParamsSearching::new(298, 298);
+        first.solve();
+        let mut chosen = ParamsSearching::new(298, 298);
+        chosen.solve_for_workload(workload);
+        assert!(chosen.num_of_limbs >= first.num_of_limbs);
+        assert!(
+            chosen.predicted_constraints(workload).unwrap()
+                <= first.predicted_constraints(workload).unwrap()
+        );
+
+        let params = gen_params_for_workload::<TargetField, BaseField>(workload).unwrap();
+        assert_eq!(params.num_limbs, chosen.num_of_limbs);
+
+        let cs = ConstraintSystem::<BaseField>::new_ref();
+        set_params::<TargetField, BaseField>(&cs, params).unwrap();
+        let a_native = TargetField::rand(&mut rng);
+        let b_native = TargetField::rand(&mut rng);
+        let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+            ark_relations::ns!(cs, "a"),
+            || Ok(a_native),
+        )
+        .unwrap();
+        let b = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+            ark_relations::ns!(cs, "b"),
+            || Ok(b_native),
+        )
+        .unwrap();
+        let mut sum = a.clone();
+        for _ in 0..20 {
+            sum += &b;
+        }
+        let product = &sum * &a;
+        let mut sum_native = a_native;
+        for _ in 0..20 {
+            sum_native += &b_native;
+        }
+        assert_eq!(product.value().unwrap(), sum_native * &a_native);
+        product
+            .enforce_equal(
+                &NonNativeFieldVar::new_witness(ark_relations::ns!(cs, "expected"), || {
+                    Ok(sum_native * &a_native)
+                })
+                .unwrap(),
+            )
+            .unwrap();
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    // a product of two long sums of products needs narrower limbs, which leave more headroom
+    // for the additions than the first layout that works
+    let workload = WorkloadProfile {
+        additions: 1024,
+        multiplications: 1,
+        equalities: 0,
+    };
+    let mut first = ParamsSearching::new(381, 255);
+    first.solve();
+    let mut chosen = ParamsSearching::new(381, 255);
+    chosen.solve_for_workload(&workload);
+    assert!(chosen.num_of_limbs > first.num_of_limbs);
+    assert!(
+        chosen.predicted_constraints(&workload).unwrap()
+            < first.predicted_constraints(&workload).unwrap()
+    );
+
+    let measure = |problem: &ParamsSearching| {
+        type TargetField = ark_bls12_381::Fr;
+        type BaseField = ark_bls12_381::Fq;
+
+        let mut rng = ark_ff::test_rng();
+        let cs = ConstraintSystem::<BaseField>::new_ref();
+        set_params::<TargetField, BaseField>(&cs, problem.params().unwrap()).unwrap();
+        let mut products = Vec::new();
+        for _ in 0..2 {
+            let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+                ark_relations::ns!(cs, "a"),
+                || Ok(TargetField::rand(&mut rng)),
+            )
+            .unwrap();
+            let b = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+                ark_relations::ns!(cs, "b"),
+                || Ok(TargetField::rand(&mut rng)),
+            )
+            .unwrap();
+            let product = &a * &b;
+            let mut sum = product.clone();
+            for _ in 0..workload.additions / 2 {
+                sum += &product;
+            }
+            products.push(sum);
+        }
+
+        let num_constraints = cs.num_constraints();
+        let product = &products[0] * &products[1];
+        assert_eq!(
+            product.value().unwrap(),
+            products[0].value().unwrap() * &products[1].value().unwrap()
+        );
+        assert!(cs.is_satisfied().unwrap());
+        cs.num_constraints() - num_constraints
+    };
+    assert!(measure(&chosen) < measure(&first));
+}
+
+#[test]
+fn congruence_wrapping_carry_test() {
+    use ark_ff::{BigInteger, FpParameters};
+    use ark_nonnative_field::{
+        layout::enforce_congruent_with_wrapping_carries, params::get_params, range_check,
+        AllocatedNonNativeFieldVar, NonNativeFieldParams,
+    };
+    use ark_r1cs_std::fields::fp::AllocatedFp;
+    use num_bigint::BigUint;
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let base_modulus =
+        BigUint::from_bytes_le(&<BaseField as PrimeField>::Params::MODULUS.to_bytes_le());
+    let layouts = [(4, 82, 72), (5, 42, 64)];
+    for (num_limbs, bits_per_top_limb, bits_per_non_top_limb) in layouts.iter() {
+        let layout = NonNativeFieldParams {
+            num_limbs: *num_limbs,
+            bits_per_top_limb: *bits_per_top_limb,
+            bits_per_non_top_limb: *bits_per_non_top_limb,
+            equality_layout: None,
+        };
+
+        // the integer `1 + q` for the `BaseField` modulus `q` is congruent to 1 modulo `q`, but
+        // not modulo the `TargetField` modulus, so carries that wrap around `q` would pass
+        for (rhs_value, congruent) in
+            [(BigUint::from(1u64), true), (&base_modulus + 1u64, false)].iter()
+        {
+            let cs = ConstraintSystem::<BaseField>::new_ref();
+            let params = get_params::<TargetField, BaseField>(&cs).unwrap();
+            let lhs = AllocatedNonNativeFieldVar::<TargetField, BaseField>::new_witness(
+                ark_relations::ns!(cs, "lhs"),
+                || Ok(TargetField::from(1u64)),
+            )
+            .unwrap();
+            let rhs: Vec<_> = (0..*num_limbs)
+                .map(|i| {
+                    let num_bits = if i == 0 {
+                        *bits_per_top_limb
+                    } else {
+                        *bits_per_non_top_limb
+                    };
+                    let position = (num_limbs - 1 - i) * bits_per_non_top_limb;
+                    let limb_value =
+                        (rhs_value >> position) & ((BigUint::from(1u64) << num_bits) - 1u64);
+                    let limb = AllocatedFp::<BaseField>::new_witness(
+                        ark_relations::ns!(cs, "rhs limb"),
+                        || {
+                            Ok(BaseField::from_le_bytes_mod_order(
+                                &limb_value.to_bytes_le(),
+                            ))
+                        },
+                    )
+                    .unwrap();
+                    range_check::enforce_range(&limb, num_bits).unwrap();
+                    limb
+                })
+                .collect();
+
+            enforce_congruent_with_wrapping_carries::<TargetField, BaseField>(
+                &cs,
+                &lhs.limbs,
+                &params,
+                &rhs,
+                &layout,
+                &BigUint::from(1u64),
+            )
+            .unwrap();
+            assert_eq!(cs.is_satisfied().unwrap(), *congruent);
+        }
+    }
+}
+
+#[test]
+fn equality_layout_test() {
+    use ark_ff::UniformRand;
+    use ark_nonnative_field::{
+        params::{
+            gen_equality_params, get_equality_params, get_params, set_equality_params,
+            ParamsSearching,
+        },
+        EqualityLayout,
+    };
+
+    let mut rng = ark_ff::test_rng();
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    // comparing two sides in a layout of their own avoids their normal forms, and 16-bit
+    // lookups prefer limbs of multiples of 16 bits
+    let mut problem = ParamsSearching::new(298, 298);
+    problem.solve();
+    for lookup_bits in [None, Some(16)].iter() {
+        let params = gen_equality_params::<TargetField, BaseField>(*lookup_bits).unwrap();
+        let layout = params.equality_layout.unwrap();
+        assert!(
+            problem
+                .predicted_equality_constraints(Some(&layout), *lookup_bits)
+                .unwrap()
+                < problem
+                    .predicted_equality_constraints(None, *lookup_bits)
+                    .unwrap()
+        );
+        if let Some(lookup_bits) = lookup_bits {
+            assert_eq!(layout.bits_per_non_top_limb % lookup_bits, 0);
+        }
+    }
+
+    // the measured constraints of an equality of two products of products
+    let measure = |layout: Option<EqualityLayout>| {
+        let cs = ConstraintSystem::<BaseField>::new_ref();
+        if let Some(layout) = layout {
+            set_equality_params::<TargetField, BaseField>(&cs, layout).unwrap();
+        }
+        let mut rng = ark_ff::test_rng();
+        let factors: Vec<_> = (0..4)
+            .map(|_| {
+                NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+                    ark_relations::ns!(cs, "factor"),
+                    || Ok(TargetField::rand(&mut rng)),
+                )
+                .unwrap()
+            })
+            .collect();
+        let left = &factors[0] * &factors[1];
+        let right = &factors[2] * &factors[3];
+        let lhs = &left * &right;
+        let rhs = &right * &left;
+
+        let num_constraints = cs.num_constraints();
+        lhs.enforce_equal(&rhs).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        cs.num_constraints() - num_constraints
+    };
+    let layout = gen_equality_params::<TargetField, BaseField>(None)
+        .unwrap()
+        .equality_layout;
+    assert!(measure(layout) < measure(None));
+
+    // two operands in the normal form are still compared limb by limb
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+    set_equality_params::<TargetField, BaseField>(&cs, layout.unwrap()).unwrap();
+    let value = TargetField::rand(&mut rng);
+    let inputs: Vec<_> = (0..2)
+        .map(|_| {
+            NonNativeFieldVar::<TargetField, BaseField>::new_input(
+                ark_relations::ns!(cs, "input"),
+                || Ok(value),
+            )
+            .unwrap()
+        })
+        .collect();
+    let num_constraints = cs.num_constraints();
+    inputs[0].enforce_equal(&inputs[1]).unwrap();
+    assert_eq!(
+        cs.num_constraints() - num_constraints,
+        get_params::<TargetField, BaseField>(&cs).unwrap().num_limbs
+    );
+    assert!(cs.is_satisfied().unwrap());
+
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+    let layout = EqualityLayout {
+        num_limbs: 5,
+        bits_per_top_limb: 42,
+        bits_per_non_top_limb: 64,
+    };
+    assert!(get_equality_params::<TargetField, BaseField>(&cs).is_none());
+    set_equality_params::<TargetField, BaseField>(&cs, layout).unwrap();
+    assert_eq!(
+        get_equality_params::<TargetField, BaseField>(&cs),
+        Some(layout)
+    );
+    assert_eq!(
+        get_params::<TargetField, BaseField>(&cs)
+            .unwrap()
+            .equality_layout,
+        Some(layout)
+    );
+    assert!(set_equality_params::<TargetField, BaseField>(
+        &cs,
+        EqualityLayout {
+            num_limbs: 4,
+            bits_per_top_limb: 42,
+            bits_per_non_top_limb: 64,
+        }
+    )
+    .is_err());
+
+    let a_native = TargetField::rand(&mut rng);
+    let b_native = TargetField::rand(&mut rng);
+    let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "a"),
+        || Ok(a_native),
+    )
+    .unwrap();
+    let b = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "b"),
+        || Ok(b_native),
+    )
+    .unwrap();
+
+    // sides with a surfeit, and a subtraction, which checks an equality itself
+    let lhs = &(&a + &b) + &a;
+    let rhs = &(&a.double().unwrap() + &b) - &b + &b;
+    lhs.enforce_equal(&rhs).unwrap();
+    let product = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "product"),
+        || Ok(a_native * &b_native),
+    )
+    .unwrap();
+    (&a * &b).enforce_equal(&product).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+
+    let other_cs = ConstraintSystem::<BaseField>::new_ref();
+    set_equality_params::<TargetField, BaseField>(&other_cs, layout).unwrap();
+    let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(other_cs, "a"),
+        || Ok(a_native),
+    )
+    .unwrap();
+    let b = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(other_cs, "b"),
+        || Ok(a_native + TargetField::from(1u64)),
+    )
+    .unwrap();
+    (&a + &a).enforce_equal(&(&b + &a)).unwrap();
+    assert!(!other_cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn equality_layout_soundness_test() {
+    use ark_ff::{BigInteger, FpParameters};
+    use ark_nonnative_field::{
+        layout::enforce_congruent_with_wrapping_carries,
+        params::{get_params, set_equality_params},
+        range_check, AllocatedNonNativeFieldVar, EqualityLayout, NonNativeFieldParams,
+    };
+    use ark_r1cs_std::{boolean::Boolean, fields::fp::AllocatedFp};
+    use num_bigint::BigUint;
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let equality_layout = EqualityLayout {
+        num_limbs: 5,
+        bits_per_top_limb: 42,
+        bits_per_non_top_limb: 64,
+    };
+    let layout = NonNativeFieldParams::from(equality_layout);
+    let base_modulus =
+        BigUint::from_bytes_le(&<BaseField as PrimeField>::Params::MODULUS.to_bytes_le());
+    // `b = 1 + q mod p` for the `BaseField` modulus `q` differs from `a = 1`, but the integer
+    // `1 + q` is congruent to both of them modulo `q`
+    let one_plus_q = &base_modulus + 1u64;
+    let a_native = TargetField::from(1u64);
+    let b_native = TargetField::from_le_bytes_mod_order(&one_plus_q.to_bytes_le());
+    assert_ne!(a_native, b_native);
+
+    // an honest prover cannot make unequal elements equal in the equality layout
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+    set_equality_params::<TargetField, BaseField>(&cs, equality_layout).unwrap();
+    let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "a"),
+        || Ok(a_native),
+    )
+    .unwrap();
+    let b = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "b"),
+        || Ok(b_native),
+    )
+    .unwrap();
+    let should_enforce =
+        Boolean::new_witness(ark_relations::ns!(cs, "should enforce"), || Ok(true)).unwrap();
+    a.conditional_enforce_equal(&b, &should_enforce).unwrap();
+    assert!(!cs.is_satisfied().unwrap());
+
+    // nor can a dishonest one, who takes the limbs of `1 + q` as the equality limbs of both
+    // elements, which are congruent to `b` and only wrap around `q` for `a`
+    for wrap in [false, true].iter() {
+        let cs = ConstraintSystem::<BaseField>::new_ref();
+        set_equality_params::<TargetField, BaseField>(&cs, equality_layout).unwrap();
+        let params = get_params::<TargetField, BaseField>(&cs).unwrap();
+        let limbs: Vec<_> = (0..layout.num_limbs)
+            .map(|i| {
+                let num_bits = if i == 0 {
+                    layout.bits_per_top_limb
+                } else {
+                    layout.bits_per_non_top_limb
+                };
+                let position = (layout.num_limbs - 1 - i) * layout.bits_per_non_top_limb;
+                let limb_value =
+                    (&one_plus_q >> position) & ((BigUint::from(1u64) << num_bits) - 1u64);
+                let limb = AllocatedFp::<BaseField>::new_witness(
+                    ark_relations::ns!(cs, "equality limb"),
+                    || {
+                        Ok(BaseField::from_le_bytes_mod_order(
+                            &limb_value.to_bytes_le(),
+                        ))
+                    },
+                )
+                .unwrap();
+                range_check::enforce_range(&limb, num_bits).unwrap();
+                limb
+            })
+            .collect();
+        let (element, claimed_value) = if *wrap {
+            (a_native, BigUint::from(1u64))
+        } else {
+            (b_native, one_plus_q.clone())
+        };
+        let element = AllocatedNonNativeFieldVar::<TargetField, BaseField>::new_witness(
+            ark_relations::ns!(cs, "element"),
+            || Ok(element),
+        )
+        .unwrap();
+        enforce_congruent_with_wrapping_carries::<TargetField, BaseField>(
+            &cs,
+            &element.limbs,
+            &params,
+            &limbs,
+            &layout,
+            &claimed_value,
+        )
+        .unwrap();
+        assert_eq!(cs.is_satisfied().unwrap(), !*wrap);
+    }
+}
+
+#[test]
+fn reduction_frequency_cost_test() {
+    use ark_nonnative_field::params::{ParamsSearching, WorkloadProfile};
+
+    let mut problem = ParamsSearching::new(298, 298);
+    problem.solve();
+    let predict = |additions: usize| {
+        problem
+            .predicted_constraints(&WorkloadProfile {
+                additions,
+                multiplications: 1,
+                equalities: 0,
+            })
+            .unwrap()
+    };
+
+    // a few additions fit into the headroom of a product, while a long accumulation forces the
+    // reduction of an operand before the multiplication
+    assert_eq!(predict(0), predict(1));
+    assert!(predict(1) <= predict(1 << 10));
+    assert!(predict(1 << 10) < predict(1 << 62));
+
+    let mut previous = 0;
+    for log_additions in 0..63 {
+        let cost = predict(1 << log_additions);
+        assert!(cost >= previous);
+        previous = cost;
+    }
+
+    // in a small base field, the sums reduce all their limbs every few additions
+    let mut problem = ParamsSearching::new(61, 255);
+    problem.solve();
+    let predict = |additions: usize| {
+        problem
+            .predicted_constraints(&WorkloadProfile {
+                additions,
+                multiplications: 1,
+                equalities: 0,
+            })
+            .unwrap()
+    };
+    assert!(predict(1 << 44) > 1000 * predict(1 << 10));
+}
+
+#[test]
+fn reduction_cost_layout_test() {
+    use ark_ff::UniformRand;
+    use ark_nonnative_field::{
+        params::{gen_params, set_params, ParamsSearching},
+        NonNativeFieldParams,
+    };
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    // the layout with the fewest additions after a multiplication needs a reduction before a
+    // product of products, which a slightly wider top limb avoids
+    let fewest_additions = NonNativeFieldParams {
+        num_limbs: 3,
+        bits_per_top_limb: 84,
+        bits_per_non_top_limb: 107,
+        equality_layout: None,
+    };
+    let chosen = gen_params::<TargetField, BaseField>().unwrap();
+    assert_eq!(chosen.num_limbs, fewest_additions.num_limbs);
+    assert_ne!(chosen, fewest_additions);
+
+    let mut problem = ParamsSearching::new(298, 298);
+    problem.solve_with_num_of_limbs(3).unwrap();
+    let mut other = problem.clone();
+    other.top_limb_size = Some(fewest_additions.bits_per_top_limb);
+    other.non_top_limb_size = Some(fewest_additions.bits_per_non_top_limb);
+    assert!(
+        problem.predicted_constraints(&problem.workload).unwrap()
+            < other.predicted_constraints(&problem.workload).unwrap()
+    );
+
+    let measure = |params: NonNativeFieldParams| {
+        let mut rng = ark_ff::test_rng();
+        let cs = ConstraintSystem::<BaseField>::new_ref();
+        set_params::<TargetField, BaseField>(&cs, params).unwrap();
+        let factors: Vec<_> = (0..4)
+            .map(|_| {
+                NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+                    ark_relations::ns!(cs, "factor"),
+                    || Ok(TargetField::rand(&mut rng)),
+                )
+                .unwrap()
+            })
+            .collect();
+        let left = &factors[0] * &factors[1];
+        let right = &factors[2] * &factors[3];
+
+        let num_constraints = cs.num_constraints();
+        let product = &left * &right;
+        assert_eq!(
+            product.value().unwrap(),
+            left.value().unwrap() * &right.value().unwrap()
+        );
+        assert!(cs.is_satisfied().unwrap());
+        cs.num_constraints() - num_constraints
+    };
+    assert!(measure(chosen) < measure(fewest_additions));
+}
+
+#[test]
+fn cost_prediction_check_test() {
+    use ark_ff::UniformRand;
+    use ark_nonnative_field::report::{ConstraintReport, Operation};
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+    let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "a"),
+        || Ok(TargetField::rand(rng)),
+    )
+    .unwrap();
+    let b = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "b"),
+        || Ok(TargetField::rand(rng)),
+    )
+    .unwrap();
+
+    let (_, report) = ConstraintReport::measure(&cs, || {
+        let mut c = a.clone();
+        for _ in 0..4 {
+            c = &c * &b;
+        }
+        Ok(c)
+    })
+    .unwrap();
+    assert_eq!(report.multiplications.num_calls, 4);
+
+    // the products with a witness have fewer additions than the products of products that the
+    // model assumes, which only shrinks their reductions slightly
+    assert!(report
+        .check_prediction::<TargetField, BaseField>(&cs, 25)
+        .unwrap()
+        .is_empty());
+
+    // a regression of the gadgets is reported for the operations that regressed
+    let mut regressed = report.clone();
+    regressed.reductions.num_constraints += report.reductions.num_constraints / 2;
+    let discrepancies = regressed
+        .check_prediction::<TargetField, BaseField>(&cs, 25)
+        .unwrap();
+    assert_eq!(discrepancies.len(), 1);
+    let discrepancy = &discrepancies[0];
+    assert_eq!(discrepancy.operation, Operation::Reduction);
+    assert_eq!(discrepancy.workload.multiplications, 4);
+    assert_eq!(discrepancy.workload.equalities, 0);
+    assert_eq!(discrepancy.actual, regressed.reductions.num_constraints);
+    assert!(discrepancy.actual > discrepancy.predicted);
+
+    assert!(cs.is_satisfied().unwrap());
+
+    // products of products and an equality of two of them, with the first products outside of
+    // the measurement, match the model operation by operation
+    let factors: Vec<_> = (0..4)
+        .map(|_| {
+            NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+                ark_relations::ns!(cs, "factor"),
+                || Ok(TargetField::rand(rng)),
+            )
+            .unwrap()
+        })
+        .collect();
+    let left = &factors[0] * &factors[1];
+    let right = &factors[2] * &factors[3];
+    let (_, report) = ConstraintReport::measure(&cs, || {
+        let lhs = &left * &right;
+        let rhs = &right * &left;
+        lhs.enforce_equal(&rhs)
+    })
+    .unwrap();
+    assert_eq!(report.multiplications.num_calls, 2);
+    assert_eq!(report.equalities.num_calls, 1);
+    assert!(report
+        .check_prediction::<TargetField, BaseField>(&cs, 25)
+        .unwrap()
+        .is_empty());
+    assert!(cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn binary_field_test() {
+    use ark_nonnative_field::binary::{BinaryFieldVar, GF2_128};
+
+    type BaseField = <Bls12_381 as PairingEngine>::Fr;
+
+    fn to_bits(value: u128) -> Vec<bool> {
+        (0..128).map(|i| (value >> i) & 1 == 1).collect()
+    }
+
+    // the product modulo `x^128 + x^7 + x^2 + x + 1`, with the coefficient of `x^i` in bit `i`
+    fn mul_native(a: u128, b: u128) -> u128 {
+        let (mut low, mut high) = (0u128, 0u128);
+        for i in 0..128 {
+            if (b >> i) & 1 == 1 {
+                low ^= a << i;
+                if i > 0 {
+                    high ^= a >> (128 - i);
+                }
+            }
+        }
+        // fold the coefficients of `x^128` and above, twice as the taps reach up to `x^7`
+        for _ in 0..2 {
+            let fold = high;
+            high = 0;
+            for tap in [7u32, 2, 1, 0].iter() {
+                low ^= fold << tap;
+                if *tap > 0 {
+                    high ^= fold >> (128 - tap);
+                }
+            }
+        }
+        low
+    }
+
+    let mut rng = ark_ff::test_rng();
+    let mut gen = || (u128::from(rng.next_u64()) << 64) | u128::from(rng.next_u64());
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+    for _ in 0..3 {
+        let a_native = gen();
+        let b_native = gen();
+        let a = BinaryFieldVar::new_witness(ark_relations::ns!(cs, "a"), GF2_128, || {
+            Ok(to_bits(a_native))
+        })
+        .unwrap();
+        let b = BinaryFieldVar::new_witness(ark_relations::ns!(cs, "b"), GF2_128, || {
+            Ok(to_bits(b_native))
+        })
+        .unwrap();
+
+        assert_eq!(
+            a.add(&b).unwrap().value().unwrap(),
+            to_bits(a_native ^ b_native)
+        );
+        let product = a.mul(&b).unwrap();
+        assert_eq!(
+            product.value().unwrap(),
+            to_bits(mul_native(a_native, b_native))
+        );
+        let constant = BinaryFieldVar::constant(GF2_128, &to_bits(b_native)).unwrap();
+        assert_eq!(
+            a.mul(&constant).unwrap().value().unwrap(),
+            product.value().unwrap()
+        );
+
+        if a_native != 0 {
+            let inverse = a.inverse().unwrap();
+            assert_eq!(a.mul(&inverse).unwrap().value().unwrap(), to_bits(1));
+        }
+    }
+    assert!(cs.is_satisfied().unwrap());
+
+    // `x · x^127 = x^128 = x^7 + x^2 + x + 1`
+    assert_eq!(mul_native(2, 1 << 127), 0x87);
+
+    // a wrong product is rejected
+    let other_cs = ConstraintSystem::<BaseField>::new_ref();
+    let a = BinaryFieldVar::new_witness(ark_relations::ns!(other_cs, "a"), GF2_128, || {
+        Ok(to_bits(3))
+    })
+    .unwrap();
+    let wrong = BinaryFieldVar::new_witness(ark_relations::ns!(other_cs, "wrong"), GF2_128, || {
+        Ok(to_bits(6))
+    })
+    .unwrap();
+    a.square().unwrap().enforce_equal(&wrong).unwrap();
+    assert!(!other_cs.is_satisfied().unwrap());
+}
+
+#[cfg(feature = "bn254")]
+#[test]
+fn pseudo_mersenne_reduction_test() {
+    use ark_ff::UniformRand;
+    use ark_nonnative_field::curves::{bn254::Fr as BaseField, curve25519::Fq25519};
+
+    fn mul_cost<TargetField: PrimeField>(a_native: TargetField, b_native: TargetField) -> usize {
+        let cs = ConstraintSystem::<BaseField>::new_ref();
+        let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+            ark_relations::ns!(cs, "a"),
+            || Ok(a_native),
+        )
+        .unwrap();
+        let b = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+            ark_relations::ns!(cs, "b"),
+            || Ok(b_native),
+        )
+        .unwrap();
+
+        let num_constraints_before = cs.num_constraints();
+        let c = &a * &b;
+        let cost = cs.num_constraints() - num_constraints_before;
+
+        assert_eq!(c.value().unwrap(), a_native * &b_native);
+        c.enforce_equal(&NonNativeFieldVar::constant(a_native * &b_native))
+            .unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        cost
+    }
+
+    let mut rng = ark_ff::test_rng();
+    let minus_one = -Fq25519::from(1u64);
+    let mut cost = 0;
+    for (a, b) in [
+        (Fq25519::rand(&mut rng), Fq25519::rand(&mut rng)),
+        (minus_one, minus_one),
+        (minus_one, Fq25519::from(19u64)),
+    ]
+    .iter()
+    {
+        cost = mul_cost(*a, *b);
+    }
+
+    // `2^255 - 19` folds the high limbs of the product, while the scalar field of BLS12-381,
+    // of the same size and with the same parameters, goes through the generic reduction
+    type OtherField = <Bls12_381 as PairingEngine>::Fr;
+    let other_cost = mul_cost(OtherField::rand(&mut rng), OtherField::rand(&mut rng));
+    assert!(cost < other_cost);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn tune_test() {
+    use ark_nonnative_field::{
+        params::{gen_params, set_params, WorkloadProfile},
+        tune::{candidates, measure, tune, TuneConfig, TuneObjective},
+    };
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let config = TuneConfig {
+        workload: WorkloadProfile {
+            additions: 4,
+            multiplications: 2,
+            equalities: 1,
+        },
+        max_num_limbs: 4,
+        objective: TuneObjective::Constraints,
+    };
+    let all_candidates = candidates::<TargetField, BaseField>(config.max_num_limbs);
+    let searched = gen_params::<TargetField, BaseField>().unwrap();
+    assert!(all_candidates.iter().any(|params| {
+        params.num_limbs == searched.num_limbs
+            && params.bits_per_non_top_limb == searched.bits_per_non_top_limb
+    }));
+
+    let (best, measurements) = tune::<TargetField, BaseField>(&config).unwrap();
+    assert!(!measurements.is_empty());
+    assert!(measurements.len() <= all_candidates.len());
+    let best_cost = measurements
+        .iter()
+        .map(|measurement| measurement.num_constraints)
+        .min()
+        .unwrap();
+    let best_measurement = measure::<TargetField, BaseField>(&best, &config.workload).unwrap();
+    assert_eq!(best_measurement.num_constraints, best_cost);
+
+    // the tuned parameters are never worse than the searched ones on the microbenchmark
+    let searched_measurement =
+        measure::<TargetField, BaseField>(&searched, &config.workload).unwrap();
+    assert!(best_measurement.num_constraints <= searched_measurement.num_constraints);
+
+    // every constraint has a nonzero entry in one of the matrices
+    for measurement in measurements.iter() {
+        assert!(measurement.density() >= measurement.num_constraints);
+        assert!(measurement.c_num_non_zero > 0);
+    }
+    let (sparsest, density_measurements) = tune::<TargetField, BaseField>(&TuneConfig {
+        objective: TuneObjective::Density,
+        ..config.clone()
+    })
+    .unwrap();
+    let least_density = density_measurements
+        .iter()
+        .map(|measurement| measurement.density())
+        .min()
+        .unwrap();
+    assert_eq!(
+        measure::<TargetField, BaseField>(&sparsest, &config.workload)
+            .unwrap()
+            .density(),
+        least_density
+    );
+
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+    set_params::<TargetField, BaseField>(&cs, best).unwrap();
+}
+
+#[test]
+fn wnaf_scalar_mul_test() {
+    use ark_ec::{AffineCurve, ProjectiveCurve};
+    use ark_ff::{BigInteger, One, Zero};
+    use ark_nonnative_field::{
+        curves::short_weierstrass::NonNativeAffineVar, error::NonNativeFieldError, wnaf::WnafVar,
+    };
+    use ark_r1cs_std::boolean::Boolean;
+    use num_bigint::{BigInt, BigUint};
+
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+    type P = ark_bls12_381::g1::Parameters;
+
+    let rng = &mut ark_ff::test_rng();
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+
+    let scalar = rng.next_u32();
+    let bits: Vec<Boolean<BaseField>> = (0..32)
+        .map(|i| {
+            Boolean::new_witness(ark_relations::ns!(cs, "bit"), || Ok((scalar >> i) & 1 == 1))
+                .unwrap()
+        })
+        .collect();
+
+    let window = 4;
+    let wnaf = WnafVar::from_bits_le(&bits, window).unwrap();
+    let digits = wnaf.value().unwrap();
+    assert_eq!(digits.len(), 33);
+    assert_eq!(
+        digits
+            .iter()
+            .rev()
+            .fold(0i64, |acc, digit| 2 * acc + *digit),
+        scalar as i64
+    );
+    for (i, digit) in digits.iter().enumerate() {
+        assert!(*digit == 0 || (digit % 2 != 0 && digit.abs() < 1 << (window - 1)));
+        if *digit != 0 {
+            assert!(digits.iter().skip(i + 1).take(window - 1).all(|d| *d == 0));
+        }
+    }
+
+    let g = ark_bls12_381::G1Affine::prime_subgroup_generator();
+    let point =
+        NonNativeAffineVar::<P, BaseField>::new_witness(ark_relations::ns!(cs, "g"), || Ok(g))
+            .unwrap();
+    let res = point.scalar_mul_wnaf(&wnaf).unwrap();
+    let expected = g.mul(ark_bls12_381::Fr::from(scalar as u64)).into_affine();
+    assert_eq!(res.value().unwrap(), expected);
+    assert!(cs.is_satisfied().unwrap());
+
+    // the recoding of a simulated scalar, whose sum is checked against its canonical bits
+    let scalar_native = ark_bls12_381::Fr::from(scalar as u64);
+    let scalar_var = NonNativeFieldVar::<ark_bls12_381::Fr, BaseField>::new_witness(
+        ark_relations::ns!(cs, "scalar"),
+        || Ok(scalar_native),
+    )
+    .unwrap();
+    let wnaf = WnafVar::from_nonnative(&scalar_var, 5).unwrap();
+    let digits = wnaf.value().unwrap();
+    assert_eq!(
+        digits
+            .iter()
+            .take(33)
+            .rev()
+            .fold(0i64, |acc, digit| 2 * acc + *digit),
+        scalar as i64
+    );
+    assert!(digits.iter().skip(33).all(|digit| *digit == 0));
+    assert!(cs.is_satisfied().unwrap());
+
+    // the form of 255 is 2^8 - 1, with one digit more than the bits
+    let other_cs = ConstraintSystem::<BaseField>::new_ref();
+    let bits: Vec<Boolean<BaseField>> = (0..8)
+        .map(|i| {
+            Boolean::new_witness(ark_relations::ns!(other_cs, "bit"), || {
+                Ok((0xffu32 >> i) & 1 == 1)
+            })
+            .unwrap()
+        })
+        .collect();
+    let wnaf = WnafVar::from_bits_le(&bits, 3).unwrap();
+    let digits = wnaf.value().unwrap();
+    assert_eq!(digits, vec![-1, 0, 0, 0, 0, 0, 0, 0, 1]);
+    assert!(other_cs.is_satisfied().unwrap());
+    assert!(matches!(
+        WnafVar::from_bits_le(&bits, 300),
+        Err(NonNativeFieldError::InvalidWindow { window: 300, .. })
+    ));
+
+    // a full-width ECDSA scalar, wider than the capacity of the native field, is recoded from
+    // its canonical bits with the sum checked in chunks
+    type Scalar = ark_nonnative_field::curves::p256::Fr;
+    type NativeField = <Bls12_381 as PairingEngine>::Fr;
+    let scalar_cs = ConstraintSystem::<NativeField>::new_ref();
+    let scalar_native = -Scalar::one();
+    let scalar_var = NonNativeFieldVar::<Scalar, NativeField>::new_witness(
+        ark_relations::ns!(scalar_cs, "scalar"),
+        || Ok(scalar_native),
+    )
+    .unwrap();
+    let wnaf = WnafVar::from_nonnative(&scalar_var, 4).unwrap();
+    let digits = wnaf.value().unwrap();
+    assert_eq!(digits.len(), Scalar::size_in_bits() + 1);
+    let sum = digits.iter().rev().fold(BigInt::zero(), |acc, digit| {
+        (acc << 1usize) + BigInt::from(*digit)
+    });
+    assert_eq!(
+        sum,
+        BigInt::from(BigUint::from_bytes_le(
+            &scalar_native.into_repr().to_bytes_le()
+        ))
+    );
+    assert!(scalar_cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn double_scalar_mul_test() {
+    use ark_ec::{AffineCurve, ProjectiveCurve};
+    use ark_nonnative_field::curves::short_weierstrass::NonNativeAffineVar;
+    use ark_r1cs_std::boolean::Boolean;
+
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+    type P = ark_bls12_381::g1::Parameters;
+
+    let rng = &mut ark_ff::test_rng();
+    let g = ark_bls12_381::G1Affine::prime_subgroup_generator();
+    let h = g.mul(ark_bls12_381::Fr::from(rng.next_u64())).into_affine();
+    let a = rng.next_u32();
+    let b = rng.next_u32() >> 11;
+    let expected = (g.mul(ark_bls12_381::Fr::from(a as u64))
+        + h.mul(ark_bls12_381::Fr::from(b as u64)))
+    .into_affine();
+
+    let mut num_constraints = Vec::new();
+    for shamir in [true, false].iter() {
+        let cs = ConstraintSystem::<BaseField>::new_ref();
+        let g_var =
+            NonNativeAffineVar::<P, BaseField>::new_witness(ark_relations::ns!(cs, "g"), || Ok(g))
+                .unwrap();
+        let h_var =
+            NonNativeAffineVar::<P, BaseField>::new_witness(ark_relations::ns!(cs, "h"), || Ok(h))
+                .unwrap();
+        // the scalars have different numbers of bits, and b an odd one
+        let a_bits: Vec<Boolean<BaseField>> = (0..32)
+            .map(|i| {
+                Boolean::new_witness(ark_relations::ns!(cs, "a"), || Ok((a >> i) & 1 == 1)).unwrap()
+            })
+            .collect();
+        let b_bits: Vec<Boolean<BaseField>> = (0..21)
+            .map(|i| {
+                Boolean::new_witness(ark_relations::ns!(cs, "b"), || Ok((b >> i) & 1 == 1)).unwrap()
+            })
+            .collect();
+
+        let res = if *shamir {
+            g_var.double_scalar_mul(&a_bits, &h_var, &b_bits).unwrap()
+        } else {
+            g_var.dual_scalar_mul(&a_bits, &h_var, &b_bits).unwrap()
+        };
+        assert_eq!(res.value().unwrap(), expected);
+        assert!(cs.is_satisfied().unwrap());
+        num_constraints.push(cs.num_constraints());
+    }
+
+    // the shared doublings make the interleaved windows cheaper than two separate ladders
+    assert!(num_constraints[0] < num_constraints[1]);
+}
+
+#[test]
+fn incomplete_addition_flags_test() {
+    use ark_ec::{AffineCurve, ProjectiveCurve};
+    use ark_nonnative_field::curves::short_weierstrass::NonNativeAffineVar;
+
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+    type P = ark_bls12_381::g1::Parameters;
+
+    let rng = &mut ark_ff::test_rng();
+    let g = ark_bls12_381::G1Affine::prime_subgroup_generator();
+    let h = g.mul(ark_bls12_381::Fr::from(rng.next_u64())).into_affine();
+
+    for (other, equal_inputs, inverse_inputs) in
+        [(h, false, false), (g, true, false), (-g, false, true)].iter()
+    {
+        let cs = ConstraintSystem::<BaseField>::new_ref();
+        let g_var =
+            NonNativeAffineVar::<P, BaseField>::new_witness(ark_relations::ns!(cs, "g"), || Ok(g))
+                .unwrap();
+        let other_var = NonNativeAffineVar::<P, BaseField>::new_witness(
+            ark_relations::ns!(cs, "other"),
+            || Ok(*other),
+        )
+        .unwrap();
+
+        let res = g_var.add_incomplete(&other_var).unwrap();
+        assert_eq!(res.equal_inputs.value().unwrap(), *equal_inputs);
+        assert_eq!(res.inverse_inputs.value().unwrap(), *inverse_inputs);
+        if !*equal_inputs && !*inverse_inputs {
+            assert_eq!(res.sum.value().unwrap(), g + *other);
+        }
+        // the exceptional cases are left to the caller instead of making the system unsatisfied
+        assert!(cs.is_satisfied().unwrap());
+    }
+}
+
+#[test]
+fn complete_addition_test() {
+    use ark_ec::{AffineCurve, ProjectiveCurve};
+    use ark_ff::Zero;
+    use ark_nonnative_field::curves::short_weierstrass::{
+        NonNativeAffineVar, NonNativeProjectiveVar,
+    };
+    use ark_r1cs_std::boolean::Boolean;
+
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+    type P = ark_bls12_381::g1::Parameters;
+
+    let rng = &mut ark_ff::test_rng();
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+
+    let g = ark_bls12_381::G1Affine::prime_subgroup_generator();
+    let h = g.mul(ark_bls12_381::Fr::from(rng.next_u64())).into_affine();
+    let g_var = NonNativeProjectiveVar::from_affine(
+        &NonNativeAffineVar::<P, BaseField>::new_witness(ark_relations::ns!(cs, "g"), || Ok(g))
+            .unwrap(),
+    );
+    let h_var = NonNativeProjectiveVar::from_affine(
+        &NonNativeAffineVar::<P, BaseField>::new_witness(ark_relations::ns!(cs, "h"), || Ok(h))
+            .unwrap(),
+    );
+    let zero = NonNativeProjectiveVar::<P, BaseField>::zero();
+
+    // the exceptional cases of the affine formulas are all handled
+    assert_eq!(g_var.add(&h_var).unwrap().value().unwrap(), g + h);
+    assert_eq!(
+        g_var.add(&g_var).unwrap().value().unwrap(),
+        g.into_projective().double().into_affine()
+    );
+    let infinity = g_var.add(&g_var.negate().unwrap()).unwrap();
+    assert!(infinity.value().unwrap().is_zero());
+    assert!(infinity.is_zero().unwrap().value().unwrap());
+    assert_eq!(zero.add(&g_var).unwrap().value().unwrap(), g);
+    assert_eq!(g_var.add(&zero).unwrap().value().unwrap(), g);
+    assert!(zero.add(&zero).unwrap().value().unwrap().is_zero());
+    assert_eq!(g_var.to_affine().unwrap().value().unwrap(), g);
+
+    for scalar in [0u8, 1, rng.next_u32() as u8].iter() {
+        let bits: Vec<Boolean<BaseField>> = (0..8)
+            .map(|i| {
+                Boolean::new_witness(ark_relations::ns!(cs, "bit"), || Ok((scalar >> i) & 1 == 1))
+                    .unwrap()
+            })
+            .collect();
+        let res = g_var.scalar_mul_le(&bits).unwrap();
+        let expected = g.mul(ark_bls12_381::Fr::from(*scalar as u64)).into_affine();
+        assert_eq!(res.value().unwrap(), expected);
+    }
+    assert!(cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn multi_scalar_mul_test() {
+    use ark_ec::{AffineCurve, ProjectiveCurve};
+    use ark_ff::Zero;
+    use ark_nonnative_field::curves::short_weierstrass::{
+        NonNativeAffineVar, NonNativeProjectiveVar,
+    };
+    use ark_r1cs_std::boolean::Boolean;
+
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+    type P = ark_bls12_381::g1::Parameters;
+
+    let rng = &mut ark_ff::test_rng();
+    let g = ark_bls12_381::G1Affine::prime_subgroup_generator();
+    // six bases split into a full chunk and a partial one, with a zero scalar and a shorter one
+    let points: Vec<_> = (0..6)
+        .map(|_| g.mul(ark_bls12_381::Fr::from(rng.next_u64())).into_affine())
+        .collect();
+    let scalars = [0u8, 1, rng.next_u32() as u8, 255, rng.next_u32() as u8, 5];
+    let lengths = [8usize, 8, 8, 8, 8, 3];
+
+    let alloc = |cs: &ConstraintSystemRef<BaseField>| {
+        let bases: Vec<_> = points
+            .iter()
+            .map(|point| {
+                NonNativeProjectiveVar::from_affine(
+                    &NonNativeAffineVar::<P, BaseField>::new_witness(
+                        ark_relations::ns!(cs, "base"),
+                        || Ok(*point),
+                    )
+                    .unwrap(),
+                )
+            })
+            .collect();
+        let bits: Vec<Vec<Boolean<BaseField>>> = scalars
+            .iter()
+            .zip(lengths.iter())
+            .map(|(scalar, length)| {
+                (0..*length)
+                    .map(|i| {
+                        Boolean::new_witness(ark_relations::ns!(cs, "bit"), || {
+                            Ok((scalar >> i) & 1 == 1)
+                        })
+                        .unwrap()
+                    })
+                    .collect()
+            })
+            .collect();
+        (bases, bits)
+    };
+
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+    let (bases, bits) = alloc(&cs);
+    let before = cs.num_constraints();
+    let res = NonNativeProjectiveVar::multi_scalar_mul_le(&bases, &bits).unwrap();
+    let shamir_constraints = cs.num_constraints() - before;
+
+    let mut expected = ark_bls12_381::G1Projective::zero();
+    for ((point, scalar), length) in points.iter().zip(scalars.iter()).zip(lengths.iter()) {
+        let scalar = (*scalar as u64) & ((1u64 << length) - 1);
+        expected += &point.mul(ark_bls12_381::Fr::from(scalar));
+    }
+    assert_eq!(res.value().unwrap(), expected.into_affine());
+    assert!(cs.is_satisfied().unwrap());
+
+    // one addition per base per bit
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+    let (bases, bits) = alloc(&cs);
+    let before = cs.num_constraints();
+    let mut naive = NonNativeProjectiveVar::<P, BaseField>::zero();
+    for i in (0..8).rev() {
+        naive = naive.double().unwrap();
+        for (base, bits) in bases.iter().zip(bits.iter()) {
+            if let Some(bit) = bits.get(i) {
+                let sum = naive.add(base).unwrap();
+                naive = bit.select(&sum, &naive).unwrap();
             }
         }
+    }
+    let naive_constraints = cs.num_constraints() - before;
+    assert_eq!(naive.value().unwrap(), expected.into_affine());
+    assert!(shamir_constraints < naive_constraints);
+}
+
+#[test]
+fn sec1_compressed_encoding_test() {
+    use ark_ec::short_weierstrass_jacobian::GroupAffine;
+    use ark_ff::{BigInteger, BitIteratorBE};
+    use ark_nonnative_field::{
+        curves::{
+            p256::{Fq, P256Parameters, PARAMS_OVER_MNT_298},
+            short_weierstrass::NonNativeAffineVar,
+        },
+        error::NonNativeFieldError,
+        params::set_params,
     };
+    use ark_r1cs_std::uint8::UInt8;
+    use std::str::FromStr;
+
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let point = GroupAffine::<P256Parameters>::new(
+        Fq::from_str(
+            "89597842475883119309267159982467775324206547854755065074295300459217223600783",
+        )
+        .unwrap(),
+        Fq::from_str(
+            "81914003552677634664438003469761717571825734889263331194638006564787885899298",
+        )
+        .unwrap(),
+        false,
+    );
+    let mut x_bits: Vec<bool> = BitIteratorBE::new(point.x.into_repr()).collect();
+    x_bits.drain(..x_bits.len() - 256);
+    let mut expected = vec![if point.y.into_repr().is_odd() {
+        3u8
+    } else {
+        2u8
+    }];
+    expected.extend(
+        x_bits
+            .chunks(8)
+            .map(|byte| byte.iter().fold(0u8, |acc, bit| (acc << 1) | *bit as u8)),
+    );
+    assert_eq!(expected.len(), 33);
+
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+    set_params::<Fq, BaseField>(&cs, PARAMS_OVER_MNT_298).unwrap();
+    let point_var = NonNativeAffineVar::<P256Parameters, BaseField>::new_witness(
+        ark_relations::ns!(cs, "point"),
+        || Ok(point),
+    )
+    .unwrap();
+    let bytes = point_var.to_sec1_compressed().unwrap();
+    assert_eq!(bytes.value().unwrap(), expected);
+
+    let bytes_var = UInt8::new_witness_vec(ark_relations::ns!(cs, "bytes"), &expected).unwrap();
+    let decoded =
+        NonNativeAffineVar::<P256Parameters, BaseField>::from_sec1_compressed(&bytes_var).unwrap();
+    assert_eq!(decoded.value().unwrap(), point);
+
+    // the other prefix selects the other square root
+    let mut negated = expected.clone();
+    negated[0] ^= 1;
+    let bytes_var = UInt8::new_witness_vec(ark_relations::ns!(cs, "bytes"), &negated).unwrap();
+    let decoded =
+        NonNativeAffineVar::<P256Parameters, BaseField>::from_sec1_compressed(&bytes_var).unwrap();
+    assert_eq!(decoded.value().unwrap(), -point);
+    assert!(cs.is_satisfied().unwrap());
+
+    // an uncompressed prefix is rejected
+    let other_cs = ConstraintSystem::<BaseField>::new_ref();
+    set_params::<Fq, BaseField>(&other_cs, PARAMS_OVER_MNT_298).unwrap();
+    let mut uncompressed = expected.clone();
+    uncompressed[0] = 4;
+    let bytes_var =
+        UInt8::new_witness_vec(ark_relations::ns!(other_cs, "bytes"), &uncompressed).unwrap();
+    NonNativeAffineVar::<P256Parameters, BaseField>::from_sec1_compressed(&bytes_var).unwrap();
+    assert!(!other_cs.is_satisfied().unwrap());
+    assert!(matches!(
+        NonNativeAffineVar::<P256Parameters, BaseField>::from_sec1_compressed(&bytes_var[1..]),
+        Err(NonNativeFieldError::LengthMismatch {
+            expected: 33,
+            found: 32
+        })
+    ));
 }
 
-macro_rules! nonnative_test {
-    ($test_name:ident, $test_target_field:ty, $test_base_field:ty) => {
-        nonnative_test_individual!(
-            allocation_test,
-            $test_name,
-            $test_target_field,
-            $test_base_field
-        );
-        nonnative_test_individual!(
-            addition_test,
-            $test_name,
-            $test_target_field,
-            $test_base_field
-        );
-        nonnative_test_individual!(
-            multiplication_test,
-            $test_name,
-            $test_target_field,
-            $test_base_field
-        );
-        nonnative_test_individual!(
-            equality_test,
-            $test_name,
-            $test_target_field,
-            $test_base_field
-        );
-        nonnative_test_individual!(
-            edge_cases_test,
-            $test_name,
-            $test_target_field,
-            $test_base_field
-        );
-        nonnative_test_individual!(
-            distribution_law_test,
-            $test_name,
-            $test_target_field,
-            $test_base_field
-        );
-        nonnative_test_individual!(
-            addition_stress_test,
-            $test_name,
-            $test_target_field,
-            $test_base_field
-        );
-        nonnative_test_individual!(
-            double_stress_test_1,
-            $test_name,
-            $test_target_field,
-            $test_base_field
-        );
-        nonnative_test_individual!(
-            double_stress_test_2,
-            $test_name,
-            $test_target_field,
-            $test_base_field
-        );
-        nonnative_test_individual!(
-            double_stress_test_3,
-            $test_name,
-            $test_target_field,
-            $test_base_field
-        );
-        nonnative_test_individual!(
-            randomized_arithmetic_test,
-            $test_name,
-            $test_target_field,
-            $test_base_field
-        );
-        nonnative_test_individual!(
-            multiplication_stress_test,
-            $test_name,
-            $test_target_field,
-            $test_base_field
-        );
-        nonnative_test_individual!(
-            mul_and_add_stress_test,
-            $test_name,
-            $test_target_field,
-            $test_base_field
+#[test]
+fn scalar_var_adapter_test() {
+    use ark_ec::{AffineCurve, ProjectiveCurve};
+    use ark_ff::UniformRand;
+    use ark_mnt4_298::{constraints::G1Var, Fr, G1Projective};
+    use ark_nonnative_field::scalar::ScalarVar;
+    use ark_r1cs_std::{bits::ToBitsGadget, groups::CurveVar};
+
+    let rng = &mut ark_ff::test_rng();
+    let cs = ConstraintSystem::<<MNT6_298 as PairingEngine>::Fr>::new_ref();
+
+    let base_native = G1Projective::rand(rng);
+    let base = G1Var::new_witness(ark_relations::ns!(cs, "base"), || Ok(base_native)).unwrap();
+
+    for scalar_native in [Fr::rand(rng), Fr::from(0u64), -Fr::from(1u64)].iter() {
+        let scalar =
+            ScalarVar::new_witness(ark_relations::ns!(cs, "scalar"), || Ok(*scalar_native))
+                .unwrap();
+        assert_eq!(scalar.to_bits_le().unwrap().len(), Fr::size_in_bits());
+
+        let res = scalar.scalar_mul::<G1Projective, G1Var>(&base).unwrap();
+        let expected = base_native.into_affine().mul(scalar_native.into_repr());
+        assert_eq!(res.value().unwrap(), expected);
+    }
+
+    // constants have the same number of bits
+    let constant = ScalarVar::from(NonNativeFieldVar::Constant(Fr::from(5u64)));
+    assert_eq!(constant.to_bits_le().unwrap().len(), Fr::size_in_bits());
+    let res = constant.scalar_mul::<G1Projective, G1Var>(&base).unwrap();
+    assert_eq!(
+        res.value().unwrap(),
+        base_native.mul(Fr::from(5u64).into_repr())
+    );
+
+    assert!(cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn ipa_check_scalars_test() {
+    use ark_ff::{Field, UniformRand};
+    use ark_nonnative_field::{
+        error::NonNativeFieldError,
+        ipa::{challenge_inverses, fold_scalars, ipa_check_scalars, s_vector},
+    };
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+    let alloc = |v: TargetField| {
+        NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+            ark_relations::ns!(cs, "alloc"),
+            || Ok(v),
+        )
+        .unwrap()
+    };
+
+    let k = 3;
+    let n = 1 << k;
+    let challenges: Vec<TargetField> = (0..k).map(|_| TargetField::rand(rng)).collect();
+    let scalars: Vec<TargetField> = (0..n).map(|_| TargetField::rand(rng)).collect();
+    let a = TargetField::rand(rng);
+    let b = TargetField::rand(rng);
+
+    // s_i has u_j for the set bits of i, the first round taking the most significant one
+    let s: Vec<TargetField> = (0..n)
+        .map(|i| {
+            let mut s_i = TargetField::from(1u64);
+            for (j, u) in challenges.iter().enumerate() {
+                if (i >> (k - 1 - j)) & 1 == 1 {
+                    s_i *= u;
+                } else {
+                    s_i *= &u.inverse().unwrap();
+                }
+            }
+            s_i
+        })
+        .collect();
+
+    let challenges_var: Vec<_> = challenges.iter().map(|u| alloc(*u)).collect();
+    let inverses_var = challenge_inverses(&challenges_var).unwrap();
+    let s_var = s_vector(&challenges_var, &inverses_var).unwrap();
+    assert_eq!(s_var.value().unwrap(), s);
+
+    // folding through all the rounds yields the inner product with s
+    let mut folded: Vec<_> = scalars.iter().map(|v| alloc(*v)).collect();
+    for (u, u_inv) in challenges_var.iter().zip(inverses_var.iter()) {
+        folded = fold_scalars(&folded, u, u_inv).unwrap();
+    }
+    assert_eq!(folded.len(), 1);
+    let expected: TargetField = s.iter().zip(scalars.iter()).map(|(s_i, v)| *s_i * v).sum();
+    assert_eq!(folded[0].value().unwrap(), expected);
+
+    let res = ipa_check_scalars(&challenges_var, &alloc(a), &alloc(b)).unwrap();
+    for i in 0..n {
+        assert_eq!(res.g_coeffs[i].value().unwrap(), a * &s[i]);
+        assert_eq!(
+            res.h_coeffs[i].value().unwrap(),
+            b * &s[i].inverse().unwrap()
         );
-        nonnative_test_individual!(
-            square_mul_add_stress_test,
-            $test_name,
-            $test_target_field,
-            $test_base_field
+    }
+    assert_eq!(res.u_coeff.value().unwrap(), a * &b);
+    for (j, u) in challenges.iter().enumerate() {
+        assert_eq!(res.l_coeffs[j].value().unwrap(), -u.square());
+        assert_eq!(
+            res.r_coeffs[j].value().unwrap(),
+            -u.inverse().unwrap().square()
         );
-        nonnative_test_individual!(
-            inverse_stress_test,
-            $test_name,
-            $test_target_field,
-            $test_base_field
+    }
+    assert!(cs.is_satisfied().unwrap());
+
+    assert!(matches!(
+        fold_scalars(&folded, &challenges_var[0], &inverses_var[0]),
+        Err(NonNativeFieldError::InvalidLength { .. })
+    ));
+}
+
+#[test]
+fn challenge_polynomial_test() {
+    use ark_ff::{Field, UniformRand};
+    use ark_nonnative_field::ipa::{challenge_inverses, challenge_polynomial_eval, s_vector};
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+
+    let challenges: Vec<_> = (0..4)
+        .map(|_| {
+            let u = TargetField::rand(rng);
+            NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+                ark_relations::ns!(cs, "challenge"),
+                || Ok(u),
+            )
+            .unwrap()
+        })
+        .collect();
+    let z_native = TargetField::rand(rng);
+    let z = NonNativeFieldVar::new_witness(ark_relations::ns!(cs, "z"), || Ok(z_native)).unwrap();
+
+    let inverses = challenge_inverses(&challenges).unwrap();
+    let b = challenge_polynomial_eval(&challenges, &inverses, &z).unwrap();
+
+    // b(z) is the inner product of s with the powers of z
+    let s = s_vector(&challenges, &inverses).unwrap().value().unwrap();
+    let expected: TargetField = s
+        .iter()
+        .enumerate()
+        .map(|(i, s_i)| *s_i * &z_native.pow(&[i as u64]))
+        .sum();
+    assert_eq!(b.value().unwrap(), expected);
+    assert!(cs.is_satisfied().unwrap());
+}
+
+#[cfg(not(ci))]
+#[test]
+fn ipa_opening_test() {
+    use ark_ec::{short_weierstrass_jacobian::GroupAffine, AffineCurve, ProjectiveCurve};
+    use ark_ff::{Field, UniformRand};
+    use ark_nonnative_field::{
+        curves::{
+            pasta::{Fq, PallasParameters},
+            short_weierstrass::NonNativeAffineVar,
+        },
+        ipa::IPAOpeningProofVar,
+    };
+
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let g = GroupAffine::<PallasParameters>::prime_subgroup_generator();
+    let generators = [
+        g.mul(Fq::rand(rng)).into_affine(),
+        g.mul(Fq::rand(rng)).into_affine(),
+    ];
+    let u_generator = g.mul(Fq::rand(rng)).into_affine();
+
+    // an opening of c_0 + c_1 X at z with a single round
+    let coeffs = [Fq::rand(rng), Fq::rand(rng)];
+    let z = Fq::rand(rng);
+    let value = coeffs[0] + &(coeffs[1] * &z);
+    let commitment = (generators[0].mul(coeffs[0]) + &generators[1].mul(coeffs[1])).into_affine();
+    let l = (generators[1].mul(coeffs[0]) + &u_generator.mul(coeffs[0] * &z)).into_affine();
+    let r = (generators[0].mul(coeffs[1]) + &u_generator.mul(coeffs[1])).into_affine();
+    let u = Fq::rand(rng);
+    let u_inv = u.inverse().unwrap();
+    let a = coeffs[0] * &u + &(coeffs[1] * &u_inv);
+    let challenge_point = (generators[0].mul(u_inv) + &generators[1].mul(u)).into_affine();
+
+    for (value, valid) in [(value, true), (value + &Fq::from(1u64), false)].iter() {
+        let cs = ConstraintSystem::<BaseField>::new_ref();
+        let point = |p: GroupAffine<PallasParameters>| {
+            NonNativeAffineVar::<PallasParameters, BaseField>::new_witness(
+                ark_relations::ns!(cs, "point"),
+                || Ok(p),
+            )
+            .unwrap()
+        };
+        let scalar = |v: Fq| {
+            NonNativeFieldVar::<Fq, BaseField>::new_witness(
+                ark_relations::ns!(cs, "scalar"),
+                || Ok(v),
+            )
+            .unwrap()
+        };
+
+        let proof = IPAOpeningProofVar {
+            l: vec![point(l)],
+            r: vec![point(r)],
+            a: scalar(a),
+            challenge_point: point(challenge_point),
+        };
+        let accumulator = proof
+            .verify(
+                &point(commitment),
+                &scalar(z),
+                &scalar(*value),
+                &point(u_generator),
+                &[scalar(u)],
+            )
+            .unwrap();
+        assert_eq!(
+            accumulator.challenge_point.value().unwrap(),
+            challenge_point
         );
+        assert_eq!(cs.is_satisfied().unwrap(), *valid);
+    }
+}
+
+#[test]
+fn error_test() {
+    use ark_nonnative_field::{
+        error::NonNativeFieldError,
+        kzg::batch_openings,
+        params::{get_params, set_params, ParamsSearching},
+        AllocatedNonNativeFieldVar, NonNativeFieldParams,
+    };
+    use ark_r1cs_std::{boolean::Boolean, select::TwoBitLookupGadget};
+    use ark_relations::r1cs::SynthesisError;
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    // no layout fits a 255-bit target field in a 20-bit base field
+    let mut problem = ParamsSearching::new(20, 255);
+    problem.solve();
+    assert_eq!(
+        problem.params(),
+        Err(NonNativeFieldError::NoParameters {
+            target_bits: 255,
+            base_bits: 20,
+        })
+    );
+
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+    let too_small = NonNativeFieldParams {
+        num_limbs: 2,
+        bits_per_top_limb: 100,
+        bits_per_non_top_limb: 100,
+        equality_layout: None,
+    };
+    match set_params::<TargetField, BaseField>(&cs, too_small) {
+        Err(NonNativeFieldError::InvalidParameters { reason, .. }) => {
+            assert_eq!(reason, "the limbs do not cover the target field")
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+
+    let a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "a"),
+        || Ok(TargetField::from(2u64)),
+    )
+    .unwrap();
+    assert_eq!(
+        batch_openings(&[a.clone(), a.clone()], &[a.clone()], &a).err(),
+        Some(NonNativeFieldError::LengthMismatch {
+            expected: 2,
+            found: 1,
+        })
+    );
+
+    // the parameters cannot change once an element uses them, but can be set again
+    let current = get_params::<TargetField, BaseField>(&cs).unwrap();
+    assert_eq!(
+        set_params::<TargetField, BaseField>(&cs, current.clone()),
+        Ok(())
+    );
+    let requested = NonNativeFieldParams {
+        num_limbs: current.num_limbs + 1,
+        ..current.clone()
     };
+    assert_eq!(
+        set_params::<TargetField, BaseField>(&cs, requested.clone()),
+        Err(NonNativeFieldError::ParametersInUse { current, requested })
+    );
+
+    // the errors convert into those of the traits of ark-r1cs-std, which surface as they are
+    let one = NonNativeFieldVar::<TargetField, BaseField>::constant(TargetField::from(1u64));
+    assert_eq!(
+        one.enforce_not_equal(&one).err(),
+        Some(SynthesisError::Unsatisfiable)
+    );
+    assert_eq!(
+        AllocatedNonNativeFieldVar::<TargetField, BaseField>::two_bit_lookup(
+            &[Boolean::TRUE, Boolean::FALSE],
+            &[TargetField::from(1u64); 4],
+        )
+        .err(),
+        Some(SynthesisError::MissingCS)
+    );
+    assert_eq!(
+        SynthesisError::from(NonNativeFieldError::from(SynthesisError::AssignmentMissing)),
+        SynthesisError::AssignmentMissing
+    );
+    assert!(cs.is_satisfied().unwrap());
 }
 
-nonnative_test!(
-    MNT46Small,
-    <MNT4_298 as PairingEngine>::Fr,
-    <MNT6_298 as PairingEngine>::Fr
-);
-nonnative_test!(
-    MNT64Small,
-    <MNT6_298 as PairingEngine>::Fr,
-    <MNT4_298 as PairingEngine>::Fr
-);
-nonnative_test!(
-    MNT46Big,
-    <MNT4_753 as PairingEngine>::Fr,
-    <MNT6_753 as PairingEngine>::Fr
-);
-nonnative_test!(
-    MNT64Big,
-    <MNT6_753 as PairingEngine>::Fr,
-    <MNT4_753 as PairingEngine>::Fr
-);
-nonnative_test!(
-    BLS12MNT4Small,
-    <Bls12_381 as PairingEngine>::Fr,
-    <MNT4_298 as PairingEngine>::Fr
-);
-nonnative_test!(
-    BLS12,
-    <Bls12_381 as PairingEngine>::Fq,
-    <Bls12_381 as PairingEngine>::Fr
-);
-#[cfg(not(ci))]
-nonnative_test!(
-    MNT6BigMNT4Small,
-    <MNT6_753 as PairingEngine>::Fr,
-    <MNT4_298 as PairingEngine>::Fr
-);
+#[cfg(feature = "zeroize")]
+#[test]
+fn zeroize_test() {
+    use ark_ff::{UniformRand, Zero};
+    use zeroize::Zeroize;
+
+    type TargetField = <MNT4_298 as PairingEngine>::Fr;
+    type BaseField = <MNT6_298 as PairingEngine>::Fr;
+
+    let rng = &mut ark_ff::test_rng();
+    let cs = ConstraintSystem::<BaseField>::new_ref();
+    let a_native = TargetField::rand(rng);
+
+    // a constant holds the value itself, which is cleared
+    let mut constant = NonNativeFieldVar::<TargetField, BaseField>::constant(a_native);
+    constant.zeroize();
+    assert_eq!(constant.value().unwrap(), TargetField::zero());
+
+    // a variable keeps its variables, so it still takes part in the constraints, while a clone
+    // taken before is a copy of its own
+    let mut a = NonNativeFieldVar::<TargetField, BaseField>::new_witness(
+        ark_relations::ns!(cs, "a"),
+        || Ok(a_native),
+    )
+    .unwrap();
+    let copy = a.clone();
+    a.zeroize();
+    assert_eq!(copy.value().unwrap(), a_native);
+    match &a {
+        NonNativeFieldVar::Var(v) => {
+            assert!(v.limbs.iter().all(|limb| limb.value().is_err()));
+        }
+        NonNativeFieldVar::Constant(_) => panic!("a witness is a variable"),
+    }
+    assert!(a.value().is_err());
+    a.enforce_equal(&copy).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+}